@@ -0,0 +1,42 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use simple_redis::{Aof, AppendFsync, BulkString, RespArray, RespFrame};
+
+const WRITES: u64 = 100;
+
+fn set_command(i: u64) -> RespFrame {
+    RespArray::new(vec![
+        BulkString::from("set").into(),
+        BulkString::from(format!("key{}", i)).into(),
+        BulkString::from("value").into(),
+    ])
+    .into()
+}
+
+// 比较三种 fsync 策略下的写入吞吐
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("aof appendfsync");
+    group.throughput(Throughput::Elements(WRITES));
+    group.sample_size(10);
+    for (name, policy) in [
+        ("always", AppendFsync::Always),
+        ("everysec", AppendFsync::EverySec),
+        ("no", AppendFsync::No),
+    ] {
+        let path = std::env::temp_dir().join(format!("simple-redis-bench-{}.aof", name));
+        let _ = std::fs::remove_file(&path);
+        let mut aof = Aof::open(&path, policy).unwrap();
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| {
+                for i in 0..WRITES {
+                    aof.append(set_command(i)).unwrap();
+                }
+            })
+        });
+        drop(aof);
+        let _ = std::fs::remove_file(&path);
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
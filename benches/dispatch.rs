@@ -0,0 +1,37 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use simple_redis::cmd::{command_names, is_command};
+use std::hint::black_box;
+
+// 原先 TryFrom<RespArray> for Command 中的 match 逐个比较字节串，相当于线性查找
+fn linear_dispatch(name: &[u8]) -> bool {
+    command_names().any(|candidate| candidate == name)
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    // 取命令表的头、中、尾以及未知命令，覆盖线性查找的最好和最坏情况
+    let commands: Vec<&[u8]> = command_names().collect();
+    let names = [
+        commands[0],
+        commands[commands.len() / 2],
+        commands[commands.len() - 1],
+        b"unknown",
+    ];
+
+    c.bench_function("dispatch linear scan", |b| {
+        b.iter(|| {
+            for name in names {
+                black_box(linear_dispatch(black_box(name)));
+            }
+        })
+    });
+    c.bench_function("dispatch binary search", |b| {
+        b.iter(|| {
+            for name in names {
+                black_box(is_command(black_box(name)));
+            }
+        })
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
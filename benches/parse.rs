@@ -0,0 +1,127 @@
+use bytes::BytesMut;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use simple_redis::{BulkString, RespArray, RespEncode, RespFrame, RespFrameIter};
+use std::hint::black_box;
+
+// pipeline 中的命令数，吞吐量按命令数报告即每秒解析的命令数
+const PIPELINE_COMMANDS: usize = 10_000;
+
+// 两种负载：大量小命令的 pipeline，以及单个带大量参数的命令（如 MSET / SADD 大批量写入）
+fn small_commands() -> Vec<u8> {
+    let commands: &[&[&str]] = &[
+        &["SET", "key", "value"],
+        &["GET", "key"],
+        &["HSET", "user:1", "name", "alice"],
+        &["HGET", "user:1", "name"],
+        &["SADD", "tags", "redis"],
+        &["INCR", "counter"],
+        &["LPUSH", "queue", "job"],
+        &["PING"],
+    ];
+    let mut buf = Vec::new();
+    for _ in 0..16 {
+        for args in commands {
+            buf.extend_from_slice(&command(args).encode());
+        }
+    }
+    buf
+}
+
+fn large_array() -> Vec<u8> {
+    let members: Vec<String> = (0..1000).map(|i| format!("member:{i}")).collect();
+    let args: Vec<&str> = ["SADD", "big"]
+        .into_iter()
+        .chain(members.iter().map(String::as_str))
+        .collect();
+    command(&args).encode()
+}
+
+// get / set 交替的 pipeline，key 和 value 各不相同
+fn pipelined_get_set() -> Vec<u8> {
+    let mut buf = Vec::new();
+    for i in 0..PIPELINE_COMMANDS {
+        let key = format!("key:{}", i / 2);
+        let frame = if i % 2 == 0 {
+            command(&["SET", &key, &format!("value:{i}")])
+        } else {
+            command(&["GET", &key])
+        };
+        buf.extend_from_slice(&frame.encode());
+    }
+    buf
+}
+
+fn command(args: &[&str]) -> RespFrame {
+    RespArray::new(
+        args.iter()
+            .map(|arg| BulkString::from(*arg).into())
+            .collect::<Vec<RespFrame>>(),
+    )
+    .into()
+}
+
+// 手写的 resp 解析器
+fn v1_parse(data: &[u8]) -> usize {
+    use simple_redis::RespDecode;
+    let mut buf = BytesMut::from(data);
+    let mut frames = 0;
+    while !buf.is_empty() {
+        black_box(RespFrame::decode(&mut buf).unwrap());
+        frames += 1;
+    }
+    frames
+}
+
+// 基于 winnow 的 respv2 解析器
+fn v2_parse(data: &[u8]) -> usize {
+    use simple_redis::RespDecodeV2;
+    let mut buf = BytesMut::from(data);
+    let mut frames = 0;
+    while !buf.is_empty() {
+        black_box(RespFrame::decode(&mut buf).unwrap());
+        frames += 1;
+    }
+    frames
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let workloads = [
+        ("small_commands", small_commands()),
+        ("large_array", large_array()),
+    ];
+    let mut group = c.benchmark_group("command_parse");
+    for (name, data) in &workloads {
+        // 两个解析器必须得到同样的帧数，否则比较没有意义
+        assert_eq!(v1_parse(data), v2_parse(data));
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        group.bench_with_input(BenchmarkId::new("resp", name), data, |b, data| {
+            b.iter(|| v1_parse(black_box(data)))
+        });
+        group.bench_with_input(BenchmarkId::new("respv2", name), data, |b, data| {
+            b.iter(|| v2_parse(black_box(data)))
+        });
+    }
+    group.finish();
+}
+
+// 用 RespFrameIter 连续解析整个 pipeline，作为后续解析优化的基准
+fn pipeline_benchmark(c: &mut Criterion) {
+    let data = pipelined_get_set();
+    let mut group = c.benchmark_group("pipeline_decode");
+    group.throughput(Throughput::Elements(PIPELINE_COMMANDS as u64));
+    group.bench_function("get_set", |b| {
+        b.iter(|| {
+            let mut buf = BytesMut::from(black_box(data.as_slice()));
+            let mut frames = 0;
+            for frame in RespFrameIter::new(&mut buf) {
+                black_box(frame.unwrap());
+                frames += 1;
+            }
+            assert_eq!(frames, PIPELINE_COMMANDS);
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark, pipeline_benchmark);
+criterion_main!(benches);
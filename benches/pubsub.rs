@@ -0,0 +1,48 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use simple_redis::{BulkString, RespEncode, RespFrame, RespVersion};
+use std::hint::black_box;
+
+const SUBSCRIBERS: [usize; 3] = [10, 100, 1000];
+const MESSAGE_SIZE: usize = 64 * 1024;
+
+// 每个订阅者拿到一份深拷贝并各自编码
+fn fan_out_cloned(frame: &RespFrame, subscribers: usize) -> usize {
+    (0..subscribers)
+        .map(|_| {
+            frame
+                .clone()
+                .into_version(RespVersion::Resp2)
+                .encode()
+                .len()
+        })
+        .sum()
+}
+
+// 所有订阅者共享同一个 frame，编码只发生一次
+fn fan_out_shared(frame: &RespFrame, subscribers: usize) -> usize {
+    let shared = frame.clone().into_shared();
+    (0..subscribers)
+        .map(|_| shared.clone().encode_for(RespVersion::Resp2).len())
+        .sum()
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let frame: RespFrame = BulkString::new(vec![b'x'; MESSAGE_SIZE]).into();
+    let mut group = c.benchmark_group("pubsub fan-out");
+    for subscribers in SUBSCRIBERS {
+        group.bench_with_input(
+            BenchmarkId::new("cloned", subscribers),
+            &subscribers,
+            |b, &n| b.iter(|| fan_out_cloned(black_box(&frame), n)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("shared", subscribers),
+            &subscribers,
+            |b, &n| b.iter(|| fan_out_shared(black_box(&frame), n)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
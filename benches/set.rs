@@ -0,0 +1,81 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use dashmap::DashSet;
+use simple_redis::SetMembers;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const SETS: usize = 10_000;
+const MEMBERS: [usize; 3] = [1, 4, 16];
+
+// 统计分配的字节数，用来比较两种存储方式的内存开销
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+// 原先每个集合都是一个 DashSet，自带多个分片的哈希表和读写锁
+fn dash_sets(members: usize) -> Vec<DashSet<String>> {
+    (0..SETS)
+        .map(|i| {
+            let set = DashSet::new();
+            for m in 0..members {
+                set.insert(format!("{}:{}", i, m));
+            }
+            set
+        })
+        .collect()
+}
+
+fn compact_sets(members: usize) -> Vec<SetMembers> {
+    (0..SETS)
+        .map(|i| (0..members).map(|m| format!("{}:{}", i, m)).collect())
+        .collect()
+}
+
+fn allocated_by<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let value = f();
+    let bytes = ALLOCATED.load(Ordering::Relaxed) - before;
+    drop(value);
+    bytes
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    for members in MEMBERS {
+        println!(
+            "{} sets of {} members: dashset {} bytes, compact {} bytes",
+            SETS,
+            members,
+            allocated_by(|| dash_sets(members)),
+            allocated_by(|| compact_sets(members)),
+        );
+    }
+
+    let mut group = c.benchmark_group("small sets");
+    for members in MEMBERS {
+        group.bench_with_input(BenchmarkId::new("dashset", members), &members, |b, &n| {
+            b.iter(|| dash_sets(black_box(n)))
+        });
+        group.bench_with_input(BenchmarkId::new("compact", members), &members, |b, &n| {
+            b.iter(|| compact_sets(black_box(n)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
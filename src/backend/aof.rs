@@ -0,0 +1,325 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+use crate::{Backend, BulkString, RespArray, RespEncode, RespFrame};
+
+const FSYNC_INTERVAL: Duration = Duration::from_secs(1);
+
+/// When the append-only file is fsynced, trading durability for throughput.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AppendFsync {
+    /// fsync after every write command.
+    Always,
+    /// fsync once per second from a background thread.
+    #[default]
+    EverySec,
+    /// Never fsync explicitly, let the OS decide.
+    No,
+}
+
+/// Append-only log of executed write commands.
+#[derive(Debug)]
+pub struct Aof {
+    file: File,
+    policy: AppendFsync,
+    // drop 时关闭，后台 fsync 线程随之退出
+    _stop: Option<Sender<()>>,
+}
+
+impl Aof {
+    pub fn open(path: impl AsRef<Path>, policy: AppendFsync) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let stop = match policy {
+            AppendFsync::EverySec => Some(spawn_fsync_thread(file.try_clone()?)),
+            AppendFsync::Always | AppendFsync::No => None,
+        };
+        Ok(Self {
+            file,
+            policy,
+            _stop: stop,
+        })
+    }
+
+    pub fn policy(&self) -> AppendFsync {
+        self.policy
+    }
+
+    pub fn append(&mut self, frame: RespFrame) -> io::Result<()> {
+        self.file.write_all(&frame.encode())?;
+        if self.policy == AppendFsync::Always {
+            self.file.sync_data()?;
+        }
+        Ok(())
+    }
+
+    /// Flush everything appended so far to disk, whatever the policy.
+    pub fn fsync(&self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+}
+
+fn spawn_fsync_thread(file: File) -> Sender<()> {
+    let (stop, stopped) = mpsc::channel::<()>();
+    thread::spawn(move || loop {
+        match stopped.recv_timeout(FSYNC_INTERVAL) {
+            Err(RecvTimeoutError::Timeout) => {
+                if let Err(e) = file.sync_data() {
+                    tracing::warn!("AOF fsync failed: {:?}", e);
+                }
+            }
+            _ => {
+                let _ = file.sync_data();
+                return;
+            }
+        }
+    });
+    stop
+}
+
+/// Execute the RESP-encoded commands read from `reader` (e.g. an AOF file)
+/// against `backend`, returns how many were applied. A truncated frame at
+/// the end of the stream is ignored.
+pub fn replay_commands(mut reader: impl Read, backend: &Backend) -> anyhow::Result<usize> {
+    let _loading = backend.start_loading();
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    Ok(backend.replay(&data)?)
+}
+
+impl FromStr for AppendFsync {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "always" => Ok(AppendFsync::Always),
+            "everysec" => Ok(AppendFsync::EverySec),
+            "no" => Ok(AppendFsync::No),
+            _ => Err(format!("invalid appendfsync policy '{}'", s)),
+        }
+    }
+}
+
+impl Backend {
+    /// Start appending write commands to `path`, replacing the AOF in use if any.
+    pub fn enable_aof(&self, path: impl AsRef<Path>, policy: AppendFsync) -> io::Result<()> {
+        let aof = Aof::open(path, policy)?;
+        *self.aof.lock().unwrap() = Some(aof);
+        Ok(())
+    }
+
+    pub fn aof_enabled(&self) -> bool {
+        self.aof.lock().unwrap().is_some()
+    }
+
+    /// Record an executed write command, a no-op when AOF is disabled.
+    pub fn append_aof(&self, frame: RespFrame) -> io::Result<()> {
+        match self.aof.lock().unwrap().as_mut() {
+            Some(aof) => aof.append(frame),
+            None => Ok(()),
+        }
+    }
+
+    /// Record a command the server issued on its own, like the DEL of an
+    /// expired key. Nothing is recorded while loading.
+    pub(crate) fn propagate(&self, args: &[&str]) {
+        if !self.is_ready() || !self.aof_enabled() {
+            return;
+        }
+        let frame = RespArray::new(
+            args.iter()
+                .map(|arg| BulkString::from(*arg).into())
+                .collect::<Vec<RespFrame>>(),
+        );
+        if let Err(e) = self.append_aof(frame.into()) {
+            tracing::warn!("AOF append failed: {:?}", e);
+        }
+    }
+
+    /// fsync the AOF now, returns false when AOF is disabled.
+    pub fn fsync_aof(&self) -> io::Result<bool> {
+        match self.aof.lock().unwrap().as_ref() {
+            Some(aof) => aof.fsync().map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    /// Replay the commands of an append-only file, returns how many were applied.
+    pub fn load_aof(&self, path: impl AsRef<Path>) -> anyhow::Result<usize> {
+        replay_commands(File::open(path)?, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::{Command, CommandExecutor};
+    use crate::{Lookup, ManualClock};
+    use anyhow::Result;
+    use std::sync::Arc;
+
+    fn command(args: &[&str]) -> RespFrame {
+        RespArray::new(
+            args.iter()
+                .map(|s| BulkString::from(*s).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+        .into()
+    }
+
+    #[test]
+    fn test_aof_always_survives_crash() -> Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("simple-redis-{}-always.aof", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let backend = Backend::new();
+        backend.enable_aof(&path, AppendFsync::Always)?;
+        for frame in [command(&["set", "a", "1"]), command(&["set", "last", "2"])] {
+            let cmd = Command::try_from(frame.clone())?;
+            cmd.execute(&backend);
+            backend.append_aof(frame)?;
+        }
+        // 模拟崩溃：不走任何正常关闭流程
+        std::mem::forget(backend);
+
+        let reopened = Backend::new();
+        assert_eq!(reopened.load_aof(&path)?, 2);
+        assert_eq!(
+            reopened.get("last"),
+            Lookup::Found(BulkString::from("2").into())
+        );
+
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_aof_resolves_renamed_commands() -> Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("simple-redis-{}-renamed.aof", std::process::id()));
+        std::fs::write(&path, command(&["myset", "k", "v"]).encode())?;
+
+        let backend = Backend::new();
+        backend.config().rename_command("set", "myset");
+        assert_eq!(backend.load_aof(&path)?, 1);
+        assert_eq!(
+            backend.get("k"),
+            Lookup::Found(BulkString::from("v").into())
+        );
+
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_expired_keys_are_recorded_as_del() -> Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("simple-redis-{}-expired.aof", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let clock = Arc::new(ManualClock::new());
+        let backend = Backend::with_clock(clock.clone());
+        backend.enable_aof(&path, AppendFsync::No)?;
+        backend.set("k".to_string(), BulkString::from("v").into());
+        backend.expire("k", std::time::Duration::from_secs(1))?;
+        clock.advance(std::time::Duration::from_secs(1));
+        assert_eq!(backend.get("k"), Lookup::Missing);
+
+        let data = std::fs::read(&path)?;
+        assert_eq!(data, command(&["del", "k"]).encode());
+
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_replay_commands_stops_on_truncated_frame() -> Result<()> {
+        let mut stream = Vec::new();
+        for args in [
+            &["set", "a", "1"][..],
+            &["rpush", "list", "x", "y"],
+            &["hset", "map", "f", "v"],
+            &["incrby", "a", "41"],
+        ] {
+            stream.extend(command(args).encode());
+        }
+        // 末尾半条命令
+        stream.extend_from_slice(b"*3\r\n$3\r\nset\r\n$1\r\nb");
+
+        let backend = Backend::new();
+        assert_eq!(replay_commands(stream.as_slice(), &backend)?, 4);
+        assert_eq!(
+            backend.get("a"),
+            Lookup::Found(BulkString::from("42").into())
+        );
+        assert_eq!(backend.list.get("list").map(|l| l.len()), Some(2));
+        assert_eq!(backend.hget("map", "f"), Some(BulkString::from("v").into()));
+        assert_eq!(backend.get("b"), Lookup::Missing);
+
+        // 中间出现非法帧则报错
+        let bad = b"*1\r\n$4\r\nping\r\n:abc\r\n".as_slice();
+        assert!(replay_commands(bad, &backend).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_not_ready_while_loading() -> Result<()> {
+        use std::sync::mpsc;
+        use std::thread;
+
+        // 收到信号之前一直阻塞的 reader，模拟很慢的加载
+        struct SlowReader {
+            started: mpsc::Sender<()>,
+            release: mpsc::Receiver<Vec<u8>>,
+            data: Option<io::Cursor<Vec<u8>>>,
+        }
+        impl Read for SlowReader {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.data.is_none() {
+                    let _ = self.started.send(());
+                    let data = self.release.recv().unwrap_or_default();
+                    self.data = Some(io::Cursor::new(data));
+                }
+                self.data.as_mut().unwrap().read(buf)
+            }
+        }
+
+        let backend = Backend::new();
+        assert!(backend.is_ready());
+        let (release, receiver) = mpsc::channel();
+        let (started, loading) = mpsc::channel();
+        let loader = {
+            let backend = backend.clone();
+            thread::spawn(move || {
+                let reader = SlowReader {
+                    started,
+                    release: receiver,
+                    data: None,
+                };
+                replay_commands(reader, &backend)
+            })
+        };
+        // 开始读取时已经处于加载状态
+        loading.recv()?;
+        assert!(!backend.is_ready());
+
+        release.send(command(&["set", "k", "v"]).encode())?;
+        assert_eq!(loader.join().unwrap()?, 1);
+        assert!(backend.is_ready());
+        assert!(matches!(backend.get("k"), Lookup::Found(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_fsync_from_str() {
+        assert_eq!("always".parse(), Ok(AppendFsync::Always));
+        assert_eq!("EVERYSEC".parse(), Ok(AppendFsync::EverySec));
+        assert_eq!("no".parse(), Ok(AppendFsync::No));
+        assert!("sometimes".parse::<AppendFsync>().is_err());
+    }
+}
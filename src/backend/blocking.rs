@@ -0,0 +1,289 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use super::{Backend, BackendError};
+use crate::RespFrame;
+
+/// Which end of a list BLMOVE pops from or pushes to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListEnd {
+    Left,
+    Right,
+}
+
+/// Clients blocked on list keys, in the order they started waiting.
+#[derive(Debug, Default)]
+pub(crate) struct BlockedClients {
+    next_id: u64,
+    waiters: VecDeque<(u64, Vec<String>)>,
+}
+
+impl BlockedClients {
+    // 同一个 key 上先阻塞的客户端优先，排在后面的不能抢先弹出
+    fn is_first_on(&self, id: u64, key: &str) -> bool {
+        self.waiters
+            .iter()
+            .take_while(|(other, _)| *other != id)
+            .all(|(_, keys)| !keys.iter().any(|k| k == key))
+    }
+}
+
+impl Backend {
+    /// Pop from the head of the first non-empty list among `keys`, waiting up
+    /// to `timeout` (forever if `None`) for another client to push an element.
+    pub fn blpop(&self, keys: &[String], timeout: Option<Duration>) -> Option<(String, RespFrame)> {
+        self.block_on_lists(keys, timeout, |key| {
+            self.lpop(key).map(|value| (key.to_string(), value))
+        })
+    }
+
+    /// Like `blpop` but pops from the tail.
+    pub fn brpop(&self, keys: &[String], timeout: Option<Duration>) -> Option<(String, RespFrame)> {
+        self.block_on_lists(keys, timeout, |key| {
+            self.rpop(key).map(|value| (key.to_string(), value))
+        })
+    }
+
+    /// Blocking `lmove`, returns `Ok(None)` on timeout.
+    pub fn blmove(
+        &self,
+        source: &str,
+        destination: &str,
+        wherefrom: ListEnd,
+        whereto: ListEnd,
+        timeout: Option<Duration>,
+    ) -> Result<Option<RespFrame>, BackendError> {
+        let keys = [source.to_string()];
+        let ret = self.block_on_lists(&keys, timeout, |_| {
+            // 已经持有 list_ready 的锁，退出阻塞时会统一唤醒其他客户端
+            self.move_element(source, destination, wherefrom, whereto)
+                .transpose()
+        });
+        ret.transpose()
+    }
+
+    /// Atomically pop an element from one end of `source` and push it to one
+    /// end of `destination`, `Ok(None)` if `source` is empty.
+    pub fn lmove(
+        &self,
+        source: &str,
+        destination: &str,
+        wherefrom: ListEnd,
+        whereto: ListEnd,
+    ) -> Result<Option<RespFrame>, BackendError> {
+        let ret = self.move_element(source, destination, wherefrom, whereto);
+        if let Ok(Some(_)) = ret {
+            self.notify_list_ready();
+        }
+        ret
+    }
+
+    fn move_element(
+        &self,
+        source: &str,
+        destination: &str,
+        wherefrom: ListEnd,
+        whereto: ListEnd,
+    ) -> Result<Option<RespFrame>, BackendError> {
+        // 阻塞时这里已经持有 list_ready 的锁，lock_key 的持有者不会再去拿它
+        let _guard = self.lock_key(destination);
+        self.check_type(destination, "list")?;
+        let value = match wherefrom {
+            ListEnd::Left => self.lpop(source),
+            ListEnd::Right => self.rpop(source),
+        };
+        let Some(value) = value else {
+            return Ok(None);
+        };
+        let mut list = self.list.entry(destination.to_string()).or_default();
+        match whereto {
+            ListEnd::Left => list.push_front(value.clone()),
+            ListEnd::Right => list.push_back(value.clone()),
+        }
+        drop(list);
+        self.touch(destination);
+        Ok(Some(value))
+    }
+
+    /// Number of clients waiting in a blocking list command.
+    pub fn blocked_clients(&self) -> usize {
+        self.list_ready.0.lock().unwrap().waiters.len()
+    }
+
+    fn block_on_lists<T>(
+        &self,
+        keys: &[String],
+        timeout: Option<Duration>,
+        mut pop: impl FnMut(&str) -> Option<T>,
+    ) -> Option<T> {
+        // 无法表示的超时时刻按一直阻塞处理，命令解析时已经拒绝这种超时
+        let deadline = timeout.and_then(|t| Instant::now().checked_add(t));
+        let (lock, cvar) = &self.list_ready;
+        // 持有锁检查各个 key，push 必须拿到锁才能通知，因此不会丢失唤醒
+        let mut blocked = lock.lock().unwrap();
+        let id = blocked.next_id;
+        blocked.next_id += 1;
+        blocked.waiters.push_back((id, keys.to_vec()));
+        let ret = loop {
+            let ret = keys
+                .iter()
+                .filter(|key| blocked.is_first_on(id, key))
+                .find_map(|key| pop(key));
+            if ret.is_some() {
+                break ret;
+            }
+            blocked = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        break None;
+                    }
+                    cvar.wait_timeout(blocked, deadline - now).unwrap().0
+                }
+                None => cvar.wait(blocked).unwrap(),
+            };
+        };
+        blocked.waiters.retain(|(other, _)| *other != id);
+        // 排在后面的客户端可能因为让位而错过了还没弹出的元素
+        cvar.notify_all();
+        ret
+    }
+
+    pub(crate) fn notify_list_ready(&self) {
+        let (lock, cvar) = &self.list_ready;
+        let _guard = lock.lock().unwrap();
+        cvar.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    fn wait_blocked(backend: &Backend, n: usize) {
+        while backend.blocked_clients() < n {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_brpop_serves_first_blocked_client() {
+        let backend = Backend::new();
+        let brpop = |backend: &Backend, timeout| {
+            let backend = backend.clone();
+            thread::spawn(move || backend.brpop(&["list".to_string()], Some(timeout)))
+        };
+        let first = brpop(&backend, Duration::from_secs(5));
+        wait_blocked(&backend, 1);
+        let second = brpop(&backend, Duration::from_millis(500));
+        wait_blocked(&backend, 2);
+
+        backend
+            .rpush("list".to_string(), vec![b"x".into()])
+            .unwrap();
+        assert_eq!(
+            first.join().unwrap(),
+            Some(("list".to_string(), b"x".into()))
+        );
+        assert_eq!(second.join().unwrap(), None);
+        assert_eq!(backend.blocked_clients(), 0);
+    }
+
+    #[test]
+    fn test_blpop_timeout_precision() {
+        let backend = Backend::new();
+        // 超时按真实时间等待，条件变量超时唤醒，既不会立刻返回也不会明显拖后
+        let start = Instant::now();
+        assert_eq!(
+            backend.blpop(&["list".to_string()], Some(Duration::from_millis(500))),
+            None
+        );
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(500), "{elapsed:?}");
+        assert!(elapsed < Duration::from_millis(750), "{elapsed:?}");
+        assert_eq!(backend.blocked_clients(), 0);
+
+        // 等待期间有元素写入时立即返回，不等到超时
+        let waiter = {
+            let backend = backend.clone();
+            thread::spawn(move || {
+                let start = Instant::now();
+                let ret = backend.blpop(&["list".to_string()], Some(Duration::from_secs(5)));
+                (ret, start.elapsed())
+            })
+        };
+        wait_blocked(&backend, 1);
+        backend
+            .rpush("list".to_string(), vec![b"x".into()])
+            .unwrap();
+        let (ret, elapsed) = waiter.join().unwrap();
+        assert_eq!(ret, Some(("list".to_string(), b"x".into())));
+        assert!(elapsed < Duration::from_secs(1), "{elapsed:?}");
+    }
+
+    #[test]
+    fn test_blocked_clients_are_served_fifo() {
+        let backend = Backend::new();
+        let waiters: Vec<_> = (0..3)
+            .map(|i| {
+                let client = backend.clone();
+                let waiter = thread::spawn(move || {
+                    client.blpop(&["list".to_string()], Some(Duration::from_secs(5)))
+                });
+                // 等上一个客户端进入阻塞后再启动下一个，保证阻塞顺序
+                wait_blocked(&backend, i + 1);
+                waiter
+            })
+            .collect();
+
+        // 一次写入多个元素，按阻塞的先后顺序依次分配
+        backend
+            .rpush(
+                "list".to_string(),
+                vec![b"a".into(), b"b".into(), b"c".into()],
+            )
+            .unwrap();
+        let popped: Vec<_> = waiters
+            .into_iter()
+            .map(|waiter| waiter.join().unwrap().map(|(_, value)| value))
+            .collect();
+        assert_eq!(
+            popped,
+            vec![Some(b"a".into()), Some(b"b".into()), Some(b"c".into())]
+        );
+        assert_eq!(backend.blocked_clients(), 0);
+    }
+
+    #[test]
+    fn test_blmove_moves_pushed_element() {
+        let backend = Backend::new();
+        let waiter = {
+            let backend = backend.clone();
+            thread::spawn(move || {
+                backend.blmove(
+                    "src",
+                    "dst",
+                    ListEnd::Right,
+                    ListEnd::Left,
+                    Some(Duration::from_secs(5)),
+                )
+            })
+        };
+        wait_blocked(&backend, 1);
+        backend
+            .rpush("src".to_string(), vec![b"a".into(), b"b".into()])
+            .unwrap();
+
+        assert_eq!(waiter.join().unwrap(), Ok(Some(b"b".into())));
+        assert_eq!(backend.lpop("dst"), Some(b"b".into()));
+        assert_eq!(backend.lpop("src"), Some(b"a".into()));
+
+        backend.set("str".to_string(), b"v".into());
+        assert_eq!(
+            backend.lmove("src", "str", ListEnd::Left, ListEnd::Left),
+            Err(BackendError::WrongType)
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use super::Backend;
+use crate::{RespFrame, RespVersion};
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+/// Keeps a connection counted in `connected_clients` for as long as it lives.
+#[derive(Debug)]
+pub struct ClientGuard {
+    backend: Backend,
+    id: u64,
+}
+
+/// Per-connection switches set with CLIENT NO-EVICT / CLIENT NO-TOUCH.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ClientFlags {
+    pub no_evict: bool,
+    // 开启后该连接的读命令不更新 key 的访问时间
+    pub no_touch: bool,
+}
+
+/// State of one client connection that commands can read while executing.
+#[derive(Debug, Default, Clone)]
+pub struct ConnectionContext {
+    /// Id assigned when the connection was accepted, 0 outside a connection.
+    pub id: u64,
+    /// Protocol negotiated with HELLO.
+    pub version: RespVersion,
+    // 只有一个数据库，SELECT 支持之前始终为 0
+    pub db: usize,
+    pub name: Option<String>,
+    pub flags: ClientFlags,
+    /// Channels and patterns the connection is subscribed to.
+    pub subscriptions: usize,
+    pub rate_limit: RateLimiter,
+}
+
+/// Token bucket limiting how many commands per second one connection may
+/// run, see `client-command-rate-limit`.
+#[derive(Debug, Default, Clone)]
+pub struct RateLimiter {
+    tokens: f64,
+    // 上次补充令牌的时间，None 表示还没有执行过命令，桶是满的
+    last: Option<Instant>,
+}
+
+impl RateLimiter {
+    // 令牌按 rate 每秒匀速补充，桶的容量也是 rate，即最多允许一秒的突发
+    fn try_acquire(&mut self, rate: usize, now: Instant) -> bool {
+        let rate = rate as f64;
+        self.tokens = match self.last {
+            Some(last) => {
+                let refill = now.saturating_duration_since(last).as_secs_f64() * rate;
+                (self.tokens + refill).min(rate)
+            }
+            None => rate,
+        };
+        self.last = Some(now);
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+impl Backend {
+    /// Register a new client connection, the returned guard unregisters it on drop.
+    pub fn connect(&self) -> ClientGuard {
+        self.clients.fetch_add(1, Ordering::Relaxed);
+        self.client_guard()
+    }
+
+    fn client_guard(&self) -> ClientGuard {
+        ClientGuard {
+            backend: self.clone(),
+            id: self.last_client_id.fetch_add(1, Ordering::Relaxed) + 1,
+        }
+    }
+
+    /// Register a new client connection unless `maxclients` connections are
+    /// already live, in which case the error frame to send back is returned.
+    pub fn try_connect(&self) -> Result<ClientGuard, RespFrame> {
+        let max = self.config.maxclients();
+        // 用 CAS 检查并占位，并发接入时也不会超过上限
+        self.clients
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+                (n < max).then_some(n + 1)
+            })
+            .map_err(|_| RespFrame::error("ERR", "max number of clients reached"))?;
+        Ok(self.client_guard())
+    }
+
+    /// Take a token from the connection's rate limit, false if it has run
+    /// out. Always true when no limit is configured.
+    pub fn allow_command(&self, ctx: &mut ConnectionContext) -> bool {
+        match self.config.client_command_rate_limit() {
+            0 => true,
+            rate => ctx.rate_limit.try_acquire(rate, self.clock.now()),
+        }
+    }
+
+    pub fn connected_clients(&self) -> usize {
+        self.clients.load(Ordering::Relaxed)
+    }
+}
+
+impl ClientGuard {
+    /// Unique id of the connection, as reported by HELLO.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl Drop for ClientGuard {
+    fn drop(&mut self) {
+        self.backend.clients.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_guard_counts_connections() {
+        let backend = Backend::new();
+        let a = backend.connect();
+        let b = backend.connect();
+        assert_eq!(backend.connected_clients(), 2);
+
+        drop(a);
+        assert_eq!(backend.connected_clients(), 1);
+        drop(b);
+        assert_eq!(backend.connected_clients(), 0);
+    }
+
+    #[test]
+    fn test_try_connect_respects_maxclients() {
+        let backend = Backend::new();
+        backend.config().set_maxclients(2);
+        let a = backend.try_connect().unwrap();
+        let _b = backend.try_connect().unwrap();
+
+        let err = backend.try_connect().unwrap_err();
+        assert_eq!(
+            err,
+            RespFrame::error("ERR", "max number of clients reached")
+        );
+        assert_eq!(backend.connected_clients(), 2);
+
+        // 断开一个连接后可以再接入
+        drop(a);
+        let _c = backend.try_connect().unwrap();
+        assert_eq!(backend.connected_clients(), 2);
+    }
+}
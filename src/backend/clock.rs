@@ -0,0 +1,79 @@
+use std::fmt::Debug;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Source of the current time for TTLs, expiry and IDLETIME, so tests can
+/// control it instead of sleeping.
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> Instant;
+
+    /// Wall clock time, used to convert absolute unix times such as EXAT.
+    fn system_now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// The real clock.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Clock that only moves when `advance` is called, for deterministic tests.
+#[derive(Debug)]
+pub struct ManualClock {
+    start: (Instant, SystemTime),
+    elapsed: Mutex<Duration>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self {
+            start: (Instant::now(), SystemTime::now()),
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.elapsed.lock().unwrap() += by;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start.0 + *self.elapsed.lock().unwrap()
+    }
+
+    fn system_now(&self) -> SystemTime {
+        self.start.1 + *self.elapsed.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock_moves_only_when_advanced() {
+        let clock = ManualClock::new();
+        let (now, system_now) = (clock.now(), clock.system_now());
+        assert_eq!(clock.now(), now);
+
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(clock.now() - now, Duration::from_secs(3));
+        assert_eq!(
+            clock.system_now().duration_since(system_now).unwrap(),
+            Duration::from_secs(3)
+        );
+    }
+}
@@ -0,0 +1,334 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering};
+use std::sync::RwLock;
+use std::time::Duration;
+
+use super::{NotifyFlags, PubSubOverflow};
+use crate::{DecodeLimits, RespVersion};
+
+// list 长度不超过该值时使用 listpack 编码，否则为 quicklist
+const LIST_MAX_LISTPACK_SIZE: usize = 128;
+// 集合的 intset / listpack 编码阈值，超过后转为 hashtable
+const SET_MAX_INTSET_ENTRIES: usize = 512;
+const SET_MAX_LISTPACK_ENTRIES: usize = 128;
+const SET_MAX_LISTPACK_VALUE: usize = 64;
+// 有序集合的 listpack 编码阈值，超过后转为 skiplist
+const ZSET_MAX_LISTPACK_ENTRIES: usize = 128;
+const ZSET_MAX_LISTPACK_VALUE: usize = 64;
+// 主动过期每个周期最多删除的 key 数和最长运行时间
+const ACTIVE_EXPIRE_CYCLE_KEYS: usize = 200;
+const ACTIVE_EXPIRE_CYCLE_MS: u64 = 25;
+// 单个 bulk string 的最大长度，和 Redis 一样默认 512MB
+const PROTO_MAX_BULK_LEN: usize = 512 * 1024 * 1024;
+// 单个 aggregate 最多的元素数和最深的嵌套层数
+const PROTO_MAX_MULTIBULK_LEN: usize = 1024 * 1024;
+const PROTO_MAX_NESTING_DEPTH: usize = 64;
+// 同时在线的客户端连接数上限
+const MAXCLIENTS: usize = 10000;
+// 每个订阅者最多积压的消息数
+const PUBSUB_BUFFER_LIMIT: usize = 10000;
+
+/// Runtime tunables of the backend, all of them can be changed on the fly.
+#[derive(Debug)]
+pub struct BackendConfig {
+    list_max_listpack_size: AtomicUsize,
+    set_max_intset_entries: AtomicUsize,
+    set_max_listpack_entries: AtomicUsize,
+    set_max_listpack_value: AtomicUsize,
+    zset_max_listpack_entries: AtomicUsize,
+    zset_max_listpack_value: AtomicUsize,
+    active_expire_cycle_keys: AtomicUsize,
+    active_expire_cycle_ms: AtomicU64,
+    // DEBUG SET-ACTIVE-EXPIRE 0 关闭主动过期，只剩惰性删除
+    active_expire_enabled: AtomicBool,
+    // 默认关闭 keyspace 通知
+    notify_keyspace_events: AtomicU32,
+    proto_max_bulk_len: AtomicUsize,
+    proto_max_multibulk_len: AtomicUsize,
+    proto_max_nesting_depth: AtomicUsize,
+    // 单个回复编码后的最大长度，0 表示不限制
+    max_reply_size: AtomicUsize,
+    maxclients: AtomicUsize,
+    // 每个连接每秒最多执行的命令数，0 表示不限制
+    client_command_rate_limit: AtomicUsize,
+    // 数据占用内存的上限，0 表示不限制；没有淘汰策略，相当于 noeviction
+    maxmemory: AtomicUsize,
+    // 订阅者缓冲区上限，0 表示不限制；满了之后按 pubsub_overflow 处理
+    pubsub_buffer_limit: AtomicUsize,
+    pubsub_overflow: AtomicU8,
+    // 开启后不论协商的协议版本，回复一律按 RESP2 编码
+    strict_resp2: AtomicBool,
+    // 测试用：HKEYS/HVALS/SMEMBERS 按字典序回复，不依赖哈希表的遍历顺序
+    sort_replies: AtomicBool,
+    command_renames: RwLock<CommandRenames>,
+}
+
+// rename-command 表：别名到内置命令名，以及不能再用原名调用的内置命令
+#[derive(Debug, Default)]
+struct CommandRenames {
+    aliases: HashMap<Vec<u8>, Vec<u8>>,
+    hidden: HashSet<Vec<u8>>,
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        Self {
+            list_max_listpack_size: AtomicUsize::new(LIST_MAX_LISTPACK_SIZE),
+            set_max_intset_entries: AtomicUsize::new(SET_MAX_INTSET_ENTRIES),
+            set_max_listpack_entries: AtomicUsize::new(SET_MAX_LISTPACK_ENTRIES),
+            set_max_listpack_value: AtomicUsize::new(SET_MAX_LISTPACK_VALUE),
+            zset_max_listpack_entries: AtomicUsize::new(ZSET_MAX_LISTPACK_ENTRIES),
+            zset_max_listpack_value: AtomicUsize::new(ZSET_MAX_LISTPACK_VALUE),
+            active_expire_cycle_keys: AtomicUsize::new(ACTIVE_EXPIRE_CYCLE_KEYS),
+            active_expire_cycle_ms: AtomicU64::new(ACTIVE_EXPIRE_CYCLE_MS),
+            active_expire_enabled: AtomicBool::new(true),
+            notify_keyspace_events: AtomicU32::new(NotifyFlags::NONE.bits()),
+            proto_max_bulk_len: AtomicUsize::new(PROTO_MAX_BULK_LEN),
+            proto_max_multibulk_len: AtomicUsize::new(PROTO_MAX_MULTIBULK_LEN),
+            proto_max_nesting_depth: AtomicUsize::new(PROTO_MAX_NESTING_DEPTH),
+            max_reply_size: AtomicUsize::new(0),
+            maxclients: AtomicUsize::new(MAXCLIENTS),
+            client_command_rate_limit: AtomicUsize::new(0),
+            maxmemory: AtomicUsize::new(0),
+            pubsub_buffer_limit: AtomicUsize::new(PUBSUB_BUFFER_LIMIT),
+            pubsub_overflow: AtomicU8::new(PubSubOverflow::default() as u8),
+            strict_resp2: AtomicBool::new(false),
+            sort_replies: AtomicBool::new(false),
+            command_renames: RwLock::default(),
+        }
+    }
+}
+
+impl BackendConfig {
+    pub fn list_max_listpack_size(&self) -> usize {
+        self.list_max_listpack_size.load(Ordering::Relaxed)
+    }
+
+    pub fn set_list_max_listpack_size(&self, size: usize) {
+        self.list_max_listpack_size.store(size, Ordering::Relaxed);
+    }
+
+    pub fn set_max_intset_entries(&self) -> usize {
+        self.set_max_intset_entries.load(Ordering::Relaxed)
+    }
+
+    pub fn set_set_max_intset_entries(&self, entries: usize) {
+        self.set_max_intset_entries
+            .store(entries, Ordering::Relaxed);
+    }
+
+    pub fn set_max_listpack_entries(&self) -> usize {
+        self.set_max_listpack_entries.load(Ordering::Relaxed)
+    }
+
+    pub fn set_set_max_listpack_entries(&self, entries: usize) {
+        self.set_max_listpack_entries
+            .store(entries, Ordering::Relaxed);
+    }
+
+    pub fn set_max_listpack_value(&self) -> usize {
+        self.set_max_listpack_value.load(Ordering::Relaxed)
+    }
+
+    pub fn set_set_max_listpack_value(&self, len: usize) {
+        self.set_max_listpack_value.store(len, Ordering::Relaxed);
+    }
+
+    pub fn zset_max_listpack_entries(&self) -> usize {
+        self.zset_max_listpack_entries.load(Ordering::Relaxed)
+    }
+
+    pub fn set_zset_max_listpack_entries(&self, entries: usize) {
+        self.zset_max_listpack_entries
+            .store(entries, Ordering::Relaxed);
+    }
+
+    pub fn zset_max_listpack_value(&self) -> usize {
+        self.zset_max_listpack_value.load(Ordering::Relaxed)
+    }
+
+    pub fn set_zset_max_listpack_value(&self, len: usize) {
+        self.zset_max_listpack_value.store(len, Ordering::Relaxed);
+    }
+
+    pub fn active_expire_cycle_keys(&self) -> usize {
+        self.active_expire_cycle_keys.load(Ordering::Relaxed)
+    }
+
+    pub fn set_active_expire_cycle_keys(&self, keys: usize) {
+        self.active_expire_cycle_keys.store(keys, Ordering::Relaxed);
+    }
+
+    pub fn active_expire_cycle_time(&self) -> Duration {
+        Duration::from_millis(self.active_expire_cycle_ms.load(Ordering::Relaxed))
+    }
+
+    pub fn set_active_expire_cycle_time(&self, time: Duration) {
+        self.active_expire_cycle_ms
+            .store(time.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    pub fn active_expire_enabled(&self) -> bool {
+        self.active_expire_enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_active_expire_enabled(&self, enabled: bool) {
+        self.active_expire_enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn notify_keyspace_events(&self) -> NotifyFlags {
+        NotifyFlags::from_bits(self.notify_keyspace_events.load(Ordering::Relaxed))
+    }
+
+    pub fn set_notify_keyspace_events(&self, flags: NotifyFlags) {
+        self.notify_keyspace_events
+            .store(flags.bits(), Ordering::Relaxed);
+    }
+
+    /// Longest bulk string accepted from clients, checked at decode time.
+    pub fn proto_max_bulk_len(&self) -> usize {
+        self.proto_max_bulk_len.load(Ordering::Relaxed)
+    }
+
+    pub fn set_proto_max_bulk_len(&self, len: usize) {
+        self.proto_max_bulk_len.store(len, Ordering::Relaxed);
+    }
+
+    /// Most elements in one aggregate accepted from clients.
+    pub fn proto_max_multibulk_len(&self) -> usize {
+        self.proto_max_multibulk_len.load(Ordering::Relaxed)
+    }
+
+    pub fn set_proto_max_multibulk_len(&self, len: usize) {
+        self.proto_max_multibulk_len.store(len, Ordering::Relaxed);
+    }
+
+    /// Deepest nesting of aggregates accepted from clients.
+    pub fn proto_max_nesting_depth(&self) -> usize {
+        self.proto_max_nesting_depth.load(Ordering::Relaxed)
+    }
+
+    pub fn set_proto_max_nesting_depth(&self, depth: usize) {
+        self.proto_max_nesting_depth.store(depth, Ordering::Relaxed);
+    }
+
+    /// The limits a client request is checked against before it's decoded.
+    pub fn decode_limits(&self) -> DecodeLimits {
+        DecodeLimits {
+            max_depth: self.proto_max_nesting_depth(),
+            max_bulk_len: self.proto_max_bulk_len(),
+            max_elements: self.proto_max_multibulk_len(),
+        }
+    }
+
+    pub fn max_reply_size(&self) -> usize {
+        self.max_reply_size.load(Ordering::Relaxed)
+    }
+
+    pub fn set_max_reply_size(&self, size: usize) {
+        self.max_reply_size.store(size, Ordering::Relaxed);
+    }
+
+    /// Most client connections accepted at the same time.
+    pub fn maxclients(&self) -> usize {
+        self.maxclients.load(Ordering::Relaxed)
+    }
+
+    pub fn set_maxclients(&self, maxclients: usize) {
+        self.maxclients.store(maxclients, Ordering::Relaxed);
+    }
+
+    /// Commands per second one connection may run, 0 means unlimited.
+    pub fn client_command_rate_limit(&self) -> usize {
+        self.client_command_rate_limit.load(Ordering::Relaxed)
+    }
+
+    pub fn set_client_command_rate_limit(&self, rate: usize) {
+        self.client_command_rate_limit
+            .store(rate, Ordering::Relaxed);
+    }
+
+    /// Memory limit in bytes for the data set, 0 means unlimited.
+    pub fn maxmemory(&self) -> usize {
+        self.maxmemory.load(Ordering::Relaxed)
+    }
+
+    pub fn set_maxmemory(&self, bytes: usize) {
+        self.maxmemory.store(bytes, Ordering::Relaxed);
+    }
+
+    /// Most messages waiting for one subscriber, 0 for no limit.
+    pub fn pubsub_buffer_limit(&self) -> usize {
+        self.pubsub_buffer_limit.load(Ordering::Relaxed)
+    }
+
+    pub fn set_pubsub_buffer_limit(&self, limit: usize) {
+        self.pubsub_buffer_limit.store(limit, Ordering::Relaxed);
+    }
+
+    pub fn pubsub_overflow(&self) -> PubSubOverflow {
+        match self.pubsub_overflow.load(Ordering::Relaxed) {
+            0 => PubSubOverflow::DropOldest,
+            1 => PubSubOverflow::DropNewest,
+            _ => PubSubOverflow::Disconnect,
+        }
+    }
+
+    pub fn set_pubsub_overflow(&self, policy: PubSubOverflow) {
+        self.pubsub_overflow.store(policy as u8, Ordering::Relaxed);
+    }
+
+    pub fn strict_resp2(&self) -> bool {
+        self.strict_resp2.load(Ordering::Relaxed)
+    }
+
+    pub fn set_strict_resp2(&self, strict: bool) {
+        self.strict_resp2.store(strict, Ordering::Relaxed);
+    }
+
+    pub fn sort_replies(&self) -> bool {
+        self.sort_replies.load(Ordering::Relaxed)
+    }
+
+    pub fn set_sort_replies(&self, sort: bool) {
+        self.sort_replies.store(sort, Ordering::Relaxed);
+    }
+
+    /// Protocol replies are encoded with on a connection that negotiated
+    /// `negotiated`, always RESP2 in strict mode.
+    pub fn reply_version(&self, negotiated: RespVersion) -> RespVersion {
+        if self.strict_resp2() {
+            RespVersion::Resp2
+        } else {
+            negotiated
+        }
+    }
+
+    /// Expose the built-in command `name` as `new_name`, like rename-command.
+    /// An empty `new_name` disables the command. Either way the original name
+    /// stops working.
+    pub fn rename_command(&self, name: &str, new_name: &str) {
+        let (name, new_name) = (name.to_lowercase(), new_name.to_lowercase());
+        let mut renames = self.command_renames.write().unwrap();
+        renames
+            .aliases
+            .retain(|_, builtin| *builtin != name.as_bytes());
+        if !new_name.is_empty() {
+            renames
+                .aliases
+                .insert(new_name.into_bytes(), name.clone().into_bytes());
+        }
+        renames.hidden.insert(name.into_bytes());
+    }
+
+    /// The built-in command a requested `name` stands for, None when that
+    /// command was renamed or disabled.
+    pub fn resolve_command<'a>(&self, name: &'a [u8]) -> Option<Cow<'a, [u8]>> {
+        let renames = self.command_renames.read().unwrap();
+        if let Some(builtin) = renames.aliases.get(name) {
+            return Some(Cow::Owned(builtin.clone()));
+        }
+        (!renames.hidden.contains(name)).then_some(Cow::Borrowed(name))
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use bytes::BytesMut;
+use dashmap::DashMap;
+
+use crate::{Backend, BulkString, RespArray, RespDecodeV2, RespEncode, RespFrame};
+
+use super::keys::Value;
+use super::{string_value, BackendError, SetMembers, ZSet};
+
+// DUMP 的格式：RESP 数组，第一项是类型名，之后是类型相关的数据。
+// 有序集合按 member、score 成对存放，score 用 f64 的 Display 输出，可以无损解析（包括 inf）
+impl Backend {
+    /// Serialize the value at `key` for RESTORE, None if the key is missing.
+    /// The TTL is not included, like in Redis.
+    pub fn dump(&self, key: &str) -> Option<Vec<u8>> {
+        let value = self.value(key)?;
+        let bulk = |s: &str| -> RespFrame { BulkString::from(s).into() };
+        let frames: Vec<RespFrame> = match value {
+            Value::String(v) => vec![bulk("string"), BulkString::new(string_value(&v)).into()],
+            // 字段的过期时间不会被保存
+            Value::Hash(fields, _) => std::iter::once(bulk("hash"))
+                .chain(fields.into_iter().flat_map(|(field, value)| {
+                    [bulk(&field), BulkString::new(string_value(&value)).into()]
+                }))
+                .collect(),
+            Value::Set(members) => std::iter::once(bulk("set"))
+                .chain(members.iter().map(bulk))
+                .collect(),
+            Value::List(items) => std::iter::once(bulk("list"))
+                .chain(
+                    items
+                        .iter()
+                        .map(|item| BulkString::new(string_value(item)).into()),
+                )
+                .collect(),
+            Value::ZSet(zset) => std::iter::once(bulk("zset"))
+                .chain(
+                    zset.iter()
+                        .flat_map(|(member, score)| [bulk(member), bulk(&score.to_string())]),
+                )
+                .collect(),
+        };
+        Some(RespArray::new(frames).encode())
+    }
+
+    /// Create `key` from a DUMP payload, expiring after `ttl` if given. An
+    /// existing key is only overwritten with `replace`.
+    pub fn restore(
+        &self,
+        key: String,
+        ttl: Option<Duration>,
+        payload: &[u8],
+        replace: bool,
+    ) -> Result<(), BackendError> {
+        // 先解析再检查 key，错误的 payload 不会删掉已有的值
+        let value = parse_payload(payload).ok_or(BackendError::BadDumpPayload)?;
+        let deadline = ttl.map(|ttl| self.deadline(ttl)).transpose()?;
+        let guard = self.lock_key(&key);
+        if self.key_type(&key).is_some() {
+            if !replace {
+                return Err(BackendError::BusyKey);
+            }
+            self.remove(&key);
+        }
+        self.insert_value(key.clone(), value);
+        if let Some(deadline) = deadline {
+            self.expires.insert(key.clone(), deadline);
+        }
+        // 和其他写入一样，不留下空的集合
+        if !self.remove_if_empty(&key) {
+            self.touch(&key);
+        }
+        drop(guard);
+        self.notify_list_ready();
+        Ok(())
+    }
+}
+
+fn parse_payload(payload: &[u8]) -> Option<Value> {
+    let mut buf = BytesMut::from(payload);
+    let RespFrame::Array(frames) = <RespFrame as RespDecodeV2>::decode(&mut buf).ok()? else {
+        return None;
+    };
+    if !buf.is_empty() {
+        return None;
+    }
+    let mut args = frames
+        .iter()
+        .map(|frame| match frame {
+            RespFrame::BulkString(s) => s.get_data().ok(),
+            _ => None,
+        })
+        .collect::<Option<Vec<Vec<u8>>>>()?
+        .into_iter();
+    let text = |arg: Vec<u8>| String::from_utf8(arg).ok();
+    let pairs = |args: Vec<Vec<u8>>| {
+        args.len().is_multiple_of(2).then(|| {
+            let mut args = args.into_iter();
+            std::iter::from_fn(move || Some((args.next()?, args.next()?)))
+        })
+    };
+    let value = match args.next()?.as_slice() {
+        b"string" => {
+            let value = args.next()?;
+            if args.next().is_some() {
+                return None;
+            }
+            Value::String(BulkString::new(value).into())
+        }
+        b"hash" => {
+            let fields = DashMap::new();
+            for (field, value) in pairs(args.collect())? {
+                fields.insert(text(field)?, BulkString::new(value).into());
+            }
+            Value::Hash(fields, HashMap::new())
+        }
+        b"set" => Value::Set(args.map(text).collect::<Option<SetMembers>>()?),
+        b"list" => Value::List(
+            args.map(|item| BulkString::new(item).into())
+                .collect::<VecDeque<RespFrame>>(),
+        ),
+        b"zset" => {
+            let mut zset = ZSet::new();
+            for (member, score) in pairs(args.collect())? {
+                let score: f64 = text(score)?.parse().ok()?;
+                if score.is_nan() {
+                    return None;
+                }
+                zset.insert(text(member)?, score);
+            }
+            Value::ZSet(zset)
+        }
+        _ => return None,
+    };
+    // Redis 中不存在空的集合类型，这样的 payload 视为无效
+    let empty = match &value {
+        Value::String(_) => false,
+        Value::Hash(fields, _) => fields.is_empty(),
+        Value::Set(members) => members.is_empty(),
+        Value::List(items) => items.is_empty(),
+        Value::ZSet(zset) => zset.is_empty(),
+    };
+    (!empty).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dump_restore_zset_keeps_scores_exactly() -> Result<(), BackendError> {
+        let backend = Backend::new();
+        let members = [
+            (f64::NEG_INFINITY, "a"),
+            (-0.1, "b"),
+            (1e-300, "c"),
+            (0.1 + 0.2, "d"),
+            (1.0 / 3.0, "e"),
+            (1.0 / 3.0, "f"),
+            (f64::INFINITY, "g"),
+        ];
+        backend.zadd(
+            "z".to_string(),
+            members
+                .iter()
+                .map(|(score, member)| (*score, member.to_string()))
+                .collect(),
+        )?;
+        let everything = |key| backend.zrange(key, &crate::ZRangeBy::Index(0, -1), false, None);
+
+        let payload = backend.dump("z").expect("z exists");
+        backend.restore("restored".to_string(), None, &payload, false)?;
+        assert_eq!(everything("restored")?, everything("z")?);
+        let expected: Vec<(String, f64)> = members
+            .iter()
+            .map(|(score, member)| (member.to_string(), *score))
+            .collect();
+        assert_eq!(everything("restored")?, expected);
+
+        // COPY 得到的副本同样保持分数和顺序
+        assert!(backend.copy("z", "copied", false));
+        assert_eq!(everything("copied")?, expected);
+
+        assert_eq!(
+            backend.restore("restored".to_string(), None, &payload, false),
+            Err(BackendError::BusyKey)
+        );
+        assert_eq!(
+            backend.restore(
+                "bad".to_string(),
+                None,
+                b"*2\r\n$4\r\nzset\r\n$1\r\nm\r\n",
+                false
+            ),
+            Err(BackendError::BadDumpPayload)
+        );
+        assert_eq!(backend.dump("missing"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_restore_other_types() -> Result<(), BackendError> {
+        let backend = Backend::new();
+        backend.set("s".to_string(), BulkString::from("value").into());
+        backend.hset("h".to_string(), "f".to_string(), b"v".into())?;
+        backend.sadd("set".to_string(), "m".to_string())?;
+        backend.rpush("l".to_string(), vec![b"a".into(), b"b".into()])?;
+
+        for key in ["s", "h", "set", "l"] {
+            let payload = backend.dump(key).expect("key exists");
+            let copy = format!("{}-restored", key);
+            backend.restore(copy.clone(), None, &payload, false)?;
+            assert_eq!(backend.dump(&copy), Some(payload));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_rejects_empty_collections() {
+        let backend = Backend::new();
+        for kind in ["hash", "set", "list", "zset"] {
+            let payload = RespArray::new(vec![BulkString::from(kind).into()]).encode();
+            assert_eq!(
+                backend.restore(kind.to_string(), None, &payload, false),
+                Err(BackendError::BadDumpPayload)
+            );
+            assert_eq!(backend.key_type(kind), None);
+        }
+    }
+}
@@ -0,0 +1,458 @@
+use super::{parse_i64, SetMembers, ZSet};
+use crate::{Backend, RespFrame};
+
+// 不超过该长度的字符串使用 embstr 编码，和 Redis 的 OBJ_ENCODING_EMBSTR_SIZE_LIMIT 一致
+const EMBSTR_SIZE_LIMIT: usize = 44;
+
+impl Backend {
+    /// The internal encoding redis would report for `key` via OBJECT ENCODING.
+    pub fn object_encoding(&self, key: &str) -> Option<&'static str> {
+        self.expire_if_needed(key);
+        if let Some(list) = self.list.get(key) {
+            return if list.len() <= self.config.list_max_listpack_size() {
+                Some("listpack")
+            } else {
+                Some("quicklist")
+            };
+        }
+        if let Some(value) = self.map.get(key) {
+            if self.raw_strings.contains(key) {
+                return Some("raw");
+            }
+            return Some(string_encoding(value.value()));
+        }
+        if let Some(set) = self.set.get(key) {
+            let current = self.set_encoding_for(&set);
+            let encoding = self
+                .set_encodings
+                .get(key)
+                .map_or(current, |e| current.max(*e));
+            return Some(encoding.as_str());
+        }
+        if self.hmap.contains_key(key) {
+            return Some("hashtable");
+        }
+        if let Some(zset) = self.zset.get(key) {
+            let current = self.zset_encoding_for(&zset);
+            let encoding = self
+                .zset_encodings
+                .get(key)
+                .map_or(current, |e| current.max(*e));
+            return Some(encoding.as_str());
+        }
+        None
+    }
+}
+
+/// Internal encodings of a set, from the most to the least compact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum SetEncoding {
+    Intset,
+    Listpack,
+    Hashtable,
+}
+
+impl SetEncoding {
+    fn as_str(self) -> &'static str {
+        match self {
+            SetEncoding::Intset => "intset",
+            SetEncoding::Listpack => "listpack",
+            SetEncoding::Hashtable => "hashtable",
+        }
+    }
+}
+
+impl Backend {
+    /// Record the encoding of the set at `key` after it grew. Like Redis the
+    /// encoding is only ever upgraded, SREM never converts it back.
+    pub(crate) fn upgrade_set_encoding(&self, key: &str) {
+        // 已经是 hashtable 时不必再遍历集合
+        if self
+            .set_encodings
+            .get(key)
+            .is_some_and(|e| *e == SetEncoding::Hashtable)
+        {
+            return;
+        }
+        // 先算出编码并释放集合的锁再写 set_encodings，和 object_encoding 一样
+        // 总是先锁 set 再锁 set_encodings，避免死锁
+        let Some(current) = self.set.get(key).map(|set| self.set_encoding_for(&set)) else {
+            return;
+        };
+        self.set_encodings
+            .entry(key.to_string())
+            .and_modify(|e| *e = (*e).max(current))
+            .or_insert(current);
+    }
+
+    // 只根据当前内容计算编码，不考虑曾经达到过的编码
+    fn set_encoding_for(&self, set: &SetMembers) -> SetEncoding {
+        let config = &self.config;
+        if set.len() <= config.set_max_intset_entries() && set.iter().all(is_int) {
+            SetEncoding::Intset
+        } else if set.len() <= config.set_max_listpack_entries()
+            && set
+                .iter()
+                .all(|m| m.len() <= config.set_max_listpack_value())
+        {
+            SetEncoding::Listpack
+        } else {
+            SetEncoding::Hashtable
+        }
+    }
+}
+
+/// Internal encodings of a sorted set, from the most to the least compact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum ZSetEncoding {
+    Listpack,
+    Skiplist,
+}
+
+impl ZSetEncoding {
+    fn as_str(self) -> &'static str {
+        match self {
+            ZSetEncoding::Listpack => "listpack",
+            ZSetEncoding::Skiplist => "skiplist",
+        }
+    }
+}
+
+impl Backend {
+    /// Record the encoding of the sorted set at `key` after it grew, removing
+    /// members never converts a skiplist back to a listpack.
+    pub(crate) fn upgrade_zset_encoding(&self, key: &str) {
+        // 已经是 skiplist 时不必再遍历成员
+        if self
+            .zset_encodings
+            .get(key)
+            .is_some_and(|e| *e == ZSetEncoding::Skiplist)
+        {
+            return;
+        }
+        // 和集合一样，先锁 zset 再锁 zset_encodings
+        let Some(current) = self.zset.get(key).map(|zset| self.zset_encoding_for(&zset)) else {
+            return;
+        };
+        self.zset_encodings
+            .entry(key.to_string())
+            .and_modify(|e| *e = (*e).max(current))
+            .or_insert(current);
+    }
+
+    fn zset_encoding_for(&self, zset: &ZSet) -> ZSetEncoding {
+        let config = &self.config;
+        if zset.len() <= config.zset_max_listpack_entries()
+            && zset
+                .iter()
+                .all(|(member, _)| member.len() <= config.zset_max_listpack_value())
+        {
+            ZSetEncoding::Listpack
+        } else {
+            ZSetEncoding::Skiplist
+        }
+    }
+}
+
+impl Backend {
+    /// Number of quicklist nodes the list at `key` would use, each node holding
+    /// up to `list-max-listpack-size` elements.
+    pub fn quicklist_nodes(&self, key: &str) -> Option<usize> {
+        self.expire_if_needed(key);
+        let node_size = self.config.list_max_listpack_size().max(1);
+        self.list
+            .get(key)
+            .map(|list| list.len().div_ceil(node_size))
+    }
+}
+
+fn string_encoding(value: &RespFrame) -> &'static str {
+    match value {
+        RespFrame::Integer(_) => "int",
+        RespFrame::BulkString(s) if parse_i64(s).is_some() => "int",
+        RespFrame::BulkString(s) if s.len() <= EMBSTR_SIZE_LIMIT => "embstr",
+        _ => "raw",
+    }
+}
+
+// 只有规范形式的整数（无前导 0、无 "+"、不超出 i64）才会以 int 编码保存
+fn is_int(s: &str) -> bool {
+    parse_i64(s.as_bytes()).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BulkString;
+
+    #[test]
+    fn test_list_encoding_threshold() {
+        let backend = Backend::new();
+        backend.config().set_list_max_listpack_size(4);
+        assert_eq!(backend.object_encoding("list"), None);
+
+        backend
+            .rpush("list".to_string(), vec![b"a".into(); 4])
+            .unwrap();
+        assert_eq!(backend.object_encoding("list"), Some("listpack"));
+
+        backend
+            .rpush("list".to_string(), vec![b"a".into()])
+            .unwrap();
+        assert_eq!(backend.object_encoding("list"), Some("quicklist"));
+    }
+
+    #[test]
+    fn test_zset_encoding_never_downgrades() {
+        let backend = Backend::new();
+        backend.config().set_zset_max_listpack_entries(4);
+        backend.config().set_zset_max_listpack_value(8);
+        let members = |range: std::ops::Range<usize>| {
+            range
+                .map(|i| (i as f64, format!("m{i}")))
+                .collect::<Vec<_>>()
+        };
+
+        backend.zadd("z".to_string(), members(0..4)).unwrap();
+        assert_eq!(backend.object_encoding("z"), Some("listpack"));
+        backend.zadd("z".to_string(), members(4..5)).unwrap();
+        assert_eq!(backend.object_encoding("z"), Some("skiplist"));
+
+        // 删除成员后仍然是 skiplist
+        backend.zmpop(&["z".to_string()], true, 4).unwrap();
+        assert_eq!(backend.zset.get("z").map(|zset| zset.len()), Some(1));
+        assert_eq!(backend.object_encoding("z"), Some("skiplist"));
+
+        // 成员过长同样转为 skiplist，key 删除后重新计算
+        backend
+            .zadd("long".to_string(), vec![(1.0, "x".repeat(9))])
+            .unwrap();
+        assert_eq!(backend.object_encoding("long"), Some("skiplist"));
+        backend.zmpop(&["long".to_string()], true, 1).unwrap();
+        assert_eq!(backend.object_encoding("long"), None);
+        backend
+            .zadd("long".to_string(), vec![(1.0, "x".to_string())])
+            .unwrap();
+        assert_eq!(backend.object_encoding("long"), Some("listpack"));
+    }
+
+    #[test]
+    fn test_string_encoding_threshold() {
+        let backend = Backend::new();
+        backend.set("embstr".to_string(), BulkString::new(vec![b'a'; 44]).into());
+        backend.set("raw".to_string(), BulkString::new(vec![b'a'; 45]).into());
+        backend.set("int".to_string(), b"12345".into());
+        backend.set("padded".to_string(), b"012345".into());
+
+        assert_eq!(backend.object_encoding("embstr"), Some("embstr"));
+        assert_eq!(backend.object_encoding("raw"), Some("raw"));
+        assert_eq!(backend.object_encoding("int"), Some("int"));
+        assert_eq!(backend.object_encoding("padded"), Some("embstr"));
+    }
+
+    #[test]
+    fn test_string_int_encoding_is_canonical() {
+        let backend = Backend::new();
+        for (value, encoding) in [
+            ("7", "int"),
+            ("0", "int"),
+            ("-7", "int"),
+            ("9223372036854775807", "int"),
+            ("-9223372036854775808", "int"),
+            ("007", "embstr"),
+            ("+7", "embstr"),
+            ("-0", "embstr"),
+            (" 7", "embstr"),
+            ("9223372036854775808", "embstr"),
+            ("-9999999999999999999", "embstr"),
+        ] {
+            backend.set("key".to_string(), BulkString::from(value).into());
+            assert_eq!(backend.object_encoding("key"), Some(encoding), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_string_range_ops_on_int_encoded_value() {
+        let backend = Backend::new();
+        backend.incr_by("n".to_string(), 100).unwrap();
+        assert_eq!(backend.object_encoding("n"), Some("int"));
+        assert_eq!(backend.getrange("n", 0, 1).unwrap(), b"10");
+        assert_eq!(backend.object_encoding("n"), Some("int"));
+
+        // SETRANGE 后仍然是整数形式，但编码变为 raw
+        assert_eq!(backend.setrange("n".to_string(), 2, b"5").unwrap(), 3);
+        assert_eq!(backend.getrange("n", 0, -1).unwrap(), b"105");
+        assert_eq!(backend.object_encoding("n"), Some("raw"));
+
+        // 重新写入整数后恢复 int 编码
+        assert_eq!(backend.incr_by("n".to_string(), 1).unwrap(), 106);
+        assert_eq!(backend.object_encoding("n"), Some("int"));
+
+        backend.set("s".to_string(), RespFrame::Integer(7));
+        backend.append("s".to_string(), b"8").unwrap();
+        assert_eq!(backend.getrange("s", 0, -1).unwrap(), b"78");
+        assert_eq!(backend.object_encoding("s"), Some("raw"));
+        backend.del(&["s".to_string()]);
+        backend.set("s".to_string(), b"9".into());
+        assert_eq!(backend.object_encoding("s"), Some("int"));
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    enum StringOp<'a> {
+        Set(&'a str),
+        Append(&'a str),
+        SetRange(usize, &'a str),
+        Incr,
+    }
+
+    #[test]
+    fn test_string_encoding_transitions() {
+        use StringOp::*;
+
+        let long = "x".repeat(EMBSTR_SIZE_LIMIT + 1);
+        let long = long.as_str();
+        // 每组操作依次执行，每一步之后检查 OBJECT ENCODING
+        let cases: &[&[(StringOp, &str)]] = &[
+            &[(Set("100"), "int"), (Append("1"), "raw"), (Incr, "int")],
+            &[
+                (Set("100"), "int"),
+                (SetRange(0, "2"), "raw"),
+                (Incr, "int"),
+            ],
+            &[
+                (Set("abc"), "embstr"),
+                (Set(long), "raw"),
+                (Set("abc"), "embstr"),
+            ],
+            &[
+                (Set("abc"), "embstr"),
+                (Append("d"), "raw"),
+                (Set("1"), "int"),
+            ],
+            &[(Set("abc"), "embstr"), (SetRange(1, "z"), "raw")],
+            &[(Set("1"), "int"), (Append("0"), "raw"), (Incr, "int")],
+            &[(Incr, "int"), (Set("abc"), "embstr"), (Set("42"), "int")],
+            &[(Append("7"), "raw"), (Incr, "int")],
+            &[(SetRange(2, "a"), "raw"), (Set(long), "raw")],
+        ];
+        for (i, ops) in cases.iter().enumerate() {
+            let backend = Backend::new();
+            for (op, expected) in ops.iter() {
+                match *op {
+                    Set(v) => backend.set("k".to_string(), BulkString::from(v).into()),
+                    Append(v) => {
+                        backend.append("k".to_string(), v.as_bytes()).unwrap();
+                    }
+                    SetRange(offset, v) => {
+                        backend
+                            .setrange("k".to_string(), offset, v.as_bytes())
+                            .unwrap();
+                    }
+                    Incr => {
+                        backend.incr_by("k".to_string(), 1).unwrap();
+                    }
+                }
+                assert_eq!(
+                    backend.object_encoding("k"),
+                    Some(*expected),
+                    "case {i} after {op:?}"
+                );
+            }
+        }
+    }
+
+    // 每种类型允许出现的编码
+    fn encoding_matches_type(key_type: &str, encoding: &str) -> bool {
+        let allowed: &[&str] = match key_type {
+            "string" => &["int", "embstr", "raw"],
+            "list" => &["listpack", "quicklist"],
+            "set" => &["intset", "listpack", "hashtable"],
+            "hash" => &["listpack", "hashtable"],
+            "zset" => &["listpack", "skiplist"],
+            _ => &[],
+        };
+        allowed.contains(&encoding)
+    }
+
+    #[test]
+    fn test_object_encoding_agrees_with_type() {
+        let backend = Backend::new();
+        backend.config().set_list_max_listpack_size(2);
+        let keys = ["str", "list", "set", "hash", "zset", "copy"];
+        let check = |step: &str| {
+            for key in keys {
+                let key_type = backend.key_type(key);
+                let encoding = backend.object_encoding(key);
+                match (key_type, encoding) {
+                    (None, None) => {}
+                    (Some(t), Some(e)) => assert!(
+                        encoding_matches_type(t, e),
+                        "{key} has type {t} but encoding {e} after {step}"
+                    ),
+                    (t, e) => panic!("{key}: type {t:?} and encoding {e:?} after {step}"),
+                }
+            }
+        };
+
+        // 每一步都会改变某个 key 的类型或编码，之后检查所有 key
+        let steps = [
+            "set int",
+            "append",
+            "rpush",
+            "sadd int",
+            "sadd str",
+            "sadd many",
+            "hset",
+            "zadd",
+            "copy list",
+            "copy set over list",
+            "rename zset",
+            "set over hash",
+            "del list",
+            "flushdb",
+        ];
+        check("start");
+        for step in steps {
+            match step {
+                "set int" => backend.set("str".to_string(), b"1".into()),
+                "append" => {
+                    backend.append("str".to_string(), b"x").unwrap();
+                }
+                "rpush" => {
+                    backend
+                        .rpush("list".to_string(), vec![b"a".into(); 3])
+                        .unwrap();
+                }
+                "sadd int" => backend.sadd("set".to_string(), "1".to_string()).unwrap(),
+                "sadd str" => backend.sadd("set".to_string(), "a".to_string()).unwrap(),
+                "sadd many" => {
+                    for i in 0..600 {
+                        backend.sadd("set".to_string(), format!("m{i}")).unwrap();
+                    }
+                }
+                "hset" => backend
+                    .hset("hash".to_string(), "f".to_string(), b"v".into())
+                    .unwrap(),
+                "zadd" => {
+                    backend
+                        .zadd("zset".to_string(), vec![(1.0, "m".to_string())])
+                        .unwrap();
+                }
+                "copy list" => {
+                    backend.copy("list", "copy", false);
+                }
+                "copy set over list" => {
+                    backend.copy("set", "copy", true);
+                }
+                "rename zset" => backend.rename("zset", "copy").unwrap(),
+                "set over hash" => backend.set("hash".to_string(), b"v".into()),
+                "del list" => {
+                    backend.del(&["list".to_string()]);
+                }
+                "flushdb" => backend.flushdb(),
+                _ => unreachable!(),
+            }
+            check(step);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use thiserror::Error;
+
+use crate::RespFrame;
+
+/// Storage-level failures, as opposed to `CommandError` which covers
+/// malformed requests.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum BackendError {
+    #[error("WRONGTYPE Operation against a key holding the wrong kind of value")]
+    WrongType,
+    #[error("OOM command not allowed when used memory > 'maxmemory'.")]
+    OutOfMemory,
+    #[error("ERR no such key")]
+    NoSuchKey,
+    #[error("ERR resulting score is not a number (NaN)")]
+    NanScore,
+    #[error("ERR value is not an integer or out of range")]
+    NotInteger,
+    #[error("ERR increment or decrement would overflow")]
+    Overflow,
+    #[error("ERR string exceeds maximum allowed size (proto-max-bulk-len)")]
+    StringTooLong,
+    #[error("ERR hash value is not a float")]
+    HashNotFloat,
+    #[error("ERR increment would produce NaN or Infinity")]
+    NotFinite,
+    #[error("BUSYKEY Target key name already exists.")]
+    BusyKey,
+    #[error("ERR DUMP payload version or checksum are wrong")]
+    BadDumpPayload,
+    #[error("ERR invalid expire time")]
+    InvalidExpireTime,
+    #[error("ERR value is out of range")]
+    OutOfRange,
+}
+
+impl From<BackendError> for RespFrame {
+    fn from(e: BackendError) -> Self {
+        // 错误信息的第一个单词就是错误码
+        let msg = e.to_string();
+        let (code, msg) = msg.split_once(' ').unwrap_or(("ERR", &msg));
+        RespFrame::error(code, msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Backend, RespEncode};
+
+    #[test]
+    fn test_wrong_type_surfaces_as_resp_error() {
+        let backend = Backend::new();
+        backend.set("key".to_string(), b"value".into());
+
+        let err = backend.sadd("key".to_string(), "member".to_string());
+        assert_eq!(err, Err(BackendError::WrongType));
+        assert_eq!(
+            backend.rpush("key".to_string(), vec![b"a".into()]),
+            Err(BackendError::WrongType)
+        );
+
+        let frame: RespFrame = BackendError::WrongType.into();
+        assert_eq!(
+            frame.encode(),
+            b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n"
+        );
+        let frame: RespFrame = BackendError::NoSuchKey.into();
+        assert_eq!(frame.encode(), b"-ERR no such key\r\n");
+    }
+}
@@ -0,0 +1,348 @@
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use super::{Backend, BackendError};
+
+// 与 Redis 的 server.hz = 10 一致，每 100ms 运行一次主动过期
+const ACTIVE_EXPIRE_INTERVAL: Duration = Duration::from_millis(100);
+
+// PTTL / TTL 的返回码
+const NO_SUCH_KEY: i64 = -2;
+const NO_TTL: i64 = -1;
+
+impl Backend {
+    /// Set a time to live on `key`, returns false if the key does not exist.
+    /// A zero TTL deletes the key right away.
+    pub fn expire(&self, key: &str, ttl: Duration) -> Result<bool, BackendError> {
+        let deadline = self.deadline(ttl)?;
+        if self.key_type(key).is_none() {
+            return Ok(false);
+        }
+        if ttl.is_zero() {
+            self.remove_value(key);
+        } else {
+            self.expires.insert(key.to_string(), deadline);
+        }
+        Ok(true)
+    }
+
+    /// Like `expire` but with an absolute unix time, a time in the past
+    /// deletes the key right away.
+    pub fn expire_at(&self, key: &str, at: SystemTime) -> Result<bool, BackendError> {
+        let ttl = at
+            .duration_since(self.clock.system_now())
+            .unwrap_or(Duration::ZERO);
+        self.expire(key, ttl)
+    }
+
+    /// Current unix time in milliseconds, as seen by the backend clock.
+    pub(crate) fn unix_millis(&self) -> i64 {
+        self.clock
+            .system_now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as i64)
+    }
+
+    /// The instant `ttl` from now, an error if it can't be represented.
+    pub(crate) fn deadline(&self, ttl: Duration) -> Result<Instant, BackendError> {
+        // 和 Redis 一样，过期时刻换算成 unix 毫秒时间戳后也不能溢出
+        i64::try_from(ttl.as_millis())
+            .ok()
+            .and_then(|ms| self.unix_millis().checked_add(ms))
+            .and_then(|_| self.clock.now().checked_add(ttl))
+            .ok_or(BackendError::InvalidExpireTime)
+    }
+
+    /// Remove the TTL of `key`, returns whether it had one.
+    pub fn persist(&self, key: &str) -> bool {
+        self.key_type(key).is_some() && self.expires.remove(key).is_some()
+    }
+
+    /// Remaining time to live of `key` in milliseconds, -1 if it has no TTL and
+    /// -2 if it does not exist.
+    pub fn pttl(&self, key: &str) -> i64 {
+        if self.key_type(key).is_none() {
+            return NO_SUCH_KEY;
+        }
+        match self.expires.get(key) {
+            Some(deadline) => deadline
+                .saturating_duration_since(self.clock.now())
+                .as_millis() as i64,
+            None => NO_TTL,
+        }
+    }
+
+    /// Like `pttl` but rounded to seconds.
+    pub fn ttl(&self, key: &str) -> i64 {
+        match self.pttl(key) {
+            ms if ms < 0 => ms,
+            ms => (ms + 500) / 1000,
+        }
+    }
+
+    /// Every key with a pending TTL and its remaining time, sorted by key.
+    /// Keys whose TTL already passed but that were not reaped yet are left
+    /// out, as they are gone for any client.
+    pub fn list_expiries(&self) -> Vec<(String, Duration)> {
+        let now = self.clock.now();
+        let mut expiries: Vec<_> = self
+            .expires
+            .iter()
+            .filter(|entry| *entry.value() > now)
+            .map(|entry| (entry.key().clone(), *entry.value() - now))
+            .collect();
+        expiries.sort();
+        expiries
+    }
+
+    /// One cycle of active expiry: remove keys whose TTL has passed, stopping
+    /// once the per-cycle key or time budget from the config is used up so a
+    /// mass expiry can't stall the server. Returns how many keys were removed,
+    /// always 0 while active expiry is disabled.
+    pub fn active_expire_cycle(&self) -> usize {
+        if !self.config.active_expire_enabled() {
+            return 0;
+        }
+        let keys = self.expired_keys(
+            self.config.active_expire_cycle_keys(),
+            self.config.active_expire_cycle_time(),
+        );
+        keys.iter().filter(|key| self.expire_if_needed(key)).count()
+    }
+
+    /// Run `active_expire_cycle` periodically from a background thread, which
+    /// exits once the backend is dropped.
+    pub fn spawn_active_expire(&self) {
+        let inner = Arc::downgrade(&self.inner);
+        thread::spawn(move || loop {
+            thread::sleep(ACTIVE_EXPIRE_INTERVAL);
+            match inner.upgrade() {
+                Some(inner) => {
+                    Backend { inner, touch: true }.active_expire_cycle();
+                }
+                None => return,
+            }
+        });
+    }
+
+    /// Lazily delete `key` if its TTL has passed, returns whether it did.
+    pub(crate) fn expire_if_needed(&self, key: &str) -> bool {
+        let now = self.clock.now();
+        if self.expires.remove_if(key, |_, d| *d <= now).is_none() {
+            return false;
+        }
+        self.remove_value(key);
+        self.record_expired_key(key);
+        // 和 Redis 一样以 DEL 写入 AOF，重放时不依赖时钟
+        self.propagate(&["del", key]);
+        true
+    }
+
+    // 从上一轮停下的位置继续扫描，到末尾后回到开头。每轮都从头扫的话，
+    // 预算总是耗在前面没过期的 key 上，后面过期的 key 永远轮不到
+    pub(crate) fn expired_keys(&self, limit: usize, budget: Duration) -> Vec<String> {
+        // 时间预算按真实时间计算，是否过期按 clock 判断
+        let start = Instant::now();
+        let now = self.clock.now();
+        let len = self.expires.len();
+        let cursor = self.expire_cursor.load(Ordering::Relaxed) % len.max(1);
+        let mut keys = Vec::new();
+        let mut scanned = 0;
+        // 两段依次遍历，第一段遍历完才开始第二段，不会同时持有分片的读锁。
+        // 期间增删 key 会让位置有偏差，最多让个别 key 晚一圈被扫到
+        let entries = self
+            .expires
+            .iter()
+            .skip(cursor)
+            .chain(self.expires.iter().take(cursor));
+        for entry in entries {
+            if keys.len() >= limit || start.elapsed() >= budget {
+                break;
+            }
+            scanned += 1;
+            if *entry.value() <= now {
+                keys.push(entry.key().clone());
+            }
+        }
+        self.expire_cursor
+            .store((cursor + scanned) % len.max(1), Ordering::Relaxed);
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, Clock, ManualClock};
+
+    fn set(backend: &Backend, key: &str) {
+        backend.set(key.to_string(), BulkString::from("v").into());
+    }
+
+    #[test]
+    fn test_expire_and_ttl() {
+        let backend = Backend::new();
+        assert!(!backend.expire("missing", Duration::from_secs(10)).unwrap());
+        assert_eq!(backend.ttl("missing"), NO_SUCH_KEY);
+
+        set(&backend, "k");
+        assert_eq!(backend.ttl("k"), NO_TTL);
+        assert!(backend.expire("k", Duration::from_secs(10)).unwrap());
+        assert_eq!(backend.ttl("k"), 10);
+
+        // 普通的 SET 会清除 TTL
+        set(&backend, "k");
+        assert_eq!(backend.ttl("k"), NO_TTL);
+
+        assert!(backend.expire("k", Duration::ZERO).unwrap());
+        assert_eq!(backend.key_type("k"), None);
+    }
+
+    #[test]
+    fn test_expire_at_and_persist() {
+        let backend = Backend::new();
+        set(&backend, "k");
+        let at = SystemTime::now() + Duration::from_secs(100);
+        assert!(backend.expire_at("k", at).unwrap());
+        assert_eq!(backend.ttl("k"), 100);
+
+        assert!(backend.persist("k"));
+        assert!(!backend.persist("k"));
+        assert_eq!(backend.ttl("k"), NO_TTL);
+
+        assert!(backend.expire_at("k", SystemTime::UNIX_EPOCH).unwrap());
+        assert_eq!(backend.key_type("k"), None);
+    }
+
+    #[test]
+    fn test_lazy_expire_on_access() {
+        let clock = Arc::new(ManualClock::new());
+        let backend = Backend::with_clock(clock.clone());
+        set(&backend, "k");
+        backend.expire("k", Duration::from_secs(10)).unwrap();
+        clock.advance(Duration::from_secs(9));
+        assert_eq!(backend.ttl("k"), 1);
+        clock.advance(Duration::from_secs(1));
+
+        // 没有访问之前数据还在，访问时才删除
+        assert!(backend.map.contains_key("k"));
+        assert_eq!(backend.get("k").found(), None);
+        assert!(!backend.map.contains_key("k"));
+        assert!(!backend.expires.contains_key("k"));
+    }
+
+    #[test]
+    fn test_active_expire_respects_key_budget() {
+        let clock = Arc::new(ManualClock::new());
+        let backend = Backend::with_clock(clock.clone());
+        backend.config().set_active_expire_cycle_keys(100);
+        backend
+            .config()
+            .set_active_expire_cycle_time(Duration::from_secs(10));
+        for i in 0..1000 {
+            let key = format!("key:{}", i);
+            set(&backend, &key);
+            backend.expire(&key, Duration::from_millis(1)).unwrap();
+        }
+        set(&backend, "persistent");
+        clock.advance(Duration::from_millis(1));
+
+        assert_eq!(backend.active_expire_cycle(), 100);
+        assert_eq!(backend.map.len(), 901);
+
+        let mut cycles = 1;
+        while backend.active_expire_cycle() > 0 {
+            cycles += 1;
+        }
+        assert_eq!(cycles, 10);
+        assert_eq!(backend.map.len(), 1);
+        assert!(backend.expires.is_empty());
+    }
+
+    #[test]
+    fn test_expired_keys_resume_from_cursor() {
+        let clock = Arc::new(ManualClock::new());
+        let backend = Backend::with_clock(clock.clone());
+        for i in 0..20 {
+            let key = format!("key:{}", i);
+            set(&backend, &key);
+            backend.expire(&key, Duration::from_millis(1)).unwrap();
+        }
+        clock.advance(Duration::from_millis(1));
+
+        // 不删除找到的 key，下一轮也接着往后扫，不会重复返回同一批
+        let budget = Duration::from_secs(10);
+        let mut first = backend.expired_keys(10, budget);
+        let mut second = backend.expired_keys(10, budget);
+        assert_eq!(first.len(), 10);
+        assert_eq!(second.len(), 10);
+        first.append(&mut second);
+        first.sort();
+        first.dedup();
+        assert_eq!(first.len(), 20);
+    }
+
+    #[test]
+    fn test_active_expire_respects_time_budget() {
+        let clock = Arc::new(ManualClock::new());
+        let backend = Backend::with_clock(clock.clone());
+        backend
+            .config()
+            .set_active_expire_cycle_time(Duration::ZERO);
+        set(&backend, "k");
+        backend.expire("k", Duration::from_millis(1)).unwrap();
+        clock.advance(Duration::from_millis(1));
+
+        assert_eq!(backend.active_expire_cycle(), 0);
+        assert!(backend.map.contains_key("k"));
+    }
+
+    #[test]
+    fn test_ttl_boundaries() {
+        // 真实时钟下设置后立即读取，已经过去的几微秒不应让 TTL 变成 99
+        let backend = Backend::new();
+        set(&backend, "k");
+        backend.expire("k", Duration::from_secs(100)).unwrap();
+        assert_eq!(backend.ttl("k"), 100);
+
+        let clock = Arc::new(ManualClock::new());
+        let backend = Backend::with_clock(clock.clone());
+        set(&backend, "k");
+        backend.expire("k", Duration::from_secs(100)).unwrap();
+        assert_eq!(backend.ttl("k"), 100);
+        assert_eq!(backend.pttl("k"), 100_000);
+
+        // 剩余时间四舍五入到秒，和 Redis 一致
+        clock.advance(Duration::from_millis(499));
+        assert_eq!(backend.ttl("k"), 100);
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(backend.ttl("k"), 100);
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(backend.ttl("k"), 99);
+
+        // 到期前 1ms 还在，恰好到期时即视为过期
+        clock.advance(Duration::from_millis(99_498));
+        assert_eq!(backend.pttl("k"), 1);
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(backend.pttl("k"), NO_SUCH_KEY);
+    }
+
+    #[test]
+    fn test_expire_at_now_deletes_key() {
+        let clock = Arc::new(ManualClock::new());
+        let backend = Backend::with_clock(clock.clone());
+        set(&backend, "k");
+        assert!(backend.expire_at("k", clock.system_now()).unwrap());
+        assert_eq!(backend.key_type("k"), None);
+
+        set(&backend, "k");
+        assert!(backend
+            .expire_at("k", clock.system_now() + Duration::from_millis(1))
+            .unwrap());
+        assert_eq!(backend.pttl("k"), 1);
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(backend.ttl("k"), NO_SUCH_KEY);
+    }
+}
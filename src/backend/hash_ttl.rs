@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime};
+
+use super::{Backend, BackendError};
+use crate::RespFrame;
+
+// HEXPIRE / HTTL 的返回码，与 Redis 7.4 一致
+const NO_SUCH_FIELD: i64 = -2;
+const NO_TTL: i64 = -1;
+const TTL_SET: i64 = 1;
+const FIELD_DELETED: i64 = 2;
+
+impl Backend {
+    /// Set a time to live on each field of a hash, returns a status per field:
+    /// 1 if set, 2 if the field was deleted right away (zero TTL) and -2 if
+    /// the field does not exist.
+    pub fn hexpire(
+        &self,
+        key: &str,
+        ttl: Duration,
+        fields: &[String],
+    ) -> Result<Vec<i64>, BackendError> {
+        self.check_type(key, "hash")?;
+        self.purge_expired_fields(key);
+
+        let deadline = self.deadline(ttl)?;
+        let mut ret = Vec::with_capacity(fields.len());
+        if let Some(hmap) = self.hmap.get(key) {
+            let mut expires = self.hash_expires.entry(key.to_string()).or_default();
+            for field in fields {
+                if !hmap.contains_key(field) {
+                    ret.push(NO_SUCH_FIELD);
+                } else if ttl.is_zero() {
+                    hmap.remove(field);
+                    expires.remove(field);
+                    ret.push(FIELD_DELETED);
+                } else {
+                    expires.insert(field.clone(), deadline);
+                    ret.push(TTL_SET);
+                }
+            }
+        } else {
+            ret.resize(fields.len(), NO_SUCH_FIELD);
+        }
+        self.remove_hash_if_empty(key);
+        Ok(ret)
+    }
+
+    /// Like `hexpire` but the fields expire at the unix time `at`, a time in
+    /// the past deletes them.
+    pub fn hexpire_at(
+        &self,
+        key: &str,
+        at: SystemTime,
+        fields: &[String],
+    ) -> Result<Vec<i64>, BackendError> {
+        let ttl = at
+            .duration_since(self.clock.system_now())
+            .unwrap_or(Duration::ZERO);
+        self.hexpire(key, ttl, fields)
+    }
+
+    /// Remove the TTL of each field, returns a status per field: 1 if the
+    /// TTL was removed, -1 if the field has no TTL and -2 if it does not exist.
+    pub fn hpersist(&self, key: &str, fields: &[String]) -> Result<Vec<i64>, BackendError> {
+        self.check_type(key, "hash")?;
+        self.purge_expired_fields(key);
+
+        let Some(hmap) = self.hmap.get(key) else {
+            return Ok(vec![NO_SUCH_FIELD; fields.len()]);
+        };
+        let mut expires = self.hash_expires.get_mut(key);
+        Ok(fields
+            .iter()
+            .map(|field| {
+                if !hmap.contains_key(field) {
+                    NO_SUCH_FIELD
+                } else if expires.as_mut().is_some_and(|e| e.remove(field).is_some()) {
+                    TTL_SET
+                } else {
+                    NO_TTL
+                }
+            })
+            .collect())
+    }
+
+    /// Get the values of `fields` and delete them, the hash goes away with
+    /// its last field.
+    pub fn hgetdel(
+        &self,
+        key: &str,
+        fields: &[String],
+    ) -> Result<Vec<Option<RespFrame>>, BackendError> {
+        self.check_type(key, "hash")?;
+        self.purge_expired_fields(key);
+
+        let Some(hmap) = self.hmap.get(key) else {
+            return Ok(vec![None; fields.len()]);
+        };
+        let mut expires = self.hash_expires.get_mut(key);
+        let values = fields
+            .iter()
+            .map(|field| {
+                if let Some(expires) = expires.as_mut() {
+                    expires.remove(field);
+                }
+                hmap.remove(field).map(|(_, value)| value)
+            })
+            .collect();
+        drop((hmap, expires));
+        self.remove_hash_if_empty(key);
+        self.touch_existing(key);
+        Ok(values)
+    }
+
+    /// Remaining time to live of each field in seconds, -1 if the field has
+    /// no TTL and -2 if it does not exist.
+    pub fn httl(&self, key: &str, fields: &[String]) -> Result<Vec<i64>, BackendError> {
+        self.check_type(key, "hash")?;
+        self.purge_expired_fields(key);
+
+        let Some(hmap) = self.hmap.get(key) else {
+            return Ok(vec![NO_SUCH_FIELD; fields.len()]);
+        };
+        let expires = self.hash_expires.get(key);
+        let now = self.clock.now();
+        Ok(fields
+            .iter()
+            .map(|field| {
+                if !hmap.contains_key(field) {
+                    return NO_SUCH_FIELD;
+                }
+                match expires.as_ref().and_then(|e| e.get(field).copied()) {
+                    // 四舍五入到秒，和 Redis 的 TTL 一样
+                    Some(deadline) => ((deadline - now).as_millis() as i64 + 500) / 1000,
+                    None => NO_TTL,
+                }
+            })
+            .collect())
+    }
+
+    /// Lazily drop the fields of `key` whose TTL has passed, and the hash
+    /// itself once it is empty.
+    pub(crate) fn purge_expired_fields(&self, key: &str) {
+        let expired: Vec<String> = match self.hash_expires.get_mut(key) {
+            Some(mut expires) => {
+                let now = self.clock.now();
+                let expired = expires
+                    .iter()
+                    .filter(|(_, deadline)| **deadline <= now)
+                    .map(|(field, _)| field.clone())
+                    .collect::<Vec<_>>();
+                expires.retain(|_, deadline| *deadline > now);
+                expired
+            }
+            None => return,
+        };
+        if expired.is_empty() {
+            return;
+        }
+        if let Some(hmap) = self.hmap.get(key) {
+            for field in &expired {
+                hmap.remove(field);
+            }
+        }
+        self.mark_memory_dirty(key);
+        self.remove_hash_if_empty(key);
+        let mut args = vec!["hdel", key];
+        args.extend(expired.iter().map(String::as_str));
+        self.propagate(&args);
+    }
+
+    /// Forget the TTL of a field that was overwritten by HSET.
+    pub(crate) fn persist_field(&self, key: &str, field: &str) {
+        if let Some(mut expires) = self.hash_expires.get_mut(key) {
+            expires.remove(field);
+        }
+    }
+
+    pub(crate) fn take_field_expires(&self, key: &str) -> HashMap<String, Instant> {
+        self.hash_expires
+            .remove(key)
+            .map(|(_, expires)| expires)
+            .unwrap_or_default()
+    }
+
+    fn remove_hash_if_empty(&self, key: &str) {
+        if !self.remove_if_empty(key) {
+            self.hash_expires
+                .remove_if(key, |_, expires| expires.is_empty());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BulkString;
+
+    #[test]
+    fn test_hexpire_status_codes() {
+        let backend = Backend::new();
+        backend
+            .hset(
+                "h".to_string(),
+                "a".to_string(),
+                BulkString::from("1").into(),
+            )
+            .unwrap();
+        backend
+            .hset(
+                "h".to_string(),
+                "b".to_string(),
+                BulkString::from("2").into(),
+            )
+            .unwrap();
+
+        let fields = ["a".to_string(), "missing".to_string()];
+        assert_eq!(
+            backend.hexpire("h", Duration::from_secs(100), &fields),
+            Ok(vec![TTL_SET, NO_SUCH_FIELD])
+        );
+        assert_eq!(
+            backend.httl("h", &["a".to_string(), "b".to_string(), "c".to_string()]),
+            Ok(vec![100, NO_TTL, NO_SUCH_FIELD])
+        );
+        assert_eq!(
+            backend.httl("missing", &fields),
+            Ok(vec![NO_SUCH_FIELD, NO_SUCH_FIELD])
+        );
+
+        // HSET 覆盖字段时清掉 TTL
+        backend
+            .hset(
+                "h".to_string(),
+                "a".to_string(),
+                BulkString::from("3").into(),
+            )
+            .unwrap();
+        assert_eq!(backend.httl("h", &fields[..1]), Ok(vec![NO_TTL]));
+    }
+
+    #[test]
+    fn test_hexpire_zero_deletes_field_and_empty_hash() {
+        let backend = Backend::new();
+        backend
+            .hset(
+                "h".to_string(),
+                "a".to_string(),
+                BulkString::from("1").into(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            backend.hexpire("h", Duration::ZERO, &["a".to_string()]),
+            Ok(vec![FIELD_DELETED])
+        );
+        assert_eq!(backend.key_type("h"), None);
+        assert!(!backend.hash_expires.contains_key("h"));
+    }
+
+    #[test]
+    fn test_hexpire_wrong_type() {
+        let backend = Backend::new();
+        backend.set("s".to_string(), BulkString::from("v").into());
+        assert_eq!(
+            backend.hexpire("s", Duration::from_secs(1), &["a".to_string()]),
+            Err(BackendError::WrongType)
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Mutex, MutexGuard};
+
+use super::Backend;
+
+// 分段数；不同的 key 落在同一段时只是多等一会儿，不影响正确性
+const STRIPES: usize = 64;
+
+/// Striped locks held by the writers that may create a key. Each type lives
+/// in its own map, so checking that a key holds no other type and inserting
+/// it has to happen under one lock shared by every type.
+#[derive(Debug)]
+pub(crate) struct KeyLocks {
+    stripes: Vec<Mutex<()>>,
+}
+
+impl Default for KeyLocks {
+    fn default() -> Self {
+        Self {
+            stripes: (0..STRIPES).map(|_| Mutex::new(())).collect(),
+        }
+    }
+}
+
+impl Backend {
+    // 持有期间不能调用 notify_list_ready：阻塞的 BLMOVE 会在持有 list_ready 锁时
+    // 写入目标 key，反过来等待会死锁
+    pub(crate) fn lock_key(&self, key: &str) -> MutexGuard<'_, ()> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        let stripe = &self.key_locks.stripes[hasher.finish() as usize % STRIPES];
+        // 只保护 ()，持有者 panic 后锁仍然可用
+        stripe.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::{Backend, BulkString};
+
+    #[test]
+    fn test_concurrent_writers_never_leave_two_types() {
+        const ROUNDS: usize = 10_000;
+        for _ in 0..5 {
+            let backend = Backend::new();
+            // 每个线程用不同类型的命令反复写同一个 key；SET 覆盖其他类型，
+            // 其余写入在 key 是其他类型时报错
+            let writers = (0..4)
+                .map(|kind| {
+                    let backend = backend.clone();
+                    thread::spawn(move || {
+                        for i in 0..ROUNDS {
+                            let key = "k".to_string();
+                            match kind {
+                                0 => backend.set(key, BulkString::from("v").into()),
+                                1 => {
+                                    let _ = backend.sadd(key, i.to_string());
+                                }
+                                2 => {
+                                    let _ = backend.rpush(key, vec![BulkString::from("v").into()]);
+                                }
+                                _ => {
+                                    let _ = backend.append(key, b"v");
+                                }
+                            }
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            for writer in writers {
+                writer.join().unwrap();
+            }
+            let types = [
+                backend.map.contains_key("k"),
+                backend.set.contains_key("k"),
+                backend.list.contains_key("k"),
+            ];
+            assert!(types.iter().filter(|t| **t).count() <= 1, "{types:?}");
+        }
+    }
+}
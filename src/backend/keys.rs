@@ -0,0 +1,364 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+use dashmap::DashMap;
+
+use super::{Backend, BackendError, Lookup, SetMembers, ZSet};
+use crate::RespFrame;
+
+/// A value of any type, detached from the keyspace.
+#[derive(Debug, Clone)]
+pub(crate) enum Value {
+    String(RespFrame),
+    // 字段和字段的过期时间
+    Hash(DashMap<String, RespFrame>, HashMap<String, Instant>),
+    Set(SetMembers),
+    List(VecDeque<RespFrame>),
+    ZSet(ZSet),
+}
+
+impl Backend {
+    /// Copy `src` to `dst`, returns false if `src` is missing or `dst` exists
+    /// and `replace` is not set.
+    pub fn copy(&self, src: &str, dst: &str, replace: bool) -> bool {
+        let Some(value) = self.value(src) else {
+            return false;
+        };
+        let guard = self.lock_key(dst);
+        // 和 Redis 一样，副本继承源 key 的 TTL
+        let deadline = self.expires.get(src).map(|d| *d);
+        if self.key_type(dst).is_some() {
+            if !replace {
+                return false;
+            }
+            self.remove(dst);
+        }
+        self.insert_value(dst.to_string(), value);
+        if let Some(deadline) = deadline {
+            self.expires.insert(dst.to_string(), deadline);
+        }
+        // 和其他写入一样，不留下空的集合
+        if !self.remove_if_empty(dst) {
+            self.touch(dst);
+        }
+        drop(guard);
+        // 可能有 BLPOP 在等待这个 key
+        self.notify_list_ready();
+        true
+    }
+
+    /// Move the value at `src` to `dst`, overwriting whatever `dst` holds.
+    /// The TTL of `src` moves along with it.
+    pub fn rename(&self, src: &str, dst: &str) -> Result<(), BackendError> {
+        // remove 会一并删掉 TTL，先记下来；已经过期的 key 会在 remove 时当作不存在
+        let deadline = self.expires.get(src).map(|d| *d);
+        let value = self.remove(src).ok_or(BackendError::NoSuchKey)?;
+        let guard = self.lock_key(dst);
+        self.remove(dst);
+        self.insert_value(dst.to_string(), value);
+        if let Some(deadline) = deadline {
+            self.expires.insert(dst.to_string(), deadline);
+        }
+        drop(guard);
+        self.notify_list_ready();
+        Ok(())
+    }
+
+    /// Like `rename` but only if `dst` doesn't exist, returns whether the key
+    /// was renamed.
+    pub fn renamenx(&self, src: &str, dst: &str) -> Result<bool, BackendError> {
+        if self.key_type(src).is_none() {
+            return Err(BackendError::NoSuchKey);
+        }
+        if self.key_type(dst).is_some() {
+            return Ok(false);
+        }
+        self.rename(src, dst)?;
+        Ok(true)
+    }
+
+    /// Delete `keys` whatever their type, returns how many existed.
+    pub fn del(&self, keys: &[String]) -> usize {
+        keys.iter()
+            .filter(|key| {
+                let removed = self.remove(key).is_some();
+                if removed {
+                    self.record_deleted_key(key);
+                }
+                removed
+            })
+            .count()
+    }
+
+    /// How many of `keys` exist, a key named twice is counted twice.
+    pub fn exists(&self, keys: &[String]) -> usize {
+        keys.iter()
+            .filter(|key| self.key_type(key).is_some())
+            .count()
+    }
+
+    /// Number of keys, not counting keys whose TTL has passed but which
+    /// haven't been removed yet.
+    pub fn dbsize(&self) -> usize {
+        let now = self.clock.now();
+        let total =
+            self.map.len() + self.hmap.len() + self.set.len() + self.list.len() + self.zset.len();
+        // 删除 key 时会同时删掉 TTL，expires 里只有还存在的 key
+        let expired = self.expires.iter().filter(|e| *e.value() <= now).count();
+        total.saturating_sub(expired)
+    }
+
+    /// Delete every key.
+    pub fn flushdb(&self) {
+        self.clear();
+    }
+
+    /// Get the string at `key` and delete it.
+    pub fn getdel(&self, key: &str) -> Lookup<RespFrame> {
+        self.expire_if_needed(key);
+        // 直接从 map 里取出值，并发的 GETDEL 只有一个能拿到
+        match self.map.remove(key) {
+            Some((_, v)) => {
+                self.mark_memory_dirty(key);
+                self.expires.remove(key);
+                self.access.remove(key);
+                self.raw_strings.remove(key);
+                self.record_deleted_key(key);
+                Lookup::Found(v)
+            }
+            None if self.key_type(key).is_some() => Lookup::WrongType,
+            None => Lookup::Missing,
+        }
+    }
+
+    /// Set `key` to `value` and return the string it held before. A key of
+    /// another type is left untouched and reported as `WrongType`.
+    pub fn getset(&self, key: String, value: RespFrame) -> Lookup<RespFrame> {
+        self.getset_with(key, value, false)
+    }
+
+    /// Like `getset`, keeping the key's TTL if `keep_ttl` is set.
+    pub fn getset_with(&self, key: String, value: RespFrame, keep_ttl: bool) -> Lookup<RespFrame> {
+        let ret = self.get(&key);
+        if ret != Lookup::WrongType {
+            self.set_with(key, value, keep_ttl);
+        }
+        ret
+    }
+
+    pub(crate) fn value(&self, key: &str) -> Option<Value> {
+        self.expire_if_needed(key);
+        self.purge_expired_fields(key);
+        if let Some(v) = self.map.get(key) {
+            return Some(Value::String(v.clone()));
+        }
+        if let Some(v) = self.hmap.get(key) {
+            let expires = self
+                .hash_expires
+                .get(key)
+                .map(|e| e.clone())
+                .unwrap_or_default();
+            return Some(Value::Hash(v.clone(), expires));
+        }
+        if let Some(v) = self.set.get(key) {
+            return Some(Value::Set(v.clone()));
+        }
+        if let Some(v) = self.list.get(key) {
+            return Some(Value::List(v.clone()));
+        }
+        self.zset.get(key).map(|v| Value::ZSet(v.clone()))
+    }
+
+    /// Remove `key` whatever its type, returning the value it held.
+    pub(crate) fn remove(&self, key: &str) -> Option<Value> {
+        self.expire_if_needed(key);
+        self.purge_expired_fields(key);
+        self.remove_value(key)
+    }
+
+    /// Delete `key` if it holds an empty hash, set, list or sorted set, along
+    /// with its TTL and recorded encoding. Called after every operation that
+    /// removes elements, like Redis empty aggregates never stay around.
+    pub(crate) fn remove_if_empty(&self, key: &str) -> bool {
+        let removed = self.hmap.remove_if(key, |_, v| v.is_empty()).is_some()
+            || self.set.remove_if(key, |_, v| v.is_empty()).is_some()
+            || self.list.remove_if(key, |_, v| v.is_empty()).is_some()
+            || self.zset.remove_if(key, |_, v| v.is_empty()).is_some();
+        if removed {
+            self.mark_memory_dirty(key);
+            self.expires.remove(key);
+            self.access.remove(key);
+            self.hash_expires.remove(key);
+            self.set_encodings.remove(key);
+            self.zset_encodings.remove(key);
+        }
+        removed
+    }
+
+    // 不检查过期，直接删除 key 的数据和 TTL
+    pub(crate) fn remove_value(&self, key: &str) -> Option<Value> {
+        self.mark_memory_dirty(key);
+        self.expires.remove(key);
+        self.access.remove(key);
+        if let Some((_, v)) = self.map.remove(key) {
+            self.raw_strings.remove(key);
+            return Some(Value::String(v));
+        }
+        if let Some((_, v)) = self.hmap.remove(key) {
+            return Some(Value::Hash(v, self.take_field_expires(key)));
+        }
+        if let Some((_, v)) = self.set.remove(key) {
+            self.set_encodings.remove(key);
+            return Some(Value::Set(v));
+        }
+        if let Some((_, v)) = self.list.remove(key) {
+            return Some(Value::List(v));
+        }
+        self.zset_encodings.remove(key);
+        self.zset.remove(key).map(|(_, v)| Value::ZSet(v))
+    }
+
+    pub(crate) fn insert_value(&self, key: String, value: Value) {
+        self.mark_memory_dirty(&key);
+        match value {
+            Value::String(v) => {
+                self.map.insert(key, v);
+            }
+            Value::Hash(v, expires) => {
+                if !expires.is_empty() {
+                    self.hash_expires.insert(key.clone(), expires);
+                }
+                self.hmap.insert(key, v);
+            }
+            Value::Set(v) => {
+                self.set.insert(key.clone(), v);
+                self.upgrade_set_encoding(&key);
+            }
+            // 调用方持有 lock_key，释放之后再唤醒等待这个 key 的 BLPOP
+            Value::List(v) => {
+                self.list.insert(key, v);
+            }
+            Value::ZSet(v) => {
+                self.zset.insert(key.clone(), v);
+                self.upgrade_zset_encoding(&key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::BulkString;
+
+    #[test]
+    fn test_concurrent_getdel_returns_value_once() {
+        for _ in 0..200 {
+            let backend = Backend::new();
+            backend.set("k".to_string(), BulkString::from("v").into());
+            let readers = (0..4)
+                .map(|_| {
+                    let backend = backend.clone();
+                    std::thread::spawn(move || backend.getdel("k").found().is_some())
+                })
+                .collect::<Vec<_>>();
+            let found = readers
+                .into_iter()
+                .map(|r| r.join().unwrap())
+                .filter(|found| *found)
+                .count();
+            assert_eq!(found, 1);
+        }
+    }
+
+    #[test]
+    fn test_getdel_wrong_type() {
+        let backend = Backend::new();
+        backend.sadd("s".to_string(), "m".to_string()).unwrap();
+        assert_eq!(backend.getdel("s"), Lookup::WrongType);
+        assert_eq!(backend.getdel("missing"), Lookup::Missing);
+        assert_eq!(backend.key_type("s"), Some("set"));
+    }
+
+    #[test]
+    fn test_rename_missing_key() {
+        let backend = Backend::new();
+        assert_eq!(backend.rename("a", "b"), Err(BackendError::NoSuchKey));
+    }
+
+    #[test]
+    fn test_rename_overwrites_other_type() {
+        let backend = Backend::new();
+        backend.set("str".to_string(), BulkString::from("v").into());
+        backend.sadd("set".to_string(), "m".to_string()).unwrap();
+
+        backend.rename("set", "str").unwrap();
+        assert_eq!(backend.key_type("str"), Some("set"));
+        assert_eq!(backend.key_type("set"), None);
+        assert!(!backend.map.contains_key("str"));
+    }
+
+    #[test]
+    fn test_copy_without_replace() {
+        let backend = Backend::new();
+        backend.set("a".to_string(), BulkString::from("1").into());
+        backend.set("b".to_string(), BulkString::from("2").into());
+
+        assert!(!backend.copy("a", "b", false));
+        assert!(!backend.copy("missing", "c", false));
+        assert!(backend.copy("a", "b", true));
+        assert_eq!(backend.get("b").found(), Some(BulkString::from("1").into()));
+    }
+
+    #[test]
+    fn test_rename_and_copy_keep_ttl() {
+        let backend = Backend::new();
+        let set = |key: &str| backend.set(key.to_string(), BulkString::from("v").into());
+        let ttl = Duration::from_secs(100);
+
+        set("k");
+        backend.expire("k", ttl).unwrap();
+        backend.rename("k", "k2").unwrap();
+        assert!((99..=100).contains(&backend.ttl("k2")));
+        assert_eq!(backend.ttl("k"), -2);
+
+        assert!(backend.copy("k2", "k3", false));
+        assert!((99..=100).contains(&backend.ttl("k3")));
+        assert!((99..=100).contains(&backend.ttl("k2")));
+
+        // 没有 TTL 的源 key 覆盖目标时，目标原来的 TTL 也一并去掉
+        set("plain");
+        assert!(backend.copy("plain", "k3", true));
+        assert_eq!(backend.ttl("k3"), -1);
+        backend.rename("plain", "k2").unwrap();
+        assert_eq!(backend.ttl("k2"), -1);
+    }
+
+    #[test]
+    fn test_failed_renamenx_and_copy_keep_target_ttl() {
+        let backend = Backend::new();
+        let set = |key: &str| backend.set(key.to_string(), BulkString::from(key).into());
+        set("src");
+        set("dst");
+        backend.expire("dst", Duration::from_secs(50)).unwrap();
+
+        assert_eq!(backend.renamenx("src", "dst"), Ok(false));
+        assert!(!backend.copy("src", "dst", false));
+        assert!((49..=50).contains(&backend.ttl("dst")));
+        assert_eq!(
+            backend.get("dst").found(),
+            Some(BulkString::from("dst").into())
+        );
+        assert_eq!(backend.key_type("src"), Some("string"));
+
+        backend.expire("src", Duration::from_secs(20)).unwrap();
+        assert_eq!(backend.renamenx("src", "new"), Ok(true));
+        assert!((19..=20).contains(&backend.ttl("new")));
+        assert_eq!(
+            backend.renamenx("src", "other"),
+            Err(BackendError::NoSuchKey)
+        );
+    }
+}
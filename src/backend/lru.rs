@@ -0,0 +1,138 @@
+use super::{Backend, Lookup};
+use crate::RespFrame;
+
+impl Backend {
+    /// Read the string at `key` without updating its access time, used by
+    /// connections in CLIENT NO-TOUCH mode.
+    pub fn get_no_touch(&self, key: &str) -> Lookup<RespFrame> {
+        self.expire_if_needed(key);
+        match self.map.get(key) {
+            Some(v) => Lookup::Found(v.value().clone()),
+            None if self.key_type(key).is_some() => Lookup::WrongType,
+            None => Lookup::Missing,
+        }
+    }
+
+    /// Seconds since `key` was last read or written, as reported by OBJECT
+    /// IDLETIME. Keys whose access was never recorded count as just touched.
+    pub fn object_idletime(&self, key: &str) -> Option<u64> {
+        self.key_type(key)?;
+        let idle = self
+            .access
+            .get(key)
+            .map(|at| self.clock.now().duration_since(*at).as_secs())
+            .unwrap_or(0);
+        Some(idle)
+    }
+
+    /// Handle to the same keyspace that leaves access times alone, commands
+    /// of CLIENT NO-TOUCH connections run against it.
+    pub fn no_touch(&self) -> Backend {
+        Backend {
+            inner: self.inner.clone(),
+            touch: false,
+        }
+    }
+
+    pub(crate) fn touch(&self, key: &str) {
+        self.mark_memory_dirty(key);
+        if self.touch {
+            self.access.insert(key.to_string(), self.clock.now());
+        }
+    }
+
+    // 读写之后 key 可能不存在（没找到或被删空），只给仍存在的 key 记录访问时间
+    pub(crate) fn touch_existing(&self, key: &str) {
+        if self.touch && self.key_type(key).is_some() {
+            self.touch(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, ManualClock};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_idletime_reset_by_get_but_not_get_no_touch() {
+        let clock = Arc::new(ManualClock::new());
+        let backend = Backend::with_clock(clock.clone());
+        assert_eq!(backend.object_idletime("k"), None);
+        backend.set("k".to_string(), BulkString::from("v").into());
+        assert_eq!(backend.object_idletime("k"), Some(0));
+
+        clock.advance(Duration::from_secs(10));
+        assert!(backend.get_no_touch("k").found().is_some());
+        assert_eq!(backend.object_idletime("k"), Some(10));
+        assert!(backend.get("k").found().is_some());
+        assert_eq!(backend.object_idletime("k"), Some(0));
+    }
+
+    #[test]
+    fn test_idletime_reset_by_reads_and_writes_of_every_type() {
+        let clock = Arc::new(ManualClock::new());
+        let backend = Backend::with_clock(clock.clone());
+        let value = || -> RespFrame { BulkString::from("v").into() };
+
+        backend
+            .hset("h".to_string(), "f".to_string(), value())
+            .unwrap();
+        backend.sadd("s".to_string(), "m".to_string()).unwrap();
+        backend
+            .rpush("l".to_string(), vec![value(), value()])
+            .unwrap();
+        backend
+            .zadd("z".to_string(), vec![(1.0, "m".to_string())])
+            .unwrap();
+        for key in ["h", "s", "l", "z"] {
+            assert_eq!(backend.object_idletime(key), Some(0));
+        }
+
+        // 写命令重置访问时间
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(backend.object_idletime("h"), Some(10));
+        backend
+            .hset("h".to_string(), "f".to_string(), value())
+            .unwrap();
+        assert_eq!(backend.object_idletime("h"), Some(0));
+
+        // 读命令同样重置
+        clock.advance(Duration::from_secs(10));
+        assert!(backend.hget("h", "f").is_some());
+        assert!(backend.sismember("s", "m").unwrap());
+        assert!(backend.lpop("l").is_some());
+        assert_eq!(backend.zscore("z", "m"), Some(1.0));
+        for key in ["h", "s", "l", "z"] {
+            assert_eq!(backend.object_idletime(key), Some(0));
+        }
+
+        // 不存在的 key 不留下访问记录
+        assert!(backend.hget("missing", "f").is_none());
+        assert!(!backend.access.contains_key("missing"));
+    }
+
+    #[test]
+    fn test_no_touch_handle_keeps_idletime() {
+        let clock = Arc::new(ManualClock::new());
+        let backend = Backend::with_clock(clock.clone());
+        backend
+            .hset(
+                "h".to_string(),
+                "f".to_string(),
+                BulkString::from("v").into(),
+            )
+            .unwrap();
+
+        clock.advance(Duration::from_secs(10));
+        let untouched = backend.no_touch();
+        assert!(untouched.hget("h", "f").is_some());
+        assert!(untouched.hgetall("h").is_some());
+        assert_eq!(backend.object_idletime("h"), Some(10));
+
+        assert!(backend.hget("h", "f").is_some());
+        assert_eq!(backend.object_idletime("h"), Some(0));
+    }
+}
@@ -0,0 +1,209 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use dashmap::{DashMap, DashSet};
+
+use crate::{Backend, RespFrame};
+
+use super::{string_value, BackendError};
+
+// 和 Redis 的 MEMORY USAGE 一样，默认只采样 5 个元素
+pub const MEMORY_USAGE_DEFAULT_SAMPLES: usize = 5;
+
+// 估算用的固定开销：key 本身的 dict entry 和 redisObject，以及集合中每个元素的 entry
+const KEY_OVERHEAD: usize = 56;
+const ENTRY_OVERHEAD: usize = 24;
+const SCORE_SIZE: usize = 8;
+
+// 已用内存的累计值：缓存每个 key 的估算结果，之后只重新估算写过的 key，
+// 不必每次都遍历整个 keyspace
+#[derive(Debug, Default)]
+pub(crate) struct MemoryAccounting {
+    // 第一次查询时全量估算一次，之后才开始记录变化的 key
+    tracking: AtomicBool,
+    total: AtomicUsize,
+    per_key: DashMap<String, usize>,
+    dirty: DashSet<String>,
+}
+
+impl Backend {
+    /// Estimate the bytes used by `key` and its value, like MEMORY USAGE.
+    /// Collections are estimated from `samples` elements, 0 samples them all.
+    pub fn memory_usage(&self, key: &str, samples: usize) -> Option<usize> {
+        self.expire_if_needed(key);
+        let value = if let Some(value) = self.map.get(key) {
+            frame_size(value.value())
+        } else if let Some(hmap) = self.hmap.get(key) {
+            let sizes = hmap
+                .iter()
+                .map(|e| e.key().len() + frame_size(e.value()) + ENTRY_OVERHEAD);
+            extrapolate(sizes, hmap.len(), samples)
+        } else if let Some(set) = self.set.get(key) {
+            let sizes = set.iter().map(|m| m.len() + ENTRY_OVERHEAD);
+            extrapolate(sizes, set.len(), samples)
+        } else if let Some(list) = self.list.get(key) {
+            let sizes = list.iter().map(frame_size);
+            extrapolate(sizes, list.len(), samples)
+        } else if let Some(zset) = self.zset.get(key) {
+            let sizes = zset
+                .iter()
+                .map(|(member, _)| member.len() + SCORE_SIZE + ENTRY_OVERHEAD);
+            extrapolate(sizes, zset.len(), samples)
+        } else {
+            return None;
+        };
+        Some(KEY_OVERHEAD + key.len() + value)
+    }
+
+    /// Estimated bytes used by all keys, the sum of MEMORY USAGE with the
+    /// default sampling. Kept as a running total, only the keys written since
+    /// the last call are estimated again.
+    pub fn used_memory(&self) -> usize {
+        let memory = &self.memory;
+        // 先收集 key 再估算，memory_usage 可能删除过期的 key，不能在遍历时调用
+        let keys: Vec<String> = if memory.tracking.swap(true, Ordering::AcqRel) {
+            memory.dirty.iter().map(|key| key.clone()).collect()
+        } else {
+            self.map
+                .iter()
+                .map(|e| e.key().clone())
+                .chain(self.hmap.iter().map(|e| e.key().clone()))
+                .chain(self.set.iter().map(|e| e.key().clone()))
+                .chain(self.list.iter().map(|e| e.key().clone()))
+                .chain(self.zset.iter().map(|e| e.key().clone()))
+                .collect()
+        };
+        for key in keys {
+            memory.dirty.remove(&key);
+            let new = self.memory_usage(&key, MEMORY_USAGE_DEFAULT_SAMPLES);
+            let old = match new {
+                Some(size) => memory.per_key.insert(key, size),
+                None => memory.per_key.remove(&key).map(|(_, size)| size),
+            };
+            memory.total.fetch_add(new.unwrap_or(0), Ordering::AcqRel);
+            memory.total.fetch_sub(old.unwrap_or(0), Ordering::AcqRel);
+        }
+        memory.total.load(Ordering::Acquire)
+    }
+
+    // key 被写入或删除后调用，下次查询已用内存时重新估算
+    pub(crate) fn mark_memory_dirty(&self, key: &str) {
+        if self.memory.tracking.load(Ordering::Acquire) {
+            self.memory.dirty.insert(key.to_string());
+        }
+    }
+
+    // 清空数据时累计值一并归零
+    pub(crate) fn reset_used_memory(&self) {
+        self.memory.dirty.clear();
+        self.memory.per_key.clear();
+        self.memory.total.store(0, Ordering::Release);
+    }
+
+    // 值要增长 growth 字节前检查 maxmemory，超出时直接拒绝，不去分配内存。
+    // 没有淘汰策略，行为和 noeviction 一致
+    pub(crate) fn check_memory_growth(&self, growth: usize) -> Result<(), BackendError> {
+        let maxmemory = self.config.maxmemory();
+        if maxmemory == 0 || growth == 0 {
+            return Ok(());
+        }
+        if self.used_memory().saturating_add(growth) > maxmemory {
+            return Err(BackendError::OutOfMemory);
+        }
+        Ok(())
+    }
+}
+
+fn frame_size(frame: &RespFrame) -> usize {
+    string_value(frame).len()
+}
+
+// 用前 samples 个元素的平均大小推算整个集合的大小
+fn extrapolate(sizes: impl Iterator<Item = usize>, len: usize, samples: usize) -> usize {
+    if samples == 0 || samples >= len {
+        return sizes.sum();
+    }
+    let sampled: usize = sizes.take(samples).sum();
+    sampled * len / samples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, ManualClock};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_memory_usage_samples() {
+        let backend = Backend::new();
+        for i in 0..3 {
+            backend
+                .hset("small".to_string(), format!("f{}", i), b"v".into())
+                .unwrap();
+        }
+        for i in 0..1000 {
+            backend
+                .hset("big".to_string(), format!("f{}", i), b"v".into())
+                .unwrap();
+        }
+        let small = backend.memory_usage("small", 0).unwrap();
+        let big = backend
+            .memory_usage("big", MEMORY_USAGE_DEFAULT_SAMPLES)
+            .unwrap();
+        assert!(big > small * 100);
+
+        // 采样全部元素时得到精确值
+        let exact = KEY_OVERHEAD
+            + "big".len()
+            + (0..1000)
+                .map(|i| format!("f{}", i).len() + 1 + ENTRY_OVERHEAD)
+                .sum::<usize>();
+        assert_eq!(backend.memory_usage("big", 0), Some(exact));
+        assert_eq!(backend.memory_usage("big", 1000), Some(exact));
+        // 采样估算和精确值相差不大
+        let estimate = backend.memory_usage("big", 5).unwrap();
+        assert!(estimate.abs_diff(exact) < exact / 10);
+
+        backend.set("str".to_string(), BulkString::from("hello").into());
+        assert_eq!(
+            backend.memory_usage("str", 5),
+            Some(KEY_OVERHEAD + "str".len() + 5)
+        );
+        assert_eq!(backend.memory_usage("missing", 5), None);
+    }
+
+    #[test]
+    fn test_used_memory_running_total_matches_full_scan() -> Result<(), BackendError> {
+        let clock = Arc::new(ManualClock::new());
+        let backend = Backend::with_clock(clock.clone());
+        let full_scan = |backend: &Backend| -> usize {
+            let keys = ["str", "hash", "list", "set", "renamed"];
+            keys.iter()
+                .filter_map(|key| backend.memory_usage(key, MEMORY_USAGE_DEFAULT_SAMPLES))
+                .sum()
+        };
+        backend.set("str".to_string(), BulkString::from("hello").into());
+        backend.hset("hash".to_string(), "f".to_string(), b"v".into())?;
+        assert_eq!(backend.used_memory(), full_scan(&backend));
+
+        // 之后的写入、删除、改名和过期都反映在累计值里
+        backend.rpush("list".to_string(), vec![b"a".into(), b"b".into()])?;
+        backend.sadd("set".to_string(), "m".to_string())?;
+        backend.setrange("str".to_string(), 10, b"world")?;
+        backend.hset("hash".to_string(), "g".to_string(), b"value".into())?;
+        backend.rename("set", "renamed")?;
+        backend.lpop("list");
+        assert_eq!(backend.used_memory(), full_scan(&backend));
+
+        // 和 Redis 一样，过期的 key 在被删除之前仍然占用内存
+        backend.expire("str", Duration::from_secs(1))?;
+        clock.advance(Duration::from_secs(1));
+        backend.del(&["hash".to_string()]);
+        let expected = full_scan(&backend);
+        assert_eq!(backend.used_memory(), expected);
+
+        backend.flushdb();
+        assert_eq!(backend.used_memory(), 0);
+        Ok(())
+    }
+}
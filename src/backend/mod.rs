@@ -1,29 +1,136 @@
-use crate::RespFrame;
+mod aof;
+mod blocking;
+mod clients;
+mod clock;
+mod config;
+mod dump;
+mod encoding;
+mod error;
+mod expire;
+mod hash_ttl;
+mod key_lock;
+mod keys;
+mod lru;
+mod memory;
+mod notify;
+mod pubsub;
+mod rng;
+mod scan;
+mod set;
+mod snapshot;
+mod stats;
+mod zset;
+
+use crate::{BulkString, RespFrame};
+use blocking::BlockedClients;
+use dashmap::mapref::entry::Entry;
 use dashmap::{DashMap, DashSet};
+use encoding::{SetEncoding, ZSetEncoding};
+use key_lock::KeyLocks;
+use memory::MemoryAccounting;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::ops::Deref;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Instant;
+
+pub use aof::{replay_commands, Aof, AppendFsync};
+pub use blocking::ListEnd;
+pub use clients::{ClientFlags, ClientGuard, ConnectionContext, RateLimiter};
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use config::BackendConfig;
+pub use error::BackendError;
+pub use memory::MEMORY_USAGE_DEFAULT_SAMPLES;
+pub use notify::NotifyFlags;
+pub use pubsub::{PubSub, PubSubOverflow, Subscriber};
+pub use rng::Rng;
+pub use set::SetMembers;
+pub use stats::{CommandStat, ParseStats};
+pub use zset::{Aggregate, LexBound, Score, ScoreBound, ZAddOptions, ZRangeBy, ZSet};
 
 #[derive(Debug, Clone)]
-pub struct Backend(Arc<BackendInner>);
+pub struct Backend {
+    inner: Arc<BackendInner>,
+    // 为 false 时读写 key 不更新访问时间，见 `Backend::no_touch`
+    touch: bool,
+}
+
+/// Outcome of reading a key expected to hold a specific type, so callers can
+/// tell a missing key (null reply) from one of another type (WRONGTYPE).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Lookup<T> {
+    Found(T),
+    Missing,
+    WrongType,
+}
+
+/// Key and (member, score) pairs popped by ZMPOP.
+pub type ZPopped = (String, Vec<(String, f64)>);
 
 #[derive(Debug)]
 pub struct BackendInner {
     pub(crate) map: DashMap<String, RespFrame>,
+    // 被 SETRANGE / APPEND 原地修改过的字符串，和 Redis 一样编码固定为 raw
+    pub(crate) raw_strings: DashSet<String>,
     pub(crate) hmap: DashMap<String, DashMap<String, RespFrame>>,
-    pub(crate) set: DashMap<String, DashSet<String>>,
+    // hash 字段的过期时间，访问时惰性删除
+    pub(crate) hash_expires: DashMap<String, HashMap<String, Instant>>,
+    pub(crate) set: DashMap<String, SetMembers>,
+    // 集合曾经达到过的编码，编码只升级不降级
+    pub(crate) set_encodings: DashMap<String, SetEncoding>,
+    pub(crate) list: DashMap<String, VecDeque<RespFrame>>,
+    pub(crate) zset: DashMap<String, ZSet>,
+    // 有序集合曾经达到过的编码，和集合一样只升级不降级
+    pub(crate) zset_encodings: DashMap<String, ZSetEncoding>,
+    // key 的过期时间，访问时惰性删除，后台线程主动清理
+    pub(crate) expires: DashMap<String, Instant>,
+    // 主动过期上一轮扫描停下的位置，下一轮从这里继续
+    expire_cursor: AtomicUsize,
+    // key 最近一次被读写的时间，用于 OBJECT IDLETIME
+    pub(crate) access: DashMap<String, Instant>,
+    // 可能创建 key 的写入在检查类型和写入期间持有，避免同一个 key 出现两种类型
+    key_locks: KeyLocks,
+    // maxmemory 检查用的已用内存累计值
+    memory: MemoryAccounting,
+    // 阻塞命令等待 list 有新元素时使用
+    list_ready: (Mutex<BlockedClients>, Condvar),
+    pub(crate) pubsub: PubSub,
+    pub(crate) config: BackendConfig,
+    pub(crate) rng: Rng,
+    // 过期相关的当前时间，测试时可以替换成手动推进的时钟
+    pub(crate) clock: Arc<dyn Clock>,
+    // 当前连接数，由 ClientGuard 维护
+    clients: AtomicUsize,
+    // 最近分配的连接 id，和 Redis 一样从 1 开始递增
+    last_client_id: AtomicU64,
+    // 按命令名统计的调用次数和耗时，用于 INFO commandstats
+    command_stats: DashMap<String, CommandStat>,
+    // 过期删除和 DEL 显式删除的 key 数分开统计，用于 INFO stats
+    stat_expired_keys: AtomicU64,
+    stat_deleted_keys: AtomicU64,
+    // 连接解码路径的统计，用于排查分包和半包读取
+    parse_frames: AtomicU64,
+    parse_bytes: AtomicU64,
+    parse_not_complete: AtomicU64,
+    // 正在进行的 AOF / 快照加载数，为 0 时健康检查才认为数据已恢复
+    loading: AtomicUsize,
+    aof: Mutex<Option<Aof>>,
+    // 测试中统计读取整个集合的次数
+    #[cfg(test)]
+    pub(crate) set_reads: AtomicUsize,
 }
 
 impl Deref for Backend {
     type Target = BackendInner;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.inner
     }
 }
 
 impl Default for Backend {
     fn default() -> Self {
-        Self(Arc::new(BackendInner::default()))
+        Self::from_inner(BackendInner::default())
     }
 }
 
@@ -31,8 +138,45 @@ impl Default for BackendInner {
     fn default() -> Self {
         Self {
             map: DashMap::new(),
+            raw_strings: DashSet::new(),
             hmap: DashMap::new(),
+            hash_expires: DashMap::new(),
             set: DashMap::new(),
+            set_encodings: DashMap::new(),
+            list: DashMap::new(),
+            zset: DashMap::new(),
+            zset_encodings: DashMap::new(),
+            expires: DashMap::new(),
+            expire_cursor: AtomicUsize::new(0),
+            access: DashMap::new(),
+            key_locks: KeyLocks::default(),
+            memory: MemoryAccounting::default(),
+            list_ready: (Mutex::default(), Condvar::new()),
+            pubsub: PubSub::default(),
+            config: BackendConfig::default(),
+            rng: Rng::default(),
+            clock: Arc::new(SystemClock),
+            clients: AtomicUsize::new(0),
+            last_client_id: AtomicU64::new(0),
+            command_stats: DashMap::new(),
+            stat_expired_keys: AtomicU64::new(0),
+            stat_deleted_keys: AtomicU64::new(0),
+            parse_frames: AtomicU64::new(0),
+            parse_bytes: AtomicU64::new(0),
+            parse_not_complete: AtomicU64::new(0),
+            loading: AtomicUsize::new(0),
+            aof: Mutex::new(None),
+            #[cfg(test)]
+            set_reads: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<T> Lookup<T> {
+    pub fn found(self) -> Option<T> {
+        match self {
+            Lookup::Found(v) => Some(v),
+            _ => None,
         }
     }
 }
@@ -42,47 +186,995 @@ impl Backend {
         Self::default()
     }
 
-    pub fn get(&self, key: &str) -> Option<RespFrame> {
-        self.map.get(key).map(|v| v.value().clone())
+    /// Backend whose random commands are deterministic, for tests.
+    pub fn new_seeded(seed: u64) -> Self {
+        Self::from_inner(BackendInner {
+            rng: Rng::new(seed),
+            ..Default::default()
+        })
+    }
+
+    /// Backend reading the current time from `clock`.
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        Self::from_inner(BackendInner {
+            clock,
+            ..Default::default()
+        })
+    }
+
+    fn from_inner(inner: BackendInner) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            touch: true,
+        }
+    }
+
+    pub fn config(&self) -> &BackendConfig {
+        &self.config
+    }
+
+    pub fn get(&self, key: &str) -> Lookup<RespFrame> {
+        let value = self.get_no_touch(key);
+        if let Lookup::Found(_) = value {
+            self.touch(key);
+        }
+        value
+    }
+
+    /// The type of the value stored at `key`, as reported by TYPE.
+    pub fn key_type(&self, key: &str) -> Option<&'static str> {
+        self.expire_if_needed(key);
+        self.purge_expired_fields(key);
+        if self.map.contains_key(key) {
+            Some("string")
+        } else if self.hmap.contains_key(key) {
+            Some("hash")
+        } else if self.set.contains_key(key) {
+            Some("set")
+        } else if self.list.contains_key(key) {
+            Some("list")
+        } else if self.zset.contains_key(key) {
+            Some("zset")
+        } else {
+            None
+        }
+    }
+
+    // 写入前检查 key 不是其他类型，和 Redis 一样返回 WRONGTYPE。
+    // 可能创建 key 的写入要先持有 lock_key，检查和写入之间不会有其他类型插进来
+    fn check_type(&self, key: &str, expected: &str) -> Result<(), BackendError> {
+        match self.key_type(key) {
+            Some(actual) if actual != expected => Err(BackendError::WrongType),
+            _ => Ok(()),
+        }
     }
 
     pub fn set(&self, key: String, value: RespFrame) {
-        self.map.insert(key, value);
+        self.set_with(key, value, false)
+    }
+
+    /// Set `key` to `value`; the key's TTL is cleared unless `keep_ttl`,
+    /// like SET ... KEEPTTL.
+    pub fn set_with(&self, key: String, value: RespFrame, keep_ttl: bool) {
+        let _guard = self.lock_key(&key);
+        // 已经过期的 key 不能把 TTL 留给新值
+        let deadline = if keep_ttl {
+            self.expire_if_needed(&key);
+            self.expires.get(&key).map(|d| *d)
+        } else {
+            None
+        };
+        // 覆盖其他类型的 key 时，旧的值也要删除
+        self.remove_value(&key);
+        self.map.insert(key.clone(), value);
+        if let Some(deadline) = deadline {
+            self.expires.insert(key.clone(), deadline);
+        }
+        self.touch(&key);
+        self.notify_keyspace_event(NotifyFlags::STRING, "set", &key);
+    }
+
+    /// Length in bytes of the string at `key`, 0 if it's missing.
+    pub fn strlen(&self, key: &str) -> Result<usize, BackendError> {
+        match self.get(key) {
+            Lookup::Found(value) => Ok(string_value(&value).len()),
+            Lookup::Missing => Ok(0),
+            Lookup::WrongType => Err(BackendError::WrongType),
+        }
+    }
+
+    /// Bytes of the string at `key` between `start` and `end` (inclusive),
+    /// negative offsets count from the end. Offsets are byte based.
+    pub fn getrange(&self, key: &str, start: i64, end: i64) -> Result<Vec<u8>, BackendError> {
+        let value = match self.get(key) {
+            Lookup::Found(value) => string_value(&value),
+            Lookup::Missing => return Ok(Vec::new()),
+            Lookup::WrongType => return Err(BackendError::WrongType),
+        };
+        let len = value.len() as i64;
+        let start = if start < 0 {
+            (start + len).max(0)
+        } else {
+            start
+        };
+        let end = if end < 0 { end + len } else { end.min(len - 1) };
+        if len == 0 || end < 0 || start > end {
+            return Ok(Vec::new());
+        }
+        Ok(value[start as usize..=end as usize].to_vec())
+    }
+
+    /// Overwrite the string at `key` from `offset` with `value`, zero-padding
+    /// if needed. Returns the new length of the string.
+    pub fn setrange(
+        &self,
+        key: String,
+        offset: usize,
+        value: &[u8],
+    ) -> Result<usize, BackendError> {
+        let _guard = self.lock_key(&key);
+        self.check_type(&key, "string")?;
+        let current_len = self
+            .map
+            .get(&key)
+            .map_or(0, |v| string_value(v.value()).len());
+        // 空值不会创建 key，也不会扩展已有的值
+        if value.is_empty() {
+            return Ok(current_len);
+        }
+        let end = offset + value.len();
+        self.check_string_len(end)?;
+        // 统计已用内存要遍历 map，只能在拿到 entry 之前检查
+        self.check_memory_growth(end.saturating_sub(current_len))?;
+        // 读取和写回在同一个 entry 里完成，中间不会有其他写入
+        let len = {
+            let mut entry = self
+                .map
+                .entry(key.clone())
+                .or_insert_with(|| BulkString::new(Vec::new()).into());
+            let mut current = string_value(entry.value());
+            if current.len() < end {
+                current.resize(end, 0);
+            }
+            current[offset..end].copy_from_slice(value);
+            let len = current.len();
+            *entry = BulkString::new(current).into();
+            len
+        };
+        self.raw_strings.insert(key.clone());
+        self.touch(&key);
+        Ok(len)
+    }
+
+    /// Set or clear the bit at `offset` of the string at `key`, zero-padding
+    /// if needed. Returns the previous bit.
+    pub fn setbit(&self, key: String, offset: usize, on: bool) -> Result<u8, BackendError> {
+        let _guard = self.lock_key(&key);
+        self.check_type(&key, "string")?;
+        let mut current = self
+            .map
+            .get(&key)
+            .map(|v| string_value(v.value()))
+            .unwrap_or_default();
+        let byte = offset / 8;
+        let mask = 0x80 >> (offset % 8);
+        self.check_string_len(byte + 1)?;
+        self.check_memory_growth((byte + 1).saturating_sub(current.len()))?;
+        if current.len() <= byte {
+            current.resize(byte + 1, 0);
+        }
+        let old = (current[byte] & mask != 0) as u8;
+        if on {
+            current[byte] |= mask;
+        } else {
+            current[byte] &= !mask;
+        }
+        self.map
+            .insert(key.clone(), BulkString::new(current).into());
+        self.raw_strings.insert(key.clone());
+        self.touch(&key);
+        Ok(old)
+    }
+
+    /// The bit at `offset` of the string at `key`, 0 past the end or for a
+    /// missing key.
+    pub fn getbit(&self, key: &str, offset: usize) -> Result<u8, BackendError> {
+        let value = match self.get(key) {
+            Lookup::Found(value) => string_value(&value),
+            Lookup::Missing => return Ok(0),
+            Lookup::WrongType => return Err(BackendError::WrongType),
+        };
+        Ok(value
+            .get(offset / 8)
+            .map_or(0, |byte| (byte & (0x80 >> (offset % 8)) != 0) as u8))
+    }
+
+    /// Append `value` to the string at `key`, creating it if missing. Returns
+    /// the new length of the string.
+    pub fn append(&self, key: String, value: &[u8]) -> Result<usize, BackendError> {
+        let _guard = self.lock_key(&key);
+        self.check_type(&key, "string")?;
+        let current_len = self
+            .map
+            .get(&key)
+            .map_or(0, |v| string_value(v.value()).len());
+        self.check_string_len(current_len + value.len())?;
+        // 和 SETRANGE 一样，在拿到 entry 之前检查 maxmemory
+        self.check_memory_growth(value.len())?;
+        let len = {
+            let mut entry = self
+                .map
+                .entry(key.clone())
+                .or_insert_with(|| BulkString::new(Vec::new()).into());
+            let mut current = string_value(entry.value());
+            current.extend_from_slice(value);
+            let len = current.len();
+            *entry = BulkString::new(current).into();
+            len
+        };
+        self.raw_strings.insert(key.clone());
+        self.touch(&key);
+        self.notify_keyspace_event(NotifyFlags::STRING, "append", &key);
+        Ok(len)
+    }
+
+    // 和 Redis 一样，写入后的字符串不能超过 proto-max-bulk-len，避免一次分配过多内存
+    fn check_string_len(&self, len: usize) -> Result<(), BackendError> {
+        if len > self.config.proto_max_bulk_len() {
+            return Err(BackendError::StringTooLong);
+        }
+        Ok(())
+    }
+
+    /// Add `delta` to the integer stored at `key`, a missing key counts as 0.
+    /// The TTL of the key is kept. Returns the new value.
+    pub fn incr_by(&self, key: String, delta: i64) -> Result<i64, BackendError> {
+        let _guard = self.lock_key(&key);
+        self.check_type(&key, "string")?;
+        // 新值最多 20 个字节；统计已用内存要遍历 map，只能在拿到 entry 之前检查
+        let len = self
+            .map
+            .get(&key)
+            .map_or(0, |v| string_value(v.value()).len());
+        self.check_memory_growth(I64_MAX_LEN.saturating_sub(len))?;
+        // 读取和写回在同一个 entry 里完成，并发的 INCR 不会丢失更新
+        let value = match self.map.entry(key.clone()) {
+            Entry::Occupied(mut entry) => {
+                let current =
+                    parse_i64(&string_value(entry.get())).ok_or(BackendError::NotInteger)?;
+                let value = current.checked_add(delta).ok_or(BackendError::Overflow)?;
+                entry.insert(BulkString::from(value.to_string()).into());
+                value
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(BulkString::from(delta.to_string()).into());
+                delta
+            }
+        };
+        self.raw_strings.remove(&key);
+        self.touch(&key);
+        self.notify_keyspace_event(NotifyFlags::STRING, "incrby", &key);
+        Ok(value)
     }
 
     pub fn hget(&self, key: &str, field: &str) -> Option<RespFrame> {
-        self.hmap
+        self.expire_if_needed(key);
+        self.purge_expired_fields(key);
+        let value = self
+            .hmap
             .get(key)
-            .and_then(|v| v.get(field).map(|v| v.value().clone()))
+            .and_then(|v| v.get(field).map(|v| v.value().clone()));
+        self.touch_existing(key);
+        value
     }
 
-    pub fn hset(&self, key: String, field: String, value: RespFrame) {
-        let hmap = self.hmap.entry(key).or_default();
-        hmap.insert(field, value);
+    pub fn hset(&self, key: String, field: String, value: RespFrame) -> Result<(), BackendError> {
+        let _guard = self.lock_key(&key);
+        self.check_type(&key, "hash")?;
+        self.persist_field(&key, &field);
+        self.hmap
+            .entry(key.clone())
+            .or_default()
+            .insert(field, value);
+        self.touch(&key);
+        Ok(())
+    }
+
+    /// Add `increment` to the float stored in `field`, a missing field counts
+    /// as 0. Returns the new value.
+    pub fn hincrbyfloat(
+        &self,
+        key: String,
+        field: String,
+        increment: f64,
+    ) -> Result<f64, BackendError> {
+        let _guard = self.lock_key(&key);
+        self.check_type(&key, "hash")?;
+        self.purge_expired_fields(&key);
+        let hmap = self.hmap.entry(key.clone()).or_default();
+        let current = match hmap.get(&field) {
+            Some(v) => std::str::from_utf8(&string_value(v.value()))
+                .ok()
+                .and_then(|s| s.parse::<f64>().ok())
+                .filter(|v| v.is_finite())
+                .ok_or(BackendError::HashNotFloat)?,
+            None => 0.0,
+        };
+        let value = current + increment;
+        if !value.is_finite() {
+            drop(hmap);
+            // 不存在的 key 会被 entry 创建，报错时不能留下空的 hash
+            self.remove_if_empty(&key);
+            return Err(BackendError::NotFinite);
+        }
+        hmap.insert(field, BulkString::from(value.to_string()).into());
+        drop(hmap);
+        self.touch(&key);
+        Ok(value)
     }
 
     pub fn hgetall(&self, key: &str) -> Option<DashMap<String, RespFrame>> {
-        self.hmap.get(key).map(|v| v.clone())
+        self.expire_if_needed(key);
+        self.purge_expired_fields(key);
+        let value = self.hmap.get(key).map(|v| v.clone());
+        self.touch_existing(key);
+        value
     }
 
     pub fn hmget(&self, key: &str, fields: &[&str]) -> Vec<Option<RespFrame>> {
-        let hmap = self.hmap.get(key);
-        if let Some(hmap) = hmap {
-            fields
+        self.expire_if_needed(key);
+        self.purge_expired_fields(key);
+        let values = match self.hmap.get(key) {
+            Some(hmap) => fields
                 .iter()
                 .map(|field| hmap.get(*field).map(|v| v.value().clone()))
-                .collect()
+                .collect(),
+            None => vec![None; fields.len()],
+        };
+        self.touch_existing(key);
+        values
+    }
+
+    /// Fields and values of the hash at `key`, empty for a missing key.
+    pub fn hentries(&self, key: &str) -> Result<Vec<(String, RespFrame)>, BackendError> {
+        self.expire_if_needed(key);
+        self.check_type(key, "hash")?;
+        self.purge_expired_fields(key);
+        let mut entries: Vec<(String, RespFrame)> = match self.hmap.get(key) {
+            Some(hmap) => hmap
+                .iter()
+                .map(|v| (v.key().to_owned(), v.value().clone()))
+                .collect(),
+            None => return Ok(vec![]),
+        };
+        self.touch(key);
+        if self.config.sort_replies() {
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+        Ok(entries)
+    }
+
+    /// Number of fields in the hash at `key`, 0 for a missing key.
+    pub fn hlen(&self, key: &str) -> Result<usize, BackendError> {
+        self.expire_if_needed(key);
+        self.check_type(key, "hash")?;
+        self.purge_expired_fields(key);
+        let len = self.hmap.get(key).map_or(0, |hmap| hmap.len());
+        self.touch_existing(key);
+        Ok(len)
+    }
+
+    /// Delete `fields` from the hash at `key`, returns how many existed.
+    pub fn hdel(&self, key: &str, fields: &[String]) -> Result<usize, BackendError> {
+        let values = self.hgetdel(key, fields)?;
+        Ok(values.iter().filter(|value| value.is_some()).count())
+    }
+
+    pub fn sadd(&self, key: String, member: String) -> Result<(), BackendError> {
+        let _guard = self.lock_key(&key);
+        self.check_type(&key, "set")?;
+        self.set.entry(key.clone()).or_default().insert(member);
+        self.upgrade_set_encoding(&key);
+        self.touch(&key);
+        Ok(())
+    }
+
+    /// Remove `members` from the set at `key`, deleting it once empty.
+    /// Returns how many members were removed.
+    pub fn srem(&self, key: &str, members: &[String]) -> Result<usize, BackendError> {
+        self.check_type(key, "set")?;
+        let removed = match self.set.get_mut(key) {
+            Some(mut set) => members.iter().filter(|member| set.remove(member)).count(),
+            None => 0,
+        };
+        self.remove_if_empty(key);
+        self.touch_existing(key);
+        Ok(removed)
+    }
+
+    pub fn sismember(&self, key: &str, member: &str) -> Result<bool, BackendError> {
+        self.check_type(key, "set")?;
+        let found = self.set.get(key).is_some_and(|v| v.contains(member));
+        self.touch_existing(key);
+        Ok(found)
+    }
+
+    /// Members of a set, sorted when `sort_replies` is on.
+    pub fn smembers(&self, key: &str) -> Result<Vec<String>, BackendError> {
+        self.check_type(key, "set")?;
+        let mut members: Vec<String> = self.read_set(key).into_iter().collect();
+        if self.config.sort_replies() {
+            members.sort();
+        }
+        Ok(members)
+    }
+
+    /// Random members of a set: a single one when `count` is `None`, up to
+    /// `count` distinct ones when positive, `|count|` possibly repeated when negative.
+    /// Counts too large to allocate are rejected with `OutOfRange`.
+    pub fn srandmember(&self, key: &str, count: Option<i64>) -> Result<Vec<String>, BackendError> {
+        self.expire_if_needed(key);
+        // 先排序，保证同样的种子得到同样的结果，不受哈希顺序影响
+        let mut members: Vec<String> = match self.set.get(key) {
+            Some(set) => set.iter().map(str::to_string).collect(),
+            None => return Ok(vec![]),
+        };
+        self.touch(key);
+        members.sort();
+        match count {
+            None => Ok(self.rng.sample(members, 1)),
+            Some(count) if count >= 0 => Ok(self.rng.sample(members, count as usize)),
+            Some(count) => {
+                let count =
+                    usize::try_from(count.unsigned_abs()).map_err(|_| BackendError::OutOfRange)?;
+                let mut drawn = Vec::new();
+                drawn
+                    .try_reserve_exact(count)
+                    .map_err(|_| BackendError::OutOfRange)?;
+                drawn.extend((0..count).map(|_| members[self.rng.below(members.len())].clone()));
+                Ok(drawn)
+            }
+        }
+    }
+
+    /// Remove and return up to `count` random members of the set at `key`.
+    pub fn spop(&self, key: &str, count: usize) -> Result<Vec<String>, BackendError> {
+        self.check_type(key, "set")?;
+        let Some(mut set) = self.set.get_mut(key) else {
+            return Ok(vec![]);
+        };
+        // 直接在成员的引用上抽样，不排序也不复制整个集合，只复制弹出的成员
+        let members: Vec<&str> = set.iter().collect();
+        let popped: Vec<String> = self
+            .rng
+            .sample(members, count)
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        for member in &popped {
+            set.remove(member);
+        }
+        drop(set);
+        self.remove_if_empty(key);
+        self.touch_existing(key);
+        Ok(popped)
+    }
+
+    /// Members present in every set at `keys`, `WrongType` if any of them
+    /// holds another type.
+    pub fn sinter(&self, keys: &[String]) -> Result<HashSet<String>, BackendError> {
+        // 和 Redis 一样先检查所有 key 的类型，再看是否有空集合
+        let mut empty = false;
+        for key in keys {
+            empty |= self.scard(key)? == 0;
+        }
+        // 任一集合为空或不存在时结果必然为空，不必再读取其他集合
+        if empty {
+            return Ok(HashSet::new());
+        }
+        let mut sets = keys.iter().map(|key| self.read_set(key));
+        let first = sets.next().unwrap_or_default();
+        Ok(sets.fold(first, |acc, set| &acc & &set))
+    }
+
+    /// Number of members of the set at `key`, 0 if it does not exist.
+    pub fn scard(&self, key: &str) -> Result<usize, BackendError> {
+        self.check_type(key, "set")?;
+        let len = self.set.get(key).map_or(0, |v| v.len());
+        self.touch_existing(key);
+        Ok(len)
+    }
+
+    pub fn sunion(&self, keys: &[String]) -> HashSet<String> {
+        keys.iter().flat_map(|key| self.read_set(key)).collect()
+    }
+
+    pub fn sdiff(&self, keys: &[String]) -> HashSet<String> {
+        let mut sets = keys.iter().map(|key| self.read_set(key));
+        let first = sets.next().unwrap_or_default();
+        sets.fold(first, |acc, set| &acc - &set)
+    }
+
+    /// Overwrite `dest` with `members`, deleting it when the result is empty.
+    /// Returns the cardinality of the stored set.
+    pub fn sstore(&self, dest: String, members: HashSet<String>) -> usize {
+        let _guard = self.lock_key(&dest);
+        // dest 原来可能是其他类型，连同 TTL 和编码一起删除
+        self.remove_value(&dest);
+        let len = members.len();
+        self.set.insert(dest.clone(), members.into_iter().collect());
+        // 和其他写入一样，结果为空时不留下 key
+        if !self.remove_if_empty(&dest) {
+            self.touch(&dest);
+        }
+        len
+    }
+
+    fn read_set(&self, key: &str) -> HashSet<String> {
+        #[cfg(test)]
+        self.set_reads
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        self.expire_if_needed(key);
+        let members = self
+            .set
+            .get(key)
+            .map(|v| v.iter().map(str::to_string).collect())
+            .unwrap_or_default();
+        self.touch_existing(key);
+        members
+    }
+
+    pub fn lpush(&self, key: String, values: Vec<RespFrame>) -> Result<usize, BackendError> {
+        let guard = self.lock_key(&key);
+        self.check_type(&key, "list")?;
+        let len = {
+            let mut list = self.list.entry(key.clone()).or_default();
+            for value in values {
+                list.push_front(value);
+            }
+            list.len()
+        };
+        self.touch(&key);
+        drop(guard);
+        self.notify_list_ready();
+        Ok(len)
+    }
+
+    pub fn rpush(&self, key: String, values: Vec<RespFrame>) -> Result<usize, BackendError> {
+        let guard = self.lock_key(&key);
+        self.check_type(&key, "list")?;
+        let len = {
+            let mut list = self.list.entry(key.clone()).or_default();
+            list.extend(values);
+            list.len()
+        };
+        self.touch(&key);
+        drop(guard);
+        self.notify_list_ready();
+        Ok(len)
+    }
+
+    pub fn lpop(&self, key: &str) -> Option<RespFrame> {
+        self.expire_if_needed(key);
+        let value = self.list.get_mut(key).and_then(|mut v| v.pop_front());
+        self.remove_if_empty(key);
+        self.touch_existing(key);
+        value
+    }
+
+    pub fn rpop(&self, key: &str) -> Option<RespFrame> {
+        self.expire_if_needed(key);
+        let value = self.list.get_mut(key).and_then(|mut v| v.pop_back());
+        self.remove_if_empty(key);
+        self.touch_existing(key);
+        value
+    }
+
+    /// Pop up to `count` elements from one end of the first non-empty list
+    /// among `keys`, as LMPOP. Returns the key popped from.
+    pub fn lmpop(
+        &self,
+        keys: &[String],
+        end: ListEnd,
+        count: usize,
+    ) -> Result<Option<(String, Vec<RespFrame>)>, BackendError> {
+        for key in keys {
+            match self.key_type(key) {
+                Some("list") => {}
+                Some(_) => return Err(BackendError::WrongType),
+                None => continue,
+            }
+            let values = match self.list.get_mut(key) {
+                Some(mut list) => {
+                    let len = list.len();
+                    let n = count.min(len);
+                    match end {
+                        ListEnd::Left => list.drain(..n).collect(),
+                        ListEnd::Right => list.drain(len - n..).rev().collect(),
+                    }
+                }
+                None => continue,
+            };
+            self.remove_if_empty(key);
+            self.touch_existing(key);
+            return Ok(Some((key.clone(), values)));
+        }
+        Ok(None)
+    }
+
+    /// Remove up to `count` elements equal to `element`, scanning from the
+    /// head for a positive `count`, from the tail for a negative one and
+    /// removing every match for 0. The list is deleted once empty.
+    pub fn lrem(&self, key: &str, count: i64, element: &[u8]) -> Result<usize, BackendError> {
+        self.check_type(key, "list")?;
+        let Some(mut list) = self.list.get_mut(key) else {
+            return Ok(0);
+        };
+        let limit = if count == 0 {
+            usize::MAX
         } else {
-            vec![None; fields.len()]
+            count.unsigned_abs() as usize
+        };
+        let mut removed = 0;
+        // 从尾部删除时先反转，retain 总是从头开始遍历
+        if count < 0 {
+            list.make_contiguous().reverse();
+        }
+        list.retain(|value| {
+            let remove = removed < limit && string_value(value) == element;
+            removed += remove as usize;
+            !remove
+        });
+        if count < 0 {
+            list.make_contiguous().reverse();
+        }
+        drop(list);
+        self.remove_if_empty(key);
+        self.touch_existing(key);
+        Ok(removed)
+    }
+
+    /// Keep only the elements between `start` and `stop` (inclusive, negative
+    /// offsets count from the end), deleting the list if nothing is left.
+    pub fn ltrim(&self, key: &str, start: i64, stop: i64) -> Result<(), BackendError> {
+        self.check_type(key, "list")?;
+        if let Some(mut list) = self.list.get_mut(key) {
+            let len = list.len() as i64;
+            let start = if start < 0 {
+                (start + len).max(0)
+            } else {
+                start
+            };
+            let stop = if stop < 0 {
+                stop + len
+            } else {
+                stop.min(len - 1)
+            };
+            if start > stop || start >= len {
+                list.clear();
+            } else {
+                list.truncate(stop as usize + 1);
+                list.drain(..start as usize);
+            }
         }
+        self.remove_if_empty(key);
+        self.touch_existing(key);
+        Ok(())
     }
 
-    pub fn sadd(&self, key: String, member: String) {
-        let set = self.set.entry(key).or_default();
-        set.insert(member);
+    /// Indices of the elements equal to `element`, scanning from the head for
+    /// a positive `rank` and from the tail for a negative one, skipping the
+    /// first `|rank| - 1` matches. A `count` of 0 returns every match and a
+    /// `maxlen` of 0 scans the whole list.
+    pub fn lpos(
+        &self,
+        key: &str,
+        element: &[u8],
+        rank: i64,
+        count: usize,
+        maxlen: usize,
+    ) -> Result<Vec<usize>, BackendError> {
+        self.check_type(key, "list")?;
+        let Some(list) = self.list.get(key) else {
+            return Ok(vec![]);
+        };
+        self.touch(key);
+        let maxlen = if maxlen == 0 { list.len() } else { maxlen };
+        let count = if count == 0 { usize::MAX } else { count };
+        let matches = |(_, v): &(usize, &RespFrame)| string_value(v) == element;
+        let indices: Box<dyn Iterator<Item = (usize, &RespFrame)>> = if rank > 0 {
+            Box::new(list.iter().enumerate().take(maxlen))
+        } else {
+            Box::new(list.iter().enumerate().rev().take(maxlen))
+        };
+        Ok(indices
+            .filter(matches)
+            .skip((rank.unsigned_abs() as usize).saturating_sub(1))
+            .take(count)
+            .map(|(i, _)| i)
+            .collect())
+    }
+
+    /// Add or update members, returns the number of newly added members.
+    pub fn zadd(&self, key: String, members: Vec<(f64, String)>) -> Result<usize, BackendError> {
+        self.zadd_with(key, members, ZAddOptions::default())
     }
 
-    pub fn sismember(&self, key: &str, member: &str) -> bool {
-        self.set.get(key).map_or(false, |v| v.contains(member))
+    /// ZADD honoring its flags, returns the number of added members, plus the
+    /// changed ones with `ch`. `options.incr` is handled by `zincrby_with`.
+    pub fn zadd_with(
+        &self,
+        key: String,
+        members: Vec<(f64, String)>,
+        options: ZAddOptions,
+    ) -> Result<usize, BackendError> {
+        let _guard = self.lock_key(&key);
+        self.check_type(&key, "zset")?;
+        let mut zset = self.zset.entry(key.clone()).or_default();
+        let mut count = 0;
+        for (score, member) in members {
+            let current = zset.score(&member);
+            let Some(score) = options.resolve(current, score) else {
+                continue;
+            };
+            let changed = current.is_some_and(|current| current != score);
+            zset.insert(member, score);
+            if current.is_none() || (options.ch && changed) {
+                count += 1;
+            }
+        }
+        // XX 等条件可能导致一个成员都没有加入
+        drop(zset);
+        self.remove_if_empty(&key);
+        self.upgrade_zset_encoding(&key);
+        self.touch_existing(&key);
+        Ok(count)
+    }
+
+    /// Pop up to `count` members with the lowest (or highest unless `min`)
+    /// scores from the first non-empty sorted set among `keys`, as ZMPOP.
+    pub fn zmpop(
+        &self,
+        keys: &[String],
+        min: bool,
+        count: usize,
+    ) -> Result<Option<ZPopped>, BackendError> {
+        for key in keys {
+            match self.key_type(key) {
+                Some("zset") => {}
+                Some(_) => return Err(BackendError::WrongType),
+                None => continue,
+            }
+            let members = match self.zset.get_mut(key) {
+                Some(mut zset) => (0..count)
+                    .map_while(|_| {
+                        if min {
+                            zset.pop_first()
+                        } else {
+                            zset.pop_last()
+                        }
+                    })
+                    .collect(),
+                None => continue,
+            };
+            self.remove_if_empty(key);
+            self.touch_existing(key);
+            return Ok(Some((key.clone(), members)));
+        }
+        Ok(None)
+    }
+
+    /// ZADD INCR: add `increment` to the score of `member` subject to the
+    /// flags, returns the new score or `None` if the update was aborted.
+    pub fn zincrby_with(
+        &self,
+        key: String,
+        member: String,
+        increment: f64,
+        options: ZAddOptions,
+    ) -> Result<Option<f64>, BackendError> {
+        let _guard = self.lock_key(&key);
+        self.check_type(&key, "zset")?;
+        let mut zset = self.zset.entry(key.clone()).or_default();
+        let score = options.resolve(zset.score(&member), increment);
+        // inf 加上 -inf 得到 NaN，和 Redis 一样报错
+        if score.is_some_and(f64::is_nan) {
+            drop(zset);
+            self.remove_if_empty(&key);
+            return Err(BackendError::NanScore);
+        }
+        if let Some(score) = score {
+            zset.insert(member, score);
+        }
+        drop(zset);
+        self.remove_if_empty(&key);
+        self.upgrade_zset_encoding(&key);
+        self.touch_existing(&key);
+        Ok(score)
+    }
+
+    /// Rank of `member` in the sorted set at `key` and its score, counted
+    /// from the highest score when `rev` is set.
+    pub fn zrank(
+        &self,
+        key: &str,
+        member: &str,
+        rev: bool,
+    ) -> Result<Option<(usize, f64)>, BackendError> {
+        self.expire_if_needed(key);
+        self.check_type(key, "zset")?;
+        let rank = self.zset.get(key).and_then(|zset| {
+            zset.rank(member).map(|(rank, score)| {
+                if rev {
+                    (zset.len() - 1 - rank, score)
+                } else {
+                    (rank, score)
+                }
+            })
+        });
+        self.touch_existing(key);
+        Ok(rank)
+    }
+
+    /// Remove `members` from the sorted set at `key`, returns how many existed.
+    pub fn zrem(&self, key: &str, members: &[String]) -> Result<usize, BackendError> {
+        self.check_type(key, "zset")?;
+        let removed = match self.zset.get_mut(key) {
+            Some(mut zset) => members
+                .iter()
+                .filter(|member| zset.remove(member).is_some())
+                .count(),
+            None => 0,
+        };
+        self.remove_if_empty(key);
+        self.touch_existing(key);
+        Ok(removed)
+    }
+
+    pub fn zscore(&self, key: &str, member: &str) -> Option<f64> {
+        self.expire_if_needed(key);
+        let score = self.zset.get(key).and_then(|v| v.score(member));
+        self.touch_existing(key);
+        score
+    }
+
+    /// Scores of `members` in the sorted set at `key`, in the same order.
+    pub fn zmscore(&self, key: &str, members: &[String]) -> Vec<Option<f64>> {
+        self.expire_if_needed(key);
+        let scores = match self.zset.get(key) {
+            Some(zset) => members.iter().map(|m| zset.score(m)).collect(),
+            None => vec![None; members.len()],
+        };
+        self.touch_existing(key);
+        scores
+    }
+
+    pub fn zrangebylex(&self, key: &str, min: &LexBound, max: &LexBound) -> Vec<String> {
+        self.expire_if_needed(key);
+        let members = self
+            .zset
+            .get(key)
+            .map(|v| v.range_by_lex(min, max))
+            .unwrap_or_default();
+        self.touch_existing(key);
+        members
+    }
+
+    /// Union of the sorted sets at `keys`, each score multiplied by its weight.
+    pub fn zunion(&self, keys: &[String], weights: &[f64], aggregate: Aggregate) -> ZSet {
+        let mut result = HashMap::new();
+        for (key, weight) in keys.iter().zip(weights) {
+            for (member, score) in self.weighted_zset(key, *weight) {
+                result
+                    .entry(member)
+                    .and_modify(|v| *v = aggregate.apply(*v, score))
+                    .or_insert(score);
+            }
+        }
+        result.into_iter().map(|(m, s)| (s, m)).collect()
+    }
+
+    /// Members present in every sorted set at `keys`, scores combined like `zunion`.
+    pub fn zinter(&self, keys: &[String], weights: &[f64], aggregate: Aggregate) -> ZSet {
+        let mut sets = keys
+            .iter()
+            .zip(weights)
+            .map(|(key, weight)| self.weighted_zset(key, *weight));
+        let Some(first) = sets.next() else {
+            return ZSet::new();
+        };
+        let result = sets.fold(first.into_iter().collect::<HashMap<_, _>>(), |acc, set| {
+            let set: HashMap<_, _> = set.into_iter().collect();
+            acc.into_iter()
+                .filter_map(|(m, s)| set.get(&m).map(|other| (m, aggregate.apply(s, *other))))
+                .collect()
+        });
+        result.into_iter().map(|(m, s)| (s, m)).collect()
+    }
+
+    /// Members of the sorted set at `key` selected by `by`, with their scores.
+    /// Equal scores are ordered by member, reversed as a whole when `rev`.
+    pub fn zrange(
+        &self,
+        key: &str,
+        by: &ZRangeBy,
+        rev: bool,
+        limit: Option<(i64, i64)>,
+    ) -> Result<Vec<(String, f64)>, BackendError> {
+        self.check_type(key, "zset")?;
+        self.expire_if_needed(key);
+        let members = match self.zset.get(key) {
+            Some(v) => v.range(by, rev, limit),
+            None => Vec::new(),
+        };
+        self.touch_existing(key);
+        Ok(members)
+    }
+
+    /// Store the members of `src` selected like ZRANGE into `dest`, returns
+    /// the cardinality of `dest`.
+    pub fn zrangestore(
+        &self,
+        dest: String,
+        src: &str,
+        by: &ZRangeBy,
+        rev: bool,
+        limit: Option<(i64, i64)>,
+    ) -> Result<usize, BackendError> {
+        // 先收集结果再写入，dest 和 src 相同时不会死锁
+        let zset: ZSet = self
+            .zrange(src, by, rev, limit)?
+            .into_iter()
+            .map(|(member, score)| (score, member))
+            .collect();
+        Ok(self.zstore(dest, zset))
+    }
+
+    /// Overwrite `dest` with `zset`, deleting it when empty. Returns its cardinality.
+    pub fn zstore(&self, dest: String, zset: ZSet) -> usize {
+        let _guard = self.lock_key(&dest);
+        // dest 原来可能是其他类型，连同 TTL 和编码一起删除
+        self.remove_value(&dest);
+        let len = zset.len();
+        self.zset.insert(dest.clone(), zset);
+        if !self.remove_if_empty(&dest) {
+            self.upgrade_zset_encoding(&dest);
+            self.touch(&dest);
+        }
+        len
+    }
+
+    fn weighted_zset(&self, key: &str, weight: f64) -> Vec<(String, f64)> {
+        self.expire_if_needed(key);
+        let members = self
+            .zset
+            .get(key)
+            .map(|v| {
+                v.iter()
+                    .map(|(m, s)| (m.to_string(), zset::nan_to_zero(s * weight)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.touch_existing(key);
+        members
+    }
+}
+
+// i64 转成字符串后的最大长度，即 i64::MIN 的长度
+const I64_MAX_LEN: usize = 20;
+
+// 字符串值可能以 bulk string 或整数的形式保存
+// 与 Redis 的 string2ll 一致：超出 i64 的大数、前导 + 或 0、空白都不算整数
+fn parse_i64(bytes: &[u8]) -> Option<i64> {
+    let s = std::str::from_utf8(bytes).ok()?;
+    s.parse::<i64>().ok().filter(|n| n.to_string() == s)
+}
+
+fn string_value(frame: &RespFrame) -> Vec<u8> {
+    match frame {
+        RespFrame::BulkString(s) => s.to_vec(),
+        RespFrame::SimpleString(s) => s.as_bytes().to_vec(),
+        RespFrame::Integer(n) => n.to_string().into_bytes(),
+        _ => Vec::new(),
     }
 }
@@ -0,0 +1,161 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::Backend;
+use crate::BulkString;
+
+/// Keyspace event classes selected by `notify-keyspace-events`, using the
+/// same letters as Redis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NotifyFlags(u32);
+
+impl NotifyFlags {
+    pub const NONE: Self = Self(0);
+    /// `K`: publish to `__keyspace@0__:<key>`.
+    pub const KEYSPACE: Self = Self(1);
+    /// `E`: publish to `__keyevent@0__:<event>`.
+    pub const KEYEVENT: Self = Self(1 << 1);
+    /// `g`: type independent commands such as DEL and EXPIRE.
+    pub const GENERIC: Self = Self(1 << 2);
+    pub const STRING: Self = Self(1 << 3);
+    pub const LIST: Self = Self(1 << 4);
+    pub const SET: Self = Self(1 << 5);
+    pub const HASH: Self = Self(1 << 6);
+    pub const ZSET: Self = Self(1 << 7);
+    pub const EXPIRED: Self = Self(1 << 8);
+    /// `A`: alias for `g$lshzx`.
+    pub const ALL: Self = Self(
+        Self::GENERIC.0
+            | Self::STRING.0
+            | Self::LIST.0
+            | Self::SET.0
+            | Self::HASH.0
+            | Self::ZSET.0
+            | Self::EXPIRED.0,
+    );
+
+    // 类型相关的标志和对应的字母，顺序即 CONFIG GET 的输出顺序
+    const CLASSES: [(char, Self); 7] = [
+        ('g', Self::GENERIC),
+        ('$', Self::STRING),
+        ('l', Self::LIST),
+        ('s', Self::SET),
+        ('h', Self::HASH),
+        ('z', Self::ZSET),
+        ('x', Self::EXPIRED),
+    ];
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub(crate) fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub(crate) fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+}
+
+impl std::ops::BitOr for NotifyFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl FromStr for NotifyFlags {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.chars().try_fold(Self::NONE, |flags, c| {
+            let flag = match c {
+                'A' => Self::ALL,
+                'K' => Self::KEYSPACE,
+                'E' => Self::KEYEVENT,
+                c => Self::CLASSES
+                    .iter()
+                    .find(|(letter, _)| *letter == c)
+                    .map(|(_, flag)| *flag)
+                    .ok_or_else(|| format!("invalid notify-keyspace-events flag '{}'", c))?,
+            };
+            Ok(flags | flag)
+        })
+    }
+}
+
+impl fmt::Display for NotifyFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.contains(Self::ALL) {
+            f.write_str("A")?;
+        } else {
+            for (letter, flag) in Self::CLASSES {
+                if self.contains(flag) {
+                    write!(f, "{}", letter)?;
+                }
+            }
+        }
+        for (letter, flag) in [('K', Self::KEYSPACE), ('E', Self::KEYEVENT)] {
+            if self.contains(flag) {
+                write!(f, "{}", letter)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Backend {
+    /// Publish a keyspace notification for `event` on `key` if its class is
+    /// enabled, to the keyspace and/or keyevent channel.
+    pub(crate) fn notify_keyspace_event(&self, class: NotifyFlags, event: &str, key: &str) {
+        let flags = self.config.notify_keyspace_events();
+        if !flags.contains(class) {
+            return;
+        }
+        if flags.contains(NotifyFlags::KEYSPACE) {
+            let channel = format!("__keyspace@0__:{}", key);
+            self.publish(&channel, BulkString::from(event).into());
+        }
+        if flags.contains(NotifyFlags::KEYEVENT) {
+            let channel = format!("__keyevent@0__:{}", event);
+            self.publish(&channel, BulkString::from(key).into());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notify_flags_round_trip() {
+        assert_eq!("".parse(), Ok(NotifyFlags::NONE));
+        let flags: NotifyFlags = "Eg$".parse().unwrap();
+        assert!(flags.contains(NotifyFlags::KEYEVENT | NotifyFlags::GENERIC));
+        assert!(!flags.contains(NotifyFlags::KEYSPACE));
+        assert_eq!(flags.to_string(), "g$E");
+        assert_eq!("KEA".parse::<NotifyFlags>().unwrap().to_string(), "AKE");
+        assert!("Q".parse::<NotifyFlags>().is_err());
+    }
+
+    #[test]
+    fn test_no_event_when_class_disabled() {
+        let backend = Backend::new();
+        let mut subscriber = backend.subscriber();
+        subscriber.subscribe("__keyevent@0__:set".to_string());
+
+        backend
+            .config()
+            .set_notify_keyspace_events("Eg".parse().unwrap());
+        backend.notify_keyspace_event(NotifyFlags::STRING, "set", "k");
+        assert!(subscriber.try_recv().is_none());
+
+        backend
+            .config()
+            .set_notify_keyspace_events("E$".parse().unwrap());
+        backend.notify_keyspace_event(NotifyFlags::STRING, "set", "k");
+        assert!(subscriber.try_recv().is_some());
+    }
+}
@@ -0,0 +1,569 @@
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use dashmap::DashMap;
+use tokio::sync::Notify;
+
+use crate::{glob::glob_match, Backend, BulkString, RespFrame, RespPush, SharedFrame};
+
+/// What PUBLISH does when a subscriber already has `pubsub-buffer-limit`
+/// messages waiting to be written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PubSubOverflow {
+    /// Discard the oldest pending message to make room.
+    DropOldest,
+    /// Discard the message being published.
+    DropNewest,
+    /// Drop everything pending and close the subscriber's connection.
+    #[default]
+    Disconnect,
+}
+
+#[derive(Debug, Default)]
+pub struct PubSub {
+    next_id: AtomicU64,
+    subscribers: DashMap<u64, Arc<Mailbox>>,
+    channels: DashMap<String, HashSet<u64>>,
+    patterns: DashMap<String, HashSet<u64>>,
+    // 串行化 publish 的 fan-out，保证所有订阅者都按 publish 的顺序收到消息
+    publish_lock: Mutex<()>,
+}
+
+// 每个订阅者待发送的消息，publish 写入，连接读出
+#[derive(Debug, Default)]
+struct Mailbox {
+    queue: Mutex<VecDeque<SharedFrame>>,
+    notify: Notify,
+    // 缓冲区满且策略为 Disconnect 时置位，之后不再接收消息
+    disconnected: AtomicBool,
+}
+
+impl Mailbox {
+    // 返回消息是否进入了队列
+    fn deliver(&self, frame: SharedFrame, limit: usize, policy: PubSubOverflow) -> bool {
+        if self.disconnected.load(Ordering::Relaxed) {
+            return false;
+        }
+        let mut queue = self.queue.lock().unwrap();
+        if limit > 0 && queue.len() >= limit {
+            match policy {
+                PubSubOverflow::DropOldest => {
+                    queue.pop_front();
+                }
+                PubSubOverflow::DropNewest => return false,
+                PubSubOverflow::Disconnect => {
+                    queue.clear();
+                    self.disconnected.store(true, Ordering::Relaxed);
+                    drop(queue);
+                    self.notify.notify_one();
+                    return false;
+                }
+            }
+        }
+        queue.push_back(frame);
+        drop(queue);
+        self.notify.notify_one();
+        true
+    }
+}
+
+/// A connection's handle into the pub/sub registry. Dropping it removes all
+/// of its subscriptions.
+#[derive(Debug)]
+pub struct Subscriber {
+    id: u64,
+    backend: Backend,
+    mailbox: Arc<Mailbox>,
+    channels: BTreeSet<String>,
+    patterns: BTreeSet<String>,
+}
+
+impl Backend {
+    pub fn subscriber(&self) -> Subscriber {
+        let id = self.pubsub.next_id.fetch_add(1, Ordering::Relaxed);
+        let mailbox = Arc::new(Mailbox::default());
+        self.pubsub.subscribers.insert(id, mailbox.clone());
+        Subscriber {
+            id,
+            backend: self.clone(),
+            mailbox,
+            channels: BTreeSet::new(),
+            patterns: BTreeSet::new(),
+        }
+    }
+
+    /// Deliver `message` to every subscriber of `channel`. A subscriber matched
+    /// by both the channel and one or more patterns receives it only once.
+    /// Subscribers whose buffer is full are handled by the configured
+    /// `PubSubOverflow` policy and don't count as receivers unless the message
+    /// was queued.
+    pub fn publish(&self, channel: &str, message: RespFrame) -> usize {
+        let pubsub = &self.pubsub;
+        let _guard = pubsub.publish_lock.lock().unwrap();
+
+        // 同一条消息只构造一次，所有订阅者共享同一个 frame 及其编码结果
+        let mut delivered = HashSet::new();
+        let mut deliveries = Vec::new();
+        if let Some(ids) = pubsub.channels.get(channel) {
+            let frame = pubsub_message(&["message", channel], message.clone()).into_shared();
+            for id in ids.iter() {
+                if delivered.insert(*id) {
+                    deliveries.push((*id, frame.clone()));
+                }
+            }
+        }
+        for entry in pubsub.patterns.iter() {
+            if !glob_match(entry.key().as_bytes(), channel.as_bytes()) {
+                continue;
+            }
+            let mut frame = None;
+            for id in entry.value().iter() {
+                if delivered.insert(*id) {
+                    let frame = frame.get_or_insert_with(|| {
+                        pubsub_message(&["pmessage", entry.key(), channel], message.clone())
+                            .into_shared()
+                    });
+                    deliveries.push((*id, frame.clone()));
+                }
+            }
+        }
+
+        let limit = self.config.pubsub_buffer_limit();
+        let policy = self.config.pubsub_overflow();
+        deliveries
+            .into_iter()
+            .filter(|(id, frame)| {
+                pubsub
+                    .subscribers
+                    .get(id)
+                    .is_some_and(|mailbox| mailbox.deliver(frame.clone(), limit, policy))
+            })
+            .count()
+    }
+}
+
+impl Backend {
+    /// Channels with at least one subscriber, optionally filtered by a glob pattern.
+    pub fn pubsub_channels(&self, pattern: Option<&str>) -> Vec<String> {
+        let mut channels: Vec<String> = self
+            .pubsub
+            .channels
+            .iter()
+            .map(|entry| entry.key().clone())
+            .filter(|channel| {
+                pattern.is_none_or(|pattern| glob_match(pattern.as_bytes(), channel.as_bytes()))
+            })
+            .collect();
+        channels.sort();
+        channels
+    }
+
+    /// Number of channels with at least one subscriber.
+    pub fn pubsub_numchannels(&self) -> usize {
+        self.pubsub.channels.len()
+    }
+
+    /// Number of patterns with at least one subscriber, as PUBSUB NUMPAT.
+    pub fn pubsub_numpat(&self) -> usize {
+        self.pubsub.patterns.len()
+    }
+}
+
+impl Subscriber {
+    pub fn subscribe(&mut self, channel: String) -> RespFrame {
+        self.backend
+            .pubsub
+            .channels
+            .entry(channel.clone())
+            .or_default()
+            .insert(self.id);
+        self.channels.insert(channel.clone());
+        self.confirmation("subscribe", Some(&channel))
+    }
+
+    pub fn psubscribe(&mut self, pattern: String) -> RespFrame {
+        self.backend
+            .pubsub
+            .patterns
+            .entry(pattern.clone())
+            .or_default()
+            .insert(self.id);
+        self.patterns.insert(pattern.clone());
+        self.confirmation("psubscribe", Some(&pattern))
+    }
+
+    /// Unsubscribe from `channels`, or from every channel if it is empty.
+    pub fn unsubscribe(&mut self, channels: Vec<String>) -> Vec<RespFrame> {
+        let channels = if channels.is_empty() {
+            self.channels.iter().cloned().collect()
+        } else {
+            channels
+        };
+        if channels.is_empty() {
+            return vec![self.confirmation("unsubscribe", None)];
+        }
+
+        channels
+            .into_iter()
+            .map(|channel| {
+                remove_subscription(&self.backend.pubsub.channels, &channel, self.id);
+                self.channels.remove(&channel);
+                self.confirmation("unsubscribe", Some(&channel))
+            })
+            .collect()
+    }
+
+    /// Unsubscribe from `patterns`, or from every pattern if it is empty.
+    pub fn punsubscribe(&mut self, patterns: Vec<String>) -> Vec<RespFrame> {
+        let patterns = if patterns.is_empty() {
+            self.patterns.iter().cloned().collect()
+        } else {
+            patterns
+        };
+        if patterns.is_empty() {
+            return vec![self.confirmation("punsubscribe", None)];
+        }
+
+        patterns
+            .into_iter()
+            .map(|pattern| {
+                remove_subscription(&self.backend.pubsub.patterns, &pattern, self.id);
+                self.patterns.remove(&pattern);
+                self.confirmation("punsubscribe", Some(&pattern))
+            })
+            .collect()
+    }
+
+    pub fn subscription_count(&self) -> usize {
+        self.channels.len() + self.patterns.len()
+    }
+
+    /// Wait for the next message. Returns None once the subscriber was
+    /// disconnected for falling behind, the connection should be closed.
+    pub async fn recv(&mut self) -> Option<SharedFrame> {
+        loop {
+            if self.is_disconnected() {
+                return None;
+            }
+            if let Some(frame) = self.try_recv() {
+                return Some(frame);
+            }
+            // 先检查队列再等待，publish 之后的 notify_one 会留下 permit，不会丢失唤醒
+            self.mailbox.notify.notified().await;
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Option<SharedFrame> {
+        self.mailbox.queue.lock().unwrap().pop_front()
+    }
+
+    /// Whether PUBLISH gave up on this subscriber because its buffer filled up
+    /// under the `Disconnect` policy.
+    pub fn is_disconnected(&self) -> bool {
+        self.mailbox.disconnected.load(Ordering::Relaxed)
+    }
+
+    fn confirmation(&self, kind: &str, name: Option<&str>) -> RespFrame {
+        let name = match name {
+            Some(name) => BulkString::from(name),
+            None => BulkString::null(),
+        };
+        RespPush::new(vec![
+            BulkString::from(kind).into(),
+            name.into(),
+            RespFrame::Integer(self.subscription_count() as i64),
+        ])
+        .into()
+    }
+}
+
+impl Drop for Subscriber {
+    fn drop(&mut self) {
+        let pubsub = &self.backend.pubsub;
+        for channel in &self.channels {
+            remove_subscription(&pubsub.channels, channel, self.id);
+        }
+        for pattern in &self.patterns {
+            remove_subscription(&pubsub.patterns, pattern, self.id);
+        }
+        pubsub.subscribers.remove(&self.id);
+    }
+}
+
+impl FromStr for PubSubOverflow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "drop-oldest" => Ok(Self::DropOldest),
+            "drop-newest" => Ok(Self::DropNewest),
+            "disconnect" => Ok(Self::Disconnect),
+            _ => Err(format!("invalid pubsub overflow policy '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for PubSubOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::DropOldest => "drop-oldest",
+            Self::DropNewest => "drop-newest",
+            Self::Disconnect => "disconnect",
+        })
+    }
+}
+
+// 最后一个订阅者离开时删除该 channel/pattern，避免 map 无限增长
+fn remove_subscription(map: &DashMap<String, HashSet<u64>>, name: &str, id: u64) {
+    if let Some(mut ids) = map.get_mut(name) {
+        ids.remove(&id);
+    }
+    map.remove_if(name, |_, ids| ids.is_empty());
+}
+
+// RESP3 下以 push 类型发送，RESP2 编码时会转换成普通数组
+fn pubsub_message(header: &[&str], message: RespFrame) -> RespFrame {
+    let mut frames: Vec<RespFrame> = header.iter().map(|s| BulkString::from(*s).into()).collect();
+    frames.push(message);
+    RespPush::new(frames).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn drain(subscriber: &mut Subscriber) -> Vec<RespFrame> {
+        let mut frames = Vec::new();
+        while let Some(frame) = subscriber.try_recv() {
+            frames.push(frame.into_frame());
+        }
+        frames
+    }
+
+    #[test]
+    fn test_publish_in_order_without_duplicates() {
+        let backend = Backend::new();
+        let mut subscriber = backend.subscriber();
+        subscriber.subscribe("news".to_string());
+        subscriber.psubscribe("n*".to_string());
+        subscriber.psubscribe("news*".to_string());
+
+        for i in 0..100 {
+            let n = backend.publish("news", BulkString::from(i.to_string()).into());
+            assert_eq!(n, 1);
+        }
+
+        let expected: Vec<RespFrame> = (0..100)
+            .map(|i| pubsub_message(&["message", "news"], BulkString::from(i.to_string()).into()))
+            .collect();
+        assert_eq!(drain(&mut subscriber), expected);
+    }
+
+    #[test]
+    fn test_pubsub_frames_depend_on_protocol() {
+        use crate::{RespEncode, RespVersion};
+
+        let backend = Backend::new();
+        let mut subscriber = backend.subscriber();
+        let confirmation = subscriber.subscribe("news".to_string());
+        assert!(matches!(confirmation, RespFrame::Push(_)));
+        assert_eq!(
+            confirmation.clone().encode(),
+            b">3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n"
+        );
+        assert_eq!(
+            confirmation.into_version(RespVersion::Resp2).encode(),
+            b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n"
+        );
+
+        backend.publish("news", BulkString::from("hi").into());
+        let message = subscriber.try_recv().unwrap();
+        assert_eq!(
+            message.encode_for(RespVersion::Resp3),
+            b">3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$2\r\nhi\r\n"
+        );
+        assert_eq!(
+            message.encode_for(RespVersion::Resp2),
+            b"*3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$2\r\nhi\r\n"
+        );
+    }
+
+    #[test]
+    fn test_publish_pattern_only_once() {
+        let backend = Backend::new();
+        let mut subscriber = backend.subscriber();
+        subscriber.psubscribe("n*".to_string());
+        subscriber.psubscribe("news*".to_string());
+
+        let n = backend.publish("news", BulkString::from("hello").into());
+        assert_eq!(n, 1);
+
+        let frames = drain(&mut subscriber);
+        assert_eq!(frames.len(), 1);
+        let RespFrame::Push(ref frame) = frames[0] else {
+            panic!("expect a push frame");
+        };
+        assert_eq!(frame[0], BulkString::from("pmessage").into());
+        assert_eq!(frame[2], BulkString::from("news").into());
+    }
+
+    #[test]
+    fn test_concurrent_publish_same_order_for_all_subscribers() {
+        let backend = Backend::new();
+        let mut s1 = backend.subscriber();
+        let mut s2 = backend.subscriber();
+        s1.subscribe("news".to_string());
+        s2.psubscribe("new?".to_string());
+
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let backend = backend.clone();
+                thread::spawn(move || {
+                    for i in 0..200 {
+                        let message = format!("{}-{}", t, i);
+                        backend.publish("news", BulkString::from(message).into());
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let payloads = |frames: Vec<RespFrame>| -> Vec<RespFrame> {
+            frames
+                .into_iter()
+                .map(|frame| match frame {
+                    RespFrame::Push(push) => push.last().cloned().unwrap(),
+                    _ => panic!("expect a push frame"),
+                })
+                .collect()
+        };
+        let m1 = payloads(drain(&mut s1));
+        let m2 = payloads(drain(&mut s2));
+        assert_eq!(m1.len(), 800);
+        assert_eq!(m1, m2);
+
+        // 每个 publisher 自己发布的消息保持先后顺序
+        for t in 0..4 {
+            let prefix = format!("{}-", t);
+            let seq: Vec<usize> = m1
+                .iter()
+                .filter_map(|frame| match frame {
+                    RespFrame::BulkString(s) => {
+                        let s = String::from_utf8_lossy(s);
+                        s.strip_prefix(&prefix).map(|n| n.parse().unwrap())
+                    }
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(seq, (0..200).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_subscribe_unsubscribe_confirmations() {
+        let backend = Backend::new();
+        let mut subscriber = backend.subscriber();
+        let frame = subscriber.subscribe("a".to_string());
+        assert_eq!(
+            frame,
+            RespPush::new(vec![
+                BulkString::from("subscribe").into(),
+                BulkString::from("a").into(),
+                RespFrame::Integer(1),
+            ])
+            .into()
+        );
+        subscriber.psubscribe("b*".to_string());
+
+        let frames = subscriber.unsubscribe(vec![]);
+        assert_eq!(
+            frames,
+            vec![RespPush::new(vec![
+                BulkString::from("unsubscribe").into(),
+                BulkString::from("a").into(),
+                RespFrame::Integer(1),
+            ])
+            .into()]
+        );
+        assert_eq!(backend.publish("a", BulkString::from("x").into()), 0);
+
+        drop(subscriber);
+        assert_eq!(backend.publish("bb", BulkString::from("x").into()), 0);
+    }
+
+    #[test]
+    fn test_empty_channels_are_removed() {
+        let backend = Backend::new();
+        let mut s1 = backend.subscriber();
+        let mut s2 = backend.subscriber();
+        s1.subscribe("news".to_string());
+        s1.subscribe("sports".to_string());
+        s2.subscribe("news".to_string());
+        s2.psubscribe("n*".to_string());
+        assert_eq!(backend.pubsub_channels(None), vec!["news", "sports"]);
+        assert_eq!(backend.pubsub_channels(Some("s*")), vec!["sports"]);
+
+        s1.unsubscribe(vec!["sports".to_string()]);
+        assert_eq!(backend.pubsub_channels(None), vec!["news"]);
+
+        s1.unsubscribe(vec![]);
+        assert_eq!(backend.pubsub_channels(None), vec!["news"]);
+
+        drop(s2);
+        assert!(backend.pubsub_channels(None).is_empty());
+        assert!(backend.pubsub.patterns.is_empty());
+    }
+
+    #[test]
+    fn test_full_buffer_follows_overflow_policy() {
+        let published = |policy: PubSubOverflow| {
+            let backend = Backend::new();
+            backend.config().set_pubsub_buffer_limit(3);
+            backend.config().set_pubsub_overflow(policy);
+            // 从不读取消息的订阅者
+            let mut subscriber = backend.subscriber();
+            subscriber.subscribe("news".to_string());
+            let receivers: Vec<usize> = (0..5)
+                .map(|i| backend.publish("news", BulkString::from(i.to_string()).into()))
+                .collect();
+            (receivers, subscriber)
+        };
+        let payloads = |subscriber: &mut Subscriber| -> Vec<RespFrame> {
+            drain(subscriber)
+                .into_iter()
+                .map(|frame| match frame {
+                    RespFrame::Push(push) => push.last().cloned().unwrap(),
+                    _ => panic!("expect a push frame"),
+                })
+                .collect()
+        };
+        let messages = |ids: &[i32]| -> Vec<RespFrame> {
+            ids.iter()
+                .map(|i| BulkString::from(i.to_string()).into())
+                .collect()
+        };
+
+        let (receivers, mut subscriber) = published(PubSubOverflow::DropOldest);
+        assert_eq!(receivers, vec![1; 5]);
+        assert_eq!(payloads(&mut subscriber), messages(&[2, 3, 4]));
+
+        let (receivers, mut subscriber) = published(PubSubOverflow::DropNewest);
+        assert_eq!(receivers, vec![1, 1, 1, 0, 0]);
+        assert_eq!(payloads(&mut subscriber), messages(&[0, 1, 2]));
+
+        let (receivers, mut subscriber) = published(PubSubOverflow::Disconnect);
+        assert_eq!(receivers, vec![1, 1, 1, 0, 0]);
+        assert!(subscriber.is_disconnected());
+        assert!(drain(&mut subscriber).is_empty());
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        assert!(rt.block_on(subscriber.recv()).is_none());
+    }
+}
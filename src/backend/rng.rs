@@ -0,0 +1,77 @@
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Small seedable generator (splitmix64) shared by the random commands
+/// (SRANDMEMBER, SPOP, HRANDFIELD, RANDOMKEY). Not cryptographically secure.
+#[derive(Debug)]
+pub struct Rng {
+    state: Mutex<u64>,
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+        Self::new(seed)
+    }
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: Mutex::new(seed),
+        }
+    }
+
+    pub fn next_u64(&self) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform index in `0..n`, `n` must be non-zero.
+    pub fn below(&self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Pick up to `count` distinct items (partial Fisher-Yates).
+    pub fn sample<T>(&self, mut items: Vec<T>, count: usize) -> Vec<T> {
+        let count = count.min(items.len());
+        for i in 0..count {
+            let j = i + self.below(items.len() - i);
+            items.swap(i, j);
+        }
+        items.truncate(count);
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng_same_seed_same_sequence() {
+        let a = Rng::new(42);
+        let b = Rng::new(42);
+        for _ in 0..16 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn test_rng_sample_is_distinct() {
+        let rng = Rng::new(7);
+        let mut picked = rng.sample((0..10).collect(), 5);
+        assert_eq!(picked.len(), 5);
+        picked.sort();
+        picked.dedup();
+        assert_eq!(picked.len(), 5);
+        assert_eq!(rng.sample(vec![1, 2], 5).len(), 2);
+    }
+}
@@ -0,0 +1,173 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use super::Backend;
+use crate::glob::glob_match;
+use crate::RespFrame;
+
+impl Backend {
+    /// One SCAN step. Keys are visited in the order of a fixed hash and the
+    /// returned cursor is the next hash value to look at, so unlike an offset
+    /// into the (resizing) maps it never skips a key that stays in the
+    /// keyspace for the whole iteration. A key may still be returned twice.
+    /// Returns the next cursor, 0 once the iteration is complete.
+    pub fn scan(
+        &self,
+        cursor: u64,
+        count: usize,
+        pattern: Option<&[u8]>,
+        key_type: Option<&str>,
+    ) -> (u64, Vec<String>) {
+        let (next, keys) = scan_step(self.all_keys(), cursor, count);
+        let keys = keys
+            .into_iter()
+            .filter(|key| pattern.is_none_or(|p| glob_match(p, key.as_bytes())))
+            .filter(|key| match self.key_type(key) {
+                Some(t) => key_type.is_none_or(|key_type| key_type.eq_ignore_ascii_case(t)),
+                // 已过期的 key 不返回
+                None => false,
+            })
+            .collect();
+        (next, keys)
+    }
+
+    /// One HSCAN step over the fields of the hash at `key`, with the same
+    /// cursor guarantees as `scan`. Returns the next cursor and the matching
+    /// fields with their values.
+    pub fn hscan(
+        &self,
+        key: &str,
+        cursor: u64,
+        count: usize,
+        pattern: Option<&str>,
+    ) -> (u64, Vec<(String, RespFrame)>) {
+        self.expire_if_needed(key);
+        self.purge_expired_fields(key);
+        let Some(hmap) = self.hmap.get(key) else {
+            return (0, Vec::new());
+        };
+        let fields = hmap.iter().map(|e| e.key().clone()).collect();
+        let (next, fields) = scan_step(fields, cursor, count);
+        let entries = fields
+            .into_iter()
+            .filter(|field| pattern.is_none_or(|p| glob_match(p.as_bytes(), field.as_bytes())))
+            .filter_map(|field| {
+                let value = hmap.get(&field)?.value().clone();
+                Some((field, value))
+            })
+            .collect();
+        drop(hmap);
+        self.touch(key);
+        (next, entries)
+    }
+
+    fn all_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.map.iter().map(|e| e.key().clone()).collect();
+        keys.extend(self.hmap.iter().map(|e| e.key().clone()));
+        keys.extend(self.set.iter().map(|e| e.key().clone()));
+        keys.extend(self.list.iter().map(|e| e.key().clone()));
+        keys.extend(self.zset.iter().map(|e| e.key().clone()));
+        keys
+    }
+}
+
+// 从 cursor 开始按哈希值顺序取出至少 count 个名字，返回下一个游标，0 表示遍历结束
+fn scan_step(names: Vec<String>, cursor: u64, count: usize) -> (u64, Vec<String>) {
+    let mut candidates: Vec<(u64, String)> = names
+        .into_iter()
+        .map(|name| (scan_hash(&name), name))
+        .filter(|(hash, _)| *hash >= cursor)
+        .collect();
+    candidates.sort_unstable();
+
+    let count = count.max(1);
+    let next = if candidates.len() > count {
+        // 相同哈希值的 key 必须在同一步返回，否则游标越过它们后会漏掉
+        let bound = candidates[count - 1].0;
+        candidates.retain(|(hash, _)| *hash <= bound);
+        bound.checked_add(1).unwrap_or(0)
+    } else {
+        0
+    };
+    (next, candidates.into_iter().map(|(_, name)| name).collect())
+}
+
+// 同一进程内稳定的哈希，游标只需要在一次运行中有效
+fn scan_hash(key: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::*;
+    use crate::BulkString;
+
+    fn full_scan(backend: &Backend, count: usize) -> Vec<String> {
+        let (mut cursor, mut keys) = (0, Vec::new());
+        loop {
+            let (next, batch) = backend.scan(cursor, count, None, None);
+            keys.extend(batch);
+            if next == 0 {
+                return keys;
+            }
+            cursor = next;
+        }
+    }
+
+    #[test]
+    fn test_scan_returns_every_key_once_without_mutation() {
+        let backend = Backend::new();
+        for i in 0..100 {
+            backend.set(format!("key:{}", i), BulkString::from("v").into());
+        }
+        backend.sadd("set".to_string(), "m".to_string()).unwrap();
+
+        let keys = full_scan(&backend, 7);
+        assert_eq!(keys.len(), 101);
+        assert_eq!(keys.iter().collect::<HashSet<_>>().len(), 101);
+
+        let (_, keys) = backend.scan(0, 1000, Some(b"key:1?"), None);
+        assert_eq!(keys.len(), 10);
+        let (_, keys) = backend.scan(0, 1000, None, Some("set"));
+        assert_eq!(keys, vec!["set".to_string()]);
+    }
+
+    #[test]
+    fn test_scan_sees_long_lived_keys_during_mutation() {
+        let backend = Backend::new();
+        let stable: HashSet<String> = (0..2000).map(|i| format!("stable:{}", i)).collect();
+        for key in &stable {
+            backend.set(key.clone(), BulkString::from("v").into());
+        }
+
+        // 另一个线程不停地增删 key，让 map 扩容和缩容
+        let stop = Arc::new(AtomicBool::new(false));
+        let churn = {
+            let (backend, stop) = (backend.clone(), stop.clone());
+            thread::spawn(move || {
+                let mut i = 0u64;
+                while !stop.load(Ordering::Relaxed) {
+                    backend.set(format!("tmp:{}", i), BulkString::from("v").into());
+                    if i >= 500 {
+                        backend.del(&[format!("tmp:{}", i - 500)]);
+                    }
+                    i += 1;
+                }
+            })
+        };
+
+        for _ in 0..2 {
+            let seen: HashSet<String> = full_scan(&backend, 20).into_iter().collect();
+            let missing: Vec<_> = stable.difference(&seen).collect();
+            assert!(missing.is_empty(), "missing keys: {:?}", missing);
+        }
+        stop.store(true, Ordering::Relaxed);
+        churn.join().unwrap();
+    }
+}
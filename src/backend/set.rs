@@ -0,0 +1,143 @@
+use std::collections::HashSet;
+
+// 成员数不超过该值时线性存储在 Vec 中，省掉哈希表的桶和分片锁
+const SMALL_SET_MAX_ENTRIES: usize = 16;
+
+/// Members of one set. Small sets are a plain vector scanned linearly, they
+/// move to a hash set once they grow past `SMALL_SET_MAX_ENTRIES` members.
+#[derive(Debug, Clone, Default)]
+pub struct SetMembers(Repr);
+
+#[derive(Debug, Clone)]
+enum Repr {
+    Small(Vec<String>),
+    Large(HashSet<String>),
+}
+
+impl Default for Repr {
+    fn default() -> Self {
+        Repr::Small(Vec::new())
+    }
+}
+
+impl SetMembers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `member`, returns false if it was already present.
+    pub fn insert(&mut self, member: String) -> bool {
+        match &mut self.0 {
+            Repr::Small(members) => {
+                if members.contains(&member) {
+                    return false;
+                }
+                if members.len() < SMALL_SET_MAX_ENTRIES {
+                    members.push(member);
+                } else {
+                    let mut large: HashSet<String> = members.drain(..).collect();
+                    large.insert(member);
+                    self.0 = Repr::Large(large);
+                }
+                true
+            }
+            Repr::Large(members) => members.insert(member),
+        }
+    }
+
+    /// Remove `member`, returns whether it was present. A large set stays
+    /// large after shrinking.
+    pub fn remove(&mut self, member: &str) -> bool {
+        match &mut self.0 {
+            Repr::Small(members) => match members.iter().position(|m| m == member) {
+                Some(i) => {
+                    members.swap_remove(i);
+                    true
+                }
+                None => false,
+            },
+            Repr::Large(members) => members.remove(member),
+        }
+    }
+
+    pub fn contains(&self, member: &str) -> bool {
+        match &self.0 {
+            Repr::Small(members) => members.iter().any(|m| m == member),
+            Repr::Large(members) => members.contains(member),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Repr::Small(members) => members.len(),
+            Repr::Large(members) => members.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Members in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        let (small, large) = match &self.0 {
+            Repr::Small(members) => (Some(members.iter()), None),
+            Repr::Large(members) => (None, Some(members.iter())),
+        };
+        small
+            .into_iter()
+            .flatten()
+            .chain(large.into_iter().flatten())
+            .map(String::as_str)
+    }
+}
+
+impl FromIterator<String> for SetMembers {
+    fn from_iter<T: IntoIterator<Item = String>>(iter: T) -> Self {
+        let mut set = SetMembers::new();
+        for member in iter {
+            set.insert(member);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_members_behave_like_hash_set_across_growth() {
+        let mut set = SetMembers::new();
+        let mut expected = HashSet::new();
+        for i in 0..40 {
+            let member = (i % 30).to_string();
+            assert_eq!(set.insert(member.clone()), expected.insert(member));
+            assert_eq!(set.len(), expected.len());
+        }
+        assert!(matches!(set.0, Repr::Large(_)));
+
+        for i in (0..30).step_by(3) {
+            let member = i.to_string();
+            assert_eq!(set.remove(&member), expected.remove(&member));
+        }
+        assert!(!set.remove("missing"));
+        assert!(set.contains("1"));
+        assert!(!set.contains("0"));
+
+        let members: HashSet<String> = set.iter().map(str::to_string).collect();
+        assert_eq!(members, expected);
+    }
+
+    #[test]
+    fn test_small_set_stays_inline() {
+        let mut set: SetMembers = ["a", "b", "a"].into_iter().map(String::from).collect();
+        assert_eq!(set.len(), 2);
+        assert!(matches!(set.0, Repr::Small(_)));
+
+        assert!(set.remove("a"));
+        assert!(!set.contains("a"));
+        assert!(set.remove("b"));
+        assert!(set.is_empty());
+    }
+}
@@ -0,0 +1,222 @@
+use std::sync::atomic::Ordering;
+
+use bytes::BytesMut;
+
+use crate::{
+    cmd::{Command, CommandError, CommandExecutor},
+    Backend, BulkString, RespArray, RespEncode, RespError, RespFrame, RespFrameIter,
+};
+
+pub(crate) struct LoadingGuard<'a>(&'a Backend);
+
+impl Drop for LoadingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.loading.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+// 快照就是一串 RESP 编码的写命令，加载时依次解析并执行即可恢复数据
+impl Backend {
+    pub fn save_snapshot(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        for entry in self.map.iter() {
+            let frame = command(&["set", entry.key()], [entry.value().clone()]);
+            buf.extend_from_slice(&frame.encode());
+        }
+        let now = self.clock.now();
+        for entry in self.hmap.iter() {
+            let expires = self.hash_expires.get(entry.key());
+            for field in entry.value().iter() {
+                let deadline = expires.as_ref().and_then(|e| e.get(field.key()).copied());
+                if deadline.is_some_and(|d| d <= now) {
+                    continue;
+                }
+                let frame = command(&["hset", entry.key(), field.key()], [field.value().clone()]);
+                buf.extend_from_slice(&frame.encode());
+                if let Some(deadline) = deadline {
+                    // HEXPIRE 以秒为单位，向上取整避免提前过期
+                    let secs = (deadline - now).as_millis().div_ceil(1000).to_string();
+                    let frame = command(
+                        &["hexpire", entry.key(), &secs, "fields", "1", field.key()],
+                        [],
+                    );
+                    buf.extend_from_slice(&frame.encode());
+                }
+            }
+        }
+        for entry in self.set.iter() {
+            for member in entry.value().iter() {
+                let frame = command(&["sadd", entry.key(), member], []);
+                buf.extend_from_slice(&frame.encode());
+            }
+        }
+        for entry in self.list.iter() {
+            let frame = command(&["rpush", entry.key()], entry.value().iter().cloned());
+            buf.extend_from_slice(&frame.encode());
+        }
+        for entry in self.zset.iter() {
+            let members = entry.value().iter().flat_map(|(member, score)| {
+                // f64 的 Display 输出可以无损地解析回来
+                [
+                    BulkString::from(score.to_string()).into(),
+                    BulkString::from(member).into(),
+                ]
+            });
+            let frame = command(&["zadd", entry.key()], members);
+            buf.extend_from_slice(&frame.encode());
+        }
+        for entry in self.expires.iter() {
+            // 已经过期还没被清理的 key 在加载时立即删除
+            let ms = entry.value().saturating_duration_since(now).as_millis();
+            let frame = command(&["pexpire", entry.key(), &ms.to_string()], []);
+            buf.extend_from_slice(&frame.encode());
+        }
+        buf
+    }
+
+    /// Replace the current data with the content of a snapshot, returns the
+    /// number of commands applied.
+    pub fn load_snapshot(&self, snapshot: &[u8]) -> Result<usize, CommandError> {
+        let _loading = self.start_loading();
+        self.clear();
+        self.replay(snapshot)
+    }
+
+    /// False while an AOF or snapshot is being loaded, so health checks don't
+    /// pass before the data is restored.
+    pub fn is_ready(&self) -> bool {
+        self.loading.load(Ordering::Acquire) == 0
+    }
+
+    // 返回的 guard 销毁时（加载完成或失败）减少计数，多个加载重叠时
+    // 先结束的那个不会提前恢复 ready
+    pub(crate) fn start_loading(&self) -> LoadingGuard<'_> {
+        self.loading.fetch_add(1, Ordering::AcqRel);
+        LoadingGuard(self)
+    }
+
+    /// Execute a stream of RESP-encoded commands against the current data,
+    /// shared by AOF and snapshot loading. A truncated frame at the end of
+    /// the stream is ignored.
+    pub(crate) fn replay(&self, data: &[u8]) -> Result<usize, CommandError> {
+        let mut buf = BytesMut::from(data);
+        let mut n = 0;
+        for frame in RespFrameIter::new(&mut buf).track_position() {
+            let frame = match frame {
+                Ok(frame) => frame,
+                // 写到一半崩溃时末尾可能只有半条命令，直接停止
+                Err(RespError::NotComplete) => break,
+                Err(e) => return Err(e.into()),
+            };
+            // AOF 中记录的是客户端发来的命令名，按 rename-command 表解析
+            Command::parse(frame, self.config())?.execute(self);
+            n += 1;
+        }
+        Ok(n)
+    }
+
+    pub(super) fn clear(&self) {
+        self.map.clear();
+        self.raw_strings.clear();
+        self.hmap.clear();
+        self.hash_expires.clear();
+        self.set.clear();
+        self.set_encodings.clear();
+        self.list.clear();
+        self.zset.clear();
+        self.zset_encodings.clear();
+        self.expires.clear();
+        self.access.clear();
+        self.reset_used_memory();
+    }
+}
+
+fn command(args: &[&str], values: impl IntoIterator<Item = RespFrame>) -> RespFrame {
+    let mut frames: Vec<RespFrame> = args.iter().map(|s| BulkString::from(*s).into()).collect();
+    frames.extend(values);
+    RespArray::new(frames).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_overlapping_loads_stay_not_ready() {
+        let backend = Backend::new();
+        let first = backend.start_loading();
+        let second = backend.start_loading();
+        // 先开始的加载结束了，另一个还在进行
+        drop(first);
+        assert!(!backend.is_ready());
+        drop(second);
+        assert!(backend.is_ready());
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("str".to_string(), BulkString::from("hello").into());
+        backend.set("int".to_string(), RespFrame::Integer(42));
+        backend
+            .hset("map".to_string(), "f1".to_string(), b"v1".into())
+            .unwrap();
+        backend.sadd("set".to_string(), "m1".to_string()).unwrap();
+        backend
+            .rpush("list".to_string(), vec![b"a".into(), b"b".into()])
+            .unwrap();
+        backend
+            .zadd(
+                "zset".to_string(),
+                vec![(0.1, "m1".to_string()), (f64::INFINITY, "m2".to_string())],
+            )
+            .unwrap();
+
+        let snapshot = backend.save_snapshot();
+        let other = Backend::new();
+        let n = other.load_snapshot(&snapshot)?;
+        assert_eq!(n, 6);
+
+        assert_eq!(
+            other.get("str").found(),
+            Some(BulkString::from("hello").into())
+        );
+        assert_eq!(other.get("int").found(), Some(RespFrame::Integer(42)));
+        assert_eq!(other.hget("map", "f1"), Some(b"v1".into()));
+        assert!(other.sismember("set", "m1").unwrap());
+        assert_eq!(other.lpop("list"), Some(b"a".into()));
+        assert_eq!(other.zscore("zset", "m1"), Some(0.1));
+        assert_eq!(other.zscore("zset", "m2"), Some(f64::INFINITY));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_snapshot_replaces_data() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("old".to_string(), BulkString::from("value").into());
+
+        let n = backend.load_snapshot(&Backend::new().save_snapshot())?;
+        assert_eq!(n, 0);
+        assert_eq!(backend.get("old").found(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_snapshot_ignores_truncated_tail() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("a".to_string(), BulkString::from("1").into());
+        let mut snapshot = backend.save_snapshot();
+        // 与 AOF 一样，末尾半条命令被忽略
+        snapshot.extend_from_slice(b"*3\r\n$3\r\nset\r\n$1\r\nb");
+
+        let other = Backend::new();
+        assert_eq!(other.load_snapshot(&snapshot)?, 1);
+        assert_eq!(other.get("a").found(), Some(BulkString::from("1").into()));
+        assert_eq!(other.get("b").found(), None);
+
+        Ok(())
+    }
+}
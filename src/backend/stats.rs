@@ -0,0 +1,81 @@
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use super::{Backend, NotifyFlags};
+
+/// Calls and total execution time of one command, as shown by
+/// INFO commandstats.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CommandStat {
+    pub calls: u64,
+    pub usec: u64,
+}
+
+/// Counters of the connection decode path, as shown by INFO stats.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ParseStats {
+    /// Frames decoded successfully.
+    pub frames: u64,
+    /// Bytes consumed by those frames.
+    pub bytes: u64,
+    /// Decode attempts that found only part of a frame in the buffer.
+    pub not_complete: u64,
+}
+
+impl Backend {
+    /// Count one execution of the (lowercase) command `name` taking `elapsed`.
+    pub fn record_command(&self, name: &str, elapsed: Duration) {
+        let mut stat = self.command_stats.entry(name.to_string()).or_default();
+        stat.calls += 1;
+        stat.usec += elapsed.as_micros() as u64;
+    }
+
+    /// Keys removed because their TTL passed, lazily or by active expiry.
+    pub fn expired_keys_total(&self) -> u64 {
+        self.stat_expired_keys.load(Ordering::Relaxed)
+    }
+
+    /// Keys removed explicitly by DEL or GETDEL.
+    pub fn deleted_keys_total(&self) -> u64 {
+        self.stat_deleted_keys.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn record_expired_key(&self, key: &str) {
+        self.stat_expired_keys.fetch_add(1, Ordering::Relaxed);
+        self.notify_keyspace_event(NotifyFlags::EXPIRED, "expired", key);
+    }
+
+    pub(crate) fn record_deleted_key(&self, key: &str) {
+        self.stat_deleted_keys.fetch_add(1, Ordering::Relaxed);
+        self.notify_keyspace_event(NotifyFlags::GENERIC, "del", key);
+    }
+
+    /// Frames and bytes decoded so far, and how often a read ended mid-frame.
+    pub fn parse_stats(&self) -> ParseStats {
+        ParseStats {
+            frames: self.parse_frames.load(Ordering::Relaxed),
+            bytes: self.parse_bytes.load(Ordering::Relaxed),
+            not_complete: self.parse_not_complete.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn record_parsed_frame(&self, len: usize) {
+        self.parse_frames.fetch_add(1, Ordering::Relaxed);
+        self.parse_bytes.fetch_add(len as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_parse_not_complete(&self) {
+        self.parse_not_complete.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Stats of every command executed so far, sorted by name.
+    pub fn command_stats(&self) -> Vec<(String, CommandStat)> {
+        let mut stats: Vec<_> = self
+            .command_stats
+            .iter()
+            .map(|entry| (entry.key().clone(), *entry.value()))
+            .collect();
+        stats.sort_by(|a, b| a.0.cmp(&b.0));
+        stats
+    }
+}
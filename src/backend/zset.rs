@@ -0,0 +1,351 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+
+/// Total-ordered wrapper so scores can be used as a BTreeSet key.
+#[derive(Debug, Clone, Copy)]
+pub struct Score(pub f64);
+
+/// One end of a ZRANGEBYLEX interval: `-`, `+`, `[member` or `(member`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LexBound {
+    NegInf,
+    PosInf,
+    Inclusive(String),
+    Exclusive(String),
+}
+
+/// One end of a BYSCORE interval: `1.5`, `(1.5`, `-inf` or `+inf`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScoreBound {
+    Inclusive(f64),
+    Exclusive(f64),
+}
+
+/// Members selected by a ZRANGE-style query. Bounds are always (min, max),
+/// REV queries swap them while parsing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ZRangeBy {
+    Index(i64, i64),
+    Score(ScoreBound, ScoreBound),
+    Lex(LexBound, LexBound),
+}
+
+/// How ZUNIONSTORE/ZINTERSTORE combine the scores of a member found in
+/// several input sets.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Aggregate {
+    #[default]
+    Sum,
+    Min,
+    Max,
+}
+
+/// Flags of ZADD. `nx`/`xx` restrict adding or updating members, `gt`/`lt`
+/// only update a member if the new score is greater/less than the current
+/// one, `ch` counts changed members too and `incr` adds to the score.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ZAddOptions {
+    pub nx: bool,
+    pub xx: bool,
+    pub gt: bool,
+    pub lt: bool,
+    pub ch: bool,
+    pub incr: bool,
+}
+
+/// Sorted set: members ordered by score, ties broken by member bytes.
+#[derive(Debug, Clone, Default)]
+pub struct ZSet {
+    scores: HashMap<String, f64>,
+    ordered: BTreeSet<(Score, String)>,
+}
+
+impl ZSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert or update `member`, returns true if it's a new member.
+    pub fn insert(&mut self, member: String, score: f64) -> bool {
+        // -0.0 和 0.0 视为同一个分数
+        let score = if score == 0.0 { 0.0 } else { score };
+        match self.scores.insert(member.clone(), score) {
+            Some(old) => {
+                self.ordered.remove(&(Score(old), member.clone()));
+                self.ordered.insert((Score(score), member));
+                false
+            }
+            None => {
+                self.ordered.insert((Score(score), member));
+                true
+            }
+        }
+    }
+
+    pub fn remove(&mut self, member: &str) -> Option<f64> {
+        let score = self.scores.remove(member)?;
+        self.ordered.remove(&(Score(score), member.to_string()));
+        Some(score)
+    }
+
+    pub fn score(&self, member: &str) -> Option<f64> {
+        self.scores.get(member).copied()
+    }
+
+    /// 0-based position of `member` in ascending order, with its score.
+    pub fn rank(&self, member: &str) -> Option<(usize, f64)> {
+        let score = self.score(member)?;
+        // BTreeSet 不支持按位置查询，只能数出排在前面的成员
+        let rank = self
+            .ordered
+            .range(..(Score(score), member.to_string()))
+            .count();
+        Some((rank, score))
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    /// Remove and return the member with the lowest score.
+    pub fn pop_first(&mut self) -> Option<(String, f64)> {
+        let (score, member) = self.ordered.pop_first()?;
+        self.scores.remove(&member);
+        Some((member, score.0))
+    }
+
+    /// Remove and return the member with the highest score.
+    pub fn pop_last(&mut self) -> Option<(String, f64)> {
+        let (score, member) = self.ordered.pop_last()?;
+        self.scores.remove(&member);
+        Some((member, score.0))
+    }
+
+    /// Iterate members in ascending (score, member) order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&str, f64)> {
+        self.ordered
+            .iter()
+            .map(|(score, member)| (member.as_str(), score.0))
+    }
+
+    /// Members within `[min, max]` in lex order; only meaningful when all
+    /// members share the same score, like in Redis.
+    pub fn range_by_lex(&self, min: &LexBound, max: &LexBound) -> Vec<String> {
+        self.iter()
+            .map(|(member, _)| member)
+            .filter(|member| min.below(member) && max.above(member))
+            .map(|member| member.to_string())
+            .collect()
+    }
+
+    /// Members selected by `by`, in descending order when `rev`. `limit` is
+    /// (offset, count) applied to BYSCORE/BYLEX results, a negative count
+    /// keeps everything after the offset.
+    pub fn range(&self, by: &ZRangeBy, rev: bool, limit: Option<(i64, i64)>) -> Vec<(String, f64)> {
+        let members: Box<dyn Iterator<Item = (&str, f64)>> = if rev {
+            Box::new(self.iter().rev())
+        } else {
+            Box::new(self.iter())
+        };
+        let selected: Box<dyn Iterator<Item = (&str, f64)>> = match by {
+            ZRangeBy::Index(start, stop) => {
+                let len = self.len() as i64;
+                let start = if *start < 0 {
+                    (len + start).max(0)
+                } else {
+                    *start
+                };
+                let stop = if *stop < 0 {
+                    len + stop
+                } else {
+                    (*stop).min(len - 1)
+                };
+                if start > stop {
+                    return Vec::new();
+                }
+                Box::new(
+                    members
+                        .skip(start as usize)
+                        .take((stop - start + 1) as usize),
+                )
+            }
+            ZRangeBy::Score(min, max) => {
+                Box::new(members.filter(|(_, score)| min.below(*score) && max.above(*score)))
+            }
+            ZRangeBy::Lex(min, max) => {
+                Box::new(members.filter(|(member, _)| min.below(member) && max.above(member)))
+            }
+        };
+        let selected = match limit {
+            // 和 Redis 一样，负的 offset 返回空结果
+            Some((offset, _)) if offset < 0 => return Vec::new(),
+            Some((offset, count)) => {
+                let count = usize::try_from(count).unwrap_or(usize::MAX);
+                Box::new(selected.skip(offset as usize).take(count))
+            }
+            None => selected,
+        };
+        selected
+            .map(|(member, score)| (member.to_string(), score))
+            .collect()
+    }
+}
+
+impl FromIterator<(f64, String)> for ZSet {
+    fn from_iter<I: IntoIterator<Item = (f64, String)>>(iter: I) -> Self {
+        let mut zset = ZSet::new();
+        for (score, member) in iter {
+            zset.insert(member, score);
+        }
+        zset
+    }
+}
+
+impl ZAddOptions {
+    /// The score `member` ends up with, or `None` if the flags forbid the update.
+    pub(crate) fn resolve(&self, current: Option<f64>, score: f64) -> Option<f64> {
+        match current {
+            Some(_) if self.nx => return None,
+            None if self.xx => return None,
+            _ => {}
+        }
+        let score = match (self.incr, current) {
+            (true, Some(current)) => current + score,
+            _ => score,
+        };
+        match current {
+            Some(current) if self.gt && score <= current => None,
+            Some(current) if self.lt && score >= current => None,
+            _ => Some(score),
+        }
+    }
+}
+
+impl Aggregate {
+    pub fn apply(self, a: f64, b: f64) -> f64 {
+        match self {
+            // inf + -inf 得到 nan，和 Redis 一样按 0 处理
+            Aggregate::Sum => nan_to_zero(a + b),
+            Aggregate::Min => a.min(b),
+            Aggregate::Max => a.max(b),
+        }
+    }
+}
+
+pub(crate) fn nan_to_zero(v: f64) -> f64 {
+    if v.is_nan() {
+        0.0
+    } else {
+        v
+    }
+}
+
+impl LexBound {
+    // 作为下界时 member 是否满足
+    fn below(&self, member: &str) -> bool {
+        match self {
+            LexBound::NegInf => true,
+            LexBound::PosInf => false,
+            LexBound::Inclusive(v) => member >= v.as_str(),
+            LexBound::Exclusive(v) => member > v.as_str(),
+        }
+    }
+
+    // 作为上界时 member 是否满足
+    fn above(&self, member: &str) -> bool {
+        match self {
+            LexBound::NegInf => false,
+            LexBound::PosInf => true,
+            LexBound::Inclusive(v) => member <= v.as_str(),
+            LexBound::Exclusive(v) => member < v.as_str(),
+        }
+    }
+}
+
+impl ScoreBound {
+    // 作为下界时 score 是否满足
+    fn below(&self, score: f64) -> bool {
+        match self {
+            ScoreBound::Inclusive(v) => score >= *v,
+            ScoreBound::Exclusive(v) => score > *v,
+        }
+    }
+
+    // 作为上界时 score 是否满足
+    fn above(&self, score: f64) -> bool {
+        match self {
+            ScoreBound::Inclusive(v) => score <= *v,
+            ScoreBound::Exclusive(v) => score < *v,
+        }
+    }
+}
+
+impl PartialEq for Score {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zset_order() {
+        let mut zset = ZSet::new();
+        assert!(zset.insert("b".to_string(), 1.0));
+        assert!(zset.insert("a".to_string(), 1.0));
+        assert!(zset.insert("c".to_string(), f64::NEG_INFINITY));
+        assert!(!zset.insert("c".to_string(), 2.0));
+
+        let members: Vec<(&str, f64)> = zset.iter().collect();
+        assert_eq!(members, vec![("a", 1.0), ("b", 1.0), ("c", 2.0)]);
+        assert_eq!(zset.score("c"), Some(2.0));
+
+        assert_eq!(zset.remove("a"), Some(1.0));
+        assert_eq!(zset.len(), 2);
+    }
+
+    #[test]
+    fn test_zset_negative_zero() {
+        let mut zset = ZSet::new();
+        zset.insert("a".to_string(), -0.0);
+        zset.insert("a".to_string(), 0.0);
+        assert_eq!(zset.iter().count(), 1);
+    }
+
+    #[test]
+    fn test_zset_range_by_lex() {
+        let mut zset = ZSet::new();
+        for member in ["d", "a", "c", "b", "e"] {
+            zset.insert(member.to_string(), 0.0);
+        }
+
+        let all = zset.range_by_lex(&LexBound::NegInf, &LexBound::PosInf);
+        assert_eq!(all, vec!["a", "b", "c", "d", "e"]);
+
+        let range = zset.range_by_lex(
+            &LexBound::Inclusive("b".to_string()),
+            &LexBound::Exclusive("d".to_string()),
+        );
+        assert_eq!(range, vec!["b", "c"]);
+    }
+}
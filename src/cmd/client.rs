@@ -0,0 +1,113 @@
+use crate::{ClientFlags, RespArray, RespFrame};
+
+use super::{
+    extract_args, validate_command, Client, ClientSubcommand, CommandError, CommandExecutor,
+    RESP_OK,
+};
+
+impl CommandExecutor for Client {
+    fn execute(self, _: &crate::Backend) -> RespFrame {
+        let mut flags = ClientFlags::default();
+        self.apply(&mut flags)
+    }
+}
+
+impl Client {
+    /// Update the flags of the connection that sent the command.
+    pub fn apply(self, flags: &mut ClientFlags) -> RespFrame {
+        match self.subcommand {
+            ClientSubcommand::NoEvict(on) => flags.no_evict = on,
+            ClientSubcommand::NoTouch(on) => flags.no_touch = on,
+        }
+        RESP_OK.clone()
+    }
+}
+
+impl TryFrom<RespArray> for Client {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["client"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let (sub, switch) = match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(sub)), Some(RespFrame::BulkString(switch))) => {
+                (sub.to_ascii_lowercase(), switch.to_ascii_lowercase())
+            }
+            _ => {
+                return Err(CommandError::InvalidArgument(
+                    "Invalid CLIENT subcommand".to_string(),
+                ))
+            }
+        };
+        let on = match switch.as_slice() {
+            b"on" => true,
+            b"off" => false,
+            _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+        };
+        let subcommand = match sub.as_slice() {
+            b"no-evict" => ClientSubcommand::NoEvict(on),
+            b"no-touch" => ClientSubcommand::NoTouch(on),
+            _ => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "Unknown subcommand '{}'",
+                    String::from_utf8_lossy(&sub)
+                )))
+            }
+        };
+        Ok(Client { subcommand })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::{Backend, BulkString, ManualClock, RespDecode};
+
+    use super::super::Get;
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_client_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nclient\r\n$8\r\nNO-TOUCH\r\n$2\r\nON\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Client = frame.try_into()?;
+        assert!(matches!(result.subcommand, ClientSubcommand::NoTouch(true)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_touch_get_keeps_idletime() {
+        let clock = Arc::new(ManualClock::new());
+        let backend = Backend::with_clock(clock.clone());
+        backend.set("k".to_string(), BulkString::from("v").into());
+        clock.advance(Duration::from_secs(10));
+
+        let mut flags = ClientFlags::default();
+        let cmd = Client {
+            subcommand: ClientSubcommand::NoTouch(true),
+        };
+        assert_eq!(cmd.apply(&mut flags), RESP_OK.clone());
+        assert!(flags.no_touch);
+
+        let get = || Get {
+            key: "k".to_string(),
+        };
+        assert_eq!(get().apply(&backend, &flags), BulkString::from("v").into());
+        assert_eq!(backend.object_idletime("k"), Some(10));
+
+        let cmd = Client {
+            subcommand: ClientSubcommand::NoTouch(false),
+        };
+        cmd.apply(&mut flags);
+        get().apply(&backend, &flags);
+        assert_eq!(backend.object_idletime("k"), Some(0));
+    }
+}
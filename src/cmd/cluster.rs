@@ -0,0 +1,224 @@
+use std::thread;
+use std::time::Duration;
+
+use crate::{BulkString, RespArray, RespFrame};
+
+use super::{
+    extract_args, parse_integer, validate_command, validate_command_at_least, Cluster,
+    CommandError, CommandExecutor, Failover, ReplicaOf, WaitAof, RESP_OK,
+};
+
+// 单机模式下 CLUSTER INFO 的固定输出
+const CLUSTER_INFO: &str = "cluster_enabled:0\r\ncluster_known_nodes:1\r\ncluster_size:0\r\n";
+
+impl CommandExecutor for Failover {
+    fn execute(self, _: &crate::Backend) -> RespFrame {
+        // 没有副本，所以既不会有进行中的故障转移，也无法发起新的
+        if self.abort {
+            RespFrame::error("ERR", "No failover in progress.")
+        } else {
+            RespFrame::error("ERR", "FAILOVER requires connected replicas.")
+        }
+    }
+}
+
+impl CommandExecutor for ReplicaOf {
+    fn execute(self, _: &crate::Backend) -> RespFrame {
+        // 本身就是主节点，REPLICAOF NO ONE 什么也不用做
+        RESP_OK.clone()
+    }
+}
+
+impl CommandExecutor for Cluster {
+    fn execute(self, _: &crate::Backend) -> RespFrame {
+        BulkString::from(CLUSTER_INFO).into()
+    }
+}
+
+impl CommandExecutor for WaitAof {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let local = match backend.fsync_aof() {
+            Ok(synced) => synced as u64,
+            Err(e) => return RespFrame::error("ERR", &format!("AOF fsync failed: {}", e)),
+        };
+        // 没有副本，要求的数量达不到时只能等到超时；timeout 为 0 时 Redis 会一直阻塞，这里直接返回
+        if (local < self.numlocal || self.numreplicas > 0) && !self.timeout.is_zero() {
+            thread::sleep(self.timeout);
+        }
+        RespArray::new(vec![
+            RespFrame::Integer(local as i64),
+            RespFrame::Integer(0),
+        ])
+        .into()
+    }
+}
+
+impl TryFrom<RespArray> for Failover {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["failover"], 0)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (None, _) => Ok(Failover { abort: false }),
+            (Some(RespFrame::BulkString(arg)), None) if arg.eq_ignore_ascii_case(b"abort") => {
+                Ok(Failover { abort: true })
+            }
+            _ => Err(CommandError::InvalidArgument(
+                "Only FAILOVER ABORT is supported".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ReplicaOf {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["replicaof"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(host)), Some(RespFrame::BulkString(port)))
+                if host.eq_ignore_ascii_case(b"no") && port.eq_ignore_ascii_case(b"one") =>
+            {
+                Ok(ReplicaOf)
+            }
+            _ => Err(CommandError::InvalidArgument(
+                "Only REPLICAOF NO ONE is supported".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Cluster {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["cluster"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(sub)) if sub.eq_ignore_ascii_case(b"info") => Ok(Cluster),
+            _ => Err(CommandError::InvalidArgument(
+                "Only CLUSTER INFO is supported".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for WaitAof {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["waitaof"], 3)?;
+
+        let args = extract_args(value, 1)?
+            .iter()
+            .map(parse_integer)
+            .collect::<Result<Vec<i64>, _>>()?;
+        match args[..] {
+            [numlocal, numreplicas, timeout] if numlocal >= 0 && numreplicas >= 0 => {
+                if timeout < 0 {
+                    return Err(CommandError::InvalidArgument(
+                        "timeout is negative".to_string(),
+                    ));
+                }
+                Ok(WaitAof {
+                    numlocal: numlocal as u64,
+                    numreplicas: numreplicas as u64,
+                    timeout: Duration::from_millis(timeout as u64),
+                })
+            }
+            _ => Err(CommandError::InvalidArgument(
+                "value is out of range, must be positive".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AppendFsync, Backend, RespDecode};
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_cluster_info_reports_disabled() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$7\r\ncluster\r\n$4\r\nINFO\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Cluster = frame.try_into()?;
+        let RespFrame::BulkString(info) = cmd.execute(&Backend::new()) else {
+            panic!("CLUSTER INFO should reply with a bulk string");
+        };
+        let info = String::from_utf8(info.get_data()?)?;
+        assert!(info.lines().any(|line| line == "cluster_enabled:0"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_replicaof_no_one_and_failover_abort() -> Result<()> {
+        let backend = Backend::new();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$9\r\nreplicaof\r\n$2\r\nNO\r\n$3\r\nONE\r\n");
+        let cmd: ReplicaOf = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+
+        buf.extend_from_slice(b"*3\r\n$9\r\nreplicaof\r\n$9\r\nlocalhost\r\n$4\r\n6379\r\n");
+        let ret: Result<ReplicaOf, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(ret.is_err());
+
+        buf.extend_from_slice(b"*2\r\n$8\r\nfailover\r\n$5\r\nABORT\r\n");
+        let cmd: Failover = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::error("ERR", "No failover in progress.")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_waitaof_reports_local_fsync() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$7\r\nwaitaof\r\n$1\r\n1\r\n$1\r\n0\r\n$1\r\n0\r\n");
+        let cmd: WaitAof = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.numlocal, 1);
+
+        // 没有开启 AOF
+        let backend = Backend::new();
+        let expected = |local| -> RespFrame {
+            RespArray::new(vec![RespFrame::Integer(local), RespFrame::Integer(0)]).into()
+        };
+        assert_eq!(cmd.execute(&backend), expected(0));
+
+        let path =
+            std::env::temp_dir().join(format!("simple-redis-{}-waitaof.aof", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        backend.enable_aof(&path, AppendFsync::No)?;
+        let cmd = WaitAof {
+            numlocal: 1,
+            numreplicas: 0,
+            timeout: Duration::ZERO,
+        };
+        assert_eq!(cmd.execute(&backend), expected(1));
+
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_waitaof_rejects_negative_arguments() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$7\r\nwaitaof\r\n$1\r\n1\r\n$1\r\n0\r\n$2\r\n-1\r\n");
+        let ret: Result<WaitAof, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(ret.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,587 @@
+use crate::{BulkString, RespArray, RespFrame, RespMap, SimpleString};
+
+use super::{
+    dispatch, extract_args, validate_command_at_least, CommandError, CommandExecutor, CommandMeta,
+    CommandMetaSubcommand,
+};
+
+// 命令名、arity（负数表示至少 |arity| 个参数，含命令名）和简介，供 COMMAND DOCS 使用
+const DOCS: &[(&str, i64, &str)] = &[
+    ("append", 3, "Appends a string to the value of a key."),
+    ("blmove", 6, "Pops an element from a list, pushes it to another list and returns it. Blocks until an element is available otherwise."),
+    ("blpop", -3, "Removes and returns the first element in a list. Blocks until an element is available otherwise."),
+    ("brpop", -3, "Removes and returns the last element in a list. Blocks until an element is available otherwise."),
+    ("client", -2, "A container for client connection commands."),
+    ("cluster", -2, "A container for Redis Cluster commands."),
+    ("command", -1, "Returns detailed information about all commands."),
+    ("config", -2, "A container for server configuration commands."),
+    ("copy", -3, "Copies the value of a key to a new key."),
+    ("dbsize", 1, "Returns the number of keys in the database."),
+    ("debug", -2, "A container for debugging commands."),
+    ("del", -2, "Deletes one or more keys."),
+    ("dump", 2, "Returns a serialized representation of the value stored at a key."),
+    ("echo", 2, "Returns the given string."),
+    ("exists", -2, "Determines whether one or more keys exist."),
+    ("expire", -3, "Sets the expiration time of a key in seconds."),
+    ("failover", -1, "Starts a coordinated failover from a server to one of its replicas."),
+    ("flushdb", -1, "Removes all keys from the current database."),
+    ("get", 2, "Returns the string value of a key."),
+    ("getdel", 2, "Returns the string value of a key after deleting the key."),
+    ("getex", -2, "Returns the string value of a key after setting its expiration time."),
+    ("getbit", 3, "Returns a bit value by offset."),
+    ("getrange", 4, "Returns a substring of the string stored at a key."),
+    ("getset", 3, "Returns the previous string value of a key after setting it to a new value."),
+    ("hello", -1, "Handshakes with the Redis server."),
+    ("hdel", -3, "Deletes one or more fields and their values from a hash. Deletes the hash if no fields remain."),
+    ("hexpire", -6, "Set expiry for hash field using relative time to expire (seconds)."),
+    ("hget", 3, "Returns the value of a field in a hash."),
+    ("hgetall", 2, "Returns all fields and values in a hash."),
+    ("hgetdel", -5, "Returns the values of one or more fields and deletes them from a hash."),
+    ("hgetex", -5, "Get the value of one or more fields of a given hash key, and optionally set their expiration."),
+    ("hincrbyfloat", 4, "Increments the floating point value of a field by a number. Uses 0 as initial value if the field doesn't exist."),
+    ("hkeys", 2, "Returns all fields in a hash."),
+    ("hlen", 2, "Returns the number of fields in a hash."),
+    ("hmget", -3, "Returns the values of all fields in a hash."),
+    ("hscan", -3, "Iterates over fields and values of a hash."),
+    ("hset", -4, "Creates or modifies the value of a field in a hash."),
+    ("httl", -5, "Returns the TTL in seconds of a hash field."),
+    ("hvals", 2, "Returns all values in a hash."),
+    ("incr", 2, "Increments the integer value of a key by one. Uses 0 as initial value if the key doesn't exist."),
+    ("incrby", 3, "Increments the integer value of a key by a number. Uses 0 as initial value if the key doesn't exist."),
+    ("info", -1, "Returns information and statistics about the server."),
+    ("lcs", -3, "Finds the longest common substring."),
+    ("lmove", 5, "Returns an element after popping it from one list and pushing it to another. Deletes the list if the last element was moved."),
+    ("lmpop", -4, "Returns multiple elements from a list after removing them. Deletes the list if the last element was popped."),
+    ("lpop", -2, "Returns the first elements in a list after removing it. Deletes the list if the last element was popped."),
+    ("lpos", -3, "Returns the index of matching elements in a list."),
+    ("lpush", -3, "Prepends one or more elements to a list. Creates the key if it doesn't exist."),
+    ("lrem", 4, "Removes elements from a list. Deletes the list if the last element was removed."),
+    ("ltrim", 4, "Removes elements from both ends a list. Deletes the list if all elements were trimmed."),
+    ("memory", -2, "A container for memory diagnostics commands."),
+    ("object", -2, "A container for object introspection commands."),
+    ("pexpire", -3, "Sets the expiration time of a key in milliseconds."),
+    ("pexpireat", -3, "Sets the expiration time of a key to a Unix milliseconds timestamp."),
+    ("ping", -1, "Returns the server's liveliness response."),
+    ("psubscribe", -2, "Listens for messages published to channels that match one or more patterns."),
+    ("pttl", 2, "Returns the expiration time in milliseconds of a key."),
+    ("publish", 3, "Posts a message to a channel."),
+    ("pubsub", -2, "A container for Pub/Sub commands."),
+    ("punsubscribe", -1, "Stops listening to messages published to channels that match one or more patterns."),
+    ("quit", -1, "Closes the connection."),
+    ("rename", 3, "Renames a key and overwrites the destination."),
+    ("renamenx", 3, "Renames a key only when the target key name doesn't exist."),
+    ("replicaof", 3, "Configures a server as replica of another, or promotes it to a master."),
+    ("reset", 1, "Resets the connection."),
+    ("restore", -4, "Creates a key from the serialized representation of a value."),
+    ("rpop", -2, "Returns and removes the last elements of a list. Deletes the list if the last element was popped."),
+    ("rpush", -3, "Appends one or more elements to a list. Creates the key if it doesn't exist."),
+    ("sadd", -3, "Adds one or more members to a set. Creates the key if it doesn't exist."),
+    ("scan", -2, "Iterates over the key names in the database."),
+    ("scard", 2, "Returns the number of members in a set."),
+    ("sdiffstore", -3, "Stores the difference of multiple sets in a key."),
+    ("set", -3, "Sets the string value of a key, ignoring its type. The key is created if it doesn't exist."),
+    ("setbit", 4, "Sets or clears the bit at offset of the string value. Creates the key if it doesn't exist."),
+    ("setrange", 4, "Overwrites a part of a string value with another by an offset. Creates the key if it doesn't exist."),
+    ("sinterstore", -3, "Stores the intersect of multiple sets in a key."),
+    ("sismember", 3, "Determines whether a member belongs to a set."),
+    ("smembers", 2, "Returns all members of a set."),
+    ("spop", -2, "Returns one or more random members from a set after removing them. Deletes the set if the last member was popped."),
+    ("srandmember", -2, "Get one or multiple random members from a set."),
+    ("srem", -3, "Removes one or more members from a set. Deletes the set if the last member was removed."),
+    ("strlen", 2, "Returns the length of a string value."),
+    ("subscribe", -2, "Listens for messages published to channels."),
+    ("substr", 4, "Returns a substring from string value."),
+    ("sunionstore", -3, "Stores the union of multiple sets in a key."),
+    ("ttl", 2, "Returns the expiration time in seconds of a key."),
+    ("unlink", -2, "Asynchronously deletes one or more keys."),
+    ("unsubscribe", -1, "Stops listening to messages posted to channels."),
+    ("waitaof", 4, "Blocks until all of the preceding write commands sent by the connection are written to the append-only file of the master and/or replicas."),
+    ("zadd", -4, "Adds one or more members to a sorted set, or updates their scores. Creates the key if it doesn't exist."),
+    ("zincrby", 4, "Increments the score of a member in a sorted set."),
+    ("zinterstore", -4, "Stores the intersect of multiple sorted sets in a key."),
+    ("zmpop", -4, "Returns the highest- or lowest-scoring members from one or more sorted sets after removing them. Deletes the sorted set if the last member was popped."),
+    ("zmscore", -3, "Returns the score of one or more members in a sorted set."),
+    ("zpopmax", -2, "Returns the highest-scoring members from a sorted set after removing them. Deletes the sorted set if the last member was popped."),
+    ("zpopmin", -2, "Returns the lowest-scoring members from a sorted set after removing them. Deletes the sorted set if the last member was popped."),
+    ("zrange", -4, "Returns members in a sorted set within a range of indexes."),
+    ("zrangebylex", -4, "Returns members in a sorted set within a lexicographical range."),
+    ("zrangestore", -5, "Stores a range of members from sorted set in a key."),
+    ("zrank", -3, "Returns the index of a member in a sorted set ordered by ascending scores."),
+    ("zrem", -3, "Removes one or more members from a sorted set. Deletes the sorted set if all members were removed."),
+    ("zrevrange", -4, "Returns members in a sorted set within a range of indexes in reverse order."),
+    ("zrevrangebyscore", -4, "Returns members in a sorted set within a range of scores in reverse order."),
+    ("zrevrank", -3, "Returns the index of a member in a sorted set ordered by descending scores."),
+    ("zscore", 3, "Returns the score of a member in a sorted set."),
+    ("zunionstore", -4, "Stores the union of multiple sorted sets in a key."),
+];
+
+// key 的访问标志，和 Redis 的 key specs 一致：RO 只读，RW 读写，OW 覆盖，RM 删除
+const RO: &[&str] = &["RO", "access"];
+const RW: &[&str] = &["RW", "access", "update"];
+const INSERT: &[&str] = &["RW", "insert"];
+const OW: &[&str] = &["OW", "update"];
+const RM: &[&str] = &["RM", "delete"];
+const RW_DELETE: &[&str] = &["RW", "access", "delete"];
+
+// key 在参数中的位置，下标从命令名之后的第一个参数算起为 1
+#[derive(Debug, Clone, Copy)]
+enum Keys {
+    // first..=last，每隔 step 一个 key；last 为负数时从末尾倒数，-1 表示最后一个参数
+    Range(usize, isize, usize),
+    // 下标处是 key 的个数，紧接着就是这些 key
+    Keynum(usize),
+}
+
+// 一个命令的全部 key spec：key 的位置和对应的访问标志
+type KeySpecs = &'static [(Keys, &'static [&'static str])];
+
+// 和 DOCS 一样按命令名排序，没有 key 的命令不需要出现在这里
+const KEY_SPECS: &[(&str, KeySpecs)] = &[
+    ("append", &[(Keys::Range(1, 1, 1), RW)]),
+    (
+        "blmove",
+        &[
+            (Keys::Range(1, 1, 1), RW_DELETE),
+            (Keys::Range(2, 2, 1), INSERT),
+        ],
+    ),
+    ("blpop", &[(Keys::Range(1, -2, 1), RW_DELETE)]),
+    ("brpop", &[(Keys::Range(1, -2, 1), RW_DELETE)]),
+    (
+        "copy",
+        &[(Keys::Range(1, 1, 1), RO), (Keys::Range(2, 2, 1), OW)],
+    ),
+    ("del", &[(Keys::Range(1, -1, 1), RM)]),
+    ("dump", &[(Keys::Range(1, 1, 1), RO)]),
+    ("exists", &[(Keys::Range(1, -1, 1), RO)]),
+    ("expire", &[(Keys::Range(1, 1, 1), RW)]),
+    ("get", &[(Keys::Range(1, 1, 1), RO)]),
+    ("getbit", &[(Keys::Range(1, 1, 1), RO)]),
+    ("getdel", &[(Keys::Range(1, 1, 1), RW_DELETE)]),
+    ("getex", &[(Keys::Range(1, 1, 1), RW)]),
+    ("getrange", &[(Keys::Range(1, 1, 1), RO)]),
+    ("getset", &[(Keys::Range(1, 1, 1), RW)]),
+    ("hdel", &[(Keys::Range(1, 1, 1), RW_DELETE)]),
+    ("hexpire", &[(Keys::Range(1, 1, 1), RW)]),
+    ("hget", &[(Keys::Range(1, 1, 1), RO)]),
+    ("hgetall", &[(Keys::Range(1, 1, 1), RO)]),
+    ("hgetdel", &[(Keys::Range(1, 1, 1), RW_DELETE)]),
+    ("hgetex", &[(Keys::Range(1, 1, 1), RW)]),
+    ("hincrbyfloat", &[(Keys::Range(1, 1, 1), RW)]),
+    ("hkeys", &[(Keys::Range(1, 1, 1), RO)]),
+    ("hlen", &[(Keys::Range(1, 1, 1), RO)]),
+    ("hmget", &[(Keys::Range(1, 1, 1), RO)]),
+    ("hscan", &[(Keys::Range(1, 1, 1), RO)]),
+    ("hset", &[(Keys::Range(1, 1, 1), RW)]),
+    ("httl", &[(Keys::Range(1, 1, 1), RO)]),
+    ("hvals", &[(Keys::Range(1, 1, 1), RO)]),
+    ("incr", &[(Keys::Range(1, 1, 1), RW)]),
+    ("incrby", &[(Keys::Range(1, 1, 1), RW)]),
+    ("lcs", &[(Keys::Range(1, 2, 1), RO)]),
+    (
+        "lmove",
+        &[
+            (Keys::Range(1, 1, 1), RW_DELETE),
+            (Keys::Range(2, 2, 1), INSERT),
+        ],
+    ),
+    ("lmpop", &[(Keys::Keynum(1), RW_DELETE)]),
+    ("lpop", &[(Keys::Range(1, 1, 1), RW_DELETE)]),
+    ("lpos", &[(Keys::Range(1, 1, 1), RO)]),
+    ("lpush", &[(Keys::Range(1, 1, 1), INSERT)]),
+    ("lrem", &[(Keys::Range(1, 1, 1), RW_DELETE)]),
+    ("ltrim", &[(Keys::Range(1, 1, 1), RW_DELETE)]),
+    ("pexpire", &[(Keys::Range(1, 1, 1), RW)]),
+    ("pexpireat", &[(Keys::Range(1, 1, 1), RW)]),
+    ("pttl", &[(Keys::Range(1, 1, 1), RO)]),
+    (
+        "rename",
+        &[
+            (Keys::Range(1, 1, 1), RW_DELETE),
+            (Keys::Range(2, 2, 1), OW),
+        ],
+    ),
+    (
+        "renamenx",
+        &[
+            (Keys::Range(1, 1, 1), RW_DELETE),
+            (Keys::Range(2, 2, 1), OW),
+        ],
+    ),
+    ("restore", &[(Keys::Range(1, 1, 1), OW)]),
+    ("rpop", &[(Keys::Range(1, 1, 1), RW_DELETE)]),
+    ("rpush", &[(Keys::Range(1, 1, 1), INSERT)]),
+    ("sadd", &[(Keys::Range(1, 1, 1), INSERT)]),
+    ("scard", &[(Keys::Range(1, 1, 1), RO)]),
+    (
+        "sdiffstore",
+        &[(Keys::Range(1, 1, 1), OW), (Keys::Range(2, -1, 1), RO)],
+    ),
+    ("set", &[(Keys::Range(1, 1, 1), RW)]),
+    ("setbit", &[(Keys::Range(1, 1, 1), RW)]),
+    ("setrange", &[(Keys::Range(1, 1, 1), RW)]),
+    (
+        "sinterstore",
+        &[(Keys::Range(1, 1, 1), OW), (Keys::Range(2, -1, 1), RO)],
+    ),
+    ("sismember", &[(Keys::Range(1, 1, 1), RO)]),
+    ("smembers", &[(Keys::Range(1, 1, 1), RO)]),
+    ("spop", &[(Keys::Range(1, 1, 1), RW_DELETE)]),
+    ("srandmember", &[(Keys::Range(1, 1, 1), RO)]),
+    ("srem", &[(Keys::Range(1, 1, 1), RW_DELETE)]),
+    ("strlen", &[(Keys::Range(1, 1, 1), RO)]),
+    ("substr", &[(Keys::Range(1, 1, 1), RO)]),
+    (
+        "sunionstore",
+        &[(Keys::Range(1, 1, 1), OW), (Keys::Range(2, -1, 1), RO)],
+    ),
+    ("ttl", &[(Keys::Range(1, 1, 1), RO)]),
+    ("unlink", &[(Keys::Range(1, -1, 1), RM)]),
+    ("zadd", &[(Keys::Range(1, 1, 1), RW)]),
+    ("zincrby", &[(Keys::Range(1, 1, 1), RW)]),
+    (
+        "zinterstore",
+        &[(Keys::Range(1, 1, 1), OW), (Keys::Keynum(2), RO)],
+    ),
+    ("zmpop", &[(Keys::Keynum(1), RW_DELETE)]),
+    ("zmscore", &[(Keys::Range(1, 1, 1), RO)]),
+    ("zpopmax", &[(Keys::Range(1, 1, 1), RW_DELETE)]),
+    ("zpopmin", &[(Keys::Range(1, 1, 1), RW_DELETE)]),
+    ("zrange", &[(Keys::Range(1, 1, 1), RO)]),
+    ("zrangebylex", &[(Keys::Range(1, 1, 1), RO)]),
+    (
+        "zrangestore",
+        &[(Keys::Range(1, 1, 1), OW), (Keys::Range(2, 2, 1), RO)],
+    ),
+    ("zrank", &[(Keys::Range(1, 1, 1), RO)]),
+    ("zrem", &[(Keys::Range(1, 1, 1), RW_DELETE)]),
+    ("zrevrange", &[(Keys::Range(1, 1, 1), RO)]),
+    ("zrevrangebyscore", &[(Keys::Range(1, 1, 1), RO)]),
+    ("zrevrank", &[(Keys::Range(1, 1, 1), RO)]),
+    ("zscore", &[(Keys::Range(1, 1, 1), RO)]),
+    (
+        "zunionstore",
+        &[(Keys::Range(1, 1, 1), OW), (Keys::Keynum(2), RO)],
+    ),
+];
+
+impl CommandExecutor for CommandMeta {
+    fn execute(self, _: &crate::Backend) -> RespFrame {
+        match self.subcommand {
+            CommandMetaSubcommand::Count => {
+                RespFrame::Integer(dispatch::command_names().count() as i64)
+            }
+            CommandMetaSubcommand::Docs(names) => docs(&names),
+            CommandMetaSubcommand::GetKeys(args, with_flags) => get_keys(&args, with_flags),
+            CommandMetaSubcommand::List => {
+                // 命令表本身按名字排序
+                RespArray::new(
+                    dispatch::command_names()
+                        .map(|name| BulkString::new(name).into())
+                        .collect::<Vec<RespFrame>>(),
+                )
+                .into()
+            }
+        }
+    }
+}
+
+// 不带命令名时返回全部命令，未知的命令直接跳过
+fn docs(names: &[String]) -> RespFrame {
+    let mut map = RespMap::new();
+    for (name, arity, summary) in DOCS {
+        if !names.is_empty() && !names.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+            continue;
+        }
+        let mut doc = RespMap::new();
+        doc.insert("summary".to_string(), BulkString::from(*summary).into());
+        doc.insert("arity".to_string(), RespFrame::Integer(*arity));
+        map.insert(name.to_string(), doc.into());
+    }
+    map.into()
+}
+
+// COMMAND GETKEYS / GETKEYSANDFLAGS：按 KEY_SPECS 从完整的命令参数中取出 key
+fn get_keys(args: &[Vec<u8>], with_flags: bool) -> RespFrame {
+    let name = args[0].to_ascii_lowercase();
+    let Some(arity) = DOCS
+        .iter()
+        .find(|(doc, _, _)| doc.as_bytes() == name)
+        .map(|(_, arity, _)| *arity)
+    else {
+        return RespFrame::error("ERR", "Invalid command specified");
+    };
+    let argc = args.len() as i64;
+    if (arity > 0 && argc != arity) || argc < -arity {
+        return RespFrame::error("ERR", "Invalid number of arguments specified for command");
+    }
+    let specs = KEY_SPECS
+        .iter()
+        .find(|(spec, _)| spec.as_bytes() == name)
+        .map_or(&[][..], |(_, specs)| *specs);
+
+    let mut keys = Vec::new();
+    for (spec, flags) in specs {
+        let indexes: Vec<usize> = match *spec {
+            Keys::Range(first, last, step) => {
+                let last = if last < 0 {
+                    args.len() as isize + last
+                } else {
+                    last
+                };
+                (first..args.len())
+                    .step_by(step)
+                    .take_while(|i| *i as isize <= last)
+                    .collect()
+            }
+            Keys::Keynum(index) => {
+                let count = args
+                    .get(index)
+                    .and_then(|n| std::str::from_utf8(n).ok())
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .unwrap_or(0);
+                (index + 1..args.len()).take(count).collect()
+            }
+        };
+        keys.extend(indexes.into_iter().map(|i| (&args[i], *flags)));
+    }
+    if keys.is_empty() {
+        return RespFrame::error("ERR", "The command has no key arguments");
+    }
+
+    let frames = keys.into_iter().map(|(key, flags)| {
+        let key: RespFrame = BulkString::new(key.clone()).into();
+        if !with_flags {
+            return key;
+        }
+        let flags = flags
+            .iter()
+            .map(|flag| SimpleString::new(*flag).into())
+            .collect::<Vec<RespFrame>>();
+        RespArray::new(vec![key, RespArray::new(flags).into()]).into()
+    });
+    RespArray::new(frames.collect::<Vec<RespFrame>>()).into()
+}
+
+impl TryFrom<RespArray> for CommandMeta {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["command"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let subcommand = match args.next() {
+            Some(RespFrame::BulkString(sub)) if sub.eq_ignore_ascii_case(b"count") => {
+                CommandMetaSubcommand::Count
+            }
+            Some(RespFrame::BulkString(sub)) if sub.eq_ignore_ascii_case(b"list") => {
+                CommandMetaSubcommand::List
+            }
+            Some(RespFrame::BulkString(sub)) if sub.eq_ignore_ascii_case(b"docs") => {
+                let mut names = Vec::new();
+                for arg in args {
+                    match arg {
+                        RespFrame::BulkString(name) => {
+                            names.push(String::from_utf8(name.get_data()?)?)
+                        }
+                        _ => {
+                            return Err(CommandError::InvalidArgument(
+                                "Invalid command name".to_string(),
+                            ))
+                        }
+                    }
+                }
+                CommandMetaSubcommand::Docs(names)
+            }
+            Some(RespFrame::BulkString(sub))
+                if sub.eq_ignore_ascii_case(b"getkeys")
+                    || sub.eq_ignore_ascii_case(b"getkeysandflags") =>
+            {
+                let with_flags = sub.eq_ignore_ascii_case(b"getkeysandflags");
+                let args = args
+                    .map(|arg| match arg {
+                        RespFrame::BulkString(arg) => Ok(arg.get_data()?),
+                        _ => Err(CommandError::InvalidArgument(
+                            "Invalid argument".to_string(),
+                        )),
+                    })
+                    .collect::<Result<Vec<_>, CommandError>>()?;
+                if args.is_empty() {
+                    return Err(CommandError::WrongArity("command|getkeys".to_string()));
+                }
+                CommandMetaSubcommand::GetKeys(args, with_flags)
+            }
+            _ => {
+                return Err(CommandError::InvalidArgument(
+                    "Invalid COMMAND subcommand".to_string(),
+                ))
+            }
+        };
+        Ok(CommandMeta { subcommand })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::{is_command, Command};
+    use crate::Backend;
+
+    use super::*;
+    use anyhow::Result;
+
+    // 新增命令时需要同时更新这里，提醒检查 COMMAND 的各个子命令
+    const EXPECTED_COMMANDS: i64 = 106;
+
+    fn command(args: &[&str]) -> Result<RespFrame> {
+        let frame = RespArray::new(
+            std::iter::once(&"command")
+                .chain(args)
+                .map(|arg| BulkString::from(*arg).into())
+                .collect::<Vec<_>>(),
+        );
+        Ok(Command::try_from(RespFrame::from(frame))?.execute(&Backend::new()))
+    }
+
+    fn command_docs(args: &[&str]) -> Result<RespFrame> {
+        let args: Vec<&str> = std::iter::once("docs")
+            .chain(args.iter().copied())
+            .collect();
+        command(&args)
+    }
+
+    #[test]
+    fn test_command_count_and_list_agree() -> Result<()> {
+        let RespFrame::Integer(count) = command(&["count"])? else {
+            panic!("COMMAND COUNT should reply with an integer");
+        };
+        let RespFrame::Array(list) = command(&["list"])? else {
+            panic!("COMMAND LIST should reply with an array");
+        };
+        assert!(list.contains(&BulkString::from("get").into()));
+
+        assert_eq!(count, EXPECTED_COMMANDS);
+        assert_eq!(list.len() as i64, EXPECTED_COMMANDS);
+        Ok(())
+    }
+
+    #[test]
+    fn test_every_command_has_docs() {
+        // 从分发表出发检查，新增命令漏写 DOCS 时这里会失败
+        for name in dispatch::command_names() {
+            let name = std::str::from_utf8(name).unwrap();
+            assert!(
+                DOCS.iter().any(|(doc, _, _)| *doc == name),
+                "{} has no docs",
+                name
+            );
+        }
+        assert_eq!(DOCS.len(), dispatch::command_names().count());
+    }
+
+    #[test]
+    fn test_command_docs_get() -> Result<()> {
+        let RespFrame::Map(docs) = command_docs(&["GET"])? else {
+            panic!("expected a map");
+        };
+        assert_eq!(docs.len(), 1);
+        let Some(RespFrame::Map(get)) = docs.get("get") else {
+            panic!("missing docs for get");
+        };
+        assert_eq!(get.get("arity"), Some(&RespFrame::Integer(2)));
+        assert!(matches!(get.get("summary"), Some(RespFrame::BulkString(s)) if !s.is_empty()));
+
+        assert_eq!(command_docs(&["nosuchcommand"])?, RespMap::new().into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_docs_only_cover_known_commands() {
+        for (name, _, _) in DOCS {
+            assert!(is_command(name.as_bytes()), "{} is not a command", name);
+        }
+    }
+
+    #[test]
+    fn test_command_getkeysandflags() -> Result<()> {
+        let bulk = |s: &str| -> RespFrame { BulkString::from(s).into() };
+        let flags = |flags: &[&str]| -> RespFrame {
+            RespArray::new(
+                flags
+                    .iter()
+                    .map(|f| SimpleString::new(*f).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into()
+        };
+
+        assert_eq!(
+            command(&["getkeysandflags", "set", "k", "v"])?,
+            RespArray::new(vec![RespArray::new(vec![
+                bulk("k"),
+                flags(&["RW", "access", "update"])
+            ])
+            .into()])
+            .into()
+        );
+        assert_eq!(
+            command(&["getkeysandflags", "COPY", "src", "dst"])?,
+            RespArray::new(vec![
+                RespArray::new(vec![bulk("src"), flags(&["RO", "access"])]).into(),
+                RespArray::new(vec![bulk("dst"), flags(&["OW", "update"])]).into(),
+            ])
+            .into()
+        );
+
+        // GETKEYS 只返回 key，包括按个数给出 key 和末尾有其他参数的命令
+        let keys = |names: &[&str]| -> RespFrame {
+            RespArray::new(names.iter().map(|n| bulk(n)).collect::<Vec<_>>()).into()
+        };
+        assert_eq!(
+            command(&[
+                "getkeys",
+                "zunionstore",
+                "d",
+                "2",
+                "a",
+                "b",
+                "WEIGHTS",
+                "1",
+                "2"
+            ])?,
+            keys(&["d", "a", "b"])
+        );
+        assert_eq!(
+            command(&["getkeys", "blpop", "a", "b", "0"])?,
+            keys(&["a", "b"])
+        );
+        assert_eq!(
+            command(&["getkeys", "del", "a", "b", "c"])?,
+            keys(&["a", "b", "c"])
+        );
+
+        assert_eq!(
+            command(&["getkeys", "ping"])?,
+            RespFrame::error("ERR", "The command has no key arguments")
+        );
+        assert_eq!(
+            command(&["getkeys", "nosuchcommand", "k"])?,
+            RespFrame::error("ERR", "Invalid command specified")
+        );
+        assert_eq!(
+            command(&["getkeys", "get", "a", "b"])?,
+            RespFrame::error("ERR", "Invalid number of arguments specified for command")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_key_specs_are_sorted_known_commands() {
+        let names: Vec<&str> = KEY_SPECS.iter().map(|(name, _)| *name).collect();
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(names, sorted);
+        for name in names {
+            assert!(is_command(name.as_bytes()), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,383 @@
+use crate::{Backend, BulkString, ConnectionContext, RespArray, RespFrame, RespMap};
+
+use super::{
+    extract_args, validate_command_at_least, CommandError, CommandExecutor, Config,
+    ConfigSubcommand, RESP_OK,
+};
+
+const CLIENT_COMMAND_RATE_LIMIT: &str = "client-command-rate-limit";
+const MAXCLIENTS: &str = "maxclients";
+const MAXMEMORY: &str = "maxmemory";
+const NOTIFY_KEYSPACE_EVENTS: &str = "notify-keyspace-events";
+const PROTO_MAX_BULK_LEN: &str = "proto-max-bulk-len";
+const PROTO_MAX_MULTIBULK_LEN: &str = "proto-max-multibulk-len";
+const PROTO_MAX_NESTING_DEPTH: &str = "proto-max-nesting-depth";
+const PUBSUB_BUFFER_LIMIT: &str = "pubsub-buffer-limit";
+const PUBSUB_OVERFLOW_POLICY: &str = "pubsub-overflow-policy";
+const STRICT_RESP2: &str = "strict-resp2";
+// 和 Redis 一样，proto-max-bulk-len 不能小于 1mb
+const MIN_PROTO_MAX_BULK_LEN: usize = 1024 * 1024;
+
+impl CommandExecutor for Config {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let config = backend.config();
+        match self.subcommand {
+            ConfigSubcommand::Get(name) => {
+                let value = match name.as_str() {
+                    CLIENT_COMMAND_RATE_LIMIT => {
+                        Some(config.client_command_rate_limit().to_string())
+                    }
+                    MAXCLIENTS => Some(config.maxclients().to_string()),
+                    MAXMEMORY => Some(config.maxmemory().to_string()),
+                    NOTIFY_KEYSPACE_EVENTS => Some(config.notify_keyspace_events().to_string()),
+                    PROTO_MAX_BULK_LEN => Some(config.proto_max_bulk_len().to_string()),
+                    PROTO_MAX_MULTIBULK_LEN => Some(config.proto_max_multibulk_len().to_string()),
+                    PROTO_MAX_NESTING_DEPTH => Some(config.proto_max_nesting_depth().to_string()),
+                    PUBSUB_BUFFER_LIMIT => Some(config.pubsub_buffer_limit().to_string()),
+                    PUBSUB_OVERFLOW_POLICY => Some(config.pubsub_overflow().to_string()),
+                    STRICT_RESP2 => Some(yes_no(config.strict_resp2()).to_string()),
+                    _ => None,
+                };
+                let mut map = RespMap::new();
+                if let Some(value) = value {
+                    map.insert(name, BulkString::from(value).into());
+                }
+                map.into()
+            }
+            ConfigSubcommand::Set(name, value) => match name.as_str() {
+                CLIENT_COMMAND_RATE_LIMIT => match value.parse() {
+                    Ok(rate) => {
+                        config.set_client_command_rate_limit(rate);
+                        RESP_OK.clone()
+                    }
+                    Err(_) => invalid_value(&name, &value),
+                },
+                MAXCLIENTS => match value.parse() {
+                    Ok(maxclients) if maxclients > 0 => {
+                        config.set_maxclients(maxclients);
+                        RESP_OK.clone()
+                    }
+                    _ => invalid_value(&name, &value),
+                },
+                MAXMEMORY => match parse_memory(&value) {
+                    Some(bytes) => {
+                        config.set_maxmemory(bytes);
+                        RESP_OK.clone()
+                    }
+                    None => invalid_value(&name, &value),
+                },
+                NOTIFY_KEYSPACE_EVENTS => match value.parse() {
+                    Ok(flags) => {
+                        config.set_notify_keyspace_events(flags);
+                        RESP_OK.clone()
+                    }
+                    Err(_) => invalid_value(&name, &value),
+                },
+                PROTO_MAX_BULK_LEN => match parse_memory(&value) {
+                    Some(len) if len >= MIN_PROTO_MAX_BULK_LEN => {
+                        config.set_proto_max_bulk_len(len);
+                        RESP_OK.clone()
+                    }
+                    _ => invalid_value(&name, &value),
+                },
+                // 命令本身就是一个数组，上限至少为 1
+                PROTO_MAX_MULTIBULK_LEN => match value.parse() {
+                    Ok(len) if len > 0 => {
+                        config.set_proto_max_multibulk_len(len);
+                        RESP_OK.clone()
+                    }
+                    _ => invalid_value(&name, &value),
+                },
+                PROTO_MAX_NESTING_DEPTH => match value.parse() {
+                    Ok(depth) if depth > 0 => {
+                        config.set_proto_max_nesting_depth(depth);
+                        RESP_OK.clone()
+                    }
+                    _ => invalid_value(&name, &value),
+                },
+                PUBSUB_BUFFER_LIMIT => match value.parse() {
+                    Ok(limit) => {
+                        config.set_pubsub_buffer_limit(limit);
+                        RESP_OK.clone()
+                    }
+                    Err(_) => invalid_value(&name, &value),
+                },
+                PUBSUB_OVERFLOW_POLICY => match value.parse() {
+                    Ok(policy) => {
+                        config.set_pubsub_overflow(policy);
+                        RESP_OK.clone()
+                    }
+                    Err(_) => invalid_value(&name, &value),
+                },
+                STRICT_RESP2 => match value.to_ascii_lowercase().as_str() {
+                    "yes" => {
+                        config.set_strict_resp2(true);
+                        RESP_OK.clone()
+                    }
+                    "no" => {
+                        config.set_strict_resp2(false);
+                        RESP_OK.clone()
+                    }
+                    _ => invalid_value(&name, &value),
+                },
+                _ => RespFrame::error(
+                    "ERR",
+                    &format!(
+                        "Unknown option or number of arguments for CONFIG SET - '{}'",
+                        name
+                    ),
+                ),
+            },
+        }
+    }
+
+    // CONFIG GET 在 RESP3 下回复 map，RESP2 下回复 key/value 交替的数组
+    fn execute_with(self, backend: &Backend, ctx: &ConnectionContext) -> RespFrame {
+        let version = backend.config().reply_version(ctx.version);
+        self.execute(backend).into_version(version)
+    }
+}
+
+fn yes_no(on: bool) -> &'static str {
+    if on {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+fn invalid_value(name: &str, value: &str) -> RespFrame {
+    RespFrame::error(
+        "ERR",
+        &format!(
+            "CONFIG SET failed (possibly related to argument '{}') - Invalid value '{}'",
+            name, value
+        ),
+    )
+}
+
+// 解析带单位的内存大小，如 "1024"、"1mb"、"512MB"，单位不区分大小写
+fn parse_memory(value: &str) -> Option<usize> {
+    let value = value.to_lowercase();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let multiplier: usize = match unit {
+        "" | "b" => 1,
+        "k" => 1000,
+        "kb" => 1024,
+        "m" => 1000 * 1000,
+        "mb" => 1024 * 1024,
+        "g" => 1000 * 1000 * 1000,
+        "gb" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    number.parse::<usize>().ok()?.checked_mul(multiplier)
+}
+
+impl TryFrom<RespArray> for Config {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["config"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter().map(|arg| match arg {
+            RespFrame::BulkString(s) => Ok(String::from_utf8(s.get_data()?)?),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid argument".to_string(),
+            )),
+        });
+        let subcommand = args.next().transpose()?.unwrap_or_default();
+        // 配置名不区分大小写
+        let name = args.next().transpose()?.unwrap_or_default().to_lowercase();
+        let value = args.next().transpose()?;
+        let subcommand = match (subcommand.to_lowercase().as_str(), value) {
+            ("get", None) => ConfigSubcommand::Get(name),
+            ("set", Some(value)) => ConfigSubcommand::Set(name, value),
+            _ => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "Unknown subcommand or wrong number of arguments for '{}'",
+                    subcommand
+                )))
+            }
+        };
+        if args.next().is_some() {
+            return Err(CommandError::WrongArity("config|set".to_string()));
+        }
+        Ok(Config { subcommand })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NotifyFlags, RespDecode};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_config_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*4\r\n$6\r\nconfig\r\n$3\r\nSET\r\n$22\r\nNOTIFY-KEYSPACE-EVENTS\r\n$2\r\nEg\r\n",
+        );
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Config = frame.try_into()?;
+        assert!(matches!(
+            result.subcommand,
+            ConfigSubcommand::Set(ref name, ref value) if name == NOTIFY_KEYSPACE_EVENTS && value == "Eg"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_set_and_get_notify_keyspace_events() {
+        let backend = Backend::new();
+        let cmd = Config {
+            subcommand: ConfigSubcommand::Set(
+                NOTIFY_KEYSPACE_EVENTS.to_string(),
+                "KEA".to_string(),
+            ),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert!(backend
+            .config()
+            .notify_keyspace_events()
+            .contains(NotifyFlags::ALL | NotifyFlags::KEYSPACE));
+
+        let cmd = Config {
+            subcommand: ConfigSubcommand::Get(NOTIFY_KEYSPACE_EVENTS.to_string()),
+        };
+        let mut expected = RespMap::new();
+        expected.insert(
+            NOTIFY_KEYSPACE_EVENTS.to_string(),
+            BulkString::from("AKE").into(),
+        );
+        assert_eq!(cmd.execute(&backend), expected.into());
+
+        let cmd = Config {
+            subcommand: ConfigSubcommand::Set(NOTIFY_KEYSPACE_EVENTS.to_string(), "Q".to_string()),
+        };
+        assert!(matches!(cmd.execute(&backend), RespFrame::Error(_)));
+    }
+
+    #[test]
+    fn test_config_get_reply_depends_on_protocol() {
+        let backend = Backend::new();
+        let get = |ctx: &ConnectionContext| {
+            Config {
+                subcommand: ConfigSubcommand::Get(MAXCLIENTS.to_string()),
+            }
+            .execute_with(&backend, ctx)
+        };
+
+        let mut ctx = ConnectionContext::default();
+        assert_eq!(
+            get(&ctx),
+            RespArray::new(vec![
+                BulkString::from(MAXCLIENTS).into(),
+                BulkString::from("10000").into(),
+            ])
+            .into()
+        );
+
+        ctx.version = crate::RespVersion::Resp3;
+        let mut expected = RespMap::new();
+        expected.insert(MAXCLIENTS.to_string(), BulkString::from("10000").into());
+        assert_eq!(get(&ctx), expected.into());
+    }
+
+    #[test]
+    fn test_config_set_proto_max_bulk_len() {
+        let backend = Backend::new();
+        let set = |value: &str| {
+            Config {
+                subcommand: ConfigSubcommand::Set(
+                    PROTO_MAX_BULK_LEN.to_string(),
+                    value.to_string(),
+                ),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(set("2mb"), RESP_OK.clone());
+        assert_eq!(backend.config().proto_max_bulk_len(), 2 * 1024 * 1024);
+        assert!(matches!(set("1kb"), RespFrame::Error(_)));
+        assert!(matches!(set("lots"), RespFrame::Error(_)));
+        assert_eq!(backend.config().proto_max_bulk_len(), 2 * 1024 * 1024);
+
+        let cmd = Config {
+            subcommand: ConfigSubcommand::Get(PROTO_MAX_BULK_LEN.to_string()),
+        };
+        let mut expected = RespMap::new();
+        expected.insert(
+            PROTO_MAX_BULK_LEN.to_string(),
+            BulkString::from("2097152").into(),
+        );
+        assert_eq!(cmd.execute(&backend), expected.into());
+    }
+
+    #[test]
+    fn test_config_set_decode_limits() {
+        let backend = Backend::new();
+        let set = |name: &str, value: &str| {
+            Config {
+                subcommand: ConfigSubcommand::Set(name.to_string(), value.to_string()),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(set(PROTO_MAX_MULTIBULK_LEN, "100"), RESP_OK.clone());
+        assert_eq!(set(PROTO_MAX_NESTING_DEPTH, "8"), RESP_OK.clone());
+        assert!(matches!(
+            set(PROTO_MAX_NESTING_DEPTH, "0"),
+            RespFrame::Error(_)
+        ));
+        assert!(matches!(
+            set(PROTO_MAX_MULTIBULK_LEN, "-1"),
+            RespFrame::Error(_)
+        ));
+
+        let limits = backend.config().decode_limits();
+        assert_eq!(limits.max_elements, 100);
+        assert_eq!(limits.max_depth, 8);
+        assert_eq!(limits.max_bulk_len, backend.config().proto_max_bulk_len());
+    }
+
+    #[test]
+    fn test_config_set_pubsub_overflow_policy() {
+        let backend = Backend::new();
+        let set = |name: &str, value: &str| {
+            Config {
+                subcommand: ConfigSubcommand::Set(name.to_string(), value.to_string()),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(set(PUBSUB_BUFFER_LIMIT, "64"), RESP_OK.clone());
+        assert_eq!(set(PUBSUB_OVERFLOW_POLICY, "Drop-Oldest"), RESP_OK.clone());
+        assert!(matches!(
+            set(PUBSUB_OVERFLOW_POLICY, "block"),
+            RespFrame::Error(_)
+        ));
+        assert!(matches!(
+            set(PUBSUB_BUFFER_LIMIT, "-1"),
+            RespFrame::Error(_)
+        ));
+        assert_eq!(backend.config().pubsub_buffer_limit(), 64);
+        assert_eq!(
+            backend.config().pubsub_overflow(),
+            crate::PubSubOverflow::DropOldest
+        );
+
+        let cmd = Config {
+            subcommand: ConfigSubcommand::Get(PUBSUB_OVERFLOW_POLICY.to_string()),
+        };
+        let mut expected = RespMap::new();
+        expected.insert(
+            PUBSUB_OVERFLOW_POLICY.to_string(),
+            BulkString::from("drop-oldest").into(),
+        );
+        assert_eq!(cmd.execute(&backend), expected.into());
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{ConnectionContext, RespArray, RespFrame, RespVersion, SimpleString, Subscriber};
+
+use super::{
+    validate_command, validate_command_at_least, CommandError, CommandExecutor, Quit, Reset,
+    RESP_OK,
+};
+
+// 连接在回复之后由网络层关闭
+impl CommandExecutor for Quit {
+    fn execute(self, _: &crate::Backend) -> RespFrame {
+        RESP_OK.clone()
+    }
+}
+
+impl CommandExecutor for Reset {
+    fn execute(self, _: &crate::Backend) -> RespFrame {
+        SimpleString::new("RESET").into()
+    }
+}
+
+impl Reset {
+    /// Drop every subscription and bring the connection back to the state of
+    /// a new one, keeping its id.
+    pub fn apply(self, subscriber: &mut Subscriber, ctx: &mut ConnectionContext) -> RespFrame {
+        subscriber.unsubscribe(vec![]);
+        subscriber.punsubscribe(vec![]);
+        ctx.version = RespVersion::Resp2;
+        ctx.db = 0;
+        ctx.name = None;
+        ctx.flags = Default::default();
+        ctx.subscriptions = 0;
+        SimpleString::new("RESET").into()
+    }
+}
+
+impl TryFrom<RespArray> for Quit {
+    type Error = CommandError;
+
+    // 和 Redis 一样忽略多余的参数
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["quit"], 0)?;
+        Ok(Quit)
+    }
+}
+
+impl TryFrom<RespArray> for Reset {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["reset"], 0)?;
+        Ok(Reset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Backend, BulkString};
+    use anyhow::Result;
+
+    #[test]
+    fn test_reset_clears_subscriptions_and_protocol() -> Result<()> {
+        let backend = Backend::new();
+        let mut subscriber = backend.subscriber();
+        subscriber.subscribe("news".to_string());
+        subscriber.psubscribe("n*".to_string());
+        let mut ctx = ConnectionContext {
+            id: 7,
+            version: RespVersion::Resp3,
+            name: Some("conn".to_string()),
+            subscriptions: 2,
+            ..Default::default()
+        };
+
+        let reset = Reset::try_from(RespArray::new(vec![BulkString::from("reset").into()]))?;
+        let reply = reset.apply(&mut subscriber, &mut ctx);
+        assert_eq!(reply, SimpleString::new("RESET").into());
+        assert_eq!(subscriber.subscription_count(), 0);
+        assert_eq!(
+            (ctx.id, ctx.version, ctx.name),
+            (7, RespVersion::Resp2, None)
+        );
+        assert_eq!(ctx.subscriptions, 0);
+
+        let args = vec![
+            BulkString::from("reset").into(),
+            BulkString::from("x").into(),
+        ];
+        assert!(Reset::try_from(RespArray::new(args)).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,304 @@
+use crate::{BackendError, RespArray, RespFrame, RespMap, SimpleString};
+
+use super::{
+    extract_args, parse_integer, validate_command_at_least, CommandError, CommandExecutor, Debug,
+    DebugSubcommand, RESP_OK,
+};
+
+// 未知子命令时列在错误信息里，新增子命令时要同步
+const DEBUG_SUBCOMMANDS: &[&str] = &[
+    "RELOAD",
+    "EXPIRES",
+    "OBJECT",
+    "SET-ACTIVE-EXPIRE",
+    "SORT-REPLIES",
+];
+
+impl CommandExecutor for Debug {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match self.subcommand {
+            DebugSubcommand::Reload => {
+                let snapshot = backend.save_snapshot();
+                match backend.load_snapshot(&snapshot) {
+                    Ok(_) => RESP_OK.clone(),
+                    Err(e) => RespFrame::error(
+                        "ERR",
+                        &format!("Error trying to load the snapshot: {}", e),
+                    ),
+                }
+            }
+            DebugSubcommand::Expires => {
+                let mut map = RespMap::new();
+                for (key, ttl) in backend.list_expiries() {
+                    map.insert(key, RespFrame::Integer(ttl.as_millis() as i64));
+                }
+                map.into()
+            }
+            DebugSubcommand::Object(key) => {
+                let Some(encoding) = backend.object_encoding(&key) else {
+                    return BackendError::NoSuchKey.into();
+                };
+                let mut info = format!("Value at:0x0 refcount:1 encoding:{}", encoding);
+                if let Some(nodes) = backend.quicklist_nodes(&key) {
+                    info.push_str(&format!(" ql_nodes:{}", nodes));
+                }
+                SimpleString::new(info).into()
+            }
+            DebugSubcommand::SetActiveExpire(enabled) => {
+                backend.config().set_active_expire_enabled(enabled);
+                RESP_OK.clone()
+            }
+            DebugSubcommand::SortReplies(sort) => {
+                backend.config().set_sort_replies(sort);
+                RESP_OK.clone()
+            }
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Debug {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["debug"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let subcommand = match args.next() {
+            Some(RespFrame::BulkString(sub)) => sub.to_ascii_lowercase(),
+            _ => {
+                return Err(CommandError::InvalidArgument(
+                    "Invalid subcommand".to_string(),
+                ))
+            }
+        };
+        match subcommand.as_slice() {
+            b"reload" => Ok(Debug {
+                subcommand: DebugSubcommand::Reload,
+            }),
+            b"expires" => Ok(Debug {
+                subcommand: DebugSubcommand::Expires,
+            }),
+            b"object" => match args.next() {
+                Some(RespFrame::BulkString(key)) => Ok(Debug {
+                    subcommand: DebugSubcommand::Object(String::from_utf8(key.get_data()?)?),
+                }),
+                _ => Err(CommandError::WrongArity("debug|object".to_string())),
+            },
+            b"set-active-expire" => match args.next() {
+                Some(flag) => Ok(Debug {
+                    subcommand: DebugSubcommand::SetActiveExpire(parse_integer(&flag)? != 0),
+                }),
+                _ => Err(CommandError::WrongArity(
+                    "debug|set-active-expire".to_string(),
+                )),
+            },
+            b"sort-replies" => match args.next() {
+                Some(flag) => Ok(Debug {
+                    subcommand: DebugSubcommand::SortReplies(parse_integer(&flag)? != 0),
+                }),
+                _ => Err(CommandError::WrongArity("debug|sort-replies".to_string())),
+            },
+            _ => Err(CommandError::InvalidArgument(format!(
+                "DEBUG subcommand '{}' not supported. Try one of: {}",
+                String::from_utf8_lossy(&subcommand),
+                DEBUG_SUBCOMMANDS.join(", ")
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Backend, BulkString, RespDecode};
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_debug_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$5\r\ndebug\r\n$6\r\nRELOAD\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result: Debug = frame.try_into()?;
+        assert!(matches!(result.subcommand, DebugSubcommand::Reload));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_reload_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("str".to_string(), BulkString::from("hello").into());
+        backend
+            .hset("map".to_string(), "f1".to_string(), b"v1".into())
+            .unwrap();
+        backend
+            .hset("map".to_string(), "f2".to_string(), RespFrame::Integer(2))
+            .unwrap();
+        backend.sadd("set".to_string(), "m1".to_string()).unwrap();
+        backend.sadd("set".to_string(), "m2".to_string()).unwrap();
+        backend
+            .rpush(
+                "list".to_string(),
+                vec![b"a".into(), b"b".into(), b"c".into()],
+            )
+            .unwrap();
+
+        let cmd = Debug {
+            subcommand: DebugSubcommand::Reload,
+        };
+        let result = cmd.execute(&backend);
+        assert_eq!(result, RESP_OK.clone());
+
+        assert_eq!(
+            backend.get("str").found(),
+            Some(BulkString::from("hello").into())
+        );
+        assert_eq!(backend.hget("map", "f1"), Some(b"v1".into()));
+        assert_eq!(backend.hget("map", "f2"), Some(RespFrame::Integer(2)));
+        assert_eq!(backend.hmap.get("map").unwrap().len(), 2);
+        assert!(backend.sismember("set", "m1").unwrap());
+        assert!(backend.sismember("set", "m2").unwrap());
+        assert_eq!(backend.set.get("set").unwrap().len(), 2);
+        let list: Vec<RespFrame> = backend.list.get("list").unwrap().iter().cloned().collect();
+        assert_eq!(list, vec![b"a".into(), b"b".into(), b"c".into()]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_object_ql_nodes() -> Result<()> {
+        let backend = Backend::new();
+        backend.config().set_list_max_listpack_size(4);
+        let debug_object = |key: &str| {
+            Debug {
+                subcommand: DebugSubcommand::Object(key.to_string()),
+            }
+            .execute(&backend)
+        };
+        let ql_nodes = |frame: RespFrame| match frame {
+            RespFrame::SimpleString(s) => s
+                .split(' ')
+                .find_map(|field| field.strip_prefix("ql_nodes:"))
+                .map(|n| n.parse::<usize>().unwrap()),
+            _ => panic!("unexpected reply {:?}", frame),
+        };
+
+        assert_eq!(debug_object("list"), BackendError::NoSuchKey.into());
+
+        backend.rpush("list".to_string(), vec![b"a".into(); 4])?;
+        assert_eq!(ql_nodes(debug_object("list")), Some(1));
+        backend.rpush("list".to_string(), vec![b"a".into()])?;
+        assert_eq!(ql_nodes(debug_object("list")), Some(2));
+        backend.rpush("list".to_string(), vec![b"a".into(); 4])?;
+        assert_eq!(ql_nodes(debug_object("list")), Some(3));
+
+        // 非 list 类型没有 ql_nodes
+        backend.set("str".to_string(), BulkString::from("v").into());
+        assert_eq!(ql_nodes(debug_object("str")), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_sort_replies_orders_hash_and_set_replies() -> Result<()> {
+        use crate::cmd::Command;
+
+        let backend = Backend::new();
+        let run = |args: &str| -> Result<RespFrame> {
+            let args = args
+                .split(' ')
+                .map(|arg| BulkString::from(arg).into())
+                .collect::<Vec<RespFrame>>();
+            Ok(Command::try_from(RespArray::new(args))?.execute(&backend))
+        };
+        let bulks = |items: &[String]| -> RespFrame {
+            RespArray::new(
+                items
+                    .iter()
+                    .map(|item| BulkString::from(item.as_str()).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into()
+        };
+
+        // 成员足够多，集合会转成哈希表存储，遍历顺序不再是插入顺序
+        let mut names: Vec<String> = (0..40).map(|i| format!("m{:02}", (i * 7) % 40)).collect();
+        for name in &names {
+            backend.hset(
+                "map".to_string(),
+                name.clone(),
+                BulkString::from(format!("v-{name}")).into(),
+            )?;
+            backend.sadd("set".to_string(), name.clone())?;
+        }
+        names.sort();
+        let values: Vec<String> = names.iter().map(|name| format!("v-{name}")).collect();
+
+        assert_eq!(run("debug sort-replies 1")?, crate::cmd::RESP_OK.clone());
+        assert_eq!(run("hkeys map")?, bulks(&names));
+        assert_eq!(run("hvals map")?, bulks(&values));
+        assert_eq!(run("smembers set")?, bulks(&names));
+        let RespFrame::Map(map) = run("hgetall map")? else {
+            panic!("HGETALL should reply with a map");
+        };
+        assert!(map.keys().eq(names.iter()));
+
+        // 关闭后内容不变，只是顺序不再保证
+        run("debug sort-replies 0")?;
+        let RespFrame::Array(members) = run("smembers set")? else {
+            panic!("SMEMBERS should reply with an array");
+        };
+        assert_eq!(members.len(), names.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_expires_lists_pending_ttls() -> Result<()> {
+        use crate::ManualClock;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let clock = Arc::new(ManualClock::new());
+        let backend = Backend::with_clock(clock.clone());
+        for key in ["a", "b", "c", "d"] {
+            backend.set(key.to_string(), BulkString::from("v").into());
+        }
+        backend.expire("a", Duration::from_secs(10)).unwrap();
+        backend.expire("b", Duration::from_millis(2500)).unwrap();
+        backend.expire("c", Duration::from_secs(1)).unwrap();
+        clock.advance(Duration::from_secs(1));
+
+        let frame = RespArray::new(vec![
+            BulkString::from("debug").into(),
+            BulkString::from("expires").into(),
+        ]);
+        let result = Debug::try_from(frame)?.execute(&backend);
+
+        // c 已过期但还没被删除，不再列出；d 没有 TTL
+        let mut expected = RespMap::new();
+        expected.insert("a".to_string(), RespFrame::Integer(9000));
+        expected.insert("b".to_string(), RespFrame::Integer(1500));
+        assert_eq!(result, expected.into());
+        assert!(backend.map.contains_key("c"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_unknown_subcommand_lists_supported_ones() {
+        let frame = RespArray::new(vec![
+            BulkString::from("debug").into(),
+            BulkString::from("jmap").into(),
+        ]);
+        let reply: RespFrame = Debug::try_from(frame).unwrap_err().into();
+        let RespFrame::Error(err) = reply else {
+            panic!("expected an error reply, got {:?}", reply);
+        };
+        assert!(err.contains("DEBUG subcommand 'jmap' not supported"));
+        assert!(err.contains("RELOAD"));
+        assert!(err.contains("SORT-REPLIES"));
+    }
+}
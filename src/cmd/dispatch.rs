@@ -0,0 +1,199 @@
+use crate::RespArray;
+
+use super::{
+    Append, BLMove, BLPop, BRPop, Client, Cluster, Command, CommandError, CommandMeta, Config,
+    Copy, DbSize, Debug, Del, Dump, Echo, Exists, Expire, Failover, FlushDb, Get, GetBit, GetDel,
+    GetEx, GetRange, GetSet, HDel, HExpire, HGet, HGetAll, HGetDel, HGetEx, HIncrByFloat, HKeys,
+    HLen, HMGet, HScan, HSet, HTtl, HVals, Hello, Incr, IncrBy, Info, LMPop, LMove, LPop, LPos,
+    LPush, LRem, LTrim, Lcs, Memory, Object, PExpire, PExpireAt, PSubscribe, PTtl, PUnsubscribe,
+    Ping, Publish, Pubsub, Quit, RPop, RPush, Rename, RenameNx, ReplicaOf, Reset, Restore, SAdd,
+    SCard, SDiffStore, SInterStore, SMembers, SPop, SRandMember, SRem, SUnionStore, Scan, Set,
+    SetBit, SetRange, SisMember, StrLen, Subscribe, Substr, Ttl, Unlink, Unsubscribe, WaitAof,
+    ZAdd, ZIncrBy, ZInterStore, ZMPop, ZMScore, ZPopMax, ZPopMin, ZRange, ZRangeByLex, ZRangeStore,
+    ZRank, ZRem, ZRevRange, ZRevRangeByScore, ZRevRank, ZScore, ZUnionStore,
+};
+
+type Parser = fn(RespArray) -> Result<Command, CommandError>;
+
+macro_rules! commands {
+    ($($name:literal => $cmd:ident),* $(,)?) => {
+        [$(($name.as_bytes(), (|v| Ok($cmd::try_from(v)?.into())) as Parser)),*]
+    };
+}
+
+// 命令名均为小写并按字节序排列，查找时直接二分；新增命令时按顺序插入
+const COMMANDS: &[(&[u8], Parser)] = &commands! {
+    "append" => Append,
+    "blmove" => BLMove,
+    "blpop" => BLPop,
+    "brpop" => BRPop,
+    "client" => Client,
+    "cluster" => Cluster,
+    "command" => CommandMeta,
+    "config" => Config,
+    "copy" => Copy,
+    "dbsize" => DbSize,
+    "debug" => Debug,
+    "del" => Del,
+    "dump" => Dump,
+    "echo" => Echo,
+    "exists" => Exists,
+    "expire" => Expire,
+    "failover" => Failover,
+    "flushdb" => FlushDb,
+    "get" => Get,
+    "getbit" => GetBit,
+    "getdel" => GetDel,
+    "getex" => GetEx,
+    "getrange" => GetRange,
+    "getset" => GetSet,
+    "hdel" => HDel,
+    "hello" => Hello,
+    "hexpire" => HExpire,
+    "hget" => HGet,
+    "hgetall" => HGetAll,
+    "hgetdel" => HGetDel,
+    "hgetex" => HGetEx,
+    "hincrbyfloat" => HIncrByFloat,
+    "hkeys" => HKeys,
+    "hlen" => HLen,
+    "hmget" => HMGet,
+    "hscan" => HScan,
+    "hset" => HSet,
+    "httl" => HTtl,
+    "hvals" => HVals,
+    "incr" => Incr,
+    "incrby" => IncrBy,
+    "info" => Info,
+    "lcs" => Lcs,
+    "lmove" => LMove,
+    "lmpop" => LMPop,
+    "lpop" => LPop,
+    "lpos" => LPos,
+    "lpush" => LPush,
+    "lrem" => LRem,
+    "ltrim" => LTrim,
+    "memory" => Memory,
+    "object" => Object,
+    "pexpire" => PExpire,
+    "pexpireat" => PExpireAt,
+    "ping" => Ping,
+    "psubscribe" => PSubscribe,
+    "pttl" => PTtl,
+    "publish" => Publish,
+    "pubsub" => Pubsub,
+    "punsubscribe" => PUnsubscribe,
+    "quit" => Quit,
+    "rename" => Rename,
+    "renamenx" => RenameNx,
+    "replicaof" => ReplicaOf,
+    "reset" => Reset,
+    "restore" => Restore,
+    "rpop" => RPop,
+    "rpush" => RPush,
+    "sadd" => SAdd,
+    "scan" => Scan,
+    "scard" => SCard,
+    "sdiffstore" => SDiffStore,
+    "set" => Set,
+    "setbit" => SetBit,
+    "setrange" => SetRange,
+    "sinterstore" => SInterStore,
+    "sismember" => SisMember,
+    "smembers" => SMembers,
+    "spop" => SPop,
+    "srandmember" => SRandMember,
+    "srem" => SRem,
+    "strlen" => StrLen,
+    "subscribe" => Subscribe,
+    "substr" => Substr,
+    "sunionstore" => SUnionStore,
+    "ttl" => Ttl,
+    "unlink" => Unlink,
+    "unsubscribe" => Unsubscribe,
+    "waitaof" => WaitAof,
+    "zadd" => ZAdd,
+    "zincrby" => ZIncrBy,
+    "zinterstore" => ZInterStore,
+    "zmpop" => ZMPop,
+    "zmscore" => ZMScore,
+    "zpopmax" => ZPopMax,
+    "zpopmin" => ZPopMin,
+    "zrange" => ZRange,
+    "zrangebylex" => ZRangeByLex,
+    "zrangestore" => ZRangeStore,
+    "zrank" => ZRank,
+    "zrem" => ZRem,
+    "zrevrange" => ZRevRange,
+    "zrevrangebyscore" => ZRevRangeByScore,
+    "zrevrank" => ZRevRank,
+    "zscore" => ZScore,
+    "zunionstore" => ZUnionStore,
+};
+
+/// Look up the parser registered for a (lowercase) command name.
+pub(super) fn parser(name: &[u8]) -> Option<Parser> {
+    COMMANDS
+        .binary_search_by_key(&name, |(candidate, _)| *candidate)
+        .ok()
+        .map(|index| COMMANDS[index].1)
+}
+
+/// Names of every registered command, in byte order.
+pub fn command_names() -> impl Iterator<Item = &'static [u8]> {
+    COMMANDS.iter().map(|(name, _)| *name)
+}
+
+/// Whether `name` is a command this server understands.
+pub fn is_command(name: &[u8]) -> bool {
+    parser(name).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespFrame};
+
+    #[test]
+    fn test_every_command_dispatches() {
+        for (name, _) in COMMANDS {
+            assert!(is_command(name), "{}", String::from_utf8_lossy(name));
+
+            // 参数不够时解析失败，但不能落到 Unrecognized
+            let frame = RespArray::new(vec![BulkString::new(*name).into()]);
+            let ret = Command::try_from(frame);
+            assert!(
+                !matches!(ret, Ok(Command::Unrecognized(_))),
+                "{}",
+                String::from_utf8_lossy(name)
+            );
+        }
+    }
+
+    #[test]
+    fn test_commands_are_sorted() {
+        // 二分查找要求严格递增，同时保证没有重名
+        for pair in COMMANDS.windows(2) {
+            assert!(
+                pair[0].0 < pair[1].0,
+                "{} must come before {}",
+                String::from_utf8_lossy(pair[1].0),
+                String::from_utf8_lossy(pair[0].0)
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_command_falls_back() {
+        assert!(!is_command(b"nosuchcommand"));
+        assert!(!is_command(b""));
+        // 和原先的 match 一样区分大小写
+        assert!(!is_command(b"GET"));
+
+        let frame: RespFrame = RespArray::new(vec![BulkString::new("nosuchcommand").into()]).into();
+        assert!(matches!(
+            Command::try_from(frame),
+            Ok(Command::Unrecognized(_))
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{BulkString, ConnectionContext, RespArray, RespFrame, RespMap, RespVersion};
+
+use super::{extract_args, validate_command_at_least, CommandError, CommandExecutor, Hello};
+
+impl CommandExecutor for Hello {
+    fn execute(self, _: &crate::Backend) -> RespFrame {
+        self.apply(&mut ConnectionContext::default())
+    }
+}
+
+impl Hello {
+    /// The protocol version requested, if any.
+    pub fn protover(&self) -> Option<RespVersion> {
+        self.protover
+    }
+
+    /// Switch the connection to the requested protocol and reply with the
+    /// server properties.
+    pub fn apply(self, ctx: &mut ConnectionContext) -> RespFrame {
+        if let Some(protover) = self.protover {
+            ctx.version = protover;
+        }
+        let proto = match ctx.version {
+            RespVersion::Resp2 => 2,
+            RespVersion::Resp3 => 3,
+        };
+
+        let mut map = RespMap::new();
+        map.insert("server".to_string(), BulkString::from("redis").into());
+        map.insert(
+            "version".to_string(),
+            BulkString::from(env!("CARGO_PKG_VERSION")).into(),
+        );
+        map.insert("proto".to_string(), RespFrame::Integer(proto));
+        map.insert("id".to_string(), RespFrame::Integer(ctx.id as i64));
+        // 单机模式的主节点，不支持 module
+        map.insert("mode".to_string(), BulkString::from("standalone").into());
+        map.insert("role".to_string(), BulkString::from("master").into());
+        map.insert("modules".to_string(), RespArray::new(vec![]).into());
+        map.into()
+    }
+}
+
+impl TryFrom<RespArray> for Hello {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["hello"], 0)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let protover = match args.next() {
+            Some(RespFrame::BulkString(v)) => match v.as_slice() {
+                b"2" => Some(RespVersion::Resp2),
+                b"3" => Some(RespVersion::Resp3),
+                _ => {
+                    return Err(CommandError::InvalidArgument(
+                        "NOPROTO unsupported protocol version".to_string(),
+                    ))
+                }
+            },
+            None => None,
+            _ => {
+                return Err(CommandError::InvalidArgument(
+                    "Invalid protocol version".to_string(),
+                ))
+            }
+        };
+        Ok(Hello { protover })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RespDecode;
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_hello_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$5\r\nhello\r\n$1\r\n3\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result: Hello = frame.try_into()?;
+        assert_eq!(result.protover, Some(RespVersion::Resp3));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$5\r\nhello\r\n$1\r\n4\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Hello::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hello_switches_protocol() -> Result<()> {
+        let mut ctx = ConnectionContext::default();
+        let cmd = Hello {
+            protover: Some(RespVersion::Resp3),
+        };
+        let result = cmd.apply(&mut ctx);
+        assert_eq!(ctx.version, RespVersion::Resp3);
+        let RespFrame::Map(map) = result else {
+            panic!("expect a map reply");
+        };
+        assert_eq!(map.get("proto"), Some(&RespFrame::Integer(3)));
+
+        let cmd = Hello { protover: None };
+        let RespFrame::Map(map) = cmd.apply(&mut ctx) else {
+            panic!("expect a map reply");
+        };
+        assert_eq!(map.get("proto"), Some(&RespFrame::Integer(3)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hello_reply_has_server_fields() {
+        let backend = crate::Backend::new();
+        let client = backend.connect();
+        let mut ctx = ConnectionContext {
+            id: client.id(),
+            ..Default::default()
+        };
+        let cmd = Hello {
+            protover: Some(RespVersion::Resp3),
+        };
+        let RespFrame::Map(map) = cmd.apply(&mut ctx) else {
+            panic!("expect a map reply");
+        };
+        for key in [
+            "server", "version", "proto", "id", "mode", "role", "modules",
+        ] {
+            assert!(map.get(key).is_some(), "missing {}", key);
+        }
+        assert_eq!(map.get("id"), Some(&RespFrame::Integer(1)));
+        assert_eq!(
+            map.get("mode"),
+            Some(&BulkString::from("standalone").into())
+        );
+        assert_eq!(map.get("role"), Some(&BulkString::from("master").into()));
+        assert_eq!(map.get("modules"), Some(&RespArray::new(vec![]).into()));
+
+        // 每个连接的 id 不同
+        assert_eq!(backend.connect().id(), 2);
+    }
+}
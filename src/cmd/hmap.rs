@@ -1,7 +1,24 @@
-use crate::cmd::{extract_args, validate_command, CommandError, HGet, HGetAll, HSet};
-use crate::{BulkString, RespArray, RespFrame};
+use std::time::Duration;
 
-use super::{validate_command_at_least, CommandExecutor, HMGet, RESP_OK};
+use crate::cmd::{
+    extract_args, validate_command, CommandError, HGet, HGetAll, HKeys, HLen, HScan, HSet, HVals,
+};
+use crate::{BackendError, BulkString, RespArray, RespFrame, RespMap};
+
+use super::{
+    extract_store_args, parse_float, parse_getex_expiry, parse_integer, validate_command_at_least,
+    CommandExecutor, GetExExpiry, HDel, HExpire, HGetDel, HGetEx, HIncrByFloat, HMGet, HTtl,
+    RESP_OK,
+};
+
+impl CommandExecutor for HDel {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.hdel(&self.key, &self.fields) {
+            Ok(removed) => RespFrame::Integer(removed as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
 
 impl CommandExecutor for HGet {
     fn execute(self, backend: &crate::Backend) -> RespFrame {
@@ -14,48 +31,181 @@ impl CommandExecutor for HGet {
 
 impl CommandExecutor for HGetAll {
     fn execute(self, backend: &crate::Backend) -> RespFrame {
-        let hmap = backend.hmap.get(&self.key);
-
-        match hmap {
-            Some(hmap) => {
-                let mut data = Vec::with_capacity(hmap.len());
-                for v in hmap.iter() {
-                    let key = v.key().to_owned();
-                    data.push((key, v.value().clone()));
-                }
-                if self.sort {
-                    data.sort_by(|a, b| a.0.cmp(&b.0));
-                }
-                let ret = data
+        let entries = match backend.hentries(&self.key) {
+            Ok(entries) => entries,
+            Err(e) => return e.into(),
+        };
+
+        // RESP3 下回复 map，RESP2 客户端在编码时转换成 field/value 交替的数组
+        let mut map = RespMap::new();
+        for (field, value) in entries {
+            map.insert(field, value);
+        }
+        map.into()
+    }
+}
+
+impl CommandExecutor for HKeys {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let fields = match backend.hentries(&self.key) {
+            Ok(entries) => entries.into_iter().map(|(f, _)| f),
+            Err(e) => return e.into(),
+        };
+        RespArray::new(
+            fields
+                .map(|f| BulkString::new(f).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+        .into()
+    }
+}
+
+impl CommandExecutor for HVals {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        // 开启 sort-replies 时按 field 排序，和 HKEYS 的顺序一一对应
+        match backend.hentries(&self.key) {
+            Ok(entries) => RespArray::new(
+                entries
                     .into_iter()
-                    .flat_map(|(k, v)| vec![BulkString::from(k).into(), v])
-                    .collect::<Vec<RespFrame>>();
-                RespArray::new(ret).into()
-            }
-            None => RespArray::new([]).into(),
+                    .map(|(_, v)| v)
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            Err(e) => e.into(),
         }
     }
 }
 
+impl CommandExecutor for HLen {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.hlen(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HScan {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let (cursor, entries) =
+            backend.hscan(&self.key, self.cursor, self.count, self.pattern.as_deref());
+        let items: Vec<RespFrame> = if self.novalues {
+            entries
+                .into_iter()
+                .map(|(field, _)| BulkString::from(field).into())
+                .collect()
+        } else {
+            entries
+                .into_iter()
+                .flat_map(|(field, value)| [BulkString::from(field).into(), value])
+                .collect()
+        };
+        RespArray::new(vec![
+            BulkString::from(cursor.to_string()).into(),
+            RespArray::new(items).into(),
+        ])
+        .into()
+    }
+}
+
 impl CommandExecutor for HSet {
     fn execute(self, backend: &crate::Backend) -> RespFrame {
-        backend.hset(self.key, self.field, self.value);
-        RESP_OK.clone()
+        match backend.hset(self.key, self.field, self.value) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HIncrByFloat {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.hincrbyfloat(self.key, self.field, self.increment) {
+            Ok(value) => BulkString::from(value.to_string()).into(),
+            Err(e) => e.into(),
+        }
     }
 }
 
 impl CommandExecutor for HMGet {
     fn execute(self, backend: &crate::Backend) -> RespFrame {
         let fields: Vec<&str> = self.fields.iter().map(|x| &**x).collect();
-        let hmap = backend.hmget(&self.key, &fields);
-        let mut data = Vec::with_capacity(hmap.len());
-        for v in hmap {
-            match v {
-                Some(value) => data.push(value),
-                None => data.push(RespFrame::Null(crate::RespNull)),
+        values_array(backend.hmget(&self.key, &fields))
+    }
+}
+
+impl CommandExecutor for HGetDel {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.hgetdel(&self.key, &self.fields) {
+            Ok(values) => values_array(values),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HGetEx {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        if backend.key_type(&self.key).is_some_and(|t| t != "hash") {
+            return BackendError::WrongType.into();
+        }
+        let fields: Vec<&str> = self.fields.iter().map(|x| &**x).collect();
+        let values = backend.hmget(&self.key, &fields);
+        let ret = match self.expiry {
+            Some(GetExExpiry::Ttl(ttl)) => backend.hexpire(&self.key, ttl, &self.fields),
+            Some(GetExExpiry::At(at)) => backend.hexpire_at(&self.key, at, &self.fields),
+            Some(GetExExpiry::Persist) => backend.hpersist(&self.key, &self.fields),
+            None => Ok(Vec::new()),
+        };
+        match ret {
+            Ok(_) => values_array(values),
+            Err(BackendError::InvalidExpireTime) => {
+                CommandError::InvalidExpireTime("hgetex".to_string()).into()
             }
+            Err(e) => e.into(),
         }
-        RespArray::new(data).into()
+    }
+}
+
+impl CommandExecutor for HExpire {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let ttl = Duration::from_secs(self.seconds);
+        match backend.hexpire(&self.key, ttl, &self.fields) {
+            Ok(ret) => status_array(ret),
+            Err(BackendError::InvalidExpireTime) => {
+                CommandError::InvalidExpireTime("hexpire".to_string()).into()
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HTtl {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.httl(&self.key, &self.fields) {
+            Ok(ret) => status_array(ret),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+// 不存在的字段回复 null
+fn values_array(values: Vec<Option<RespFrame>>) -> RespFrame {
+    let data = values
+        .into_iter()
+        .map(|v| v.unwrap_or(RespFrame::Null(crate::RespNull)))
+        .collect::<Vec<_>>();
+    RespArray::new(data).into()
+}
+
+fn status_array(ret: Vec<i64>) -> RespFrame {
+    RespArray::new(ret.into_iter().map(RespFrame::Integer).collect::<Vec<_>>()).into()
+}
+
+impl TryFrom<RespArray> for HDel {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, fields) = extract_store_args(value, "hdel")?;
+        Ok(HDel { key, fields })
     }
 }
 
@@ -88,7 +238,103 @@ impl TryFrom<RespArray> for HGetAll {
         match args.next() {
             Some(RespFrame::BulkString(key)) => Ok(HGetAll {
                 key: String::from_utf8(key.get_data()?)?,
-                sort: false,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for HLen {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hlen"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(HLen {
+                key: String::from_utf8(key.get_data()?)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for HKeys {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hkeys"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(HKeys {
+                key: String::from_utf8(key.get_data()?)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for HScan {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["hscan"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.get_data()?)?,
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let cursor = match args.next() {
+            Some(RespFrame::BulkString(cursor)) => std::str::from_utf8(&cursor)
+                .ok()
+                .and_then(|c| c.parse().ok())
+                .ok_or_else(|| CommandError::InvalidArgument("invalid cursor".to_string()))?,
+            _ => return Err(CommandError::InvalidArgument("invalid cursor".to_string())),
+        };
+        let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+        let mut scan = HScan {
+            key,
+            cursor,
+            count: super::keys::SCAN_DEFAULT_COUNT,
+            pattern: None,
+            novalues: false,
+        };
+        while let Some(option) = args.next() {
+            let RespFrame::BulkString(option) = option else {
+                return Err(syntax_error());
+            };
+            match option.to_ascii_lowercase().as_slice() {
+                b"novalues" => scan.novalues = true,
+                b"count" => match args.next().map(|n| parse_integer(&n)).transpose()? {
+                    Some(n) if n >= 1 => scan.count = n as usize,
+                    _ => return Err(syntax_error()),
+                },
+                b"match" => match args.next() {
+                    Some(RespFrame::BulkString(pattern)) => {
+                        scan.pattern = Some(String::from_utf8(pattern.get_data()?)?)
+                    }
+                    _ => return Err(syntax_error()),
+                },
+                _ => return Err(syntax_error()),
+            }
+        }
+        Ok(scan)
+    }
+}
+
+impl TryFrom<RespArray> for HVals {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hvals"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(HVals {
+                key: String::from_utf8(key.get_data()?)?,
             }),
             _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
         }
@@ -117,6 +363,30 @@ impl TryFrom<RespArray> for HSet {
     }
 }
 
+impl TryFrom<RespArray> for HIncrByFloat {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hincrbyfloat"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (
+                Some(RespFrame::BulkString(key)),
+                Some(RespFrame::BulkString(field)),
+                Some(increment),
+            ) => Ok(HIncrByFloat {
+                key: String::from_utf8(key.get_data()?)?,
+                field: String::from_utf8(field.get_data()?)?,
+                increment: parse_float(&increment)?,
+            }),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid key, field or increment".to_string(),
+            )),
+        }
+    }
+}
+
 impl TryFrom<RespArray> for HMGet {
     type Error = CommandError;
 
@@ -131,17 +401,142 @@ impl TryFrom<RespArray> for HMGet {
         };
         // 读取所有的 field 名字
         let mut fields = Vec::with_capacity(fields_len);
-        while let Some(RespFrame::BulkString(field)) = args.next() {
-            let field = String::from_utf8(field.get_data()?)?;
-            fields.push(field);
+        for arg in args {
+            match arg {
+                RespFrame::BulkString(field) => fields.push(String::from_utf8(field.get_data()?)?),
+                _ => return Err(CommandError::InvalidArgument("Invalid field".to_string())),
+            }
         }
         Ok(HMGet { key, fields })
     }
 }
 
+impl TryFrom<RespArray> for HExpire {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["hexpire"], 5)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(k)) => String::from_utf8(k.get_data()?)?,
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let seconds = match args.next() {
+            Some(seconds) => parse_integer(&seconds)?,
+            None => return Err(CommandError::WrongArity("hexpire".to_string())),
+        };
+        // 负数或换算成毫秒后溢出的时间都不合法
+        let seconds = u64::try_from(seconds)
+            .ok()
+            .filter(|seconds| {
+                seconds
+                    .checked_mul(1000)
+                    .is_some_and(|ms| ms <= i64::MAX as u64)
+            })
+            .ok_or_else(|| CommandError::InvalidExpireTime("hexpire".to_string()))?;
+        let fields = extract_fields(args)?;
+        Ok(HExpire {
+            key,
+            seconds,
+            fields,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HGetDel {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["hgetdel"], 4)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(k)) => String::from_utf8(k.get_data()?)?,
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let fields = extract_fields(args)?;
+        Ok(HGetDel { key, fields })
+    }
+}
+
+impl TryFrom<RespArray> for HGetEx {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["hgetex"], 4)?;
+
+        let mut args = extract_args(value, 1)?.into_iter().peekable();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(k)) => String::from_utf8(k.get_data()?)?,
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        // 过期选项在 FIELDS 之前，最多一个
+        let expiry = match args.peek() {
+            Some(RespFrame::BulkString(kw)) if kw.eq_ignore_ascii_case(b"fields") => None,
+            Some(_) => {
+                let option = args.next().unwrap();
+                Some(parse_getex_expiry(option, &mut args, "hgetex")?)
+            }
+            None => None,
+        };
+        let fields = extract_fields(args)?;
+        Ok(HGetEx {
+            key,
+            fields,
+            expiry,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HTtl {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["httl"], 4)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(k)) => String::from_utf8(k.get_data()?)?,
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let fields = extract_fields(args)?;
+        Ok(HTtl { key, fields })
+    }
+}
+
+// 解析 `FIELDS numfields field [field ...]`
+fn extract_fields(mut args: impl Iterator<Item = RespFrame>) -> Result<Vec<String>, CommandError> {
+    match args.next() {
+        Some(RespFrame::BulkString(kw)) if kw.eq_ignore_ascii_case(b"fields") => {}
+        _ => {
+            return Err(CommandError::InvalidArgument(
+                "Mandatory argument FIELDS is missing or not at the right position".to_string(),
+            ))
+        }
+    }
+    let numfields = match args.next() {
+        Some(numfields) => parse_integer(&numfields)?,
+        None => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+    };
+    let mut fields = Vec::new();
+    for arg in args {
+        match arg {
+            RespFrame::BulkString(field) => fields.push(String::from_utf8(field.get_data()?)?),
+            _ => return Err(CommandError::InvalidArgument("Invalid field".to_string())),
+        }
+    }
+    if numfields <= 0 || numfields as usize != fields.len() {
+        return Err(CommandError::InvalidArgument(
+            "The `numfields` parameter must match the number of arguments".to_string(),
+        ));
+    }
+    Ok(fields)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::RespDecode;
+    use crate::{BackendError, BulkString, RespDecode, RespEncode, RespVersion};
 
     use super::*;
     use anyhow::Result;
@@ -189,6 +584,22 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_hset_from_short_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*1\r\n$4\r\nhset\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result = HSet::try_from(frame.clone());
+        assert!(result.is_err());
+
+        let result = crate::cmd::Command::try_from(frame);
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_hmget_from_resp_array() -> Result<()> {
         let mut buf = BytesMut::new();
@@ -243,15 +654,11 @@ mod tests {
 
         let cmd = HGetAll {
             key: "map".to_string(),
-            sort: true,
         };
         let result = cmd.execute(&backend);
-        let expected = RespArray::new(vec![
-            BulkString::from("hello").into(),
-            BulkString::from("world").into(),
-            BulkString::from("hello1").into(),
-            BulkString::from("world1").into(),
-        ]);
+        let mut expected = RespMap::new();
+        expected.insert("hello".to_string(), BulkString::from("world").into());
+        expected.insert("hello1".to_string(), BulkString::from("world1").into());
         assert_eq!(result, expected.into());
 
         let cmd = HMGet {
@@ -272,4 +679,458 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_hgetall_reply_depends_on_protocol() {
+        let backend = crate::Backend::new();
+        backend
+            .hset(
+                "map".to_string(),
+                "a".to_string(),
+                BulkString::from("1").into(),
+            )
+            .unwrap();
+        backend
+            .hset(
+                "map".to_string(),
+                "b".to_string(),
+                BulkString::from("2").into(),
+            )
+            .unwrap();
+        let hgetall = || {
+            HGetAll {
+                key: "map".to_string(),
+            }
+            .execute(&backend)
+        };
+
+        assert_eq!(
+            hgetall().into_version(RespVersion::Resp3).encode(),
+            b"%2\r\n+a\r\n$1\r\n1\r\n+b\r\n$1\r\n2\r\n"
+        );
+        assert_eq!(
+            hgetall().into_version(RespVersion::Resp2).encode(),
+            b"*4\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n$1\r\n2\r\n"
+        );
+    }
+
+    #[test]
+    fn test_hmget_without_fields_is_arity_error() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$5\r\nhmget\r\n$3\r\nmap\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let err = HMGet::try_from(frame).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR wrong number of arguments for 'hmget' command"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hmget_missing_hash_returns_nulls() -> Result<()> {
+        let backend = crate::Backend::new();
+        let cmd = HMGet {
+            key: "missing".to_string(),
+            fields: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        };
+        let result = cmd.execute(&backend);
+        assert_eq!(
+            result,
+            RespArray::new(vec![RespFrame::Null(crate::RespNull); 3]).into()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hexpire_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*7\r\n$7\r\nhexpire\r\n$3\r\nmap\r\n$2\r\n10\r\n$6\r\nFIELDS\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n",
+        );
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: HExpire = frame.try_into()?;
+        assert_eq!(result.key, "map");
+        assert_eq!(result.seconds, 10);
+        assert_eq!(result.fields, vec!["a".to_string(), "b".to_string()]);
+
+        // numfields 和实际字段数不一致
+        buf.extend_from_slice(
+            b"*6\r\n$4\r\nhttl\r\n$3\r\nmap\r\n$6\r\nFIELDS\r\n$1\r\n3\r\n$1\r\na\r\n$1\r\nb\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(HTtl::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hexpire_rejects_overflowing_ttl() -> Result<()> {
+        let backend = crate::Backend::new();
+        backend.hset("h".to_string(), "f".to_string(), b"v".into())?;
+        let hexpire = |seconds: &str| -> RespFrame {
+            let args = ["hexpire", "h", seconds, "FIELDS", "1", "f"]
+                .into_iter()
+                .map(|arg| BulkString::from(arg).into())
+                .collect::<Vec<RespFrame>>();
+            match HExpire::try_from(RespArray::new(args)) {
+                Ok(cmd) => cmd.execute(&backend),
+                Err(e) => e.into(),
+            }
+        };
+        let invalid = RespFrame::error("ERR", "invalid expire time in 'hexpire' command");
+
+        assert_eq!(hexpire(&i64::MAX.to_string()), invalid);
+        assert_eq!(hexpire("-1"), invalid);
+        // 毫秒数可以表示，但加上当前时间后溢出
+        assert_eq!(hexpire(&(i64::MAX / 1000).to_string()), invalid);
+        assert_eq!(
+            backend.hexpire("h", Duration::MAX, &["f".to_string()]),
+            Err(BackendError::InvalidExpireTime)
+        );
+        assert_eq!(backend.httl("h", &["f".to_string()])?, vec![-1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hget_after_field_expires() -> Result<()> {
+        let clock = std::sync::Arc::new(crate::ManualClock::new());
+        let backend = crate::Backend::with_clock(clock.clone());
+        backend.hset("map".to_string(), "a".to_string(), b"1".into())?;
+        backend.hset("map".to_string(), "b".to_string(), b"2".into())?;
+
+        let cmd = HExpire {
+            key: "map".to_string(),
+            seconds: 10,
+            fields: vec!["a".to_string(), "c".to_string()],
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new(vec![RespFrame::Integer(1), RespFrame::Integer(-2)]).into()
+        );
+        let cmd = HTtl {
+            key: "map".to_string(),
+            fields: vec!["a".to_string(), "b".to_string()],
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new(vec![RespFrame::Integer(10), RespFrame::Integer(-1)]).into()
+        );
+
+        backend.hexpire(
+            "map",
+            Duration::from_secs(20),
+            &["a".to_string(), "b".to_string()],
+        )?;
+        let hget = |field: &str| {
+            HGet {
+                key: "map".to_string(),
+                field: field.to_string(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(hget("a"), b"1".into());
+
+        clock.advance(Duration::from_secs(20));
+        assert_eq!(hget("a"), RespFrame::Null(crate::RespNull));
+        assert_eq!(hget("b"), RespFrame::Null(crate::RespNull));
+        // 所有字段过期后 key 也被删除
+        assert_eq!(backend.key_type("map"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hincrbyfloat() -> Result<()> {
+        let backend = crate::Backend::new();
+        let hincrbyfloat = |field: &str, increment| {
+            HIncrByFloat {
+                key: "map".to_string(),
+                field: field.to_string(),
+                increment,
+            }
+            .execute(&backend)
+        };
+
+        // 不存在的字段以增量作为初始值
+        assert_eq!(hincrbyfloat("new", 10.5), BulkString::from("10.5").into());
+        assert_eq!(hincrbyfloat("new", 0.5), BulkString::from("11").into());
+
+        backend.hset("map".to_string(), "n".to_string(), b"5.0e3".into())?;
+        assert_eq!(hincrbyfloat("n", 200.0), BulkString::from("5200").into());
+        assert_eq!(
+            hincrbyfloat("n", -5200.25),
+            BulkString::from("-0.25").into()
+        );
+
+        backend.hset("map".to_string(), "s".to_string(), b"abc".into())?;
+        assert_eq!(
+            hincrbyfloat("s", 1.0).encode(),
+            b"-ERR hash value is not a float\r\n"
+        );
+        assert_eq!(backend.hget("map", "s"), Some(b"abc".into()));
+
+        // 结果不是有限值时报错，不会创建 key
+        let ret = HIncrByFloat {
+            key: "newkey".to_string(),
+            field: "f".to_string(),
+            increment: f64::INFINITY,
+        }
+        .execute(&backend);
+        assert_eq!(ret, BackendError::NotFinite.into());
+        assert_eq!(backend.exists(&["newkey".to_string()]), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_concurrent_hset_hgetall_is_consistent() -> Result<()> {
+        use std::thread;
+
+        const WRITERS: usize = 4;
+        const WRITES: usize = 2000;
+        let backend = crate::Backend::new();
+
+        // 每个值都由字段名和写入序号组成，读到的值必须能完整还原字段名
+        let writers = (0..WRITERS)
+            .map(|w| {
+                let backend = backend.clone();
+                thread::spawn(move || {
+                    for i in 0..WRITES {
+                        let field = format!("f{}", i % 16);
+                        let value = format!("{}:{}:{}", field, w, "x".repeat(i % 64));
+                        HSet {
+                            key: "map".to_string(),
+                            field,
+                            value: BulkString::from(value).into(),
+                        }
+                        .execute(&backend);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut reads = 0;
+        while reads < WRITES || !writers.iter().all(|w| w.is_finished()) {
+            let RespFrame::Map(map) = HGetAll {
+                key: "map".to_string(),
+            }
+            .execute(&backend) else {
+                panic!("expected map");
+            };
+            assert!(map.len() <= 16);
+            for (field, value) in map.iter() {
+                let RespFrame::BulkString(value) = value else {
+                    panic!("expected bulk string");
+                };
+                let value = String::from_utf8(value.get_data()?)?;
+                let mut parts = value.splitn(3, ':');
+                assert_eq!(parts.next(), Some(field.as_str()));
+                let writer: usize = parts.next().unwrap().parse()?;
+                assert!(writer < WRITERS);
+                assert!(parts.next().unwrap().bytes().all(|b| b == b'x'));
+            }
+            reads += 1;
+        }
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        assert_eq!(backend.hmap.get("map").map(|m| m.len()), Some(16));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hscan_novalues_returns_only_fields() -> Result<()> {
+        let backend = crate::Backend::new();
+        for i in 0..25 {
+            backend.hset(
+                "map".to_string(),
+                format!("f{i}"),
+                BulkString::from(format!("v{i}")).into(),
+            )?;
+        }
+        let scan = |args: &str| -> Result<(String, Vec<String>)> {
+            let RespFrame::Array(reply) = run::<HScan>(&backend, args)? else {
+                panic!("HSCAN should reply with an array");
+            };
+            let [RespFrame::BulkString(cursor), RespFrame::Array(items)] = &reply[..] else {
+                panic!("unexpected HSCAN reply {:?}", reply);
+            };
+            let items = items
+                .iter()
+                .map(|item| match item {
+                    RespFrame::BulkString(s) => String::from_utf8_lossy(s).into_owned(),
+                    other => panic!("unexpected item {:?}", other),
+                })
+                .collect();
+            Ok((String::from_utf8_lossy(cursor).into_owned(), items))
+        };
+
+        let mut cursor = "0".to_string();
+        let mut fields = Vec::new();
+        loop {
+            let (next, batch) = scan(&format!("hscan map {cursor} novalues"))?;
+            assert!(batch.iter().all(|f| f.starts_with('f')), "{:?}", batch);
+            fields.extend(batch);
+            if next == "0" {
+                break;
+            }
+            cursor = next;
+        }
+        fields.sort();
+        let mut expected: Vec<String> = (0..25).map(|i| format!("f{i}")).collect();
+        expected.sort();
+        assert_eq!(fields, expected);
+
+        // 不带 NOVALUES 时字段和值交替返回
+        let (next, items) = scan("hscan map 0 count 100 match f1")?;
+        assert_eq!(next, "0");
+        assert_eq!(items, vec!["f1".to_string(), "v1".to_string()]);
+        let (_, items) = scan("hscan map 0 count 100 match f1 novalues")?;
+        assert_eq!(items, vec!["f1".to_string()]);
+
+        assert!(run::<HScan>(&backend, "hscan map 0 count").is_err());
+        Ok(())
+    }
+
+    fn run<T>(backend: &crate::Backend, args: &str) -> Result<RespFrame>
+    where
+        T: TryFrom<RespArray, Error = CommandError> + CommandExecutor,
+    {
+        let args = args
+            .split(' ')
+            .map(|arg| BulkString::from(arg).into())
+            .collect::<Vec<RespFrame>>();
+        Ok(T::try_from(RespArray::new(args))?.execute(backend))
+    }
+
+    fn values(values: &[Option<&str>]) -> RespFrame {
+        RespArray::new(
+            values
+                .iter()
+                .map(|v| match v {
+                    Some(v) => BulkString::from(*v).into(),
+                    None => RespFrame::Null(crate::RespNull),
+                })
+                .collect::<Vec<RespFrame>>(),
+        )
+        .into()
+    }
+
+    #[test]
+    fn test_hgetdel() -> Result<()> {
+        let backend = crate::Backend::new();
+        for field in ["a", "b", "c"] {
+            backend.hset(
+                "map".to_string(),
+                field.to_string(),
+                field.as_bytes().into(),
+            )?;
+        }
+        backend.hexpire("map", Duration::from_secs(100), &["a".to_string()])?;
+
+        assert_eq!(
+            run::<HGetDel>(&backend, "hgetdel map FIELDS 3 a b missing")?,
+            values(&[Some("a"), Some("b"), None])
+        );
+        assert_eq!(backend.hget("map", "a"), None);
+        assert_eq!(backend.hget("map", "c"), Some(b"c".into()));
+        assert!(!backend
+            .hash_expires
+            .get("map")
+            .is_some_and(|e| e.contains_key("a")));
+
+        // 删除最后一个字段后 hash 也被删除
+        assert_eq!(
+            run::<HGetDel>(&backend, "hgetdel map FIELDS 1 c")?,
+            values(&[Some("c")])
+        );
+        assert_eq!(backend.key_type("map"), None);
+        assert!(run::<HGetDel>(&backend, "hgetdel map FIELDS 2 c").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hgetex() -> Result<()> {
+        let backend = crate::Backend::new();
+        for field in ["a", "b"] {
+            backend.hset(
+                "map".to_string(),
+                field.to_string(),
+                field.as_bytes().into(),
+            )?;
+        }
+        let ttl =
+            |backend: &crate::Backend| backend.httl("map", &["a".to_string(), "b".to_string()]);
+
+        assert_eq!(
+            run::<HGetEx>(&backend, "hgetex map FIELDS 2 a missing")?,
+            values(&[Some("a"), None])
+        );
+        assert_eq!(ttl(&backend)?, vec![-1, -1]);
+
+        assert_eq!(
+            run::<HGetEx>(&backend, "hgetex map EX 100 FIELDS 2 a b")?,
+            values(&[Some("a"), Some("b")])
+        );
+        assert_eq!(ttl(&backend)?, vec![100, 100]);
+
+        assert_eq!(
+            run::<HGetEx>(&backend, "hgetex map PERSIST FIELDS 1 a")?,
+            values(&[Some("a")])
+        );
+        assert_eq!(ttl(&backend)?, vec![-1, 100]);
+
+        // 过去的时间点直接删除字段，但仍返回读到的值
+        assert_eq!(
+            run::<HGetEx>(&backend, "hgetex map EXAT 1 FIELDS 1 b")?,
+            values(&[Some("b")])
+        );
+        assert_eq!(backend.hget("map", "b"), None);
+
+        assert!(run::<HGetEx>(&backend, "hgetex map EX 0 FIELDS 1 a").is_err());
+        assert!(run::<HGetEx>(&backend, "hgetex map EX 10 PX 10 FIELDS 1 a").is_err());
+        backend.set("str".to_string(), BulkString::from("v").into());
+        assert_eq!(
+            run::<HGetEx>(&backend, "hgetex str FIELDS 1 a")?.encode(),
+            b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_reads_missing_vs_wrong_type() -> Result<()> {
+        let backend = crate::Backend::new();
+        backend.set("string".to_string(), BulkString::from("value").into());
+        let run = |name: &str, key: &str| -> Result<RespFrame> {
+            let frame = RespArray::new(vec![
+                BulkString::from(name).into(),
+                BulkString::from(key).into(),
+            ]);
+            Ok(crate::cmd::Command::try_from(RespFrame::from(frame))?.execute(&backend))
+        };
+        let wrong_type: RespFrame = crate::BackendError::WrongType.into();
+
+        for name in ["hkeys", "hvals", "hlen", "hgetall"] {
+            assert_eq!(run(name, "string")?, wrong_type, "{}", name);
+        }
+        assert_eq!(run("hkeys", "missing")?, RespArray::new(vec![]).into());
+        assert_eq!(run("hvals", "missing")?, RespArray::new(vec![]).into());
+        assert_eq!(run("hlen", "missing")?, RespFrame::Integer(0));
+        assert_eq!(run("hgetall", "missing")?, RespMap::new().into());
+        // 检查类型不会创建 key，也不会改动原来的值
+        assert_eq!(backend.key_type("missing"), None);
+        assert_eq!(
+            backend.get("string"),
+            crate::Lookup::Found(BulkString::from("value").into())
+        );
+
+        backend.hset("hash".to_string(), "f".to_string(), b"v".into())?;
+        assert_eq!(run("hlen", "hash")?, RespFrame::Integer(1));
+        Ok(())
+    }
 }
@@ -0,0 +1,259 @@
+use crate::{BulkString, ConnectionContext, RespArray, RespFrame, RespVersion};
+
+use super::{extract_args, validate_command_at_least, CommandError, CommandExecutor, Info};
+
+impl CommandExecutor for Info {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        self.apply(backend, RespVersion::default())
+    }
+
+    fn execute_with(self, backend: &crate::Backend, ctx: &ConnectionContext) -> RespFrame {
+        let version = backend.config().reply_version(ctx.version);
+        self.apply(backend, version)
+    }
+}
+
+impl Info {
+    /// Render the requested sections, `version` is the protocol negotiated by
+    /// the calling connection.
+    pub fn apply(self, backend: &crate::Backend, version: RespVersion) -> RespFrame {
+        let proto = match version {
+            RespVersion::Resp2 => 2,
+            RespVersion::Resp3 => 3,
+        };
+        let commandstats = backend
+            .command_stats()
+            .iter()
+            .map(|(name, stat)| {
+                format!(
+                    "cmdstat_{}:calls={},usec={}\r\n",
+                    name, stat.calls, stat.usec
+                )
+            })
+            .collect::<String>();
+        let parse = backend.parse_stats();
+        // 第三项表示该段是否包含在默认输出中，和 Redis 一样 commandstats 不在其中
+        let sections = [
+            (
+                "server",
+                format!(
+                    "# Server\r\nredis_version:{}\r\n",
+                    env!("CARGO_PKG_VERSION")
+                ),
+                true,
+            ),
+            (
+                "clients",
+                format!(
+                    "# Clients\r\nconnected_clients:{}\r\nprotocol_version:{}\r\n",
+                    backend.connected_clients(),
+                    proto
+                ),
+                true,
+            ),
+            (
+                "stats",
+                format!(
+                    "# Stats\r\nexpired_keys:{}\r\ndeleted_keys:{}\r\npubsub_channels:{}\r\npubsub_patterns:{}\r\nparse_frames:{}\r\nparse_bytes:{}\r\nparse_not_complete:{}\r\n",
+                    backend.expired_keys_total(),
+                    backend.deleted_keys_total(),
+                    backend.pubsub_numchannels(),
+                    backend.pubsub_numpat(),
+                    parse.frames,
+                    parse.bytes,
+                    parse.not_complete
+                ),
+                true,
+            ),
+            (
+                "commandstats",
+                format!("# Commandstats\r\n{}", commandstats),
+                false,
+            ),
+        ];
+
+        let section = self.section.map(|s| s.to_ascii_lowercase());
+        let info = sections
+            .iter()
+            .filter(|(name, _, default)| match section.as_deref() {
+                None | Some("default") => *default,
+                Some("all") | Some("everything") => true,
+                Some(section) => section == *name,
+            })
+            .map(|(_, text, _)| text.as_str())
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        BulkString::new(info).into()
+    }
+}
+
+impl TryFrom<RespArray> for Info {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["info"], 0)?;
+        if value.len() > 2 {
+            return Err(CommandError::InvalidArgument(
+                "info command must have at most 1 argument".to_string(),
+            ));
+        }
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(section)) => Ok(Info {
+                section: Some(String::from_utf8(section.get_data()?)?),
+            }),
+            None => Ok(Info { section: None }),
+            _ => Err(CommandError::InvalidArgument("Invalid section".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::Command;
+    use crate::{Backend, RespDecode};
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_info_reads_protocol_from_connection_context() -> Result<()> {
+        let backend = Backend::new();
+        let info = |ctx: &ConnectionContext| -> Result<String> {
+            let frame = RespArray::new(vec![
+                BulkString::from("info").into(),
+                BulkString::from("clients").into(),
+            ]);
+            let cmd = Command::try_from(frame)?;
+            let RespFrame::BulkString(info) = cmd.execute_with(&backend, ctx) else {
+                panic!("INFO should reply with a bulk string");
+            };
+            Ok(String::from_utf8(info.get_data()?)?)
+        };
+
+        let mut ctx = ConnectionContext::default();
+        assert!(info(&ctx)?.contains("protocol_version:2\r\n"));
+        ctx.version = RespVersion::Resp3;
+        assert!(info(&ctx)?.contains("protocol_version:3\r\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_info_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$4\r\ninfo\r\n$7\r\nclients\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result: Info = frame.try_into()?;
+        assert_eq!(result.section, Some("clients".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_info_clients_section() -> Result<()> {
+        let backend = Backend::new();
+        let _client = backend.connect();
+
+        let cmd = Info {
+            section: Some("clients".to_string()),
+        };
+        let result = cmd.apply(&backend, RespVersion::Resp3);
+        assert_eq!(
+            result,
+            BulkString::new("# Clients\r\nconnected_clients:1\r\nprotocol_version:3\r\n").into()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_info_stats_reports_pubsub_counts() {
+        let backend = Backend::new();
+        let stats = |backend: &Backend| {
+            Info {
+                section: Some("stats".to_string()),
+            }
+            .apply(backend, RespVersion::Resp2)
+        };
+        assert_eq!(
+            stats(&backend),
+            BulkString::new("# Stats\r\nexpired_keys:0\r\ndeleted_keys:0\r\npubsub_channels:0\r\npubsub_patterns:0\r\nparse_frames:0\r\nparse_bytes:0\r\nparse_not_complete:0\r\n").into()
+        );
+
+        let mut a = backend.subscriber();
+        let mut b = backend.subscriber();
+        a.subscribe("news".to_string());
+        a.subscribe("sports".to_string());
+        b.subscribe("news".to_string());
+        b.psubscribe("news.*".to_string());
+        assert_eq!(
+            stats(&backend),
+            BulkString::new("# Stats\r\nexpired_keys:0\r\ndeleted_keys:0\r\npubsub_channels:2\r\npubsub_patterns:1\r\nparse_frames:0\r\nparse_bytes:0\r\nparse_not_complete:0\r\n").into()
+        );
+
+        drop(a);
+        assert_eq!(
+            stats(&backend),
+            BulkString::new("# Stats\r\nexpired_keys:0\r\ndeleted_keys:0\r\npubsub_channels:1\r\npubsub_patterns:1\r\nparse_frames:0\r\nparse_bytes:0\r\nparse_not_complete:0\r\n").into()
+        );
+    }
+
+    #[test]
+    fn test_info_counts_expired_and_deleted_keys_separately() {
+        use crate::{BulkString, ManualClock, RespPush};
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let clock = Arc::new(ManualClock::new());
+        let backend = Backend::with_clock(clock.clone());
+        backend
+            .config()
+            .set_notify_keyspace_events("Egx".parse().unwrap());
+        let mut subscriber = backend.subscriber();
+        subscriber.subscribe("__keyevent@0__:expired".to_string());
+        subscriber.subscribe("__keyevent@0__:del".to_string());
+        let event = |event: &str, key: &str| -> RespFrame {
+            RespPush::new(vec![
+                BulkString::from("message").into(),
+                BulkString::from(format!("__keyevent@0__:{}", event)).into(),
+                BulkString::from(key).into(),
+            ])
+            .into()
+        };
+
+        for key in ["a", "b"] {
+            backend.set(key.to_string(), BulkString::from("v").into());
+        }
+        backend.expire("a", Duration::from_secs(1)).unwrap();
+        clock.advance(Duration::from_secs(1));
+        assert!(backend.get("a").found().is_none());
+        assert_eq!(
+            subscriber.try_recv().map(|f| f.into_frame()),
+            Some(event("expired", "a"))
+        );
+
+        backend.del(&["b".to_string(), "missing".to_string()]);
+        assert_eq!(
+            subscriber.try_recv().map(|f| f.into_frame()),
+            Some(event("del", "b"))
+        );
+        assert!(subscriber.try_recv().is_none());
+
+        let stats = Info {
+            section: Some("stats".to_string()),
+        }
+        .apply(&backend, RespVersion::Resp2);
+        assert_eq!(
+            stats,
+            BulkString::new(
+                "# Stats\r\nexpired_keys:1\r\ndeleted_keys:1\r\npubsub_channels:2\r\npubsub_patterns:0\r\nparse_frames:0\r\nparse_bytes:0\r\nparse_not_complete:0\r\n"
+            )
+            .into()
+        );
+    }
+}
@@ -0,0 +1,840 @@
+use std::time::{Duration, SystemTime};
+
+use crate::{Backend, BackendError, BulkString, RespArray, RespFrame, RespNull};
+
+use super::{
+    extract_args, parse_integer, validate_command, validate_command_at_least, CommandError,
+    CommandExecutor, Copy, DbSize, Del, Dump, Exists, Expire, FlushDb, PExpire, PExpireAt, PTtl,
+    Rename, RenameNx, Restore, Scan, Ttl, Unlink, RESP_OK,
+};
+
+impl CommandExecutor for Copy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let copied = backend.copy(&self.source, &self.destination, self.replace);
+        RespFrame::Integer(copied as i64)
+    }
+}
+
+impl CommandExecutor for Dump {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.dump(&self.key) {
+            Some(payload) => BulkString::new(payload).into(),
+            None => RespFrame::Null(RespNull),
+        }
+    }
+}
+
+impl CommandExecutor for Restore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.restore(self.key, self.ttl, &self.payload, self.replace) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for Rename {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.rename(&self.key, &self.new_key) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for RenameNx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.renamenx(&self.0.key, &self.0.new_key) {
+            Ok(renamed) => RespFrame::Integer(renamed as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for Del {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.del(&self.keys) as i64)
+    }
+}
+
+impl CommandExecutor for Exists {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.exists(&self.keys) as i64)
+    }
+}
+
+impl CommandExecutor for Unlink {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.0.execute(backend)
+    }
+}
+
+impl CommandExecutor for FlushDb {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        backend.flushdb();
+        RESP_OK.clone()
+    }
+}
+
+impl CommandExecutor for DbSize {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.dbsize() as i64)
+    }
+}
+
+impl CommandExecutor for Expire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // 和 Redis 一样，秒数换算成毫秒时不能溢出
+        match self.seconds.checked_mul(1000) {
+            Some(milliseconds) => expire(backend, &self.key, milliseconds, "expire"),
+            None => CommandError::InvalidExpireTime("expire".to_string()).into(),
+        }
+    }
+}
+
+impl CommandExecutor for PExpire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        expire(backend, &self.key, self.milliseconds, "pexpire")
+    }
+}
+
+impl CommandExecutor for PExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // 早于 1970 的时间同样视为已经过去
+        let at = SystemTime::UNIX_EPOCH + Duration::from_millis(self.unix_millis.max(0) as u64);
+        expire_reply(backend.expire_at(&self.key, at), "pexpireat")
+    }
+}
+
+fn expire(backend: &Backend, key: &str, milliseconds: i64, name: &str) -> RespFrame {
+    let ttl = Duration::from_millis(milliseconds.max(0) as u64);
+    expire_reply(backend.expire(key, ttl), name)
+}
+
+fn expire_reply(ret: Result<bool, BackendError>, name: &str) -> RespFrame {
+    match ret {
+        Ok(exists) => RespFrame::Integer(exists as i64),
+        Err(BackendError::InvalidExpireTime) => {
+            CommandError::InvalidExpireTime(name.to_string()).into()
+        }
+        Err(e) => e.into(),
+    }
+}
+
+impl CommandExecutor for Ttl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.ttl(&self.key))
+    }
+}
+
+impl CommandExecutor for PTtl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.pttl(&self.key))
+    }
+}
+
+impl CommandExecutor for Scan {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let (cursor, keys) = backend.scan(
+            self.cursor,
+            self.count,
+            self.pattern.as_deref(),
+            self.key_type.as_deref(),
+        );
+        let keys: Vec<RespFrame> = keys
+            .into_iter()
+            .map(|k| BulkString::from(k).into())
+            .collect();
+        RespArray::new(vec![
+            BulkString::from(cursor.to_string()).into(),
+            RespArray::new(keys).into(),
+        ])
+        .into()
+    }
+}
+
+impl TryFrom<RespArray> for Copy {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["copy"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let (source, destination) = match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(source)), Some(RespFrame::BulkString(destination))) => (
+                String::from_utf8(source.get_data()?)?,
+                String::from_utf8(destination.get_data()?)?,
+            ),
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+
+        // 只有一个 db，不支持 DB 选项
+        let mut replace = false;
+        for arg in args {
+            match arg {
+                RespFrame::BulkString(opt) if opt.eq_ignore_ascii_case(b"replace") => {
+                    replace = true
+                }
+                _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+            }
+        }
+        Ok(Copy {
+            source,
+            destination,
+            replace,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Dump {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["dump"], 1)?;
+
+        match extract_args(value, 1)?.into_iter().next() {
+            Some(RespFrame::BulkString(key)) => Ok(Dump {
+                key: String::from_utf8(key.get_data()?)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Restore {
+    type Error = CommandError;
+
+    // restore <key> <ttl> <serialized-value> [REPLACE]，ttl 为毫秒，0 表示不过期
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["restore"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let (key, ttl, payload) = match (args.next(), args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(ttl), Some(RespFrame::BulkString(payload))) => {
+                (
+                    String::from_utf8(key.get_data()?)?,
+                    parse_integer(&ttl)?,
+                    payload.get_data()?,
+                )
+            }
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        if ttl < 0 {
+            return Err(CommandError::InvalidArgument(
+                "Invalid TTL value, must be >= 0".to_string(),
+            ));
+        }
+
+        let mut replace = false;
+        for arg in args {
+            match arg {
+                RespFrame::BulkString(opt) if opt.eq_ignore_ascii_case(b"replace") => {
+                    replace = true
+                }
+                _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+            }
+        }
+        Ok(Restore {
+            key,
+            ttl: (ttl > 0).then(|| Duration::from_millis(ttl as u64)),
+            payload,
+            replace,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Rename {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        parse_rename(value, "rename")
+    }
+}
+
+impl TryFrom<RespArray> for RenameNx {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        parse_rename(value, "renamenx").map(RenameNx)
+    }
+}
+
+// RENAME 和 RENAMENX 的参数相同：<key> <newkey>
+fn parse_rename(value: RespArray, name: &'static str) -> Result<Rename, CommandError> {
+    validate_command(&value, &[name], 2)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    match (args.next(), args.next()) {
+        (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(new_key))) => Ok(Rename {
+            key: String::from_utf8(key.get_data()?)?,
+            new_key: String::from_utf8(new_key.get_data()?)?,
+        }),
+        _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+    }
+}
+
+impl TryFrom<RespArray> for Del {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Del {
+            keys: parse_keys(value, "del")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Unlink {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Unlink(Del {
+            keys: parse_keys(value, "unlink")?,
+        }))
+    }
+}
+
+impl TryFrom<RespArray> for Exists {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Exists {
+            keys: parse_keys(value, "exists")?,
+        })
+    }
+}
+
+fn parse_keys(value: RespArray, name: &'static str) -> Result<Vec<String>, CommandError> {
+    validate_command_at_least(&value, &[name], 1)?;
+
+    let mut keys = Vec::with_capacity(value.len() - 1);
+    for arg in extract_args(value, 1)? {
+        match arg {
+            RespFrame::BulkString(key) => keys.push(String::from_utf8(key.get_data()?)?),
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+    Ok(keys)
+}
+
+impl TryFrom<RespArray> for FlushDb {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["flushdb"], 0)?;
+        // 删除总是同步完成，ASYNC / SYNC 只做校验
+        match extract_args(value, 1)?.as_slice() {
+            [] => Ok(FlushDb),
+            [RespFrame::BulkString(mode)]
+                if mode.eq_ignore_ascii_case(b"async") || mode.eq_ignore_ascii_case(b"sync") =>
+            {
+                Ok(FlushDb)
+            }
+            _ => Err(CommandError::InvalidArgument("syntax error".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for DbSize {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["dbsize"], 0)?;
+        Ok(DbSize)
+    }
+}
+
+impl TryFrom<RespArray> for Expire {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["expire"], 2)?;
+        let (key, seconds) = extract_key_and_integer(value)?;
+        Ok(Expire { key, seconds })
+    }
+}
+
+impl TryFrom<RespArray> for PExpire {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["pexpire"], 2)?;
+        let (key, milliseconds) = extract_key_and_integer(value)?;
+        Ok(PExpire { key, milliseconds })
+    }
+}
+
+impl TryFrom<RespArray> for PExpireAt {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["pexpireat"], 2)?;
+        let (key, unix_millis) = extract_key_and_integer(value)?;
+        Ok(PExpireAt { key, unix_millis })
+    }
+}
+
+impl TryFrom<RespArray> for Ttl {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["ttl"], 1)?;
+        Ok(Ttl {
+            key: extract_key(value)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PTtl {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["pttl"], 1)?;
+        Ok(PTtl {
+            key: extract_key(value)?,
+        })
+    }
+}
+
+// SCAN 默认每次返回的 key 数
+pub(super) const SCAN_DEFAULT_COUNT: usize = 10;
+
+impl TryFrom<RespArray> for Scan {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["scan"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let cursor = match args.next() {
+            Some(RespFrame::BulkString(cursor)) => std::str::from_utf8(&cursor)
+                .ok()
+                .and_then(|c| c.parse().ok())
+                .ok_or_else(|| CommandError::InvalidArgument("invalid cursor".to_string()))?,
+            _ => return Err(CommandError::InvalidArgument("invalid cursor".to_string())),
+        };
+        let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+        let mut scan = Scan {
+            cursor,
+            count: SCAN_DEFAULT_COUNT,
+            pattern: None,
+            key_type: None,
+        };
+        while let Some(option) = args.next() {
+            let (RespFrame::BulkString(option), Some(arg)) = (option, args.next()) else {
+                return Err(syntax_error());
+            };
+            match option.to_ascii_lowercase().as_slice() {
+                b"count" => match parse_integer(&arg)? {
+                    n if n >= 1 => scan.count = n as usize,
+                    _ => return Err(syntax_error()),
+                },
+                b"match" => match arg {
+                    RespFrame::BulkString(pattern) => scan.pattern = Some(pattern.get_data()?),
+                    _ => return Err(syntax_error()),
+                },
+                b"type" => scan.key_type = Some(bulk_string(arg)?),
+                _ => return Err(syntax_error()),
+            }
+        }
+        Ok(scan)
+    }
+}
+
+fn bulk_string(frame: RespFrame) -> Result<String, CommandError> {
+    match frame {
+        RespFrame::BulkString(s) => Ok(String::from_utf8(s.get_data()?)?),
+        _ => Err(CommandError::InvalidArgument("syntax error".to_string())),
+    }
+}
+
+fn extract_key(value: RespArray) -> Result<String, CommandError> {
+    match extract_args(value, 1)?.into_iter().next() {
+        Some(RespFrame::BulkString(key)) => Ok(String::from_utf8(key.get_data()?)?),
+        _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+    }
+}
+
+fn extract_key_and_integer(value: RespArray) -> Result<(String, i64), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    match (args.next(), args.next()) {
+        (Some(RespFrame::BulkString(key)), Some(n)) => {
+            Ok((String::from_utf8(key.get_data()?)?, parse_integer(&n)?))
+        }
+        _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::GetDel;
+    use crate::{RespDecode, RespPush};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_scan_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$4\r\nscan\r\n$1\r\n0\r\n$5\r\nMATCH\r\n$3\r\nk:*\r\n$5\r\nCOUNT\r\n$3\r\n100\r\n",
+        );
+        let cmd: Scan = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!((cmd.cursor, cmd.count), (0, 100));
+        assert_eq!(cmd.pattern.as_deref(), Some(&b"k:*"[..]));
+
+        let backend = Backend::new();
+        backend.set("k:1".to_string(), BulkString::from("v").into());
+        backend.set("other".to_string(), BulkString::from("v").into());
+        let expected = RespArray::new(vec![
+            BulkString::from("0").into(),
+            RespArray::new(vec![BulkString::from("k:1").into()]).into(),
+        ]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_match_pattern_is_matched_bytewise() -> Result<()> {
+        let backend = Backend::new();
+        for key in ["ключ", "é", "e"] {
+            backend.set(key.to_string(), BulkString::from("v").into());
+        }
+        let scan = |pattern: &[u8]| -> Result<Vec<String>> {
+            let frame = RespArray::new(vec![
+                BulkString::from("scan").into(),
+                BulkString::from("0").into(),
+                BulkString::from("match").into(),
+                BulkString::new(pattern.to_vec()).into(),
+            ]);
+            let (_, mut keys) =
+                backend.scan(0, 100, Scan::try_from(frame)?.pattern.as_deref(), None);
+            keys.sort();
+            Ok(keys)
+        };
+
+        // "к" 是 0xd0 0xba，模式按字节匹配
+        assert_eq!(scan(b"\xd0\xba*")?, vec!["ключ".to_string()]);
+        // "é" 占两个字节，? 只匹配一个字节
+        assert_eq!(scan(b"?")?, vec!["e".to_string()]);
+        assert_eq!(scan(b"??")?, vec!["é".to_string()]);
+        // 非 UTF-8 的模式不报错，只是匹配不到
+        assert!(scan(b"\xff*")?.is_empty());
+        // key 仍然是 String，非 UTF-8 的 key 在解析时就被拒绝，存不进来
+        let set = RespArray::new(vec![
+            BulkString::from("set").into(),
+            BulkString::new(b"\xff".to_vec()).into(),
+            BulkString::from("v").into(),
+        ]);
+        assert!(crate::cmd::Command::try_from(set).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dbsize_skips_logically_expired_keys() -> Result<()> {
+        use crate::ManualClock;
+        use std::sync::Arc;
+
+        let clock = Arc::new(ManualClock::new());
+        let backend = Backend::with_clock(clock.clone());
+        backend.set("a".to_string(), BulkString::from("v").into());
+        backend.set("b".to_string(), BulkString::from("v").into());
+        backend.sadd("s".to_string(), "m".to_string()).unwrap();
+        backend.expire("a", Duration::from_secs(1)).unwrap();
+        backend.expire("s", Duration::from_secs(10)).unwrap();
+
+        let dbsize = || -> Result<RespFrame> {
+            let frame = RespArray::new(vec![BulkString::from("dbsize").into()]);
+            Ok(DbSize::try_from(frame)?.execute(&backend))
+        };
+        assert_eq!(dbsize()?, RespFrame::Integer(3));
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(dbsize()?, RespFrame::Integer(2));
+        // 只是不计数，key 还没有被删除
+        assert!(backend.map.contains_key("a"));
+        Ok(())
+    }
+
+    fn list(backend: &Backend, key: &str) -> Vec<RespFrame> {
+        backend.list.get(key).unwrap().iter().cloned().collect()
+    }
+
+    fn zset(backend: &Backend, key: &str) -> Vec<(String, f64)> {
+        backend
+            .zset
+            .get(key)
+            .unwrap()
+            .iter()
+            .map(|(m, s)| (m.to_string(), s))
+            .collect()
+    }
+
+    #[test]
+    fn test_copy_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$4\r\ncopy\r\n$1\r\na\r\n$1\r\nb\r\n$7\r\nREPLACE\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Copy = frame.try_into()?;
+        assert_eq!(result.source, "a");
+        assert_eq!(result.destination, "b");
+        assert!(result.replace);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_list_and_zset() -> Result<()> {
+        let backend = Backend::new();
+        backend.rpush(
+            "list".to_string(),
+            vec![b"a".into(), b"b".into(), b"c".into()],
+        )?;
+        backend.zadd(
+            "zset".to_string(),
+            vec![
+                (3.0, "c".to_string()),
+                (1.0, "a".to_string()),
+                (2.0, "b".to_string()),
+            ],
+        )?;
+
+        for (src, dst) in [("list", "list2"), ("zset", "zset2")] {
+            let cmd = Copy {
+                source: src.to_string(),
+                destination: dst.to_string(),
+                replace: false,
+            };
+            assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        }
+
+        assert_eq!(list(&backend, "list2"), list(&backend, "list"));
+        assert_eq!(
+            zset(&backend, "zset2"),
+            vec![
+                ("a".to_string(), 1.0),
+                ("b".to_string(), 2.0),
+                ("c".to_string(), 3.0)
+            ]
+        );
+
+        // 拷贝之后两份数据互不影响
+        backend.rpush("list2".to_string(), vec![b"d".into()])?;
+        assert_eq!(list(&backend, "list").len(), 3);
+        assert_eq!(list(&backend, "list2").len(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rename_list_and_zset() -> Result<()> {
+        let backend = Backend::new();
+        backend.rpush(
+            "list".to_string(),
+            vec![b"a".into(), b"b".into(), b"c".into()],
+        )?;
+        backend.zadd(
+            "zset".to_string(),
+            vec![(2.0, "b".to_string()), (1.0, "a".to_string())],
+        )?;
+
+        for (key, new_key) in [("list", "renamed-list"), ("zset", "renamed-zset")] {
+            let cmd = Rename {
+                key: key.to_string(),
+                new_key: new_key.to_string(),
+            };
+            assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+            assert_eq!(backend.key_type(key), None);
+        }
+
+        assert_eq!(
+            list(&backend, "renamed-list"),
+            vec![b"a".into(), b"b".into(), b"c".into()]
+        );
+        assert_eq!(backend.key_type("renamed-list"), Some("list"));
+        assert_eq!(
+            zset(&backend, "renamed-zset"),
+            vec![("a".to_string(), 1.0), ("b".to_string(), 2.0)]
+        );
+
+        let cmd = Rename {
+            key: "missing".to_string(),
+            new_key: "other".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::NoSuchKey.into());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_string_keeps_source() {
+        let backend = Backend::new();
+        backend.set("a".to_string(), BulkString::from("1").into());
+        let cmd = Copy {
+            source: "a".to_string(),
+            destination: "b".to_string(),
+            replace: false,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.key_type("a"), Some("string"));
+        assert_eq!(backend.key_type("b"), Some("string"));
+    }
+
+    #[test]
+    fn test_expire_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nexpire\r\n$1\r\nk\r\n$3\r\n100\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Expire = frame.try_into()?;
+        assert_eq!(result.key, "k");
+        assert_eq!(result.seconds, 100);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expire_rejects_overflowing_ttl() {
+        let backend = Backend::new();
+        backend.set("k".to_string(), BulkString::from("v").into());
+
+        let cmd = Expire {
+            key: "k".to_string(),
+            seconds: i64::MAX,
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::error("ERR", "invalid expire time in 'expire' command")
+        );
+        let cmd = PExpire {
+            key: "k".to_string(),
+            milliseconds: i64::MAX,
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::error("ERR", "invalid expire time in 'pexpire' command")
+        );
+        assert_eq!(
+            backend.expire("k", Duration::MAX),
+            Err(BackendError::InvalidExpireTime)
+        );
+        assert_eq!(backend.get("k"), crate::Lookup::Found(b"v".into()));
+    }
+
+    #[test]
+    fn test_pexpireat_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("k".to_string(), BulkString::from("v").into());
+
+        let mut buf = BytesMut::new();
+        let at = backend.unix_millis() + 100_000;
+        buf.extend_from_slice(
+            format!("*3\r\n$9\r\npexpireat\r\n$1\r\nk\r\n$13\r\n{}\r\n", at).as_bytes(),
+        );
+        let cmd: PExpireAt = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.ttl("k"), 100);
+
+        // 过去的时间直接删除 key
+        let cmd = PExpireAt {
+            key: "k".to_string(),
+            unix_millis: -1,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.key_type("k"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expire_and_ttl_commands() {
+        let backend = Backend::new();
+        backend.set("k".to_string(), BulkString::from("v").into());
+
+        let ttl = |key: &str| {
+            Ttl {
+                key: key.to_string(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(ttl("k"), RespFrame::Integer(-1));
+        assert_eq!(ttl("missing"), RespFrame::Integer(-2));
+
+        let cmd = PExpire {
+            key: "k".to_string(),
+            milliseconds: 100_000,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(ttl("k"), RespFrame::Integer(100));
+
+        // 负数的过期时间直接删除 key
+        let cmd = Expire {
+            key: "k".to_string(),
+            seconds: -1,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(ttl("k"), RespFrame::Integer(-2));
+    }
+
+    #[test]
+    fn test_del_publishes_keyevent() {
+        let backend = Backend::new();
+        backend
+            .config()
+            .set_notify_keyspace_events("Eg".parse().unwrap());
+        let mut subscriber = backend.subscriber();
+        subscriber.subscribe("__keyevent@0__:del".to_string());
+        backend.set("a".to_string(), BulkString::from("1").into());
+
+        let cmd = Del {
+            keys: vec!["a".to_string(), "missing".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let message = subscriber.try_recv().unwrap().into_frame();
+        assert_eq!(
+            message,
+            RespPush::new(vec![
+                BulkString::from("message").into(),
+                BulkString::from("__keyevent@0__:del").into(),
+                BulkString::from("a").into(),
+            ])
+            .into()
+        );
+        // 不存在的 key 不产生事件，SET 属于未开启的 $ 类
+        assert!(subscriber.try_recv().is_none());
+    }
+
+    #[test]
+    fn test_del_and_unlink_remove_any_type() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset("h".to_string(), "f".to_string(), b"v".into())?;
+        backend.sadd("s".to_string(), "m".to_string())?;
+        backend.rpush("l".to_string(), vec![b"a".into()])?;
+        backend.zadd("z".to_string(), vec![(1.0, "m".to_string())])?;
+
+        // GETDEL 只处理字符串，类型不对时保留原值
+        let cmd = GetDel {
+            key: "h".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::WrongType.into());
+        assert_eq!(backend.key_type("h"), Some("hash"));
+
+        let cmd = Del {
+            keys: vec!["h".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.key_type("h"), None);
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$6\r\nunlink\r\n$1\r\ns\r\n$1\r\nl\r\n$1\r\nz\r\n$7\r\nmissing\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Unlink = frame.try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+        for key in ["s", "l", "z"] {
+            assert_eq!(backend.key_type(key), None);
+        }
+        Ok(())
+    }
+}
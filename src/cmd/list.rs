@@ -0,0 +1,835 @@
+use std::time::{Duration, Instant};
+
+use crate::{BulkString, ListEnd, RespArray, RespFrame};
+
+use super::{
+    extract_args, extract_mpop_args, extract_pop_args, parse_integer, validate_command,
+    validate_command_at_least, BLMove, BLPop, BRPop, CommandError, CommandExecutor, LMPop, LMove,
+    LPop, LPos, LPush, LRem, LTrim, RPop, RPush, RESP_OK,
+};
+
+impl CommandExecutor for LPush {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.lpush(self.key, self.values) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for RPush {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.rpush(self.key, self.values) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LPop {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        pop(backend, self.key, ListEnd::Left, self.count)
+    }
+}
+
+impl CommandExecutor for RPop {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        pop(backend, self.key, ListEnd::Right, self.count)
+    }
+}
+
+// 没有 count 时回复单个元素，有 count 时回复数组，key 不存在时回复对应的 null
+fn pop(backend: &crate::Backend, key: String, end: ListEnd, count: Option<usize>) -> RespFrame {
+    match (backend.lmpop(&[key], end, count.unwrap_or(1)), count) {
+        (Ok(Some((_, values))), None) => values
+            .into_iter()
+            .next()
+            .unwrap_or(RespFrame::Null(crate::RespNull)),
+        (Ok(Some((_, values))), Some(_)) => RespArray::new(values).into(),
+        (Ok(None), None) => RespFrame::Null(crate::RespNull),
+        (Ok(None), Some(_)) => RespArray::null().into(),
+        (Err(e), _) => e.into(),
+    }
+}
+
+impl CommandExecutor for LRem {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.lrem(&self.key, self.count, &self.element) {
+            Ok(removed) => RespFrame::Integer(removed as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LTrim {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.ltrim(&self.key, self.start, self.stop) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for BLPop {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.blpop(&self.keys, block_timeout(self.timeout)) {
+            Some((key, value)) => RespArray::new(vec![BulkString::from(key).into(), value]).into(),
+            None => RespArray::null().into(),
+        }
+    }
+}
+
+impl CommandExecutor for BRPop {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.brpop(&self.keys, block_timeout(self.timeout)) {
+            Some((key, value)) => RespArray::new(vec![BulkString::from(key).into(), value]).into(),
+            None => RespArray::null().into(),
+        }
+    }
+}
+
+impl CommandExecutor for LMPop {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let end = if self.from_left {
+            ListEnd::Left
+        } else {
+            ListEnd::Right
+        };
+        match backend.lmpop(&self.keys, end, self.count.unwrap_or(1)) {
+            Ok(Some((key, values))) => RespArray::new(vec![
+                BulkString::from(key).into(),
+                RespArray::new(values).into(),
+            ])
+            .into(),
+            Ok(None) => RespArray::null().into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for BLMove {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let ret = backend.blmove(
+            &self.source,
+            &self.destination,
+            self.wherefrom,
+            self.whereto,
+            block_timeout(self.timeout),
+        );
+        match ret {
+            Ok(Some(value)) => value,
+            Ok(None) => RespFrame::Null(crate::RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LMove {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let ret = backend.lmove(
+            &self.source,
+            &self.destination,
+            self.wherefrom,
+            self.whereto,
+        );
+        match ret {
+            Ok(Some(value)) => value,
+            Ok(None) => RespFrame::Null(crate::RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LPos {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let count = self.count.unwrap_or(1);
+        let ret = backend.lpos(&self.key, &self.element, self.rank, count, self.maxlen);
+        match (ret, self.count) {
+            (Ok(indices), Some(_)) => RespArray::new(
+                indices
+                    .into_iter()
+                    .map(|i| RespFrame::Integer(i as i64))
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            (Ok(indices), None) => match indices.first() {
+                Some(i) => RespFrame::Integer(*i as i64),
+                None => RespFrame::Null(crate::RespNull),
+            },
+            (Err(e), _) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LPush {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["lpush"], 2)?;
+
+        let (key, values) = extract_push_args(value)?;
+        Ok(LPush { key, values })
+    }
+}
+
+impl TryFrom<RespArray> for RPush {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["rpush"], 2)?;
+
+        let (key, values) = extract_push_args(value)?;
+        Ok(RPush { key, values })
+    }
+}
+
+impl TryFrom<RespArray> for LPop {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = extract_pop_args(value, "lpop")?;
+        Ok(LPop { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for RPop {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = extract_pop_args(value, "rpop")?;
+        Ok(RPop { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for LRem {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lrem"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (
+                Some(RespFrame::BulkString(key)),
+                Some(count),
+                Some(RespFrame::BulkString(element)),
+            ) => Ok(LRem {
+                key: String::from_utf8(key.get_data()?)?,
+                count: parse_integer(&count)?,
+                element: element.get_data()?,
+            }),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid key or element".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LTrim {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["ltrim"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(start), Some(stop)) => Ok(LTrim {
+                key: String::from_utf8(key.get_data()?)?,
+                start: parse_integer(&start)?,
+                stop: parse_integer(&stop)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for BLPop {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["blpop"], 2)?;
+
+        let (keys, timeout) = extract_keys_and_timeout(value)?;
+        Ok(BLPop { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for BRPop {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["brpop"], 2)?;
+
+        let (keys, timeout) = extract_keys_and_timeout(value)?;
+        Ok(BRPop { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for LMPop {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, from_left, count) = extract_mpop_args(value, "lmpop", ["left", "right"])?;
+        Ok(LMPop {
+            keys,
+            from_left,
+            count,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for BLMove {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["blmove"], 5)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut key = || match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(String::from_utf8(key.get_data()?)?),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let (source, destination) = (key()?, key()?);
+        let (wherefrom, whereto) = (parse_list_end(args.next())?, parse_list_end(args.next())?);
+        let timeout = parse_timeout(args.next())?;
+        Ok(BLMove {
+            source,
+            destination,
+            wherefrom,
+            whereto,
+            timeout,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for LMove {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lmove"], 4)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut key = || match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(String::from_utf8(key.get_data()?)?),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let (source, destination) = (key()?, key()?);
+        let (wherefrom, whereto) = (parse_list_end(args.next())?, parse_list_end(args.next())?);
+        Ok(LMove {
+            source,
+            destination,
+            wherefrom,
+            whereto,
+        })
+    }
+}
+
+fn extract_keys_and_timeout(value: RespArray) -> Result<(Vec<String>, f64), CommandError> {
+    let mut args = extract_args(value, 1)?;
+    let timeout = parse_timeout(args.pop())?;
+
+    let mut keys = Vec::with_capacity(args.len());
+    for arg in args {
+        match arg {
+            RespFrame::BulkString(key) => keys.push(String::from_utf8(key.get_data()?)?),
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+    Ok((keys, timeout))
+}
+
+fn parse_timeout(frame: Option<RespFrame>) -> Result<f64, CommandError> {
+    let timeout = match frame {
+        Some(RespFrame::BulkString(timeout)) => String::from_utf8(timeout.get_data()?)?
+            .parse::<f64>()
+            .map_err(|_| {
+                CommandError::InvalidArgument("timeout is not a float or out of range".into())
+            })?,
+        _ => return Err(CommandError::InvalidArgument("Invalid timeout".to_string())),
+    };
+    if !timeout.is_finite() || timeout < 0.0 {
+        return Err(CommandError::InvalidArgument(
+            "timeout is negative".to_string(),
+        ));
+    }
+    // 超时时刻必须能用 Instant 表示
+    let duration =
+        Duration::try_from_secs_f64(timeout).map_err(|_| CommandError::TimeoutOutOfRange)?;
+    if Instant::now().checked_add(duration).is_none() {
+        return Err(CommandError::TimeoutOutOfRange);
+    }
+    Ok(timeout)
+}
+
+fn parse_list_end(frame: Option<RespFrame>) -> Result<ListEnd, CommandError> {
+    match frame {
+        Some(RespFrame::BulkString(end)) if end.eq_ignore_ascii_case(b"left") => Ok(ListEnd::Left),
+        Some(RespFrame::BulkString(end)) if end.eq_ignore_ascii_case(b"right") => {
+            Ok(ListEnd::Right)
+        }
+        _ => Err(CommandError::InvalidArgument("syntax error".to_string())),
+    }
+}
+
+// timeout 为 0 表示一直阻塞
+fn block_timeout(timeout: f64) -> Option<Duration> {
+    (timeout != 0.0)
+        .then(|| Duration::try_from_secs_f64(timeout).ok())
+        .flatten()
+}
+
+impl TryFrom<RespArray> for LPos {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["lpos"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let (key, element) = match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(element))) => {
+                (String::from_utf8(key.get_data()?)?, element.get_data()?)
+            }
+            _ => {
+                return Err(CommandError::InvalidArgument(
+                    "Invalid key or element".to_string(),
+                ))
+            }
+        };
+
+        let mut lpos = LPos {
+            key,
+            element,
+            rank: 1,
+            count: None,
+            maxlen: 0,
+        };
+        while let Some(option) = args.next() {
+            let (RespFrame::BulkString(option), Some(arg)) = (option, args.next()) else {
+                return Err(CommandError::InvalidArgument("syntax error".to_string()));
+            };
+            let n = parse_integer(&arg)?;
+            match option.to_ascii_lowercase().as_slice() {
+                b"rank" if n == 0 || n == i64::MIN => {
+                    return Err(CommandError::InvalidArgument(
+                        "RANK can't be zero: use 1 to start from the first match, 2 from the second ... or use negative to start from the end of the list".to_string(),
+                    ))
+                }
+                b"rank" => lpos.rank = n,
+                b"count" | b"maxlen" if n < 0 => {
+                    return Err(CommandError::InvalidArgument(format!(
+                        "{} can't be negative",
+                        String::from_utf8_lossy(&option).to_uppercase()
+                    )))
+                }
+                // COUNT 0 表示返回所有匹配的位置
+                b"count" => lpos.count = Some(n as usize),
+                b"maxlen" => lpos.maxlen = n as usize,
+                _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+            }
+        }
+        Ok(lpos)
+    }
+}
+
+fn extract_push_args(value: RespArray) -> Result<(String, Vec<RespFrame>), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = match args.next() {
+        Some(RespFrame::BulkString(key)) => String::from_utf8(key.get_data()?)?,
+        _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+    };
+    Ok((key, args.collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Backend, BackendError, RespDecode};
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+    use std::thread;
+
+    #[test]
+    fn test_lpush_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\nlpush\r\n$4\r\nlist\r\n$1\r\na\r\n$1\r\nb\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result: LPush = frame.try_into()?;
+        assert_eq!(result.key, "list");
+        assert_eq!(
+            result.values,
+            vec![
+                RespFrame::BulkString(b"a".into()),
+                RespFrame::BulkString(b"b".into())
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blpop_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\nblpop\r\n$2\r\nl1\r\n$2\r\nl2\r\n$3\r\n0.5\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result: BLPop = frame.try_into()?;
+        assert_eq!(result.keys, vec!["l1", "l2"]);
+        assert_eq!(result.timeout, 0.5);
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$5\r\nblpop\r\n$2\r\nl1\r\n$2\r\n-1\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(BLPop::try_from(frame).is_err());
+
+        // 无法表示的超时不会让连接 panic
+        let request = |args: &[&str]| {
+            RespArray::new(
+                args.iter()
+                    .map(|arg| BulkString::from(*arg).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+        };
+        assert!(matches!(
+            BLPop::try_from(request(&["blpop", "l", "1e300"])),
+            Err(CommandError::TimeoutOutOfRange)
+        ));
+        assert!(matches!(
+            BRPop::try_from(request(&["brpop", "l", "1e300"])),
+            Err(CommandError::TimeoutOutOfRange)
+        ));
+        assert!(matches!(
+            BLMove::try_from(request(&["blmove", "a", "b", "LEFT", "RIGHT", "1e300"])),
+            Err(CommandError::TimeoutOutOfRange)
+        ));
+        assert_eq!(
+            RespFrame::from(CommandError::TimeoutOutOfRange),
+            RespFrame::error("ERR", "timeout is out of range")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blmove_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$6\r\nblmove\r\n$3\r\nsrc\r\n$3\r\ndst\r\n$5\r\nRIGHT\r\n$4\r\nleft\r\n$1\r\n0\r\n",
+        );
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: BLMove = frame.try_into()?;
+        assert_eq!(result.source, "src");
+        assert_eq!(result.destination, "dst");
+        assert_eq!(
+            (result.wherefrom, result.whereto),
+            (ListEnd::Right, ListEnd::Left)
+        );
+        assert_eq!(result.timeout, 0.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lmove_command() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$5\r\nlmove\r\n$3\r\nsrc\r\n$3\r\ndst\r\n$4\r\nLEFT\r\n$5\r\nright\r\n",
+        );
+        let cmd: LMove = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(
+            (cmd.wherefrom, cmd.whereto),
+            (ListEnd::Left, ListEnd::Right)
+        );
+
+        let backend = Backend::new();
+        backend.rpush("src".to_string(), vec![b"a".into(), b"b".into()])?;
+        assert_eq!(cmd.execute(&backend), b"a".into());
+        assert_eq!(backend.rpop("dst"), Some(b"a".into()));
+
+        // source 为空时回复 null
+        let cmd = LMove {
+            source: "missing".to_string(),
+            destination: "dst".to_string(),
+            wherefrom: ListEnd::Left,
+            whereto: ListEnd::Left,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(crate::RespNull));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lpush_rpush_commands() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = LPush {
+            key: "list".to_string(),
+            values: vec![b"a".into(), b"b".into()],
+        };
+        let result = cmd.execute(&backend);
+        assert_eq!(result, RespFrame::Integer(2));
+
+        let cmd = RPush {
+            key: "list".to_string(),
+            values: vec![b"c".into()],
+        };
+        let result = cmd.execute(&backend);
+        assert_eq!(result, RespFrame::Integer(3));
+
+        let list: Vec<RespFrame> = backend.list.get("list").unwrap().iter().cloned().collect();
+        assert_eq!(list, vec![b"b".into(), b"a".into(), b"c".into()]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_value_push_returns_final_length() {
+        let backend = Backend::new();
+        // 一次推入多个值时返回全部推入之后的长度
+        let cmd = LPush {
+            key: "l1".to_string(),
+            values: vec![b"a".into(), b"b".into(), b"c".into()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+
+        let cmd = RPush {
+            key: "l2".to_string(),
+            values: vec![b"a".into(), b"b".into(), b"c".into()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+    }
+
+    #[test]
+    fn test_blpop_pops_from_first_non_empty_list() -> Result<()> {
+        let backend = Backend::new();
+        backend.rpush("l2".to_string(), vec![b"x".into()]).unwrap();
+
+        let cmd = BLPop {
+            keys: vec!["l1".to_string(), "l2".to_string()],
+            timeout: 1.0,
+        };
+        let result = cmd.execute(&backend);
+        let expected = RespArray::new(vec![BulkString::from("l2").into(), b"x".into()]);
+        assert_eq!(result, expected.into());
+        assert!(!backend.list.contains_key("l2"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blpop_timeout() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = BLPop {
+            keys: vec!["list".to_string()],
+            timeout: 0.05,
+        };
+        let result = cmd.execute(&backend);
+        assert_eq!(result, RespArray::null().into());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blpop_unblocked_by_lpush() -> Result<()> {
+        let backend = Backend::new();
+
+        let cloned = backend.clone();
+        let handle = thread::spawn(move || {
+            let cmd = BLPop {
+                keys: vec!["list".to_string()],
+                timeout: 0.0,
+            };
+            cmd.execute(&cloned)
+        });
+
+        thread::sleep(Duration::from_millis(50));
+        let cmd = LPush {
+            key: "list".to_string(),
+            values: vec![b"hello".into()],
+        };
+        cmd.execute(&backend);
+
+        let result = handle.join().unwrap();
+        let expected = RespArray::new(vec![BulkString::from("list").into(), b"hello".into()]);
+        assert_eq!(result, expected.into());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lpush_wrong_type() -> Result<()> {
+        let backend = Backend::new();
+        backend.sadd("set".to_string(), "member".to_string())?;
+
+        let cmd = LPush {
+            key: "set".to_string(),
+            values: vec![b"a".into()],
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::WrongType.into());
+        assert!(!backend.list.contains_key("set"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lpos_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*7\r\n$4\r\nlpos\r\n$4\r\nlist\r\n$1\r\na\r\n$4\r\nRANK\r\n$2\r\n-1\r\n$5\r\nCOUNT\r\n$1\r\n0\r\n",
+        );
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: LPos = frame.try_into()?;
+        assert_eq!(result.key, "list");
+        assert_eq!(result.element, b"a");
+        assert_eq!(result.rank, -1);
+        assert_eq!(result.count, Some(0));
+
+        buf.extend_from_slice(
+            b"*5\r\n$4\r\nlpos\r\n$4\r\nlist\r\n$1\r\na\r\n$4\r\nRANK\r\n$1\r\n0\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(LPos::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lpos_count_zero_returns_all() -> Result<()> {
+        let backend = Backend::new();
+        let values = ["a", "b", "a", "a", "c", "a", "a"];
+        backend.rpush(
+            "list".to_string(),
+            values.iter().map(|v| v.as_bytes().into()).collect(),
+        )?;
+        let lpos = |rank, count| {
+            LPos {
+                key: "list".to_string(),
+                element: b"a".to_vec(),
+                rank,
+                count,
+                maxlen: 0,
+            }
+            .execute(&backend)
+        };
+        let indices = |v: &[i64]| {
+            RespFrame::from(RespArray::new(
+                v.iter().map(|i| RespFrame::Integer(*i)).collect::<Vec<_>>(),
+            ))
+        };
+
+        assert_eq!(lpos(1, Some(0)), indices(&[0, 2, 3, 5, 6]));
+        // 从尾部开始，跳过第一个匹配
+        assert_eq!(lpos(-2, Some(0)), indices(&[5, 3, 2, 0]));
+        assert_eq!(lpos(-1, Some(2)), indices(&[6, 5]));
+        assert_eq!(lpos(2, None), RespFrame::Integer(2));
+        assert_eq!(lpos(10, None), RespFrame::Null(crate::RespNull));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lmpop() -> Result<()> {
+        let backend = Backend::new();
+        let lmpop = |args: &str| -> Result<RespFrame> {
+            let args = std::iter::once("lmpop")
+                .chain(args.split(' '))
+                .map(|arg| BulkString::from(arg).into())
+                .collect::<Vec<RespFrame>>();
+            Ok(LMPop::try_from(RespArray::new(args))?.execute(&backend))
+        };
+        backend.rpush(
+            "l2".to_string(),
+            vec![b"a".into(), b"b".into(), b"c".into()],
+        )?;
+
+        // 第一个 key 为空，从第二个 key 弹出
+        assert_eq!(
+            lmpop("2 l1 l2 LEFT")?,
+            RespArray::new(vec![
+                BulkString::from("l2").into(),
+                RespArray::new(vec![b"a".into()]).into()
+            ])
+            .into()
+        );
+        assert_eq!(
+            lmpop("2 l1 l2 RIGHT COUNT 10")?,
+            RespArray::new(vec![
+                BulkString::from("l2").into(),
+                RespArray::new(vec![b"c".into(), b"b".into()]).into()
+            ])
+            .into()
+        );
+        assert_eq!(backend.key_type("l2"), None);
+        assert_eq!(lmpop("2 l1 l2 LEFT")?, RespArray::null().into());
+
+        backend.set("s".to_string(), b"v".into());
+        assert_eq!(lmpop("1 s LEFT")?, BackendError::WrongType.into());
+        assert!(lmpop("0 l1 LEFT").is_err());
+        assert!(lmpop("1 l1 UP").is_err());
+        assert!(lmpop("1 l1 LEFT COUNT 0").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pop_rem_trim_remove_empty_list() -> Result<()> {
+        let backend = Backend::new();
+        let run = |cmd: &str| -> Result<RespFrame> {
+            let args = cmd
+                .split(' ')
+                .map(|arg| BulkString::from(arg).into())
+                .collect::<Vec<RespFrame>>();
+            let cmd = crate::cmd::Command::try_from(RespArray::new(args))?;
+            Ok(cmd.execute(&backend))
+        };
+        let exists = |key: &str| run(&format!("exists {key}"));
+
+        backend.set("s".to_string(), b"v".into());
+        for cmd in [
+            "lpush s a",
+            "rpush s a",
+            "lpop s",
+            "rpop s 2",
+            "lrem s 0 a",
+            "ltrim s 0 -1",
+        ] {
+            assert_eq!(run(cmd)?, BackendError::WrongType.into(), "{cmd}");
+        }
+        assert_eq!(run("get s")?, b"v".into());
+
+        run("rpush l a b")?;
+        assert_eq!(run("lpop l")?, b"a".into());
+        assert_eq!(run("rpop l")?, b"b".into());
+        assert_eq!(exists("l")?, RespFrame::Integer(0));
+        assert_eq!(run("lpop l")?, RespFrame::Null(crate::RespNull));
+        assert_eq!(run("rpop l 1")?, RespArray::null().into());
+
+        run("rpush l a b c")?;
+        assert_eq!(
+            run("rpop l 5")?,
+            RespArray::new(vec![b"c".into(), b"b".into(), b"a".into()]).into()
+        );
+        assert_eq!(exists("l")?, RespFrame::Integer(0));
+
+        run("rpush l a b a c a")?;
+        assert_eq!(run("lrem l -1 a")?, RespFrame::Integer(1));
+        assert_eq!(
+            run("lpop l 10")?,
+            RespArray::new(vec![b"a".into(), b"b".into(), b"a".into(), b"c".into()]).into()
+        );
+        run("rpush l a a")?;
+        assert_eq!(run("lrem l 0 a")?, RespFrame::Integer(2));
+        assert_eq!(exists("l")?, RespFrame::Integer(0));
+
+        run("rpush l a b c")?;
+        assert_eq!(run("ltrim l 1 -1")?, RESP_OK.clone());
+        assert_eq!(run("lpos l c")?, RespFrame::Integer(1));
+        assert_eq!(run("ltrim l 5 10")?, RESP_OK.clone());
+        assert_eq!(exists("l")?, RespFrame::Integer(0));
+        Ok(())
+    }
+}
@@ -1,24 +1,299 @@
-use crate::cmd::{extract_args, validate_command, CommandError, Get, Set};
-use crate::{RespArray, RespFrame, RespNull};
+use crate::cmd::{
+    extract_args, parse_getex_expiry, parse_integer, validate_command, CommandError, Get, GetDel,
+    GetEx, GetExExpiry, GetSet, Set,
+};
+use crate::{
+    BackendError, BulkString, ClientFlags, ConnectionContext, Lookup, RespArray, RespFrame,
+    RespMap, RespNull,
+};
 
-use super::{CommandExecutor, RESP_OK};
+use super::{
+    validate_command_at_least, Append, CommandExecutor, GetBit, GetRange, Incr, IncrBy, Lcs,
+    SetBit, SetRange, StrLen, Substr, RESP_OK,
+};
 
 impl CommandExecutor for Get {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        self.apply(backend, &ClientFlags::default())
+    }
+
+    fn execute_with(self, backend: &crate::Backend, ctx: &ConnectionContext) -> RespFrame {
+        self.apply(backend, &ctx.flags)
+    }
+}
+
+impl Get {
+    /// Read the key, leaving its access time alone for NO-TOUCH connections.
+    pub fn apply(self, backend: &crate::Backend, flags: &ClientFlags) -> RespFrame {
+        let value = if flags.no_touch {
+            backend.get_no_touch(&self.key)
+        } else {
+            backend.get(&self.key)
+        };
+        match value {
+            Lookup::Found(value) => value,
+            Lookup::Missing => RespFrame::Null(RespNull),
+            Lookup::WrongType => BackendError::WrongType.into(),
+        }
+    }
+}
+
+impl CommandExecutor for GetDel {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.getdel(&self.key) {
+            Lookup::Found(value) => value,
+            Lookup::Missing => RespFrame::Null(RespNull),
+            Lookup::WrongType => BackendError::WrongType.into(),
+        }
+    }
+}
+
+impl CommandExecutor for StrLen {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.strlen(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for GetEx {
     fn execute(self, backend: &crate::Backend) -> RespFrame {
         match backend.get(&self.key) {
-            Some(value) => value,
-            None => RespFrame::Null(RespNull),
+            Lookup::Found(value) => {
+                let ret = match self.expiry {
+                    Some(GetExExpiry::Ttl(ttl)) => backend.expire(&self.key, ttl),
+                    Some(GetExExpiry::At(at)) => backend.expire_at(&self.key, at),
+                    Some(GetExExpiry::Persist) => Ok(backend.persist(&self.key)),
+                    None => Ok(false),
+                };
+                match ret {
+                    Ok(_) => value,
+                    Err(BackendError::InvalidExpireTime) => {
+                        CommandError::InvalidExpireTime("getex".to_string()).into()
+                    }
+                    Err(e) => e.into(),
+                }
+            }
+            Lookup::Missing => RespFrame::Null(RespNull),
+            Lookup::WrongType => BackendError::WrongType.into(),
         }
     }
 }
 
 impl CommandExecutor for Set {
     fn execute(self, backend: &crate::Backend) -> RespFrame {
-        backend.set(self.key, self.value.clone());
+        if self.get {
+            return match backend.getset_with(self.key, self.value, self.keepttl) {
+                Lookup::Found(value) => value,
+                Lookup::Missing => RespFrame::Null(RespNull),
+                Lookup::WrongType => BackendError::WrongType.into(),
+            };
+        }
+        backend.set_with(self.key, self.value, self.keepttl);
         RESP_OK.clone()
     }
 }
 
+impl CommandExecutor for GetSet {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.getset(self.key, self.value) {
+            Lookup::Found(value) => value,
+            Lookup::Missing => RespFrame::Null(RespNull),
+            Lookup::WrongType => BackendError::WrongType.into(),
+        }
+    }
+}
+
+impl CommandExecutor for GetRange {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.getrange(&self.key, self.start, self.end) {
+            Ok(value) => BulkString::new(value).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for Substr {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        self.0.execute(backend)
+    }
+}
+
+impl CommandExecutor for SetRange {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.setrange(self.key, self.offset, &self.value) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SetBit {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.setbit(self.key, self.offset, self.on) {
+            Ok(bit) => RespFrame::Integer(bit as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for GetBit {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.getbit(&self.key, self.offset) {
+            Ok(bit) => RespFrame::Integer(bit as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for Append {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.append(self.key, &self.value) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for Incr {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        IncrBy {
+            key: self.key,
+            increment: 1,
+        }
+        .execute(backend)
+    }
+}
+
+impl CommandExecutor for IncrBy {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.incr_by(self.key, self.increment) {
+            Ok(value) => RespFrame::Integer(value),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for Lcs {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let (a, b) = match (backend.get(&self.key1), backend.get(&self.key2)) {
+            (Lookup::WrongType, _) | (_, Lookup::WrongType) => {
+                return BackendError::WrongType.into()
+            }
+            (a, b) => match (string_bytes(a.found()), string_bytes(b.found())) {
+                (Ok(a), Ok(b)) => (a, b),
+                (Err(e), _) | (_, Err(e)) => return e.into(),
+            },
+        };
+        // 和 Redis 一样，DP 表占用的临时内存不能超过 proto-max-bulk-len
+        let cells = (a.len() + 1).checked_mul(b.len() + 1);
+        let limit = backend.config().proto_max_bulk_len() / std::mem::size_of::<usize>();
+        if cells.is_none_or(|cells| cells > limit) {
+            return RespFrame::error(
+                "ERR",
+                "Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len",
+            );
+        }
+        let table = lcs_table(&a, &b);
+        let len = table[a.len()][b.len()];
+
+        if self.len_only {
+            return RespFrame::Integer(len as i64);
+        }
+
+        // 从两个字符串的末尾开始回溯，得到公共子序列以及每一段连续匹配的区间
+        let (mut i, mut j) = (a.len(), b.len());
+        let mut result = Vec::with_capacity(len);
+        let mut matches = Vec::new();
+        let mut range: Option<((usize, usize), (usize, usize))> = None;
+        while i > 0 && j > 0 {
+            if a[i - 1] == b[j - 1] {
+                result.push(a[i - 1]);
+                range = match range {
+                    Some(((a_start, a_end), (b_start, b_end))) if a_start == i && b_start == j => {
+                        Some(((i - 1, a_end), (j - 1, b_end)))
+                    }
+                    Some(r) => {
+                        matches.push(r);
+                        Some(((i - 1, i - 1), (j - 1, j - 1)))
+                    }
+                    None => Some(((i - 1, i - 1), (j - 1, j - 1))),
+                };
+                i -= 1;
+                j -= 1;
+            } else {
+                if let Some(r) = range.take() {
+                    matches.push(r);
+                }
+                if table[i - 1][j] > table[i][j - 1] {
+                    i -= 1;
+                } else {
+                    j -= 1;
+                }
+            }
+        }
+        if let Some(r) = range {
+            matches.push(r);
+        }
+
+        if self.idx {
+            let matches = matches
+                .into_iter()
+                .map(|((a_start, a_end), (b_start, b_end))| {
+                    RespArray::new(vec![
+                        RespArray::new(vec![
+                            RespFrame::Integer(a_start as i64),
+                            RespFrame::Integer(a_end as i64),
+                        ])
+                        .into(),
+                        RespArray::new(vec![
+                            RespFrame::Integer(b_start as i64),
+                            RespFrame::Integer(b_end as i64),
+                        ])
+                        .into(),
+                    ])
+                    .into()
+                })
+                .collect::<Vec<RespFrame>>();
+            let mut map = RespMap::new();
+            map.insert("matches".to_string(), RespArray::new(matches).into());
+            map.insert("len".to_string(), RespFrame::Integer(len as i64));
+            return map.into();
+        }
+
+        result.reverse();
+        BulkString::new(result).into()
+    }
+}
+
+// 取出字符串类型的值，不存在的 key 视为空字符串
+// 不存在的 key 视为空字符串，其他类型的值返回 WRONGTYPE
+fn string_bytes(value: Option<RespFrame>) -> Result<Vec<u8>, BackendError> {
+    match value {
+        None => Ok(Vec::new()),
+        Some(RespFrame::BulkString(s)) => Ok(s.0.unwrap_or_default()),
+        Some(RespFrame::SimpleString(s)) => Ok(s.0.into_bytes()),
+        Some(RespFrame::Integer(n)) => Ok(n.to_string().into_bytes()),
+        Some(_) => Err(BackendError::WrongType),
+    }
+}
+
+// table[i][j] 为 a[..i] 与 b[..j] 的最长公共子序列长度
+fn lcs_table(a: &[u8], b: &[u8]) -> Vec<Vec<usize>> {
+    let mut table = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            table[i][j] = if a[i - 1] == b[j - 1] {
+                table[i - 1][j - 1] + 1
+            } else {
+                table[i - 1][j].max(table[i][j - 1])
+            };
+        }
+    }
+    table
+}
+
 impl TryFrom<RespArray> for Get {
     type Error = CommandError;
 
@@ -35,15 +310,107 @@ impl TryFrom<RespArray> for Get {
     }
 }
 
+impl TryFrom<RespArray> for GetDel {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["getdel"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(GetDel {
+                key: String::from_utf8(key.get_data()?)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for StrLen {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["strlen"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(StrLen {
+                key: String::from_utf8(key.get_data()?)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for GetEx {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["getex"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.get_data()?)?,
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let expiry = match args.next() {
+            Some(option) => Some(parse_getex_expiry(option, &mut args, "getex")?),
+            None => None,
+        };
+        // 只能指定一个过期选项
+        if args.next().is_some() {
+            return Err(CommandError::InvalidArgument("syntax error".to_string()));
+        }
+        Ok(GetEx { key, expiry })
+    }
+}
+
 impl TryFrom<RespArray> for Set {
     type Error = CommandError;
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["set"], 2)?;
+        validate_command_at_least(&value, &["set"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let (key, value) = match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(value)) => {
+                (String::from_utf8(key.get_data()?)?, value)
+            }
+            _ => {
+                return Err(CommandError::InvalidArgument(
+                    "Invalid key or value".to_string(),
+                ))
+            }
+        };
+        // 目前只支持 GET 和 KEEPTTL 选项
+        let (mut get, mut keepttl) = (false, false);
+        for option in args {
+            match option {
+                RespFrame::BulkString(option) if option.eq_ignore_ascii_case(b"get") => get = true,
+                RespFrame::BulkString(option) if option.eq_ignore_ascii_case(b"keepttl") => {
+                    keepttl = true
+                }
+                _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+            }
+        }
+        Ok(Set {
+            key,
+            value,
+            get,
+            keepttl,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for GetSet {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["getset"], 2)?;
 
         let mut args = extract_args(value, 1)?.into_iter();
         match (args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(value)) => Ok(Set {
+            (Some(RespFrame::BulkString(key)), Some(value)) => Ok(GetSet {
                 key: String::from_utf8(key.get_data()?)?,
                 value,
             }),
@@ -54,13 +421,243 @@ impl TryFrom<RespArray> for Set {
     }
 }
 
+impl TryFrom<RespArray> for GetRange {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        parse_getrange(value, "getrange")
+    }
+}
+
+// SUBSTR 是 GETRANGE 已废弃的别名，参数完全相同
+impl TryFrom<RespArray> for Substr {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Substr(parse_getrange(value, "substr")?))
+    }
+}
+
+fn parse_getrange(value: RespArray, name: &'static str) -> Result<GetRange, CommandError> {
+    validate_command(&value, &[name], 3)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    match (args.next(), args.next(), args.next()) {
+        (Some(RespFrame::BulkString(key)), Some(start), Some(end)) => Ok(GetRange {
+            key: String::from_utf8(key.get_data()?)?,
+            start: parse_integer(&start)?,
+            end: parse_integer(&end)?,
+        }),
+        _ => Err(CommandError::InvalidArgument(
+            "Invalid key or range".to_string(),
+        )),
+    }
+}
+
+impl TryFrom<RespArray> for Incr {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["incr"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(Incr {
+                key: String::from_utf8(key.get_data()?)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for IncrBy {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["incrby"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(increment)) => Ok(IncrBy {
+                key: String::from_utf8(key.get_data()?)?,
+                increment: parse_integer(&increment)?,
+            }),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid key or increment".to_string(),
+            )),
+        }
+    }
+}
+
+// 和 Redis 一样限制字符串最大 512MB
+const MAX_STRING_SIZE: usize = 512 * 1024 * 1024;
+
+impl TryFrom<RespArray> for SetRange {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["setrange"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (
+                Some(RespFrame::BulkString(key)),
+                Some(offset),
+                Some(RespFrame::BulkString(value)),
+            ) => {
+                let value = value.get_data()?;
+                let offset = parse_integer(&offset)?;
+                // 和 Redis 一样，空值什么都不写，不检查 offset 是否超过字符串上限
+                if offset < 0
+                    || (!value.is_empty() && offset as usize + value.len() > MAX_STRING_SIZE)
+                {
+                    return Err(CommandError::InvalidArgument(
+                        "offset is out of range".to_string(),
+                    ));
+                }
+                Ok(SetRange {
+                    key: String::from_utf8(key.get_data()?)?,
+                    offset: offset as usize,
+                    value,
+                })
+            }
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid key or value".to_string(),
+            )),
+        }
+    }
+}
+
+// 和 Redis 一样，bit offset 不能超过 512MB 字符串的位数
+fn parse_bit_offset(frame: &RespFrame) -> Result<usize, CommandError> {
+    match parse_integer(frame) {
+        Ok(offset) if offset >= 0 && (offset as usize) < MAX_STRING_SIZE * 8 => Ok(offset as usize),
+        _ => Err(CommandError::InvalidArgument(
+            "bit offset is not an integer or out of range".to_string(),
+        )),
+    }
+}
+
+impl TryFrom<RespArray> for SetBit {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["setbit"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(offset), Some(bit)) => {
+                let offset = parse_bit_offset(&offset)?;
+                let on = match parse_integer(&bit) {
+                    Ok(0) => false,
+                    Ok(1) => true,
+                    _ => {
+                        return Err(CommandError::InvalidArgument(
+                            "bit is not an integer or out of range".to_string(),
+                        ))
+                    }
+                };
+                Ok(SetBit {
+                    key: String::from_utf8(key.get_data()?)?,
+                    offset,
+                    on,
+                })
+            }
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid key or value".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for GetBit {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["getbit"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(offset)) => Ok(GetBit {
+                key: String::from_utf8(key.get_data()?)?,
+                offset: parse_bit_offset(&offset)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Append {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["append"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(value))) => Ok(Append {
+                key: String::from_utf8(key.get_data()?)?,
+                value: value.get_data()?,
+            }),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid key or value".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Lcs {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["lcs"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let (key1, key2) = match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key1)), Some(RespFrame::BulkString(key2))) => (
+                String::from_utf8(key1.get_data()?)?,
+                String::from_utf8(key2.get_data()?)?,
+            ),
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+
+        let (mut len_only, mut idx) = (false, false);
+        for arg in args {
+            match arg {
+                RespFrame::BulkString(opt) if opt.eq_ignore_ascii_case(b"len") => len_only = true,
+                RespFrame::BulkString(opt) if opt.eq_ignore_ascii_case(b"idx") => idx = true,
+                _ => {
+                    return Err(CommandError::InvalidArgument(
+                        "Invalid lcs option".to_string(),
+                    ))
+                }
+            }
+        }
+        if len_only && idx {
+            return Err(CommandError::InvalidArgument(
+                "If you want both the length and indexes, please just use IDX.".to_string(),
+            ));
+        }
+
+        Ok(Lcs {
+            key1,
+            key2,
+            len_only,
+            idx,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::time::SystemTime;
+
     use anyhow::Result;
     use bytes::BytesMut;
 
     use super::*;
-    use crate::{Backend, RespDecode};
+    use crate::cmd::Command;
+    use crate::{Backend, RespDecode, RespEncode};
 
     #[test]
     fn test_get_from_resp_array() -> Result<()> {
@@ -95,6 +692,8 @@ mod tests {
         let cmd = Set {
             key: "hello".to_string(),
             value: RespFrame::BulkString(b"world".into()),
+            get: false,
+            keepttl: false,
         };
         let result = cmd.execute(&backend);
         assert_eq!(result, RESP_OK.clone());
@@ -107,4 +706,801 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_set_empty_value_is_present() -> Result<()> {
+        let backend = Backend::new();
+        let run = |raw: &[u8]| -> Result<RespFrame> {
+            let mut buf = BytesMut::from(raw);
+            let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+            Ok(cmd.execute(&backend))
+        };
+
+        // $0 是空字符串，和 $-1 的 null 不同
+        assert_eq!(
+            run(b"*3\r\n$3\r\nset\r\n$3\r\nkey\r\n$0\r\n\r\n")?,
+            RESP_OK.clone()
+        );
+        assert_eq!(
+            run(b"*2\r\n$6\r\nexists\r\n$3\r\nkey\r\n")?,
+            RespFrame::Integer(1)
+        );
+        assert_eq!(
+            run(b"*2\r\n$6\r\nstrlen\r\n$3\r\nkey\r\n")?,
+            RespFrame::Integer(0)
+        );
+        let value = run(b"*2\r\n$3\r\nget\r\n$3\r\nkey\r\n")?;
+        assert_eq!(value, BulkString::new(Vec::new()).into());
+        assert_ne!(value, BulkString(None).into());
+        assert_eq!(value.encode(), b"$0\r\n\r\n");
+
+        assert_eq!(
+            run(b"*2\r\n$6\r\nstrlen\r\n$7\r\nmissing\r\n")?,
+            RespFrame::Integer(0)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_missing_vs_wrong_type() -> Result<()> {
+        let backend = Backend::new();
+        backend
+            .hset("map".to_string(), "field".to_string(), b"value".into())
+            .unwrap();
+
+        let cmd = Get {
+            key: "missing".to_string(),
+        };
+        assert_eq!(backend.get("missing"), Lookup::Missing);
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+
+        let cmd = Get {
+            key: "map".to_string(),
+        };
+        assert_eq!(backend.get("map"), Lookup::WrongType);
+        assert_eq!(cmd.execute(&backend), BackendError::WrongType.into());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_keepttl() -> Result<()> {
+        use crate::ManualClock;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let clock = Arc::new(ManualClock::new());
+        let backend = Backend::with_clock(clock.clone());
+        let set = |args: &[&str]| -> Result<RespFrame> {
+            let frame = RespArray::new(
+                std::iter::once("set")
+                    .chain(args.iter().copied())
+                    .map(|arg| BulkString::from(arg).into())
+                    .collect::<Vec<RespFrame>>(),
+            );
+            Ok(Set::try_from(frame)?.execute(&backend))
+        };
+
+        set(&["k", "v1"])?;
+        backend.expire("k", Duration::from_secs(100)).unwrap();
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(set(&["k", "v2", "KEEPTTL"])?, RESP_OK.clone());
+        assert_eq!(backend.ttl("k"), 90);
+        assert_eq!(
+            set(&["k", "v3", "keepttl", "GET"])?,
+            BulkString::from("v2").into()
+        );
+        assert_eq!(backend.ttl("k"), 90);
+
+        // 不带 KEEPTTL 的 SET 清除 TTL
+        assert_eq!(set(&["k", "v4"])?, RESP_OK.clone());
+        assert_eq!(backend.ttl("k"), -1);
+        assert_eq!(
+            backend.get("k"),
+            Lookup::Found(BulkString::from("v4").into())
+        );
+
+        // 已经过期的 key 不会把 TTL 留给新值
+        backend.expire("k", Duration::from_secs(5)).unwrap();
+        clock.advance(Duration::from_secs(5));
+        set(&["k", "v5", "KEEPTTL"])?;
+        assert_eq!(backend.ttl("k"), -1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_get_on_wrong_type_keeps_value() -> Result<()> {
+        let backend = Backend::new();
+        backend
+            .hset("map".to_string(), "field".to_string(), b"value".into())
+            .unwrap();
+        let command = |args: &[&str]| -> Result<RespFrame> {
+            let frame = RespArray::new(
+                args.iter()
+                    .map(|arg| BulkString::from(*arg).into())
+                    .collect::<Vec<RespFrame>>(),
+            );
+            Ok(match args[0] {
+                "set" => Set::try_from(frame)?.execute(&backend),
+                _ => GetSet::try_from(frame)?.execute(&backend),
+            })
+        };
+
+        // 不覆盖 hash，直接返回 WRONGTYPE
+        assert_eq!(
+            command(&["set", "map", "v", "GET"])?,
+            BackendError::WrongType.into()
+        );
+        assert_eq!(
+            command(&["getset", "map", "v"])?,
+            BackendError::WrongType.into()
+        );
+        assert_eq!(backend.key_type("map"), Some("hash"));
+        assert_eq!(backend.hget("map", "field").unwrap(), b"value".into());
+
+        assert_eq!(
+            command(&["set", "s", "1", "get"])?,
+            RespFrame::Null(RespNull)
+        );
+        assert_eq!(
+            command(&["getset", "s", "2"])?,
+            BulkString::from("1").into()
+        );
+        assert_eq!(
+            command(&["set", "s", "3", "GET"])?,
+            BulkString::from("2").into()
+        );
+        assert!(command(&["set", "s", "4", "NX"]).is_err());
+
+        // 不带 GET 的 SET 仍然覆盖其他类型
+        assert_eq!(command(&["set", "map", "v"])?, RESP_OK.clone());
+        assert_eq!(backend.key_type("map"), Some("string"));
+        assert!(!backend.hmap.contains_key("map"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lcs_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$3\r\nlcs\r\n$4\r\nkey1\r\n$4\r\nkey2\r\n$3\r\nLEN\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result: Lcs = frame.try_into()?;
+        assert_eq!(result.key1, "key1");
+        assert_eq!(result.key2, "key2");
+        assert!(result.len_only);
+        assert!(!result.idx);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lcs_len_and_idx_conflict() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$3\r\nlcs\r\n$4\r\nkey1\r\n$4\r\nkey2\r\n$3\r\nlen\r\n$3\r\nidx\r\n",
+        );
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result = Lcs::try_from(frame);
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lcs_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("key1".to_string(), BulkString::from("ohmytext").into());
+        backend.set("key2".to_string(), BulkString::from("mynewtext").into());
+
+        let cmd = Lcs {
+            key1: "key1".to_string(),
+            key2: "key2".to_string(),
+            len_only: false,
+            idx: false,
+        };
+        let result = cmd.execute(&backend);
+        assert_eq!(result, RespFrame::BulkString(b"mytext".into()));
+
+        let cmd = Lcs {
+            key1: "key1".to_string(),
+            key2: "key2".to_string(),
+            len_only: true,
+            idx: false,
+        };
+        let result = cmd.execute(&backend);
+        assert_eq!(result, RespFrame::Integer(6));
+
+        let cmd = Lcs {
+            key1: "key1".to_string(),
+            key2: "key2".to_string(),
+            len_only: false,
+            idx: true,
+        };
+        let result = cmd.execute(&backend);
+        let range = |a: i64, b: i64, c: i64, d: i64| -> RespFrame {
+            RespArray::new(vec![
+                RespArray::new(vec![RespFrame::Integer(a), RespFrame::Integer(b)]).into(),
+                RespArray::new(vec![RespFrame::Integer(c), RespFrame::Integer(d)]).into(),
+            ])
+            .into()
+        };
+        let mut expected = RespMap::new();
+        expected.insert(
+            "matches".to_string(),
+            RespArray::new(vec![range(4, 7, 5, 8), range(2, 3, 0, 1)]).into(),
+        );
+        expected.insert("len".to_string(), RespFrame::Integer(6));
+        assert_eq!(result, expected.into());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lcs_missing_key() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("key1".to_string(), BulkString::from("ohmytext").into());
+
+        let cmd = Lcs {
+            key1: "key1".to_string(),
+            key2: "missing".to_string(),
+            len_only: false,
+            idx: false,
+        };
+        let result = cmd.execute(&backend);
+        assert_eq!(result, RespFrame::BulkString(b"".into()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lcs_rejects_oversized_and_non_string_values() {
+        let backend = Backend::new();
+        backend.config().set_proto_max_bulk_len(1024 * 1024);
+        backend.set("a".to_string(), BulkString::new(vec![b'a'; 1024]).into());
+        backend.set("b".to_string(), BulkString::new(vec![b'b'; 1024]).into());
+        let lcs = |key1: &str, key2: &str| {
+            Lcs {
+                key1: key1.to_string(),
+                key2: key2.to_string(),
+                len_only: true,
+                idx: false,
+            }
+            .execute(&backend)
+        };
+
+        assert_eq!(
+            lcs("a", "b"),
+            RespFrame::error(
+                "ERR",
+                "Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len"
+            )
+        );
+        assert_eq!(lcs("a", "missing"), RespFrame::Integer(0));
+
+        backend.set("double".to_string(), RespFrame::Double(1.5));
+        assert_eq!(lcs("double", "missing"), BackendError::WrongType.into());
+    }
+
+    #[test]
+    fn test_getrange_setrange_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$8\r\ngetrange\r\n$3\r\nkey\r\n$1\r\n0\r\n$2\r\n-1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: GetRange = frame.try_into()?;
+        assert_eq!((result.start, result.end), (0, -1));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*4\r\n$8\r\nsetrange\r\n$3\r\nkey\r\n$1\r\n2\r\n$3\r\na\x00\xff\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: SetRange = frame.try_into()?;
+        assert_eq!(result.offset, 2);
+        assert_eq!(result.value, b"a\x00\xff");
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$8\r\nsetrange\r\n$3\r\nkey\r\n$2\r\n-1\r\n$1\r\na\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(SetRange::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_getrange_binary_safe() -> Result<()> {
+        let backend = Backend::new();
+        let value = b"\x00ab\xff\x00\xe4\xbd\xa0";
+        backend.set("key".to_string(), BulkString::new(value.to_vec()).into());
+
+        let range = |start, end| {
+            GetRange {
+                key: "key".to_string(),
+                start,
+                end,
+            }
+            .execute(&backend)
+        };
+        let bulk = |v: &[u8]| -> RespFrame { BulkString::new(v.to_vec()).into() };
+
+        assert_eq!(range(0, -1), bulk(value));
+        assert_eq!(range(0, 0), bulk(b"\x00"));
+        assert_eq!(range(3, 4), bulk(b"\xff\x00"));
+        // 按字节而不是字符截取，多字节 UTF-8 字符会被拆开
+        assert_eq!(range(-2, -1), bulk(b"\xbd\xa0"));
+        assert_eq!(range(5, 100), bulk(b"\xe4\xbd\xa0"));
+        assert_eq!(range(4, 2), bulk(b""));
+        assert_eq!(range(-100, 1), bulk(b"\x00a"));
+
+        let missing = GetRange {
+            key: "missing".to_string(),
+            start: 0,
+            end: -1,
+        };
+        assert_eq!(missing.execute(&backend), bulk(b""));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_getex_exat_sets_absolute_expiry() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("key".to_string(), BulkString::from("value").into());
+
+        let at = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)?
+            .as_secs()
+            + 100;
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\ngetex\r\n$3\r\nkey\r\n$4\r\nEXAT\r\n");
+        let at = at.to_string();
+        buf.extend_from_slice(format!("${}\r\n{}\r\n", at.len(), at).as_bytes());
+        let cmd: GetEx = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.execute(&backend), BulkString::from("value").into());
+        // EXAT 截断到秒，剩余时间在 99 到 100 秒之间
+        assert!((99..=100).contains(&backend.ttl("key")));
+
+        let cmd = GetEx {
+            key: "key".to_string(),
+            expiry: Some(GetExExpiry::Persist),
+        };
+        assert_eq!(cmd.execute(&backend), BulkString::from("value").into());
+        assert_eq!(backend.ttl("key"), -1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_getex_rejects_conflicting_options() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$5\r\ngetex\r\n$3\r\nkey\r\n$2\r\nEX\r\n$2\r\n10\r\n$7\r\nPERSIST\r\n",
+        );
+        let ret: Result<GetEx, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(matches!(ret, Err(CommandError::InvalidArgument(ref e)) if e == "syntax error"));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$5\r\ngetex\r\n$3\r\nkey\r\n$4\r\nEXAT\r\n$2\r\n10\r\n$2\r\nPX\r\n",
+        );
+        let ret: Result<GetEx, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(ret.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_getex_rejects_overflowing_expire() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("k".to_string(), b"v".into());
+        let getex = |unit: &str, time: &str| -> Result<RespFrame> {
+            let args = ["getex", "k", unit, time]
+                .into_iter()
+                .map(|arg| BulkString::from(arg).into())
+                .collect::<Vec<RespFrame>>();
+            Ok(match GetEx::try_from(RespArray::new(args)) {
+                Ok(cmd) => cmd.execute(&backend),
+                Err(e) => e.into(),
+            })
+        };
+        let invalid = RespFrame::error("ERR", "invalid expire time in 'getex' command");
+
+        let max = i64::MAX.to_string();
+        assert_eq!(getex("EX", &max)?, invalid);
+        assert_eq!(getex("EXAT", &max)?, invalid);
+        assert_eq!(getex("PX", &max)?, invalid);
+        assert_eq!(getex("EX", "0")?, invalid);
+        assert_eq!(backend.ttl("k"), -1);
+
+        assert_eq!(getex("EX", "100")?, b"v".into());
+        assert_eq!(backend.ttl("k"), 100);
+        Ok(())
+    }
+
+    #[test]
+    fn test_substr_matches_getrange() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("key".to_string(), BulkString::from("Hello World").into());
+
+        let command = |name: &str, start: &str, end: &str| -> Result<Command> {
+            let frame = RespArray::new(
+                [name, "key", start, end]
+                    .into_iter()
+                    .map(|arg| BulkString::from(arg).into())
+                    .collect::<Vec<_>>(),
+            );
+            Ok(Command::try_from(RespFrame::from(frame))?)
+        };
+        for (start, end) in [("0", "4"), ("-5", "-1"), ("3", "1"), ("0", "100")] {
+            let substr = command("substr", start, end)?;
+            assert!(matches!(substr, Command::Substr(_)));
+            assert_eq!(
+                substr.execute(&backend),
+                command("getrange", start, end)?.execute(&backend)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_setrange_binary_safe() -> Result<()> {
+        let backend = Backend::new();
+        let setrange = |key: &str, offset, value: &[u8]| {
+            SetRange {
+                key: key.to_string(),
+                offset,
+                value: value.to_vec(),
+            }
+            .execute(&backend)
+        };
+
+        // 不存在的 key 用 \x00 填充到 offset
+        assert_eq!(setrange("key", 3, b"\xff\x00z"), RespFrame::Integer(6));
+        assert_eq!(
+            backend.get("key"),
+            Lookup::Found(BulkString::new(b"\x00\x00\x00\xff\x00z".to_vec()).into())
+        );
+
+        assert_eq!(setrange("key", 1, b"\x80"), RespFrame::Integer(6));
+        assert_eq!(
+            backend.get("key"),
+            Lookup::Found(BulkString::new(b"\x00\x80\x00\xff\x00z".to_vec()).into())
+        );
+
+        // 空值不创建 key
+        assert_eq!(setrange("empty", 10, b""), RespFrame::Integer(0));
+        assert_eq!(backend.get("empty"), Lookup::Missing);
+        let huge_offset = RespArray::new(vec![
+            BulkString::from("setrange").into(),
+            BulkString::from("empty").into(),
+            BulkString::from((MAX_STRING_SIZE * 2).to_string()).into(),
+            BulkString::from("").into(),
+        ]);
+        assert_eq!(
+            SetRange::try_from(huge_offset)?.execute(&backend),
+            RespFrame::Integer(0)
+        );
+        assert_eq!(backend.get("empty"), Lookup::Missing);
+        // 已有的值也不会被扩展
+        assert_eq!(setrange("key", 100, b""), RespFrame::Integer(6));
+
+        backend.hset("map".to_string(), "f".to_string(), b"v".into())?;
+        assert_eq!(setrange("map", 0, b"x"), BackendError::WrongType.into());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_incr_and_incrby() -> Result<()> {
+        let backend = Backend::new();
+        let incrby = |key: &str, increment| {
+            IncrBy {
+                key: key.to_string(),
+                increment,
+            }
+            .execute(&backend)
+        };
+        let incr = |key: &str| {
+            Incr {
+                key: key.to_string(),
+            }
+            .execute(&backend)
+        };
+
+        assert_eq!(incr("n"), RespFrame::Integer(1));
+        assert_eq!(incrby("n", 41), RespFrame::Integer(42));
+        assert_eq!(incrby("n", -50), RespFrame::Integer(-8));
+        assert_eq!(
+            backend.get("n"),
+            Lookup::Found(BulkString::from("-8").into())
+        );
+
+        backend.set(
+            "max".to_string(),
+            BulkString::from(i64::MAX.to_string()).into(),
+        );
+        assert_eq!(incr("max"), BackendError::Overflow.into());
+
+        backend.hset("map".to_string(), "f".to_string(), b"v".into())?;
+        assert_eq!(incr("map"), BackendError::WrongType.into());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_concurrent_incr_loses_no_updates() {
+        use std::thread;
+
+        const THREADS: i64 = 8;
+        const INCRS: i64 = 5000;
+        let backend = Backend::new();
+        let handles = (0..THREADS)
+            .map(|_| {
+                let backend = backend.clone();
+                thread::spawn(move || {
+                    for _ in 0..INCRS {
+                        Incr {
+                            key: "n".to_string(),
+                        }
+                        .execute(&backend);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(
+            backend.get("n"),
+            Lookup::Found(BulkString::from((THREADS * INCRS).to_string()).into())
+        );
+    }
+
+    #[test]
+    fn test_incr_respects_maxmemory() {
+        let backend = Backend::new();
+        backend.set("big".to_string(), BulkString::from("x".repeat(2048)).into());
+        backend.config().set_maxmemory(1024);
+        let incr = Incr {
+            key: "n".to_string(),
+        };
+        assert_eq!(incr.execute(&backend), BackendError::OutOfMemory.into());
+        assert_eq!(backend.get("n"), Lookup::Missing);
+    }
+
+    #[test]
+    fn test_append_respects_maxmemory() {
+        let backend = Backend::new();
+        backend.set("big".to_string(), BulkString::from("x".repeat(2048)).into());
+        backend.config().set_maxmemory(1024);
+        let append = Append {
+            key: "big".to_string(),
+            value: b"more".to_vec(),
+        };
+        assert_eq!(append.execute(&backend), BackendError::OutOfMemory.into());
+        assert_eq!(
+            backend.get("big").found(),
+            Some(BulkString::from("x".repeat(2048)).into())
+        );
+    }
+
+    #[test]
+    fn test_incr_rejects_big_number() {
+        let backend = Backend::new();
+        let big = "123456789012345678901234567890";
+        backend.set("big".to_string(), BulkString::from(big).into());
+        let ret = Incr {
+            key: "big".to_string(),
+        }
+        .execute(&backend);
+        assert_eq!(
+            ret,
+            RespFrame::error("ERR", "value is not an integer or out of range")
+        );
+        // 值保持不变
+        assert_eq!(
+            backend.get("big"),
+            Lookup::Found(BulkString::from(big).into())
+        );
+
+        for value in ["+1", "01", " 1", "1.5", ""] {
+            backend.set("k".to_string(), BulkString::from(value).into());
+            let ret = IncrBy {
+                key: "k".to_string(),
+                increment: 1,
+            }
+            .execute(&backend);
+            assert_eq!(ret, BackendError::NotInteger.into(), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn test_append_to_int_encoded_value() {
+        let backend = Backend::new();
+        let append = |key: &str, value: &str| {
+            Append {
+                key: key.to_string(),
+                value: value.as_bytes().to_vec(),
+            }
+            .execute(&backend)
+        };
+        let get = |key: &str| {
+            Get {
+                key: key.to_string(),
+            }
+            .execute(&backend)
+        };
+
+        Set {
+            key: "n".to_string(),
+            value: BulkString::from("5").into(),
+            get: false,
+            keepttl: false,
+        }
+        .execute(&backend);
+        assert_eq!(backend.object_encoding("n"), Some("int"));
+        assert_eq!(append("n", "0"), RespFrame::Integer(2));
+        assert_eq!(get("n"), BulkString::from("50").into());
+        assert_eq!(backend.object_encoding("n"), Some("raw"));
+
+        // INCR 之后以整数 frame 保存，同样按十进制展开
+        backend.incr_by("m".to_string(), -12).unwrap();
+        assert_eq!(append("m", "3"), RespFrame::Integer(4));
+        assert_eq!(get("m"), BulkString::from("-123").into());
+        assert_eq!(backend.object_encoding("m"), Some("raw"));
+    }
+
+    #[test]
+    fn test_setrange_and_append_respect_max_string_size() {
+        let backend = Backend::new();
+        backend.config().set_proto_max_bulk_len(16);
+        let setrange = |offset, value: &[u8]| {
+            SetRange {
+                key: "key".to_string(),
+                offset,
+                value: value.to_vec(),
+            }
+            .execute(&backend)
+        };
+        let append = |value: &[u8]| {
+            Append {
+                key: "key".to_string(),
+                value: value.to_vec(),
+            }
+            .execute(&backend)
+        };
+
+        assert_eq!(setrange(10, b"abcdef"), RespFrame::Integer(16));
+        assert_eq!(
+            setrange(1_000_000, b"x"),
+            BackendError::StringTooLong.into()
+        );
+        assert_eq!(setrange(15, b"xy"), BackendError::StringTooLong.into());
+        assert_eq!(append(b"z"), BackendError::StringTooLong.into());
+        // 失败时原值不变
+        assert_eq!(append(b""), RespFrame::Integer(16));
+
+        backend.set("key".to_string(), BulkString::from("hello").into());
+        assert_eq!(append(b" world"), RespFrame::Integer(11));
+        assert_eq!(
+            backend.get("key"),
+            Lookup::Found(BulkString::from("hello world").into())
+        );
+        assert_eq!(append(b" again"), BackendError::StringTooLong.into());
+    }
+
+    #[test]
+    fn test_setbit_getbit() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = |args: &[&str]| -> Result<RespFrame> {
+            let frame = RespArray::new(
+                args.iter()
+                    .map(|arg| BulkString::from(*arg).into())
+                    .collect::<Vec<_>>(),
+            );
+            Ok(Command::try_from(RespFrame::from(frame))?.execute(&backend))
+        };
+
+        assert_eq!(cmd(&["setbit", "key", "7", "1"])?, RespFrame::Integer(0));
+        assert_eq!(cmd(&["setbit", "key", "7", "1"])?, RespFrame::Integer(1));
+        assert_eq!(cmd(&["setbit", "key", "9", "1"])?, RespFrame::Integer(0));
+        assert_eq!(
+            backend.get("key"),
+            Lookup::Found(BulkString::new(b"\x01\x40".to_vec()).into())
+        );
+        assert_eq!(cmd(&["getbit", "key", "7"])?, RespFrame::Integer(1));
+        assert_eq!(cmd(&["getbit", "key", "8"])?, RespFrame::Integer(0));
+        assert_eq!(cmd(&["getbit", "key", "1000"])?, RespFrame::Integer(0));
+        assert_eq!(cmd(&["setbit", "key", "7", "0"])?, RespFrame::Integer(1));
+        assert_eq!(cmd(&["getbit", "missing", "0"])?, RespFrame::Integer(0));
+
+        assert!(cmd(&["setbit", "key", "0", "2"]).is_err());
+        assert!(cmd(&["setbit", "key", "-1", "1"]).is_err());
+        assert!(cmd(&["getbit", "key", "4294967296"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bit_offset_bounds() -> Result<()> {
+        let frame = |args: &[&str]| {
+            RespArray::new(
+                args.iter()
+                    .map(|arg| BulkString::from(*arg).into())
+                    .collect::<Vec<_>>(),
+            )
+        };
+        let out_of_range = |err: CommandError| {
+            assert!(
+                err.to_string()
+                    .contains("bit offset is not an integer or out of range"),
+                "{}",
+                err
+            )
+        };
+
+        // 最大的 offset 是 2^32 - 1，只解析不执行，避免分配 512MB
+        let max = SetBit::try_from(frame(&["setbit", "key", "4294967295", "1"]))?;
+        assert_eq!(max.offset, 4294967295);
+        let backend = Backend::new();
+        let getbit = GetBit::try_from(frame(&["getbit", "key", "4294967295"]))?;
+        assert_eq!(getbit.execute(&backend), RespFrame::Integer(0));
+        let large = SetBit::try_from(frame(&["setbit", "key", "8388607", "1"]))?;
+        assert_eq!(large.execute(&backend), RespFrame::Integer(0));
+        assert_eq!(
+            GetBit::try_from(frame(&["getbit", "key", "8388607"]))?.execute(&backend),
+            RespFrame::Integer(1)
+        );
+
+        for offset in ["4294967296", "-1", "abc", "1.5"] {
+            out_of_range(SetBit::try_from(frame(&["setbit", "key", offset, "1"])).unwrap_err());
+            out_of_range(GetBit::try_from(frame(&["getbit", "key", offset])).unwrap_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_bit_and_range_growth_respects_maxmemory() {
+        let backend = Backend::new();
+        backend.set("small".to_string(), BulkString::from("x").into());
+        backend.config().set_maxmemory(1024);
+        let used = backend.used_memory();
+
+        // 巨大的 offset 在分配前就被拒绝，key 不会被创建
+        let setbit = SetBit {
+            key: "bits".to_string(),
+            offset: 8 * 100 * 1024 * 1024,
+            on: true,
+        };
+        assert_eq!(setbit.execute(&backend), BackendError::OutOfMemory.into());
+        assert_eq!(backend.get("bits"), Lookup::Missing);
+        let setrange = SetRange {
+            key: "small".to_string(),
+            offset: 100 * 1024 * 1024,
+            value: b"y".to_vec(),
+        };
+        assert_eq!(setrange.execute(&backend), BackendError::OutOfMemory.into());
+        assert_eq!(
+            backend.get("small"),
+            Lookup::Found(BulkString::from("x").into())
+        );
+        assert_eq!(backend.used_memory(), used);
+
+        // 不增长的写入和限制内的增长不受影响
+        let setbit = |offset| SetBit {
+            key: "small".to_string(),
+            offset,
+            on: true,
+        };
+        assert_eq!(setbit(0).execute(&backend), RespFrame::Integer(0));
+        assert_eq!(setbit(8 * 64).execute(&backend), RespFrame::Integer(0));
+
+        backend.config().set_maxmemory(0);
+        assert_eq!(
+            setbit(8 * 100 * 1024).execute(&backend),
+            RespFrame::Integer(0)
+        );
+    }
 }
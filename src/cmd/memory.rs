@@ -0,0 +1,107 @@
+use crate::{RespArray, RespFrame, RespNull, MEMORY_USAGE_DEFAULT_SAMPLES};
+
+use super::{
+    extract_args, parse_integer, validate_command_at_least, CommandError, CommandExecutor, Memory,
+    MemorySubcommand,
+};
+
+impl CommandExecutor for Memory {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match self.subcommand {
+            MemorySubcommand::Usage { key, samples } => match backend.memory_usage(&key, samples) {
+                Some(bytes) => RespFrame::Integer(bytes as i64),
+                None => RespFrame::Null(RespNull),
+            },
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Memory {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["memory"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let subcommand = match args.next() {
+            Some(RespFrame::BulkString(sub)) => sub.to_ascii_lowercase(),
+            _ => {
+                return Err(CommandError::InvalidArgument(
+                    "Invalid subcommand".to_string(),
+                ))
+            }
+        };
+        match subcommand.as_slice() {
+            b"usage" => {
+                let key = match args.next() {
+                    Some(RespFrame::BulkString(key)) => String::from_utf8(key.get_data()?)?,
+                    _ => return Err(CommandError::WrongArity("memory|usage".to_string())),
+                };
+                let samples = match (args.next(), args.next(), args.next()) {
+                    (None, _, _) => MEMORY_USAGE_DEFAULT_SAMPLES,
+                    (Some(RespFrame::BulkString(opt)), Some(n), None)
+                        if opt.eq_ignore_ascii_case(b"samples") =>
+                    {
+                        usize::try_from(parse_integer(&n)?).map_err(|_| {
+                            CommandError::InvalidArgument(
+                                "value is out of range, must be positive".to_string(),
+                            )
+                        })?
+                    }
+                    _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+                };
+                Ok(Memory {
+                    subcommand: MemorySubcommand::Usage { key, samples },
+                })
+            }
+            _ => Err(CommandError::InvalidArgument(format!(
+                "Unknown MEMORY subcommand '{}'",
+                String::from_utf8_lossy(&subcommand)
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Backend, RespDecode};
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn memory(cmd: &[u8]) -> Result<Memory, CommandError> {
+        let mut buf = BytesMut::from(cmd);
+        RespArray::decode(&mut buf).unwrap().try_into()
+    }
+
+    #[test]
+    fn test_memory_usage_command() -> Result<()> {
+        let cmd = memory(b"*3\r\n$6\r\nmemory\r\n$5\r\nUSAGE\r\n$3\r\nkey\r\n")?;
+        assert!(matches!(
+            cmd.subcommand,
+            MemorySubcommand::Usage { ref key, samples } if key == "key" && samples == MEMORY_USAGE_DEFAULT_SAMPLES
+        ));
+        let cmd = memory(
+            b"*5\r\n$6\r\nmemory\r\n$5\r\nusage\r\n$3\r\nkey\r\n$7\r\nSAMPLES\r\n$1\r\n0\r\n",
+        )?;
+        assert!(matches!(
+            cmd.subcommand,
+            MemorySubcommand::Usage { samples: 0, .. }
+        ));
+        assert!(memory(
+            b"*5\r\n$6\r\nmemory\r\n$5\r\nusage\r\n$3\r\nkey\r\n$7\r\nSAMPLES\r\n$2\r\n-1\r\n"
+        )
+        .is_err());
+        assert!(
+            memory(b"*4\r\n$6\r\nmemory\r\n$5\r\nusage\r\n$3\r\nkey\r\n$7\r\nSAMPLES\r\n").is_err()
+        );
+
+        let backend = Backend::new();
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        backend.sadd("key".to_string(), "member".to_string())?;
+        let cmd = memory(b"*3\r\n$6\r\nmemory\r\n$5\r\nusage\r\n$3\r\nkey\r\n")?;
+        assert!(matches!(cmd.execute(&backend), RespFrame::Integer(n) if n > 0));
+        Ok(())
+    }
+}
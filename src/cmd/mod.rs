@@ -1,11 +1,36 @@
+mod client;
+mod cluster;
+mod command;
+mod config;
+mod connection;
+mod debug;
+mod dispatch;
 mod echo;
+mod hello;
 mod hmap;
+mod info;
+mod keys;
+mod list;
 mod map;
+mod memory;
+mod object;
+mod ping;
+mod pubsub;
+mod rewrite;
 mod set;
+mod zset;
 
-use crate::{Backend, RespArray, RespError, RespFrame, SimpleString};
+use std::borrow::Cow;
+use std::time::{Duration, SystemTime};
+
+use crate::{
+    Aggregate, Backend, BackendConfig, BulkString, ConnectionContext, LexBound, ListEnd, RespArray,
+    RespError, RespFrame, RespVersion, ScoreBound, SimpleString, ZAddOptions, ZRangeBy,
+};
+pub use dispatch::{command_names, is_command};
 use enum_dispatch::enum_dispatch;
 use lazy_static::lazy_static;
+pub use rewrite::AofEntry;
 use thiserror::Error;
 
 lazy_static! {
@@ -18,6 +43,15 @@ pub enum CommandError {
     InvalidCommand(String),
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
+    #[error("ERR wrong number of arguments for '{0}' command")]
+    WrongArity(String),
+    #[error("ERR invalid expire time in '{0}' command")]
+    InvalidExpireTime(String),
+    // nan 和无法解析的浮点数，回复和 Redis 完全一致
+    #[error("ERR value is not a valid float")]
+    NotFloat,
+    #[error("ERR timeout is out of range")]
+    TimeoutOutOfRange,
 
     #[error("{0}")]
     RespError(#[from] RespError),
@@ -25,9 +59,35 @@ pub enum CommandError {
     Utf8Error(#[from] std::string::FromUtf8Error),
 }
 
+impl From<CommandError> for RespFrame {
+    fn from(e: CommandError) -> Self {
+        match e {
+            CommandError::WrongArity(name) => RespFrame::error(
+                "ERR",
+                &format!("wrong number of arguments for '{}' command", name),
+            ),
+            CommandError::InvalidExpireTime(name) => {
+                RespFrame::error("ERR", &format!("invalid expire time in '{}' command", name))
+            }
+            CommandError::NotFloat => RespFrame::error("ERR", "value is not a valid float"),
+            CommandError::TimeoutOutOfRange => RespFrame::error("ERR", "timeout is out of range"),
+            e => RespFrame::error("ERR", &e.to_string()),
+        }
+    }
+}
+
 #[enum_dispatch]
 pub trait CommandExecutor {
     fn execute(self, backend: &Backend) -> RespFrame;
+
+    /// Execute on behalf of the connection described by `ctx`. Commands
+    /// whose reply depends on the connection override this.
+    fn execute_with(self, backend: &Backend, _ctx: &ConnectionContext) -> RespFrame
+    where
+        Self: Sized,
+    {
+        self.execute(backend)
+    }
 }
 
 #[enum_dispatch(CommandExecutor)]
@@ -35,13 +95,110 @@ pub trait CommandExecutor {
 pub enum Command {
     Get(Get),
     Set(Set),
+    GetDel(GetDel),
+    GetEx(GetEx),
+    GetSet(GetSet),
     HGet(HGet),
     HSet(HSet),
+    HIncrByFloat(HIncrByFloat),
     HGetAll(HGetAll),
+    HKeys(HKeys),
+    HLen(HLen),
+    HScan(HScan),
+    HVals(HVals),
     HMGet(HMGet),
+    HExpire(HExpire),
+    HTtl(HTtl),
+    HGetDel(HGetDel),
+    HDel(HDel),
+    HGetEx(HGetEx),
     Echo(Echo),
     SAdd(SAdd),
+    SRem(SRem),
+    SPop(SPop),
     SisMember(SisMember),
+    SMembers(SMembers),
+    SCard(SCard),
+    SRandMember(SRandMember),
+    SInterStore(SInterStore),
+    SUnionStore(SUnionStore),
+    SDiffStore(SDiffStore),
+    GetRange(GetRange),
+    Substr(Substr),
+    StrLen(StrLen),
+    SetRange(SetRange),
+    SetBit(SetBit),
+    GetBit(GetBit),
+    Append(Append),
+    Incr(Incr),
+    IncrBy(IncrBy),
+    Lcs(Lcs),
+    Copy(Copy),
+    Dump(Dump),
+    Exists(Exists),
+    Restore(Restore),
+    Rename(Rename),
+    RenameNx(RenameNx),
+    Del(Del),
+    Unlink(Unlink),
+    FlushDb(FlushDb),
+    DbSize(DbSize),
+    Expire(Expire),
+    PExpire(PExpire),
+    PExpireAt(PExpireAt),
+    Ttl(Ttl),
+    Scan(Scan),
+    PTtl(PTtl),
+    LPush(LPush),
+    RPush(RPush),
+    LPop(LPop),
+    RPop(RPop),
+    LRem(LRem),
+    LTrim(LTrim),
+    BLPop(BLPop),
+    BRPop(BRPop),
+    BLMove(BLMove),
+    LMove(LMove),
+    LMPop(LMPop),
+    LPos(LPos),
+    Publish(Publish),
+    Subscribe(Subscribe),
+    Unsubscribe(Unsubscribe),
+    PSubscribe(PSubscribe),
+    PUnsubscribe(PUnsubscribe),
+    Pubsub(Pubsub),
+    Config(Config),
+    Debug(Debug),
+    Memory(Memory),
+    Object(Object),
+    Client(Client),
+    Failover(Failover),
+    ReplicaOf(ReplicaOf),
+    WaitAof(WaitAof),
+    Cluster(Cluster),
+    ZAdd(ZAdd),
+    ZMPop(ZMPop),
+    ZPopMin(ZPopMin),
+    ZPopMax(ZPopMax),
+    ZRem(ZRem),
+    ZScore(ZScore),
+    ZRank(ZRank),
+    ZRevRank(ZRevRank),
+    ZIncrBy(ZIncrBy),
+    ZMScore(ZMScore),
+    ZRange(ZRange),
+    ZRangeByLex(ZRangeByLex),
+    ZRangeStore(ZRangeStore),
+    ZRevRange(ZRevRange),
+    ZRevRangeByScore(ZRevRangeByScore),
+    ZUnionStore(ZUnionStore),
+    ZInterStore(ZInterStore),
+    Hello(Hello),
+    Info(Info),
+    CommandMeta(CommandMeta),
+    Ping(Ping),
+    Quit(Quit),
+    Reset(Reset),
 
     Unrecognized(Unrecognized),
 }
@@ -55,6 +212,36 @@ pub struct Get {
 pub struct Set {
     key: String,
     value: RespFrame,
+    // SET ... GET 回复旧值
+    get: bool,
+    // SET ... KEEPTTL 保留原来的过期时间，否则 SET 会清除 TTL
+    keepttl: bool,
+}
+
+#[derive(Debug)]
+pub struct GetSet {
+    key: String,
+    value: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct GetDel {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct GetEx {
+    key: String,
+    expiry: Option<GetExExpiry>,
+}
+
+#[derive(Debug, PartialEq)]
+enum GetExExpiry {
+    // EX / PX
+    Ttl(Duration),
+    // EXAT / PXAT
+    At(SystemTime),
+    Persist,
 }
 
 #[derive(Debug)]
@@ -73,7 +260,38 @@ pub struct HSet {
 #[derive(Debug)]
 pub struct HGetAll {
     key: String,
-    sort: bool,
+}
+
+#[derive(Debug)]
+pub struct HKeys {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct HLen {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct HScan {
+    key: String,
+    cursor: u64,
+    count: usize,
+    pattern: Option<String>,
+    // NOVALUES 只返回字段名
+    novalues: bool,
+}
+
+#[derive(Debug)]
+pub struct HVals {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct HIncrByFloat {
+    key: String,
+    field: String,
+    increment: f64,
 }
 
 #[derive(Debug)]
@@ -82,6 +300,38 @@ pub struct HMGet {
     fields: Vec<String>,
 }
 
+#[derive(Debug)]
+pub struct HExpire {
+    key: String,
+    seconds: u64,
+    fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HTtl {
+    key: String,
+    fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HGetDel {
+    key: String,
+    fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HDel {
+    key: String,
+    fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HGetEx {
+    key: String,
+    fields: Vec<String>,
+    expiry: Option<GetExExpiry>,
+}
+
 #[derive(Debug)]
 pub struct Echo {
     message: String,
@@ -93,6 +343,19 @@ pub struct SAdd {
     member: String,
 }
 
+#[derive(Debug)]
+pub struct SRem {
+    key: String,
+    members: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SPop {
+    key: String,
+    // None 时回复单个成员，否则回复数组
+    count: Option<usize>,
+}
+
 #[derive(Debug)]
 pub struct SisMember {
     key: String,
@@ -100,7 +363,552 @@ pub struct SisMember {
 }
 
 #[derive(Debug)]
-pub struct Unrecognized;
+pub struct SMembers {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct SCard {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct SRandMember {
+    key: String,
+    count: Option<i64>,
+}
+
+#[derive(Debug)]
+pub struct SInterStore {
+    dest: String,
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SUnionStore {
+    dest: String,
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SDiffStore {
+    dest: String,
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct GetRange {
+    key: String,
+    start: i64,
+    end: i64,
+}
+
+#[derive(Debug)]
+pub struct Substr(GetRange);
+
+#[derive(Debug)]
+pub struct StrLen {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct SetRange {
+    key: String,
+    offset: usize,
+    value: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct SetBit {
+    key: String,
+    offset: usize,
+    on: bool,
+}
+
+#[derive(Debug)]
+pub struct GetBit {
+    key: String,
+    offset: usize,
+}
+
+#[derive(Debug)]
+pub struct Append {
+    key: String,
+    value: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct Incr {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct IncrBy {
+    key: String,
+    increment: i64,
+}
+
+#[derive(Debug)]
+pub struct Lcs {
+    key1: String,
+    key2: String,
+    len_only: bool,
+    idx: bool,
+}
+
+#[derive(Debug)]
+pub struct Dump {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct Restore {
+    key: String,
+    ttl: Option<Duration>,
+    payload: Vec<u8>,
+    replace: bool,
+}
+
+#[derive(Debug)]
+pub struct Copy {
+    source: String,
+    destination: String,
+    replace: bool,
+}
+
+#[derive(Debug)]
+pub struct Rename {
+    key: String,
+    new_key: String,
+}
+
+#[derive(Debug)]
+pub struct RenameNx(Rename);
+
+#[derive(Debug)]
+pub struct FlushDb;
+
+#[derive(Debug)]
+pub struct DbSize;
+
+#[derive(Debug)]
+pub struct Del {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Exists {
+    keys: Vec<String>,
+}
+
+// 删除总是同步完成，UNLINK 和 DEL 的效果相同
+#[derive(Debug)]
+pub struct Unlink(Del);
+
+#[derive(Debug)]
+pub struct Scan {
+    cursor: u64,
+    count: usize,
+    // 原始字节，glob 直接匹配 key 的字节
+    pattern: Option<Vec<u8>>,
+    key_type: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Expire {
+    key: String,
+    seconds: i64,
+}
+
+#[derive(Debug)]
+pub struct PExpire {
+    key: String,
+    milliseconds: i64,
+}
+
+#[derive(Debug)]
+pub struct PExpireAt {
+    key: String,
+    unix_millis: i64,
+}
+
+#[derive(Debug)]
+pub struct Ttl {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct PTtl {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct LPush {
+    key: String,
+    values: Vec<RespFrame>,
+}
+
+#[derive(Debug)]
+pub struct RPush {
+    key: String,
+    values: Vec<RespFrame>,
+}
+
+#[derive(Debug)]
+pub struct LPop {
+    key: String,
+    // None 时回复单个元素，否则回复数组
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct RPop {
+    key: String,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct LRem {
+    key: String,
+    count: i64,
+    element: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct LTrim {
+    key: String,
+    start: i64,
+    stop: i64,
+}
+
+#[derive(Debug)]
+pub struct BLPop {
+    keys: Vec<String>,
+    timeout: f64,
+}
+
+#[derive(Debug)]
+pub struct BRPop {
+    keys: Vec<String>,
+    timeout: f64,
+}
+
+#[derive(Debug)]
+pub struct LMPop {
+    keys: Vec<String>,
+    from_left: bool,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct BLMove {
+    source: String,
+    destination: String,
+    wherefrom: ListEnd,
+    whereto: ListEnd,
+    timeout: f64,
+}
+
+#[derive(Debug)]
+pub struct LMove {
+    source: String,
+    destination: String,
+    wherefrom: ListEnd,
+    whereto: ListEnd,
+}
+
+#[derive(Debug)]
+pub struct LPos {
+    key: String,
+    element: Vec<u8>,
+    rank: i64,
+    // None 时只返回第一个匹配的位置，而不是数组
+    count: Option<usize>,
+    maxlen: usize,
+}
+
+#[derive(Debug)]
+pub struct Publish {
+    channel: String,
+    message: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct Subscribe {
+    channels: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Unsubscribe {
+    channels: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct PSubscribe {
+    patterns: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct PUnsubscribe {
+    patterns: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Pubsub {
+    subcommand: PubsubSubcommand,
+}
+
+#[derive(Debug)]
+enum PubsubSubcommand {
+    Channels(Option<String>),
+}
+
+#[derive(Debug)]
+pub struct Config {
+    subcommand: ConfigSubcommand,
+}
+
+#[derive(Debug)]
+enum ConfigSubcommand {
+    Get(String),
+    Set(String, String),
+}
+
+#[derive(Debug)]
+pub struct Debug {
+    subcommand: DebugSubcommand,
+}
+
+#[derive(Debug)]
+enum DebugSubcommand {
+    Reload,
+    Expires,
+    Object(String),
+    SetActiveExpire(bool),
+    SortReplies(bool),
+}
+
+#[derive(Debug)]
+pub struct Memory {
+    subcommand: MemorySubcommand,
+}
+
+#[derive(Debug)]
+enum MemorySubcommand {
+    Usage { key: String, samples: usize },
+}
+
+#[derive(Debug)]
+pub struct Client {
+    subcommand: ClientSubcommand,
+}
+
+#[derive(Debug)]
+enum ClientSubcommand {
+    NoEvict(bool),
+    NoTouch(bool),
+}
+
+#[derive(Debug)]
+pub struct Failover {
+    abort: bool,
+}
+
+#[derive(Debug)]
+pub struct ReplicaOf;
+
+#[derive(Debug)]
+pub struct Cluster;
+
+#[derive(Debug)]
+pub struct WaitAof {
+    numlocal: u64,
+    numreplicas: u64,
+    timeout: Duration,
+}
+
+/// COMMAND, named so to not clash with the `Command` enum.
+#[derive(Debug)]
+pub struct CommandMeta {
+    subcommand: CommandMetaSubcommand,
+}
+
+#[derive(Debug)]
+enum CommandMetaSubcommand {
+    Count,
+    Docs(Vec<String>),
+    // 第二项表示是否同时返回每个 key 的访问标志（GETKEYSANDFLAGS）
+    GetKeys(Vec<Vec<u8>>, bool),
+    List,
+}
+
+#[derive(Debug)]
+pub struct Object {
+    subcommand: ObjectSubcommand,
+}
+
+#[derive(Debug)]
+enum ObjectSubcommand {
+    Encoding(String),
+    IdleTime(String),
+}
+
+#[derive(Debug)]
+pub struct ZMPop {
+    keys: Vec<String>,
+    min: bool,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct ZPopMin {
+    key: String,
+    count: Option<usize>,
+    min: bool,
+}
+
+#[derive(Debug)]
+pub struct ZPopMax(ZPopMin);
+
+#[derive(Debug)]
+pub struct ZRem {
+    key: String,
+    members: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ZAdd {
+    key: String,
+    members: Vec<(f64, String)>,
+    options: ZAddOptions,
+}
+
+#[derive(Debug)]
+pub struct ZScore {
+    key: String,
+    member: String,
+}
+
+#[derive(Debug)]
+pub struct ZIncrBy {
+    key: String,
+    increment: f64,
+    member: String,
+}
+
+#[derive(Debug)]
+pub struct ZRank {
+    key: String,
+    member: String,
+    withscore: bool,
+    rev: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRevRank(ZRank);
+
+#[derive(Debug)]
+pub struct ZMScore {
+    key: String,
+    members: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ZRange {
+    key: String,
+    range: ZRangeBy,
+    rev: bool,
+    limit: Option<(i64, i64)>,
+    withscores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRevRange {
+    key: String,
+    start: i64,
+    stop: i64,
+    with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRevRangeByScore {
+    key: String,
+    max: ScoreBound,
+    min: ScoreBound,
+    with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRangeByLex {
+    key: String,
+    min: LexBound,
+    max: LexBound,
+}
+
+#[derive(Debug)]
+pub struct ZRangeStore {
+    dest: String,
+    src: String,
+    range: ZRangeBy,
+    rev: bool,
+    limit: Option<(i64, i64)>,
+}
+
+#[derive(Debug)]
+pub struct ZUnionStore {
+    dest: String,
+    keys: Vec<String>,
+    weights: Vec<f64>,
+    aggregate: Aggregate,
+}
+
+#[derive(Debug)]
+pub struct ZInterStore {
+    dest: String,
+    keys: Vec<String>,
+    weights: Vec<f64>,
+    aggregate: Aggregate,
+}
+
+#[derive(Debug)]
+pub struct Hello {
+    protover: Option<RespVersion>,
+}
+
+#[derive(Debug)]
+pub struct Info {
+    section: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Ping {
+    message: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Quit;
+
+#[derive(Debug)]
+pub struct Reset;
+
+#[derive(Debug)]
+pub struct Unrecognized {
+    name: String,
+    args: Vec<String>,
+}
+
+impl Unrecognized {
+    fn new(value: &RespArray) -> Self {
+        let mut args = value.iter().map(|arg| match arg {
+            RespFrame::BulkString(s) => String::from_utf8_lossy(s).into_owned(),
+            _ => String::new(),
+        });
+        Self {
+            name: args.next().unwrap_or_default(),
+            args: args.collect(),
+        }
+    }
+}
 
 impl TryFrom<RespFrame> for Command {
     type Error = CommandError;
@@ -119,17 +927,9 @@ impl TryFrom<RespArray> for Command {
     type Error = CommandError;
     fn try_from(v: RespArray) -> Result<Self, Self::Error> {
         match v.first() {
-            Some(RespFrame::BulkString(ref cmd)) => match cmd.as_ref() {
-                b"get" => Ok(Get::try_from(v)?.into()),
-                b"set" => Ok(Set::try_from(v)?.into()),
-                b"hget" => Ok(HGet::try_from(v)?.into()),
-                b"hset" => Ok(HSet::try_from(v)?.into()),
-                b"hgetall" => Ok(HGetAll::try_from(v)?.into()),
-                b"hmget" => Ok(HMGet::try_from(v)?.into()),
-                b"echo" => Ok(Echo::try_from(v)?.into()),
-                b"sadd" => Ok(SAdd::try_from(v)?.into()),
-                b"sismember" => Ok(SisMember::try_from(v)?.into()),
-                _ => Ok(Unrecognized.into()),
+            Some(RespFrame::BulkString(ref cmd)) => match dispatch::parser(cmd.as_ref()) {
+                Some(parser) => parser(v),
+                None => Ok(Unrecognized::new(&v).into()),
             },
             _ => Err(CommandError::InvalidCommand(
                 "Command must have a BulkString as the first argument".to_string(),
@@ -138,9 +938,120 @@ impl TryFrom<RespArray> for Command {
     }
 }
 
+impl Command {
+    /// Parse a client request, applying the rename-command table of `config`
+    /// before dispatching: an alias runs the command it stands for, while a
+    /// renamed or disabled name is unknown.
+    pub fn parse(frame: RespFrame, config: &BackendConfig) -> Result<Self, CommandError> {
+        let RespFrame::Array(mut array) = frame else {
+            return frame.try_into();
+        };
+        let builtin = match array.first() {
+            Some(RespFrame::BulkString(name)) => match config.resolve_command(name) {
+                None => return Ok(Unrecognized::new(&array).into()),
+                Some(Cow::Owned(builtin)) => Some(builtin),
+                Some(Cow::Borrowed(_)) => None,
+            },
+            _ => None,
+        };
+        if let (Some(builtin), Some(frames)) = (builtin, array.0.as_mut()) {
+            frames[0] = BulkString::new(builtin).into();
+        }
+        array.try_into()
+    }
+
+    /// Blocking commands may park the executing thread until data arrives.
+    pub fn is_blocking(&self) -> bool {
+        matches!(
+            self,
+            Command::BLPop(_) | Command::BRPop(_) | Command::BLMove(_) | Command::WaitAof(_)
+        )
+    }
+
+    /// Commands that modify the data and must be recorded in the AOF.
+    pub fn is_write(&self) -> bool {
+        matches!(
+            self,
+            Command::Set(_)
+                | Command::SetRange(_)
+                | Command::SetBit(_)
+                | Command::Append(_)
+                | Command::Incr(_)
+                | Command::IncrBy(_)
+                | Command::HSet(_)
+                | Command::HIncrByFloat(_)
+                | Command::HExpire(_)
+                | Command::HGetDel(_)
+                | Command::HDel(_)
+                | Command::HGetEx(_)
+                | Command::SAdd(_)
+                | Command::SRem(_)
+                | Command::SPop(_)
+                | Command::Copy(_)
+                | Command::Restore(_)
+                | Command::Rename(_)
+                | Command::RenameNx(_)
+                | Command::Del(_)
+                | Command::Unlink(_)
+                | Command::FlushDb(_)
+                | Command::GetDel(_)
+                | Command::GetEx(_)
+                | Command::GetSet(_)
+                | Command::Expire(_)
+                | Command::PExpire(_)
+                | Command::PExpireAt(_)
+                | Command::SInterStore(_)
+                | Command::SUnionStore(_)
+                | Command::SDiffStore(_)
+                | Command::LPush(_)
+                | Command::RPush(_)
+                | Command::LPop(_)
+                | Command::RPop(_)
+                | Command::LRem(_)
+                | Command::LTrim(_)
+                | Command::LMPop(_)
+                | Command::LMove(_)
+                | Command::BLPop(_)
+                | Command::BRPop(_)
+                | Command::BLMove(_)
+                | Command::ZAdd(_)
+                | Command::ZIncrBy(_)
+                | Command::ZMPop(_)
+                | Command::ZPopMin(_)
+                | Command::ZPopMax(_)
+                | Command::ZRem(_)
+                | Command::ZRangeStore(_)
+                | Command::ZUnionStore(_)
+                | Command::ZInterStore(_)
+        )
+    }
+
+    /// Commands a client may still issue once it has subscribed to a channel
+    /// or pattern.
+    pub fn allowed_in_subscribe_mode(&self) -> bool {
+        matches!(
+            self,
+            Command::Subscribe(_)
+                | Command::Unsubscribe(_)
+                | Command::PSubscribe(_)
+                | Command::PUnsubscribe(_)
+                | Command::Ping(_)
+                | Command::Quit(_)
+                | Command::Reset(_)
+        )
+    }
+}
+
 impl CommandExecutor for Unrecognized {
     fn execute(self, _: &Backend) -> RespFrame {
-        RESP_OK.clone()
+        let args: String = self.args.iter().map(|arg| format!("'{}' ", arg)).collect();
+        RespFrame::error(
+            "ERR",
+            &format!(
+                "unknown command '{}', with args beginning with: {}",
+                self.name, args
+            ),
+        )
     }
 }
 
@@ -149,12 +1060,9 @@ fn validate_command(
     names: &[&'static str],
     n_args: usize,
 ) -> Result<(), CommandError> {
+    debug_assert!(!names.is_empty(), "command names must not be empty");
     if value.len() != n_args + names.len() {
-        return Err(CommandError::InvalidArgument(format!(
-            "{} command must have exactly {} argument",
-            names.join(" "),
-            n_args
-        )));
+        return Err(CommandError::WrongArity(names.join("|")));
     }
 
     validate_command_name(value, names)?;
@@ -167,12 +1075,9 @@ fn validate_command_at_least(
     names: &[&'static str],
     n_args: usize,
 ) -> Result<(), CommandError> {
+    debug_assert!(!names.is_empty(), "command names must not be empty");
     if value.len() < n_args + names.len() {
-        return Err(CommandError::InvalidArgument(format!(
-            "{} command must have at least {} argument",
-            names.join(" "),
-            n_args
-        )));
+        return Err(CommandError::WrongArity(names.join("|")));
     }
 
     validate_command_name(value, names)?;
@@ -181,6 +1086,14 @@ fn validate_command_at_least(
 }
 
 fn validate_command_name(value: &RespArray, names: &[&'static str]) -> Result<(), CommandError> {
+    // 数组长度小于命令名个数时直接返回错误，避免 value[i] 越界
+    if value.len() < names.len() {
+        return Err(CommandError::InvalidCommand(format!(
+            "{} command is incomplete",
+            names.join(" ")
+        )));
+    }
+
     for (i, name) in names.iter().enumerate() {
         match value[i] {
             RespFrame::BulkString(ref cmd) => {
@@ -208,6 +1121,169 @@ fn validate_command_name(value: &RespArray, names: &[&'static str]) -> Result<()
     Ok(())
 }
 
+fn parse_integer(frame: &RespFrame) -> Result<i64, CommandError> {
+    let invalid =
+        || CommandError::InvalidArgument("value is not an integer or out of range".to_string());
+    match frame {
+        RespFrame::BulkString(s) => std::str::from_utf8(s)
+            .map_err(|_| invalid())?
+            .parse()
+            .map_err(|_| invalid()),
+        _ => Err(invalid()),
+    }
+}
+
+// 解析 GETEX / HGETEX 的 EX seconds | PX ms | EXAT ts | PXAT ts | PERSIST
+fn parse_getex_expiry(
+    option: RespFrame,
+    args: &mut impl Iterator<Item = RespFrame>,
+    name: &str,
+) -> Result<GetExExpiry, CommandError> {
+    let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+    let RespFrame::BulkString(option) = option else {
+        return Err(syntax_error());
+    };
+    let option = String::from_utf8(option.get_data()?)?.to_lowercase();
+    if option == "persist" {
+        return Ok(GetExExpiry::Persist);
+    }
+    let time = match args.next() {
+        Some(time) => parse_integer(&time)?,
+        None => return Err(syntax_error()),
+    };
+    let invalid = || CommandError::InvalidExpireTime(name.to_string());
+    if time <= 0 {
+        return Err(invalid());
+    }
+    // 秒换算成毫秒时不能溢出，相对时间在执行时再检查
+    let millis = match option.as_str() {
+        "ex" | "exat" => time.checked_mul(1000).ok_or_else(invalid)?,
+        "px" | "pxat" => time,
+        _ => return Err(syntax_error()),
+    };
+    let duration = Duration::from_millis(millis as u64);
+    Ok(match option.as_str() {
+        "ex" | "px" => GetExExpiry::Ttl(duration),
+        _ => GetExExpiry::At(SystemTime::UNIX_EPOCH + duration),
+    })
+}
+
+fn parse_float(frame: &RespFrame) -> Result<f64, CommandError> {
+    match frame {
+        RespFrame::BulkString(s) => {
+            let value: f64 = std::str::from_utf8(s)
+                .map_err(|_| CommandError::NotFloat)?
+                .parse()
+                .map_err(|_| CommandError::NotFloat)?;
+            if value.is_nan() {
+                return Err(CommandError::NotFloat);
+            }
+            Ok(value)
+        }
+        _ => Err(CommandError::NotFloat),
+    }
+}
+
+// 解析 LMPOP/ZMPOP 的 <numkeys> <key> [key ...] <where> [COUNT count]，
+// 返回的 bool 表示 where 是否为 `ends` 中的第一个
+fn extract_mpop_args(
+    value: RespArray,
+    name: &'static str,
+    ends: [&str; 2],
+) -> Result<(Vec<String>, bool, Option<usize>), CommandError> {
+    validate_command_at_least(&value, &[name], 3)?;
+
+    let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+    let args = extract_args(value, 1)?
+        .into_iter()
+        .map(|arg| match arg {
+            RespFrame::BulkString(arg) => Ok(String::from_utf8(arg.get_data()?)?),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid argument".to_string(),
+            )),
+        })
+        .collect::<Result<Vec<String>, CommandError>>()?;
+    let mut args = args.into_iter();
+
+    let numkeys: usize = match args.next().map(|v| v.parse()) {
+        Some(Ok(n)) if n > 0 => n,
+        _ => {
+            return Err(CommandError::InvalidArgument(
+                "numkeys should be greater than 0".to_string(),
+            ))
+        }
+    };
+    let keys: Vec<String> = args.by_ref().take(numkeys).collect();
+    if keys.len() != numkeys {
+        return Err(syntax_error());
+    }
+    let first = match args.next().map(|v| v.to_ascii_lowercase()) {
+        Some(end) if end == ends[0] => true,
+        Some(end) if end == ends[1] => false,
+        _ => return Err(syntax_error()),
+    };
+    let count = match (args.next(), args.next()) {
+        (None, _) => None,
+        (Some(option), Some(count)) if option.eq_ignore_ascii_case("count") => {
+            match count.parse() {
+                Ok(n) if n > 0 => Some(n),
+                _ => {
+                    return Err(CommandError::InvalidArgument(
+                        "count should be greater than 0".to_string(),
+                    ))
+                }
+            }
+        }
+        _ => return Err(syntax_error()),
+    };
+    if args.next().is_some() {
+        return Err(syntax_error());
+    }
+    Ok((keys, first, count))
+}
+
+// 解析 <dest> <key> [key ...]，SREM / HDEL / ZREM 的 <key> <member> [member ...] 格式相同
+fn extract_store_args(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, Vec<String>), CommandError> {
+    validate_command_at_least(&value, &[name], 2)?;
+
+    let mut names = extract_args(value, 1)?
+        .into_iter()
+        .map(|arg| match arg {
+            RespFrame::BulkString(key) => Ok(String::from_utf8(key.get_data()?)?),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        })
+        .collect::<Result<Vec<String>, CommandError>>()?;
+    let keys = names.split_off(1);
+    Ok((names.remove(0), keys))
+}
+
+// 解析 LPOP / RPOP / SPOP / ZPOPMIN / ZPOPMAX 的 <key> [count]
+fn extract_pop_args(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, Option<usize>), CommandError> {
+    validate_command_at_least(&value, &[name], 1)?;
+    if value.len() > 3 {
+        return Err(CommandError::WrongArity(name.to_string()));
+    }
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = match args.next() {
+        Some(RespFrame::BulkString(key)) => String::from_utf8(key.get_data()?)?,
+        _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+    };
+    let count = match args.next() {
+        Some(count) => Some(usize::try_from(parse_integer(&count)?).map_err(|_| {
+            CommandError::InvalidArgument("value is out of range, must be positive".to_string())
+        })?),
+        None => None,
+    };
+    Ok((key, count))
+}
+
 fn extract_args(value: RespArray, start: usize) -> Result<Vec<RespFrame>, CommandError> {
     match value.0 {
         None => Err(CommandError::InvalidArgument(
@@ -220,7 +1296,7 @@ fn extract_args(value: RespArray, start: usize) -> Result<Vec<RespFrame>, Comman
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{RespDecode, RespNull};
+    use crate::{BulkString, RespDecode, RespEncode, RespNull};
     use anyhow::Result;
     use bytes::BytesMut;
 
@@ -240,4 +1316,163 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_emptied_collections_are_removed() -> Result<()> {
+        let cases = [
+            ("hset k f v", "hdel k f"),
+            ("hset k f v", "hgetdel k FIELDS 1 f"),
+            ("sadd k m", "srem k m"),
+            ("sadd k m", "spop k"),
+            ("sadd k m", "spop k 3"),
+            ("zadd k 1 m", "zrem k m"),
+            ("zadd k 1 m", "zpopmin k"),
+            ("zadd k 1 m", "zpopmax k 2"),
+            ("zadd k 1 m", "zmpop 1 k MIN"),
+            ("rpush k a", "lpop k"),
+            ("rpush k a", "rpop k 2"),
+            ("rpush k a", "lrem k 0 a"),
+            ("rpush k a", "ltrim k 1 0"),
+            ("rpush k a", "lmpop 1 k LEFT"),
+        ];
+        for (create, empty) in cases {
+            let backend = Backend::new();
+            let run = |cmd: &str| -> Result<RespFrame> {
+                let args = cmd
+                    .split(' ')
+                    .map(|arg| BulkString::from(arg).into())
+                    .collect::<Vec<RespFrame>>();
+                Ok(Command::try_from(RespArray::new(args))?.execute(&backend))
+            };
+            run(create)?;
+            run("expire k 100")?;
+            assert!(!matches!(run(empty)?, RespFrame::Error(_)), "{empty}");
+
+            assert_eq!(run("exists k")?, RespFrame::Integer(0), "{empty}");
+            assert_eq!(run("dbsize")?, RespFrame::Integer(0), "{empty}");
+            assert_eq!(backend.key_type("k"), None, "{empty}");
+            // 重新创建的 key 不应继承之前的 TTL
+            run(create)?;
+            assert_eq!(run("ttl k")?, RespFrame::Integer(-1), "{empty}");
+        }
+
+        // 报错或结果为空的写入不会创建 key
+        for cmd in [
+            "hincrbyfloat k f inf",
+            "sinterstore k s missing",
+            "zinterstore k 2 z missing",
+        ] {
+            let backend = Backend::new();
+            let run = |cmd: &str| -> Result<RespFrame> {
+                let args = cmd
+                    .split(' ')
+                    .map(|arg| BulkString::from(arg).into())
+                    .collect::<Vec<RespFrame>>();
+                Ok(Command::try_from(RespArray::new(args))?.execute(&backend))
+            };
+            run("sadd s m")?;
+            run("zadd z 1 m")?;
+            run(cmd)?;
+            assert_eq!(run("exists k")?, RespFrame::Integer(0), "{cmd}");
+            assert_eq!(run("dbsize")?, RespFrame::Integer(2), "{cmd}");
+        }
+
+        // RESTORE 空集合的 payload 被拒绝，同样不会留下空 key
+        for kind in ["hash", "set", "list", "zset"] {
+            let backend = Backend::new();
+            let payload = RespArray::new(vec![BulkString::from(kind).into()]).encode();
+            let restore = RespArray::new(vec![
+                BulkString::from("restore").into(),
+                BulkString::from("k").into(),
+                BulkString::from("0").into(),
+                BulkString::new(payload).into(),
+            ]);
+            let ret = Command::try_from(restore)?.execute(&backend);
+            assert!(matches!(ret, RespFrame::Error(_)), "{kind}");
+            assert_eq!(backend.key_type("k"), None, "{kind}");
+            assert_eq!(backend.dbsize(), 0, "{kind}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_command_name_on_short_array() {
+        let value = RespArray::new(vec![BulkString::from("config").into()]);
+        let ret = validate_command_name(&value, &["config", "get"]);
+        assert!(matches!(ret, Err(CommandError::InvalidCommand(_))));
+
+        let value = RespArray::new(vec![]);
+        let ret = validate_command(&value, &["config", "get"], 1);
+        assert!(matches!(ret, Err(CommandError::WrongArity(_))));
+    }
+
+    #[test]
+    fn test_allowed_in_subscribe_mode() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$3\r\nget\r\n$5\r\nhello\r\n*1\r\n$4\r\nping\r\n");
+
+        let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+        assert!(!cmd.allowed_in_subscribe_mode());
+
+        let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+        assert!(cmd.allowed_in_subscribe_mode());
+
+        // QUIT 和 RESET 同样可以退出订阅状态
+        buf.extend_from_slice(b"*1\r\n$4\r\nquit\r\n*1\r\n$5\r\nreset\r\n");
+        for _ in 0..2 {
+            let cmd: Command = RespArray::decode(&mut buf)?.try_into()?;
+            assert!(cmd.allowed_in_subscribe_mode());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rename_command_table() -> Result<()> {
+        let backend = Backend::new();
+        let request = |args: &[&str]| -> RespFrame {
+            RespArray::new(
+                args.iter()
+                    .map(|arg| BulkString::from(*arg).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into()
+        };
+        let run = |args: &[&str]| -> Result<RespFrame> {
+            Ok(Command::parse(request(args), backend.config())?.execute(&backend))
+        };
+        backend.set("key".to_string(), BulkString::from("value").into());
+
+        backend.config().rename_command("get", "fetch");
+        assert!(matches!(
+            Command::parse(request(&["fetch", "key"]), backend.config())?,
+            Command::Get(_)
+        ));
+        assert_eq!(run(&["fetch", "key"])?, BulkString::from("value").into());
+        assert_eq!(
+            run(&["get", "key"])?,
+            RespFrame::error(
+                "ERR",
+                "unknown command 'get', with args beginning with: 'key' "
+            )
+        );
+
+        backend.config().rename_command("flushdb", "");
+        assert!(matches!(
+            Command::parse(request(&["flushdb"]), backend.config())?,
+            Command::Unrecognized(_)
+        ));
+        assert_eq!(
+            run(&["flushdb"])?,
+            RespFrame::error(
+                "ERR",
+                "unknown command 'flushdb', with args beginning with: "
+            )
+        );
+        assert_eq!(run(&["fetch", "key"])?, BulkString::from("value").into());
+
+        // 没有改名的命令不受影响
+        assert_eq!(run(&["del", "key"])?, RespFrame::Integer(1));
+        Ok(())
+    }
 }
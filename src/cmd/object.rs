@@ -0,0 +1,96 @@
+use crate::{BulkString, RespArray, RespFrame, RespNull};
+
+use super::{
+    extract_args, validate_command, CommandError, CommandExecutor, Object, ObjectSubcommand,
+};
+
+impl CommandExecutor for Object {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match self.subcommand {
+            ObjectSubcommand::Encoding(key) => match backend.object_encoding(&key) {
+                Some(encoding) => BulkString::from(encoding).into(),
+                None => RespFrame::Null(RespNull),
+            },
+            ObjectSubcommand::IdleTime(key) => match backend.object_idletime(&key) {
+                Some(idle) => RespFrame::Integer(idle as i64),
+                None => RespFrame::Null(RespNull),
+            },
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Object {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["object"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(sub)), Some(RespFrame::BulkString(key)))
+                if sub.eq_ignore_ascii_case(b"encoding") =>
+            {
+                Ok(Object {
+                    subcommand: ObjectSubcommand::Encoding(String::from_utf8(key.get_data()?)?),
+                })
+            }
+            (Some(RespFrame::BulkString(sub)), Some(RespFrame::BulkString(key)))
+                if sub.eq_ignore_ascii_case(b"idletime") =>
+            {
+                Ok(Object {
+                    subcommand: ObjectSubcommand::IdleTime(String::from_utf8(key.get_data()?)?),
+                })
+            }
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid OBJECT subcommand or key".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Backend, RespDecode};
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_object_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nobject\r\n$8\r\nENCODING\r\n$4\r\nlist\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result: Object = frame.try_into()?;
+        assert!(matches!(result.subcommand, ObjectSubcommand::Encoding(ref key) if key == "list"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_encoding_list_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.config().set_list_max_listpack_size(8);
+        let encoding = |backend: &Backend| {
+            Object {
+                subcommand: ObjectSubcommand::Encoding("list".to_string()),
+            }
+            .execute(backend)
+        };
+        assert_eq!(encoding(&backend), RespFrame::Null(RespNull));
+
+        backend
+            .rpush("list".to_string(), vec![b"a".into(); 3])
+            .unwrap();
+        assert_eq!(encoding(&backend), BulkString::from("listpack").into());
+
+        backend
+            .rpush("list".to_string(), vec![b"a".into(); 10])
+            .unwrap();
+        assert_eq!(encoding(&backend), BulkString::from("quicklist").into());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use crate::{BulkString, RespArray, RespFrame, SimpleString};
+
+use super::{extract_args, validate_command_at_least, CommandError, CommandExecutor, Ping};
+
+impl CommandExecutor for Ping {
+    fn execute(self, _: &crate::Backend) -> RespFrame {
+        match self.message {
+            Some(message) => BulkString::from(message).into(),
+            None => SimpleString::new("PONG").into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Ping {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["ping"], 0)?;
+        if value.len() > 2 {
+            return Err(CommandError::InvalidArgument(
+                "ping command must have at most 1 argument".to_string(),
+            ));
+        }
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(message)) => Ok(Ping {
+                message: Some(String::from_utf8(message.get_data()?)?),
+            }),
+            None => Ok(Ping { message: None }),
+            _ => Err(CommandError::InvalidArgument("Invalid message".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Backend, RespDecode};
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_ping_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*1\r\n$4\r\nping\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result: Ping = frame.try_into()?;
+        assert_eq!(result.message, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ping_command() -> Result<()> {
+        let backend = Backend::new();
+        let result = Ping { message: None }.execute(&backend);
+        assert_eq!(result, SimpleString::new("PONG").into());
+
+        let result = Ping {
+            message: Some("hello".to_string()),
+        }
+        .execute(&backend);
+        assert_eq!(result, BulkString::from("hello").into());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{BulkString, RespArray, RespFrame, Subscriber};
+
+use super::{
+    extract_args, validate_command, validate_command_at_least, CommandError, CommandExecutor,
+    PSubscribe, PUnsubscribe, Publish, Pubsub, PubsubSubcommand, Subscribe, Unsubscribe,
+};
+
+impl CommandExecutor for Publish {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let n = backend.publish(&self.channel, self.message);
+        RespFrame::Integer(n as i64)
+    }
+}
+
+impl CommandExecutor for Pubsub {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match self.subcommand {
+            PubsubSubcommand::Channels(pattern) => {
+                let channels = backend.pubsub_channels(pattern.as_deref());
+                RespArray::new(
+                    channels
+                        .into_iter()
+                        .map(|channel| BulkString::from(channel).into())
+                        .collect::<Vec<RespFrame>>(),
+                )
+                .into()
+            }
+        }
+    }
+}
+
+// 订阅类命令需要连接上的 Subscriber，由 network 层调用 apply 执行
+impl CommandExecutor for Subscribe {
+    fn execute(self, _: &crate::Backend) -> RespFrame {
+        subscriber_required("subscribe")
+    }
+}
+
+impl CommandExecutor for Unsubscribe {
+    fn execute(self, _: &crate::Backend) -> RespFrame {
+        subscriber_required("unsubscribe")
+    }
+}
+
+impl CommandExecutor for PSubscribe {
+    fn execute(self, _: &crate::Backend) -> RespFrame {
+        subscriber_required("psubscribe")
+    }
+}
+
+impl CommandExecutor for PUnsubscribe {
+    fn execute(self, _: &crate::Backend) -> RespFrame {
+        subscriber_required("punsubscribe")
+    }
+}
+
+impl Subscribe {
+    pub fn apply(self, subscriber: &mut Subscriber) -> Vec<RespFrame> {
+        self.channels
+            .into_iter()
+            .map(|channel| subscriber.subscribe(channel))
+            .collect()
+    }
+}
+
+impl Unsubscribe {
+    pub fn apply(self, subscriber: &mut Subscriber) -> Vec<RespFrame> {
+        subscriber.unsubscribe(self.channels)
+    }
+}
+
+impl PSubscribe {
+    pub fn apply(self, subscriber: &mut Subscriber) -> Vec<RespFrame> {
+        self.patterns
+            .into_iter()
+            .map(|pattern| subscriber.psubscribe(pattern))
+            .collect()
+    }
+}
+
+impl PUnsubscribe {
+    pub fn apply(self, subscriber: &mut Subscriber) -> Vec<RespFrame> {
+        subscriber.punsubscribe(self.patterns)
+    }
+}
+
+fn subscriber_required(name: &str) -> RespFrame {
+    RespFrame::error(
+        "ERR",
+        &format!("{} is only allowed on a client connection", name),
+    )
+}
+
+impl TryFrom<RespArray> for Publish {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["publish"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(channel)), Some(message)) => Ok(Publish {
+                channel: String::from_utf8(channel.get_data()?)?,
+                message,
+            }),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid channel or message".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Pubsub {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["pubsub"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(RespFrame::BulkString(sub)), pattern, None)
+                if sub.eq_ignore_ascii_case(b"channels") =>
+            {
+                let pattern = match pattern {
+                    Some(RespFrame::BulkString(pattern)) => {
+                        Some(String::from_utf8(pattern.get_data()?)?)
+                    }
+                    Some(_) => {
+                        return Err(CommandError::InvalidArgument("Invalid pattern".to_string()))
+                    }
+                    None => None,
+                };
+                Ok(Pubsub {
+                    subcommand: PubsubSubcommand::Channels(pattern),
+                })
+            }
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid PUBSUB subcommand".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Subscribe {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["subscribe"], 1)?;
+
+        Ok(Subscribe {
+            channels: extract_names(value)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Unsubscribe {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["unsubscribe"], 0)?;
+
+        Ok(Unsubscribe {
+            channels: extract_names(value)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PSubscribe {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["psubscribe"], 1)?;
+
+        Ok(PSubscribe {
+            patterns: extract_names(value)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PUnsubscribe {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["punsubscribe"], 0)?;
+
+        Ok(PUnsubscribe {
+            patterns: extract_names(value)?,
+        })
+    }
+}
+
+fn extract_names(value: RespArray) -> Result<Vec<String>, CommandError> {
+    let args = extract_args(value, 1)?;
+    let mut names = Vec::with_capacity(args.len());
+    for arg in args {
+        match arg {
+            RespFrame::BulkString(name) => names.push(String::from_utf8(name.get_data()?)?),
+            _ => return Err(CommandError::InvalidArgument("Invalid channel".to_string())),
+        }
+    }
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Backend, RespDecode, RespPush};
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_publish_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$7\r\npublish\r\n$4\r\nnews\r\n$5\r\nhello\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result: Publish = frame.try_into()?;
+        assert_eq!(result.channel, "news");
+        assert_eq!(result.message, RespFrame::BulkString(b"hello".into()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_subscribe_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$9\r\nsubscribe\r\n$1\r\na\r\n$1\r\nb\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result: Subscribe = frame.try_into()?;
+        assert_eq!(result.channels, vec!["a", "b"]);
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*1\r\n$11\r\nunsubscribe\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result: Unsubscribe = frame.try_into()?;
+        assert!(result.channels.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_subscribe_publish_commands() -> Result<()> {
+        let backend = Backend::new();
+        let mut subscriber = backend.subscriber();
+        let cmd = Subscribe {
+            channels: vec!["a".to_string(), "b".to_string()],
+        };
+        let frames = cmd.apply(&mut subscriber);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(subscriber.subscription_count(), 2);
+
+        let cmd = Publish {
+            channel: "b".to_string(),
+            message: BulkString::from("hello").into(),
+        };
+        let result = cmd.execute(&backend);
+        assert_eq!(result, RespFrame::Integer(1));
+
+        let message = subscriber.try_recv().unwrap().into_frame();
+        let expected = RespPush::new(vec![
+            BulkString::from("message").into(),
+            BulkString::from("b").into(),
+            BulkString::from("hello").into(),
+        ]);
+        assert_eq!(message, expected.into());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pubsub_channels_drops_empty_channel() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$6\r\npubsub\r\n$8\r\nCHANNELS\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let channels = || Pubsub::try_from(frame.clone());
+
+        let backend = Backend::new();
+        let mut subscriber = backend.subscriber();
+        Subscribe {
+            channels: vec!["news".to_string()],
+        }
+        .apply(&mut subscriber);
+        assert_eq!(
+            channels()?.execute(&backend),
+            RespArray::new(vec![BulkString::from("news").into()]).into()
+        );
+
+        Unsubscribe {
+            channels: vec!["news".to_string()],
+        }
+        .apply(&mut subscriber);
+        assert_eq!(
+            channels()?.execute(&backend),
+            RespArray::new(Vec::<RespFrame>::new()).into()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,280 @@
+use crate::{BulkString, ListEnd, RespArray, RespFrame};
+
+use super::{Command, GetEx, GetExExpiry, HGetEx};
+
+/// How an executed write command is recorded in the AOF.
+#[derive(Debug, PartialEq)]
+pub enum AofEntry {
+    /// Append the command as it was received.
+    Verbatim,
+    /// Append these equivalent commands instead.
+    Rewritten(Vec<RespFrame>),
+    /// SPOP of this key, recorded as SREM of the members in the reply.
+    PoppedMembers(String),
+    /// BLPOP / BRPOP, recorded as LPOP / RPOP of the key in the reply.
+    BlockingPop(ListEnd),
+}
+
+impl Command {
+    /// Decide how to record the command before executing it. Relative expire
+    /// times are made absolute against `unix_millis`, the current time, so
+    /// replaying the AOF later gives the same deadlines.
+    pub fn aof_entry(&self, unix_millis: i64) -> AofEntry {
+        // 溢出时命令本身会报错，不会写入 AOF
+        let at = |ttl_millis: i64| unix_millis.checked_add(ttl_millis);
+        let rewritten = |frames: Option<Vec<RespFrame>>| match frames {
+            Some(frames) => AofEntry::Rewritten(frames),
+            None => AofEntry::Verbatim,
+        };
+        match self {
+            Command::Expire(cmd) => rewritten(
+                cmd.seconds
+                    .checked_mul(1000)
+                    .and_then(at)
+                    .map(|at| vec![pexpireat(&cmd.key, at)]),
+            ),
+            Command::PExpire(cmd) => {
+                rewritten(at(cmd.milliseconds).map(|at| vec![pexpireat(&cmd.key, at)]))
+            }
+            Command::GetEx(GetEx {
+                key,
+                expiry: Some(GetExExpiry::Ttl(ttl)),
+            }) => rewritten(
+                i64::try_from(ttl.as_millis())
+                    .ok()
+                    .and_then(at)
+                    .map(|at| vec![pexpireat(key, at)]),
+            ),
+            Command::HExpire(cmd) => rewritten(
+                i64::try_from(cmd.seconds.saturating_mul(1000))
+                    .ok()
+                    .and_then(at)
+                    .map(|at| vec![hgetex_pxat(&cmd.key, at, &cmd.fields)]),
+            ),
+            Command::HGetEx(HGetEx {
+                key,
+                fields,
+                expiry: Some(GetExExpiry::Ttl(ttl)),
+            }) => rewritten(
+                i64::try_from(ttl.as_millis())
+                    .ok()
+                    .and_then(at)
+                    .map(|at| vec![hgetex_pxat(key, at, fields)]),
+            ),
+            // RESTORE 先不带 TTL 恢复，再单独设置绝对的过期时间
+            Command::Restore(cmd) => match cmd.ttl {
+                Some(ttl) => {
+                    rewritten(i64::try_from(ttl.as_millis()).ok().and_then(at).map(|at| {
+                        let mut args = vec![
+                            BulkString::from("restore").into(),
+                            BulkString::from(cmd.key.as_str()).into(),
+                            BulkString::from("0").into(),
+                            BulkString::new(cmd.payload.clone()).into(),
+                        ];
+                        if cmd.replace {
+                            args.push(BulkString::from("replace").into());
+                        }
+                        vec![RespArray::new(args).into(), pexpireat(&cmd.key, at)]
+                    }))
+                }
+                None => AofEntry::Verbatim,
+            },
+            Command::SPop(cmd) => AofEntry::PoppedMembers(cmd.key.clone()),
+            Command::BLPop(_) => AofEntry::BlockingPop(ListEnd::Left),
+            Command::BRPop(_) => AofEntry::BlockingPop(ListEnd::Right),
+            // 超时的 BLMOVE 重放成 LMOVE 时 source 同样为空，不会有变化
+            Command::BLMove(cmd) => AofEntry::Rewritten(vec![command(&[
+                "lmove",
+                &cmd.source,
+                &cmd.destination,
+                list_end(cmd.wherefrom),
+                list_end(cmd.whereto),
+            ])]),
+            _ => AofEntry::Verbatim,
+        }
+    }
+}
+
+impl AofEntry {
+    /// The frames to append for a command received as `frame` that replied
+    /// with `reply`.
+    pub fn into_frames(self, frame: RespFrame, reply: &RespFrame) -> Vec<RespFrame> {
+        match self {
+            AofEntry::Verbatim => vec![frame],
+            AofEntry::Rewritten(frames) => frames,
+            AofEntry::PoppedMembers(key) => {
+                let members = match reply {
+                    RespFrame::BulkString(member) => vec![member.clone().into()],
+                    RespFrame::Array(RespArray(Some(members))) => members.clone(),
+                    // 没有弹出任何成员，数据没有变化
+                    _ => return vec![],
+                };
+                if members.is_empty() {
+                    return vec![];
+                }
+                let mut args = vec![
+                    BulkString::from("srem").into(),
+                    BulkString::from(key).into(),
+                ];
+                args.extend(members);
+                vec![RespArray::new(args).into()]
+            }
+            AofEntry::BlockingPop(end) => match reply {
+                // 回复为 [key, value]，超时则为 null
+                RespFrame::Array(RespArray(Some(reply))) => match reply.first() {
+                    Some(RespFrame::BulkString(key)) => {
+                        let name = match end {
+                            ListEnd::Left => "lpop",
+                            ListEnd::Right => "rpop",
+                        };
+                        vec![RespArray::new(vec![
+                            BulkString::from(name).into(),
+                            key.clone().into(),
+                        ])
+                        .into()]
+                    }
+                    _ => vec![],
+                },
+                _ => vec![],
+            },
+        }
+    }
+}
+
+fn list_end(end: ListEnd) -> &'static str {
+    match end {
+        ListEnd::Left => "left",
+        ListEnd::Right => "right",
+    }
+}
+
+fn pexpireat(key: &str, at: i64) -> RespFrame {
+    command(&["pexpireat", key, &at.to_string()])
+}
+
+// 用 HGETEX PXAT 记录字段的绝对过期时间
+fn hgetex_pxat(key: &str, at: i64, fields: &[String]) -> RespFrame {
+    let (at, numfields) = (at.to_string(), fields.len().to_string());
+    let mut args = vec!["hgetex", key, "pxat", &at, "fields", &numfields];
+    args.extend(fields.iter().map(String::as_str));
+    command(&args)
+}
+
+fn command(args: &[&str]) -> RespFrame {
+    RespArray::new(
+        args.iter()
+            .map(|arg| BulkString::from(*arg).into())
+            .collect::<Vec<RespFrame>>(),
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::CommandExecutor;
+    use crate::{Backend, BackendConfig, Lookup};
+    use anyhow::Result;
+
+    fn parse(args: &[&str]) -> Result<Command> {
+        Ok(Command::parse(command(args), &BackendConfig::default())?)
+    }
+
+    #[test]
+    fn test_relative_expires_are_made_absolute() -> Result<()> {
+        let now = 1_700_000_000_000;
+        assert_eq!(
+            parse(&["expire", "k", "10"])?.aof_entry(now),
+            AofEntry::Rewritten(vec![pexpireat("k", now + 10_000)])
+        );
+        assert_eq!(
+            parse(&["pexpire", "k", "-5"])?.aof_entry(now),
+            AofEntry::Rewritten(vec![pexpireat("k", now - 5)])
+        );
+        assert_eq!(
+            parse(&["getex", "k", "px", "250"])?.aof_entry(now),
+            AofEntry::Rewritten(vec![pexpireat("k", now + 250)])
+        );
+        assert_eq!(
+            parse(&["hexpire", "h", "3", "fields", "2", "a", "b"])?.aof_entry(now),
+            AofEntry::Rewritten(vec![command(&[
+                "hgetex",
+                "h",
+                "pxat",
+                &(now + 3000).to_string(),
+                "fields",
+                "2",
+                "a",
+                "b"
+            ])])
+        );
+        // 已经是绝对时间或没有副作用的命令原样记录
+        assert_eq!(
+            parse(&["getex", "k", "persist"])?.aof_entry(now),
+            AofEntry::Verbatim
+        );
+        assert_eq!(
+            parse(&["set", "k", "v"])?.aof_entry(now),
+            AofEntry::Verbatim
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_spop_is_recorded_as_srem() -> Result<()> {
+        let backend = Backend::new();
+        for member in ["a", "b", "c"] {
+            backend.sadd("s".to_string(), member.to_string())?;
+        }
+        let cmd = parse(&["spop", "s", "2"])?;
+        let entry = cmd.aof_entry(0);
+        let reply = cmd.execute(&backend);
+        let frames = entry.into_frames(command(&["spop", "s", "2"]), &reply);
+
+        // 在弹出前的数据上重放 SREM，得到同样的结果
+        let replayed = Backend::new();
+        for member in ["a", "b", "c"] {
+            replayed.sadd("s".to_string(), member.to_string())?;
+        }
+        assert_eq!(frames.len(), 1);
+        Command::try_from(frames[0].clone())?.execute(&replayed);
+        assert_eq!(replayed.smembers("s")?, backend.smembers("s")?);
+
+        // 空集合的 SPOP 不记录
+        let cmd = parse(&["spop", "missing"])?;
+        let entry = cmd.aof_entry(0);
+        let reply = cmd.execute(&backend);
+        assert!(entry
+            .into_frames(command(&["spop", "missing"]), &reply)
+            .is_empty());
+        assert_eq!(backend.get("missing"), Lookup::Missing);
+        Ok(())
+    }
+
+    #[test]
+    fn test_blocking_pops_are_recorded_as_pops() -> Result<()> {
+        let backend = Backend::new();
+        backend.rpush("b".to_string(), vec![b"x".into(), b"y".into()])?;
+        let cmd = parse(&["brpop", "a", "b", "0"])?;
+        let entry = cmd.aof_entry(0);
+        let reply = cmd.execute(&backend);
+        assert_eq!(
+            entry.into_frames(command(&["brpop", "a", "b", "0"]), &reply),
+            vec![command(&["rpop", "b"])]
+        );
+
+        // 超时没有弹出时不记录
+        let cmd = parse(&["blpop", "a", "0.01"])?;
+        let entry = cmd.aof_entry(0);
+        let reply = cmd.execute(&backend);
+        assert!(entry
+            .into_frames(command(&["blpop", "a", "0.01"]), &reply)
+            .is_empty());
+
+        assert_eq!(
+            parse(&["blmove", "b", "c", "LEFT", "RIGHT", "0"])?.aof_entry(0),
+            AofEntry::Rewritten(vec![command(&["lmove", "b", "c", "left", "right"])])
+        );
+        Ok(())
+    }
+}
@@ -1,18 +1,127 @@
-use crate::{RespArray, RespFrame};
+use crate::{BulkString, RespArray, RespFrame, RespNull};
 
-use super::{extract_args, validate_command, CommandError, CommandExecutor, SAdd, SisMember};
+use super::{
+    extract_args, extract_pop_args, extract_store_args, validate_command,
+    validate_command_at_least, CommandError, CommandExecutor, SAdd, SCard, SDiffStore, SInterStore,
+    SMembers, SPop, SRandMember, SRem, SUnionStore, SisMember,
+};
 
 impl CommandExecutor for SAdd {
     fn execute(self, backend: &crate::Backend) -> RespFrame {
-        backend.sadd(self.key, self.member);
-        RespFrame::Integer(1)
+        match backend.sadd(self.key, self.member) {
+            Ok(()) => RespFrame::Integer(1),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SRem {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.srem(&self.key, &self.members) {
+            Ok(removed) => RespFrame::Integer(removed as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SPop {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let members = match backend.spop(&self.key, self.count.unwrap_or(1)) {
+            Ok(members) => members,
+            Err(e) => return e.into(),
+        };
+        match self.count {
+            None => match members.into_iter().next() {
+                Some(member) => BulkString::new(member).into(),
+                None => RespFrame::Null(RespNull),
+            },
+            Some(_) => RespArray::new(
+                members
+                    .into_iter()
+                    .map(|m| BulkString::new(m).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+        }
     }
 }
 
 impl CommandExecutor for SisMember {
     fn execute(self, backend: &crate::Backend) -> RespFrame {
-        let result = backend.sismember(&self.key, &self.member);
-        RespFrame::Integer(if result { 1 } else { 0 })
+        match backend.sismember(&self.key, &self.member) {
+            Ok(found) => RespFrame::Integer(found as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SMembers {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.smembers(&self.key) {
+            Ok(members) => RespArray::new(
+                members
+                    .into_iter()
+                    .map(|m| BulkString::new(m).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SCard {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.scard(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SRandMember {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let members = match backend.srandmember(&self.key, self.count) {
+            Ok(members) => members,
+            Err(e) => return e.into(),
+        };
+        match self.count {
+            None => match members.into_iter().next() {
+                Some(member) => BulkString::new(member).into(),
+                None => RespFrame::Null(RespNull),
+            },
+            Some(_) => RespArray::new(
+                members
+                    .into_iter()
+                    .map(|m| BulkString::new(m).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+        }
+    }
+}
+
+impl CommandExecutor for SInterStore {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let members = match backend.sinter(&self.keys) {
+            Ok(members) => members,
+            Err(e) => return e.into(),
+        };
+        RespFrame::Integer(backend.sstore(self.dest, members) as i64)
+    }
+}
+
+impl CommandExecutor for SUnionStore {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let members = backend.sunion(&self.keys);
+        RespFrame::Integer(backend.sstore(self.dest, members) as i64)
+    }
+}
+
+impl CommandExecutor for SDiffStore {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let members = backend.sdiff(&self.keys);
+        RespFrame::Integer(backend.sstore(self.dest, members) as i64)
     }
 }
 
@@ -35,6 +144,56 @@ impl TryFrom<RespArray> for SAdd {
     }
 }
 
+impl TryFrom<RespArray> for SRem {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, members) = extract_store_args(value, "srem")?;
+        Ok(SRem { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for SPop {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = extract_pop_args(value, "spop")?;
+        Ok(SPop { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for SMembers {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["smembers"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(SMembers {
+                key: String::from_utf8(key.get_data()?)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for SCard {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["scard"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(SCard {
+                key: String::from_utf8(key.get_data()?)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
 impl TryFrom<RespArray> for SisMember {
     type Error = CommandError;
 
@@ -56,13 +215,102 @@ impl TryFrom<RespArray> for SisMember {
     }
 }
 
+impl TryFrom<RespArray> for SRandMember {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["srandmember"], 1)?;
+        if value.len() > 3 {
+            return Err(CommandError::InvalidArgument("syntax error".to_string()));
+        }
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.get_data()?)?,
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let count = match args.next() {
+            Some(RespFrame::BulkString(count)) => Some(
+                String::from_utf8(count.get_data()?)?
+                    .parse::<i64>()
+                    .map_err(|_| {
+                        CommandError::InvalidArgument(
+                            "value is not an integer or out of range".to_string(),
+                        )
+                    })?,
+            ),
+            Some(_) => return Err(CommandError::InvalidArgument("Invalid count".to_string())),
+            None => None,
+        };
+        Ok(SRandMember { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for SInterStore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (dest, keys) = extract_store_args(value, "sinterstore")?;
+        Ok(SInterStore { dest, keys })
+    }
+}
+
+impl TryFrom<RespArray> for SUnionStore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (dest, keys) = extract_store_args(value, "sunionstore")?;
+        Ok(SUnionStore { dest, keys })
+    }
+}
+
+impl TryFrom<RespArray> for SDiffStore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (dest, keys) = extract_store_args(value, "sdiffstore")?;
+        Ok(SDiffStore { dest, keys })
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::RespDecode;
+    use crate::{Backend, BackendError, RespDecode, RespEncode};
 
     use super::*;
     use anyhow::Result;
     use bytes::BytesMut;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_smembers_encode_into_matches_encode() {
+        let backend = Backend::new();
+        backend.config().set_sort_replies(true);
+        for i in 0..100_000 {
+            backend
+                .sadd("big".to_string(), format!("member:{i}"))
+                .unwrap();
+        }
+
+        let mut direct = BytesMut::new();
+        SMembers {
+            key: "big".to_string(),
+        }
+        .execute(&backend)
+        .encode_into(&mut direct);
+
+        let encoded = RespArray::new(
+            backend
+                .smembers("big")
+                .unwrap()
+                .into_iter()
+                .map(|m| BulkString::new(m).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+        .encode();
+        assert_eq!(direct.len(), encoded.len());
+        assert!(direct[..] == encoded[..]);
+    }
 
     #[test]
     fn test_sadd_from_resp_array() -> Result<()> {
@@ -118,4 +366,368 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_set_reads_on_wrong_type() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("str".to_string(), BulkString::from("v").into());
+        backend
+            .hset(
+                "hash".to_string(),
+                "f".to_string(),
+                BulkString::from("v").into(),
+            )
+            .unwrap();
+        backend.sadd("set".to_string(), "m".to_string()).unwrap();
+        let command = |args: &[&str]| -> Result<RespFrame> {
+            let frame = RespArray::new(
+                args.iter()
+                    .map(|arg| BulkString::from(*arg).into())
+                    .collect::<Vec<RespFrame>>(),
+            );
+            Ok(match args[0] {
+                "sismember" => SisMember::try_from(frame)?.execute(&backend),
+                "smembers" => SMembers::try_from(frame)?.execute(&backend),
+                _ => SCard::try_from(frame)?.execute(&backend),
+            })
+        };
+
+        for key in ["str", "hash"] {
+            for args in [
+                &["sismember", key, "m"][..],
+                &["smembers", key],
+                &["scard", key],
+            ] {
+                assert_eq!(command(args)?, BackendError::WrongType.into(), "{:?}", args);
+            }
+        }
+        assert_eq!(command(&["sismember", "set", "m"])?, RespFrame::Integer(1));
+        assert_eq!(command(&["scard", "set"])?, RespFrame::Integer(1));
+        assert_eq!(command(&["scard", "missing"])?, RespFrame::Integer(0));
+        assert_eq!(
+            command(&["sismember", "missing", "m"])?,
+            RespFrame::Integer(0)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_encoding_never_downgrades_on_srem() -> Result<()> {
+        let backend = Backend::new();
+        backend.config.set_set_max_intset_entries(3);
+        backend.config.set_set_max_listpack_entries(5);
+        backend.config.set_set_max_listpack_value(8);
+        let add = |member: &str| {
+            SAdd {
+                key: "key".to_string(),
+                member: member.to_string(),
+            }
+            .execute(&backend)
+        };
+        let encoding = || backend.object_encoding("key");
+
+        for member in ["1", "2", "3"] {
+            add(member);
+        }
+        assert_eq!(encoding(), Some("intset"));
+        // 非整数成员转为 listpack
+        add("a");
+        assert_eq!(encoding(), Some("listpack"));
+        // 超过 listpack 条数后转为 hashtable
+        add("b");
+        add("c");
+        assert_eq!(encoding(), Some("hashtable"));
+
+        let cmd: SRem = RespArray::new(
+            ["srem", "key", "a", "b", "c", "missing"]
+                .iter()
+                .map(|s| BulkString::from(*s).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+        .try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+        // 只剩少量整数成员，编码仍保持 hashtable
+        assert_eq!(encoding(), Some("hashtable"));
+
+        // 删除最后的成员后 key 不存在，重新创建时从 intset 开始
+        SRem {
+            key: "key".to_string(),
+            members: vec!["1".to_string(), "2".to_string(), "3".to_string()],
+        }
+        .execute(&backend);
+        assert!(!backend.set.contains_key("key"));
+        add("1");
+        assert_eq!(encoding(), Some("intset"));
+
+        // 过长的成员直接转为 hashtable
+        let backend = Backend::new();
+        backend.config.set_set_max_listpack_value(8);
+        backend.sadd("long".to_string(), "a".repeat(9))?;
+        assert_eq!(backend.object_encoding("long"), Some("hashtable"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_srandmember_seeded_is_deterministic() -> Result<()> {
+        let sequence = |backend: &Backend| {
+            for member in ["a", "b", "c", "d", "e", "f"] {
+                backend.sadd("key".to_string(), member.to_string()).unwrap();
+            }
+            (0..8)
+                .map(|_| {
+                    SRandMember {
+                        key: "key".to_string(),
+                        count: None,
+                    }
+                    .execute(backend)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let a = sequence(&Backend::new_seeded(42));
+        let b = sequence(&Backend::new_seeded(42));
+        assert_eq!(a, b);
+        assert!(a.iter().all(|v| matches!(v, RespFrame::BulkString(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_srandmember_uniform_for_every_encoding() -> Result<()> {
+        const DRAWS_PER_MEMBER: usize = 1000;
+
+        let backend = Backend::new_seeded(7);
+        let sets: [(&str, Vec<String>); 3] = [
+            ("ints", (0..8).map(|i| i.to_string()).collect()),
+            ("small", (0..8).map(|i| format!("m{}", i)).collect()),
+            // 超过 set-max-listpack-value 的长成员
+            (
+                "large",
+                (0..40)
+                    .map(|i| format!("{}{}", "x".repeat(70), i))
+                    .collect(),
+            ),
+        ];
+        for (key, members) in &sets {
+            for member in members {
+                backend.sadd(key.to_string(), member.clone())?;
+            }
+        }
+        assert_eq!(backend.object_encoding("ints"), Some("intset"));
+        assert_eq!(backend.object_encoding("small"), Some("listpack"));
+        assert_eq!(backend.object_encoding("large"), Some("hashtable"));
+
+        for (key, members) in &sets {
+            let mut counts: HashMap<String, usize> = HashMap::new();
+            for _ in 0..members.len() * DRAWS_PER_MEMBER {
+                let drawn = backend.srandmember(key, None).unwrap();
+                *counts.entry(drawn[0].clone()).or_default() += 1;
+            }
+            // 每个成员都应被抽到，且次数在期望值的 ±20% 以内
+            assert_eq!(counts.len(), members.len(), "{}", key);
+            for (member, count) in counts {
+                assert!(
+                    (800..=1200).contains(&count),
+                    "{}: {} drawn {} times",
+                    key,
+                    member,
+                    count
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_srandmember_count() -> Result<()> {
+        let backend = Backend::new_seeded(1);
+        backend.sadd("key".to_string(), "a".to_string()).unwrap();
+        backend.sadd("key".to_string(), "b".to_string()).unwrap();
+
+        let cmd = SRandMember {
+            key: "key".to_string(),
+            count: Some(5),
+        };
+        let RespFrame::Array(distinct) = cmd.execute(&backend) else {
+            panic!("expected array");
+        };
+        assert_eq!(distinct.len(), 2);
+
+        let cmd = SRandMember {
+            key: "key".to_string(),
+            count: Some(-5),
+        };
+        let RespFrame::Array(repeated) = cmd.execute(&backend) else {
+            panic!("expected array");
+        };
+        assert_eq!(repeated.len(), 5);
+
+        // 无法分配的负数 count 返回错误而不是 panic
+        for count in [i64::MIN, i64::MIN + 1] {
+            let cmd = SRandMember {
+                key: "key".to_string(),
+                count: Some(count),
+            };
+            assert_eq!(
+                cmd.execute(&backend),
+                RespFrame::error("ERR", "value is out of range")
+            );
+        }
+
+        let cmd = SRandMember {
+            key: "missing".to_string(),
+            count: None,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sinterstore_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*4\r\n$11\r\nsinterstore\r\n$4\r\ndest\r\n$2\r\ns1\r\n$2\r\ns2\r\n",
+        );
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result: SInterStore = frame.try_into()?;
+        assert_eq!(result.dest, "dest");
+        assert_eq!(result.keys, vec!["s1", "s2"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sinterstore_command() -> Result<()> {
+        let backend = Backend::new();
+        for member in ["a", "b", "c"] {
+            backend.sadd("s1".to_string(), member.to_string()).unwrap();
+        }
+        for member in ["b", "c", "d"] {
+            backend.sadd("s2".to_string(), member.to_string()).unwrap();
+        }
+        backend.sadd("s3".to_string(), "x".to_string()).unwrap();
+
+        let cmd = SInterStore {
+            dest: "dest".to_string(),
+            keys: vec!["s1".to_string(), "s2".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert!(backend.sismember("dest", "b").unwrap());
+        assert!(backend.sismember("dest", "c").unwrap());
+        assert!(!backend.sismember("dest", "a").unwrap());
+
+        // 结果为空时删除已存在的 dest
+        let cmd = SInterStore {
+            dest: "dest".to_string(),
+            keys: vec!["s1".to_string(), "s3".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert_eq!(backend.key_type("dest"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sinter_on_wrong_type() -> Result<()> {
+        let backend = Backend::new();
+        backend.sadd("s".to_string(), "a".to_string())?;
+        backend.sadd("dest".to_string(), "old".to_string())?;
+        backend.set("str".to_string(), BulkString::from("v").into());
+        let keys = |keys: &[&str]| keys.iter().map(|k| k.to_string()).collect::<Vec<_>>();
+
+        // 不存在的 key 在前面时同样要检查后面 key 的类型
+        for keys in [keys(&["s", "str"]), keys(&["missing", "str"])] {
+            assert_eq!(backend.sinter(&keys), Err(BackendError::WrongType));
+            let cmd = SInterStore {
+                dest: "dest".to_string(),
+                keys,
+            };
+            assert_eq!(cmd.execute(&backend), BackendError::WrongType.into());
+            // 报错时 dest 保持不变
+            assert!(backend.sismember("dest", "old")?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_sstore_overwrites_other_types() -> Result<()> {
+        let backend = Backend::new();
+        backend.sadd("s1".to_string(), "a".to_string())?;
+        backend.set("dest".to_string(), BulkString::from("v").into());
+
+        let cmd = SUnionStore {
+            dest: "dest".to_string(),
+            keys: vec!["s1".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.key_type("dest"), Some("set"));
+        assert!(!backend.map.contains_key("dest"));
+
+        // 结果为空时原来的字符串同样被删除
+        backend.set("dest".to_string(), BulkString::from("v").into());
+        let cmd = SInterStore {
+            dest: "dest".to_string(),
+            keys: vec!["s1".to_string(), "missing".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert_eq!(backend.key_type("dest"), None);
+        assert!(!backend.access.contains_key("dest"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sunionstore_sdiffstore_commands() -> Result<()> {
+        let backend = Backend::new();
+        for member in ["a", "b"] {
+            backend.sadd("s1".to_string(), member.to_string()).unwrap();
+        }
+        backend.sadd("s2".to_string(), "b".to_string()).unwrap();
+        let keys = vec!["s1".to_string(), "s2".to_string()];
+
+        let cmd = SUnionStore {
+            dest: "union".to_string(),
+            keys: keys.clone(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+
+        let cmd = SDiffStore {
+            dest: "diff".to_string(),
+            keys,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert!(backend.sismember("diff", "a").unwrap());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sinterstore_short_circuits_on_missing_key() {
+        let backend = Backend::new();
+        for i in 0..1000 {
+            backend.sadd("big1".to_string(), i.to_string()).unwrap();
+            backend.sadd("big2".to_string(), i.to_string()).unwrap();
+        }
+        backend.sadd("dest".to_string(), "old".to_string()).unwrap();
+
+        let cmd = SInterStore {
+            dest: "dest".to_string(),
+            keys: vec![
+                "big1".to_string(),
+                "big2".to_string(),
+                "missing".to_string(),
+            ],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert_eq!(backend.key_type("dest"), None);
+        // 两个大集合都没有被完整读取
+        assert_eq!(
+            backend.set_reads.load(std::sync::atomic::Ordering::Relaxed),
+            0
+        );
+    }
 }
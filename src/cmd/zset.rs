@@ -0,0 +1,1609 @@
+use crate::{
+    Aggregate, BulkString, LexBound, RespArray, RespFrame, RespNull, ScoreBound, ZAddOptions,
+    ZRangeBy,
+};
+
+use super::{
+    extract_args, extract_mpop_args, extract_pop_args, extract_store_args, parse_float,
+    parse_integer, validate_command, validate_command_at_least, CommandError, CommandExecutor,
+    ZAdd, ZIncrBy, ZInterStore, ZMPop, ZMScore, ZPopMax, ZPopMin, ZRange, ZRangeByLex, ZRangeStore,
+    ZRank, ZRem, ZRevRange, ZRevRangeByScore, ZRevRank, ZScore, ZUnionStore,
+};
+use crate::{ConnectionContext, RespVersion};
+
+// dest, keys, weights, aggregate
+type ZStoreArgs = (String, Vec<String>, Vec<f64>, Aggregate);
+
+impl CommandExecutor for ZAdd {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        if self.options.incr {
+            // 解析时已保证 INCR 只有一对 score/member
+            let Some((increment, member)) = self.members.into_iter().next() else {
+                return RespFrame::Null(RespNull);
+            };
+            return match backend.zincrby_with(self.key, member, increment, self.options) {
+                Ok(Some(score)) => RespFrame::Double(score),
+                Ok(None) => RespFrame::Null(RespNull),
+                Err(e) => e.into(),
+            };
+        }
+        match backend.zadd_with(self.key, self.members, self.options) {
+            Ok(count) => RespFrame::Integer(count as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZIncrBy {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let options = ZAddOptions {
+            incr: true,
+            ..Default::default()
+        };
+        match backend.zincrby_with(self.key, self.member, self.increment, options) {
+            Ok(Some(score)) => RespFrame::Double(score),
+            // 没有 NX/XX 等条件，更新不会被跳过
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+// RESP3 下 score 为 double，RESP2 连接在编码时会转换成 bulk string
+impl CommandExecutor for ZMPop {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.zmpop(&self.keys, self.min, self.count.unwrap_or(1)) {
+            Ok(Some((key, members))) => {
+                let members = members
+                    .into_iter()
+                    .map(|(member, score)| {
+                        RespArray::new(vec![
+                            BulkString::from(member).into(),
+                            RespFrame::Double(score),
+                        ])
+                        .into()
+                    })
+                    .collect::<Vec<RespFrame>>();
+                RespArray::new(vec![
+                    BulkString::from(key).into(),
+                    RespArray::new(members).into(),
+                ])
+                .into()
+            }
+            Ok(None) => RespArray::null().into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZPopMin {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        self.apply(backend, RespVersion::default())
+    }
+
+    fn execute_with(self, backend: &crate::Backend, ctx: &ConnectionContext) -> RespFrame {
+        let version = backend.config().reply_version(ctx.version);
+        self.apply(backend, version)
+    }
+}
+
+impl ZPopMin {
+    /// Without a count the reply is a flat `[member, score]`, with a count
+    /// RESP3 nests each pair like ZRANGE WITHSCORES does.
+    fn apply(self, backend: &crate::Backend, version: RespVersion) -> RespFrame {
+        let keys = [self.key];
+        let members = match backend.zmpop(&keys, self.min, self.count.unwrap_or(1)) {
+            Ok(Some((_, members))) => members,
+            Ok(None) => vec![],
+            Err(e) => return e.into(),
+        };
+        let frames: Vec<RespFrame> = match (self.count, version) {
+            (Some(_), RespVersion::Resp3) => members
+                .into_iter()
+                .map(|(member, score)| {
+                    RespArray::new(vec![
+                        BulkString::from(member).into(),
+                        RespFrame::Double(score),
+                    ])
+                    .into()
+                })
+                .collect(),
+            _ => members
+                .into_iter()
+                .flat_map(|(member, score)| {
+                    [BulkString::from(member).into(), RespFrame::Double(score)]
+                })
+                .collect(),
+        };
+        RespArray::new(frames).into()
+    }
+}
+
+impl CommandExecutor for ZPopMax {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        self.0.execute(backend)
+    }
+
+    fn execute_with(self, backend: &crate::Backend, ctx: &ConnectionContext) -> RespFrame {
+        self.0.execute_with(backend, ctx)
+    }
+}
+
+impl CommandExecutor for ZRem {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.zrem(&self.key, &self.members) {
+            Ok(removed) => RespFrame::Integer(removed as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZScore {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.zscore(&self.key, &self.member) {
+            Some(score) => RespFrame::Double(score),
+            None => RespFrame::Null(RespNull),
+        }
+    }
+}
+
+impl CommandExecutor for ZRank {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.zrank(&self.key, &self.member, self.rev) {
+            Ok(Some((rank, score))) if self.withscore => RespArray::new(vec![
+                RespFrame::Integer(rank as i64),
+                RespFrame::Double(score),
+            ])
+            .into(),
+            Ok(Some((rank, _))) => RespFrame::Integer(rank as i64),
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZRevRank {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        self.0.execute(backend)
+    }
+}
+
+impl CommandExecutor for ZMScore {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let scores = backend.zmscore(&self.key, &self.members);
+        RespArray::new(
+            scores
+                .into_iter()
+                .map(|score| match score {
+                    Some(score) => RespFrame::Double(score),
+                    None => RespFrame::Null(RespNull),
+                })
+                .collect::<Vec<RespFrame>>(),
+        )
+        .into()
+    }
+}
+
+impl CommandExecutor for ZRange {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        self.apply(backend, RespVersion::default())
+    }
+
+    fn execute_with(self, backend: &crate::Backend, ctx: &ConnectionContext) -> RespFrame {
+        let version = backend.config().reply_version(ctx.version);
+        self.apply(backend, version)
+    }
+}
+
+impl ZRange {
+    /// WITHSCORES replies are `[member, score]` pairs in RESP3 and a flat
+    /// member/score array in RESP2, like Redis.
+    fn apply(self, backend: &crate::Backend, version: RespVersion) -> RespFrame {
+        let members = match backend.zrange(&self.key, &self.range, self.rev, self.limit) {
+            Ok(members) => members,
+            Err(e) => return e.into(),
+        };
+        let frames: Vec<RespFrame> = match (self.withscores, version) {
+            (false, _) => members
+                .into_iter()
+                .map(|(member, _)| BulkString::from(member).into())
+                .collect(),
+            (true, RespVersion::Resp2) => members
+                .into_iter()
+                .flat_map(|(member, score)| {
+                    [BulkString::from(member).into(), RespFrame::Double(score)]
+                })
+                .collect(),
+            (true, RespVersion::Resp3) => members
+                .into_iter()
+                .map(|(member, score)| {
+                    RespArray::new(vec![
+                        BulkString::from(member).into(),
+                        RespFrame::Double(score),
+                    ])
+                    .into()
+                })
+                .collect(),
+        };
+        RespArray::new(frames).into()
+    }
+}
+
+// ZREVRANGE/ZREVRANGEBYSCORE 等价于带 REV 的 ZRANGE，回复格式也相同
+impl CommandExecutor for ZRevRange {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        ZRange::from(self).execute(backend)
+    }
+
+    fn execute_with(self, backend: &crate::Backend, ctx: &ConnectionContext) -> RespFrame {
+        ZRange::from(self).execute_with(backend, ctx)
+    }
+}
+
+impl CommandExecutor for ZRevRangeByScore {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        ZRange::from(self).execute(backend)
+    }
+
+    fn execute_with(self, backend: &crate::Backend, ctx: &ConnectionContext) -> RespFrame {
+        ZRange::from(self).execute_with(backend, ctx)
+    }
+}
+
+impl From<ZRevRange> for ZRange {
+    fn from(cmd: ZRevRange) -> Self {
+        ZRange {
+            key: cmd.key,
+            range: ZRangeBy::Index(cmd.start, cmd.stop),
+            rev: true,
+            limit: None,
+            withscores: cmd.with_scores,
+        }
+    }
+}
+
+impl From<ZRevRangeByScore> for ZRange {
+    fn from(cmd: ZRevRangeByScore) -> Self {
+        ZRange {
+            key: cmd.key,
+            range: ZRangeBy::Score(cmd.min, cmd.max),
+            rev: true,
+            limit: None,
+            withscores: cmd.with_scores,
+        }
+    }
+}
+
+impl CommandExecutor for ZRangeByLex {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let members = backend.zrangebylex(&self.key, &self.min, &self.max);
+        RespArray::new(
+            members
+                .into_iter()
+                .map(|m| BulkString::new(m).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+        .into()
+    }
+}
+
+impl CommandExecutor for ZRangeStore {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        match backend.zrangestore(self.dest, &self.src, &self.range, self.rev, self.limit) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZUnionStore {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let zset = backend.zunion(&self.keys, &self.weights, self.aggregate);
+        RespFrame::Integer(backend.zstore(self.dest, zset) as i64)
+    }
+}
+
+impl CommandExecutor for ZInterStore {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let zset = backend.zinter(&self.keys, &self.weights, self.aggregate);
+        RespFrame::Integer(backend.zstore(self.dest, zset) as i64)
+    }
+}
+
+impl TryFrom<RespArray> for ZAdd {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["zadd"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.get_data()?)?,
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+
+        let mut args = args.peekable();
+        let mut options = ZAddOptions::default();
+        while let Some(RespFrame::BulkString(flag)) = args.peek() {
+            let flag = match flag.to_ascii_lowercase().as_slice() {
+                b"nx" => &mut options.nx,
+                b"xx" => &mut options.xx,
+                b"gt" => &mut options.gt,
+                b"lt" => &mut options.lt,
+                b"ch" => &mut options.ch,
+                b"incr" => &mut options.incr,
+                _ => break,
+            };
+            *flag = true;
+            args.next();
+        }
+        if options.nx && options.xx {
+            return Err(CommandError::InvalidArgument(
+                "XX and NX options at the same time are not compatible".to_string(),
+            ));
+        }
+        if [options.nx, options.gt, options.lt]
+            .iter()
+            .filter(|f| **f)
+            .count()
+            > 1
+        {
+            return Err(CommandError::InvalidArgument(
+                "GT, LT, and/or NX options at the same time are not compatible".to_string(),
+            ));
+        }
+
+        let args: Vec<RespFrame> = args.collect();
+        if args.is_empty() || !args.len().is_multiple_of(2) {
+            return Err(CommandError::InvalidArgument("syntax error".to_string()));
+        }
+        if options.incr && args.len() > 2 {
+            return Err(CommandError::InvalidArgument(
+                "INCR option supports a single increment-element pair".to_string(),
+            ));
+        }
+        let mut members = Vec::with_capacity(args.len() / 2);
+        for pair in args.chunks(2) {
+            let score = parse_float(&pair[0])?;
+            let member = match &pair[1] {
+                RespFrame::BulkString(member) => String::from_utf8(member.get_data()?)?,
+                _ => return Err(CommandError::InvalidArgument("Invalid member".to_string())),
+            };
+            members.push((score, member));
+        }
+        Ok(ZAdd {
+            key,
+            members,
+            options,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZMPop {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, min, count) = extract_mpop_args(value, "zmpop", ["min", "max"])?;
+        Ok(ZMPop { keys, min, count })
+    }
+}
+
+// ZRANK/ZREVRANK key member [WITHSCORE]
+fn parse_zrank(value: RespArray, name: &'static str, rev: bool) -> Result<ZRank, CommandError> {
+    validate_command_at_least(&value, &[name], 2)?;
+    if value.len() > 4 {
+        return Err(CommandError::InvalidArgument("syntax error".to_string()));
+    }
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(member))) =
+        (args.next(), args.next())
+    else {
+        return Err(CommandError::InvalidArgument(
+            "Invalid key or member".to_string(),
+        ));
+    };
+    let withscore = match args.next() {
+        None => false,
+        Some(RespFrame::BulkString(arg)) if arg.eq_ignore_ascii_case(b"withscore") => true,
+        Some(_) => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+    };
+    Ok(ZRank {
+        key: String::from_utf8(key.get_data()?)?,
+        member: String::from_utf8(member.get_data()?)?,
+        withscore,
+        rev,
+    })
+}
+
+impl TryFrom<RespArray> for ZRank {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        parse_zrank(value, "zrank", false)
+    }
+}
+
+impl TryFrom<RespArray> for ZRevRank {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        parse_zrank(value, "zrevrank", true).map(ZRevRank)
+    }
+}
+
+impl TryFrom<RespArray> for ZScore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["zscore"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(member))) => Ok(ZScore {
+                key: String::from_utf8(key.get_data()?)?,
+                member: String::from_utf8(member.get_data()?)?,
+            }),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid key or member".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ZIncrBy {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["zincrby"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (
+                Some(RespFrame::BulkString(key)),
+                Some(increment),
+                Some(RespFrame::BulkString(member)),
+            ) => Ok(ZIncrBy {
+                key: String::from_utf8(key.get_data()?)?,
+                increment: parse_float(&increment)?,
+                member: String::from_utf8(member.get_data()?)?,
+            }),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid key or member".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ZMScore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["zmscore"], 2)?;
+
+        let mut args = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| match arg {
+                RespFrame::BulkString(s) => Ok(String::from_utf8(s.get_data()?)?),
+                _ => Err(CommandError::InvalidArgument(
+                    "Invalid key or member".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<String>, CommandError>>()?;
+        let members = args.split_off(1);
+        Ok(ZMScore {
+            key: args.remove(0),
+            members,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRangeByLex {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["zrangebylex"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (
+                Some(RespFrame::BulkString(key)),
+                Some(RespFrame::BulkString(min)),
+                Some(RespFrame::BulkString(max)),
+            ) => Ok(ZRangeByLex {
+                key: String::from_utf8(key.get_data()?)?,
+                min: parse_lex_bound(&min)?,
+                max: parse_lex_bound(&max)?,
+            }),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid key or range".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ZRange {
+    type Error = CommandError;
+
+    // zrange <key> <start> <stop> [BYSCORE | BYLEX] [REV] [LIMIT offset count] [WITHSCORES]
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["zrange"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.get_data()?)?,
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let options = parse_range_options(args, true)?;
+        Ok(ZRange {
+            key,
+            range: options.range,
+            rev: options.rev,
+            limit: options.limit,
+            withscores: options.withscores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRangeStore {
+    type Error = CommandError;
+
+    // zrangestore <dst> <src> <min> <max> [BYSCORE | BYLEX] [REV] [LIMIT offset count]
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["zrangestore"], 4)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut key = || match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(String::from_utf8(key.get_data()?)?),
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let (dest, src) = (key()?, key()?);
+        let options = parse_range_options(args, false)?;
+        Ok(ZRangeStore {
+            dest,
+            src,
+            range: options.range,
+            rev: options.rev,
+            limit: options.limit,
+        })
+    }
+}
+
+struct RangeOptions {
+    range: ZRangeBy,
+    rev: bool,
+    limit: Option<(i64, i64)>,
+    withscores: bool,
+}
+
+// ZREVRANGE/ZREVRANGEBYSCORE 共用：<key> <a> <b> [WITHSCORES]
+fn parse_rev_range_args(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, Vec<u8>, Vec<u8>, bool), CommandError> {
+    validate_command_at_least(&value, &[name], 3)?;
+    if value.len() > 5 {
+        return Err(CommandError::InvalidArgument("syntax error".to_string()));
+    }
+
+    let mut args = extract_args(value, 1)?.into_iter().map(|arg| match arg {
+        RespFrame::BulkString(arg) => Ok(arg.get_data()?),
+        _ => Err(CommandError::InvalidArgument(
+            "Invalid argument".to_string(),
+        )),
+    });
+    let (Some(key), Some(a), Some(b)) = (args.next(), args.next(), args.next()) else {
+        return Err(CommandError::InvalidArgument("syntax error".to_string()));
+    };
+    let with_scores = match args.next().transpose()? {
+        None => false,
+        Some(arg) if arg.eq_ignore_ascii_case(b"withscores") => true,
+        Some(_) => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+    };
+    Ok((String::from_utf8(key?)?, a?, b?, with_scores))
+}
+
+impl TryFrom<RespArray> for ZPopMin {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = extract_pop_args(value, "zpopmin")?;
+        Ok(ZPopMin {
+            key,
+            count,
+            min: true,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZPopMax {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = extract_pop_args(value, "zpopmax")?;
+        Ok(ZPopMax(ZPopMin {
+            key,
+            count,
+            min: false,
+        }))
+    }
+}
+
+impl TryFrom<RespArray> for ZRem {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, members) = extract_store_args(value, "zrem")?;
+        Ok(ZRem { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for ZRevRange {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, start, stop, with_scores) = parse_rev_range_args(value, "zrevrange")?;
+        Ok(ZRevRange {
+            key,
+            start: parse_integer(&BulkString::new(start).into())?,
+            stop: parse_integer(&BulkString::new(stop).into())?,
+            with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRevRangeByScore {
+    type Error = CommandError;
+
+    // 和 Redis 一样先给上界再给下界
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, max, min, with_scores) = parse_rev_range_args(value, "zrevrangebyscore")?;
+        Ok(ZRevRangeByScore {
+            key,
+            max: parse_score_bound(&max)?,
+            min: parse_score_bound(&min)?,
+            with_scores,
+        })
+    }
+}
+
+// ZRANGE 和 ZRANGESTORE 共用：<start> <stop> 及之后的选项，ZRANGESTORE 不接受 WITHSCORES
+fn parse_range_options(
+    mut args: impl Iterator<Item = RespFrame>,
+    allow_withscores: bool,
+) -> Result<RangeOptions, CommandError> {
+    let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+    let mut next_arg = || match args.next() {
+        Some(RespFrame::BulkString(arg)) => Ok(Some(arg.get_data()?)),
+        Some(_) => Err(CommandError::InvalidArgument(
+            "Invalid argument".to_string(),
+        )),
+        None => Ok(None),
+    };
+    let mut required = || next_arg().and_then(|arg| arg.ok_or_else(syntax_error));
+    let (start, stop) = (required()?, required()?);
+
+    let integer = |arg: Vec<u8>| parse_integer(&BulkString::new(arg).into());
+    let (mut by_score, mut by_lex, mut rev, mut limit) = (false, false, false, None);
+    let mut withscores = false;
+    while let Some(option) = next_arg()? {
+        match option.to_ascii_lowercase().as_slice() {
+            b"byscore" => by_score = true,
+            b"bylex" => by_lex = true,
+            b"rev" => rev = true,
+            b"withscores" if allow_withscores => withscores = true,
+            b"limit" => {
+                let offset = next_arg()?.ok_or_else(syntax_error)?;
+                let count = next_arg()?.ok_or_else(syntax_error)?;
+                limit = Some((integer(offset)?, integer(count)?));
+            }
+            _ => return Err(syntax_error()),
+        }
+    }
+
+    // BYSCORE、BYLEX 加上 REV 时先给出的是上界
+    let (min, max) = if rev {
+        (&stop, &start)
+    } else {
+        (&start, &stop)
+    };
+    let range =
+        match (by_score, by_lex) {
+            (true, true) => return Err(syntax_error()),
+            (true, false) => ZRangeBy::Score(parse_score_bound(min)?, parse_score_bound(max)?),
+            (false, true) if withscores => {
+                return Err(CommandError::InvalidArgument(
+                    "syntax error, WITHSCORES not supported in combination with BYLEX".to_string(),
+                ))
+            }
+            (false, true) => ZRangeBy::Lex(parse_lex_bound(min)?, parse_lex_bound(max)?),
+            (false, false) if limit.is_some() => return Err(CommandError::InvalidArgument(
+                "syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX"
+                    .to_string(),
+            )),
+            // 按下标查询时 REV 只改变遍历方向
+            (false, false) => ZRangeBy::Index(integer(start)?, integer(stop)?),
+        };
+    Ok(RangeOptions {
+        range,
+        rev,
+        limit,
+        withscores,
+    })
+}
+
+impl TryFrom<RespArray> for ZUnionStore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (dest, keys, weights, aggregate) = extract_zstore_args(value, "zunionstore")?;
+        Ok(ZUnionStore {
+            dest,
+            keys,
+            weights,
+            aggregate,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZInterStore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (dest, keys, weights, aggregate) = extract_zstore_args(value, "zinterstore")?;
+        Ok(ZInterStore {
+            dest,
+            keys,
+            weights,
+            aggregate,
+        })
+    }
+}
+
+// 解析 <dest> <numkeys> <key> [key ...] [WEIGHTS w [w ...]] [AGGREGATE SUM|MIN|MAX]
+fn extract_zstore_args(value: RespArray, name: &'static str) -> Result<ZStoreArgs, CommandError> {
+    validate_command_at_least(&value, &[name], 3)?;
+
+    let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+    let args = extract_args(value, 1)?
+        .into_iter()
+        .map(|arg| match arg {
+            RespFrame::BulkString(arg) => Ok(String::from_utf8(arg.get_data()?)?),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid argument".to_string(),
+            )),
+        })
+        .collect::<Result<Vec<String>, CommandError>>()?;
+    let mut args = args.into_iter();
+
+    let dest = args.next().ok_or_else(syntax_error)?;
+    let numkeys: usize = match args.next().map(|v| v.parse()) {
+        Some(Ok(n)) if n > 0 => n,
+        _ => {
+            return Err(CommandError::InvalidArgument(format!(
+                "at least 1 input key is needed for '{}' command",
+                name
+            )))
+        }
+    };
+    let keys: Vec<String> = args.by_ref().take(numkeys).collect();
+    if keys.len() != numkeys {
+        return Err(syntax_error());
+    }
+
+    let mut weights = vec![1.0; numkeys];
+    let mut aggregate = Aggregate::default();
+    while let Some(option) = args.next() {
+        match option.to_ascii_lowercase().as_str() {
+            "weights" => {
+                for weight in weights.iter_mut() {
+                    *weight = match args.next().map(|v| v.parse::<f64>()) {
+                        Some(Ok(w)) if !w.is_nan() => w,
+                        _ => {
+                            return Err(CommandError::InvalidArgument(
+                                "weight value is not a float".to_string(),
+                            ))
+                        }
+                    };
+                }
+            }
+            "aggregate" => {
+                aggregate = match args.next().map(|v| v.to_ascii_lowercase()).as_deref() {
+                    Some("sum") => Aggregate::Sum,
+                    Some("min") => Aggregate::Min,
+                    Some("max") => Aggregate::Max,
+                    _ => return Err(syntax_error()),
+                };
+            }
+            _ => return Err(syntax_error()),
+        }
+    }
+    Ok((dest, keys, weights, aggregate))
+}
+
+fn parse_lex_bound(bound: &[u8]) -> Result<LexBound, CommandError> {
+    let member = |v: &[u8]| String::from_utf8(v.to_vec());
+    match bound {
+        b"-" => Ok(LexBound::NegInf),
+        b"+" => Ok(LexBound::PosInf),
+        [b'[', rest @ ..] => Ok(LexBound::Inclusive(member(rest)?)),
+        [b'(', rest @ ..] => Ok(LexBound::Exclusive(member(rest)?)),
+        _ => Err(CommandError::InvalidArgument(
+            "min or max not valid string range item".to_string(),
+        )),
+    }
+}
+
+// 接受 "inf"、"+inf"、"-inf"，拒绝 nan
+fn parse_score_bound(bound: &[u8]) -> Result<ScoreBound, CommandError> {
+    let (exclusive, score) = match bound {
+        [b'(', rest @ ..] => (true, rest),
+        _ => (false, bound),
+    };
+    let score = std::str::from_utf8(score)
+        .ok()
+        .and_then(|s| s.parse::<f64>().ok())
+        .filter(|v| !v.is_nan())
+        .ok_or_else(|| CommandError::InvalidArgument("min or max is not a float".to_string()))?;
+    Ok(if exclusive {
+        ScoreBound::Exclusive(score)
+    } else {
+        ScoreBound::Inclusive(score)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Backend, RespDecode, RespEncode, RespVersion};
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_zadd_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$4\r\nzadd\r\n$4\r\nzset\r\n$3\r\n1.5\r\n$1\r\na\r\n$4\r\n-inf\r\n$1\r\nb\r\n",
+        );
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result: ZAdd = frame.try_into()?;
+        assert_eq!(result.key, "zset");
+        assert_eq!(
+            result.members,
+            vec![(1.5, "a".to_string()), (f64::NEG_INFINITY, "b".to_string())]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zadd_from_resp_array_invalid_score() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$4\r\nzadd\r\n$4\r\nzset\r\n$3\r\nnan\r\n$1\r\na\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(ZAdd::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zscore_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nzscore\r\n$4\r\nzset\r\n$1\r\na\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result: ZScore = frame.try_into()?;
+        assert_eq!(result.key, "zset");
+        assert_eq!(result.member, "a");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zadd_zscore_commands() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = ZAdd {
+            key: "zset".to_string(),
+            members: vec![(1.5, "a".to_string()), (2.0, "b".to_string())],
+            options: ZAddOptions::default(),
+        };
+        let result = cmd.execute(&backend);
+        assert_eq!(result, RespFrame::Integer(2));
+
+        let cmd = ZAdd {
+            key: "zset".to_string(),
+            members: vec![(3.0, "a".to_string()), (4.0, "c".to_string())],
+            options: ZAddOptions::default(),
+        };
+        let result = cmd.execute(&backend);
+        assert_eq!(result, RespFrame::Integer(1));
+
+        let cmd = ZScore {
+            key: "zset".to_string(),
+            member: "a".to_string(),
+        };
+        let result = cmd.execute(&backend);
+        assert_eq!(result, RespFrame::Double(3.0));
+
+        let cmd = ZScore {
+            key: "zset".to_string(),
+            member: "none".to_string(),
+        };
+        let result = cmd.execute(&backend);
+        assert_eq!(result, RespFrame::Null(RespNull));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zmscore_keeps_order_with_nulls() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$7\r\nzmscore\r\n$4\r\nzset\r\n$1\r\nb\r\n$4\r\nnone\r\n$1\r\na\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: ZMScore = frame.try_into()?;
+
+        let backend = Backend::new();
+        backend
+            .zadd(
+                "zset".to_string(),
+                vec![(1.0, "a".to_string()), (2.5, "b".to_string())],
+            )
+            .unwrap();
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new(vec![
+                RespFrame::Double(2.5),
+                RespFrame::Null(RespNull),
+                RespFrame::Double(1.0),
+            ])
+            .into()
+        );
+
+        let cmd = ZMScore {
+            key: "missing".to_string(),
+            members: vec!["a".to_string(), "b".to_string()],
+        };
+        assert_eq!(
+            cmd.execute(&backend)
+                .into_version(RespVersion::Resp2)
+                .encode(),
+            b"*2\r\n$-1\r\n$-1\r\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zscore_reply_depends_on_protocol() -> Result<()> {
+        let backend = Backend::new();
+        backend
+            .zadd("zset".to_string(), vec![(1.5, "a".to_string())])
+            .unwrap();
+
+        let score = |version: RespVersion| {
+            ZScore {
+                key: "zset".to_string(),
+                member: "a".to_string(),
+            }
+            .execute(&backend)
+            .into_version(version)
+            .encode()
+        };
+        assert_eq!(score(RespVersion::Resp2), b"$3\r\n1.5\r\n");
+        assert_eq!(score(RespVersion::Resp3), b",+1.5\r\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrangebylex_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$11\r\nzrangebylex\r\n$4\r\nzset\r\n$2\r\n[b\r\n$1\r\n+\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result: ZRangeByLex = frame.try_into()?;
+        assert_eq!(result.key, "zset");
+        assert_eq!(result.min, LexBound::Inclusive("b".to_string()));
+        assert_eq!(result.max, LexBound::PosInf);
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$11\r\nzrangebylex\r\n$4\r\nzset\r\n$1\r\nb\r\n$1\r\n+\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(ZRangeByLex::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrangebylex_command() -> Result<()> {
+        let backend = Backend::new();
+        let members = ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|m| (0.0, m.to_string()))
+            .collect();
+        backend.zadd("zset".to_string(), members).unwrap();
+
+        let range = |min: LexBound, max: LexBound| {
+            ZRangeByLex {
+                key: "zset".to_string(),
+                min,
+                max,
+            }
+            .execute(&backend)
+        };
+        let expected = |members: &[&str]| -> RespFrame {
+            RespArray::new(
+                members
+                    .iter()
+                    .map(|m| BulkString::new(*m).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into()
+        };
+
+        assert_eq!(
+            range(LexBound::NegInf, LexBound::PosInf),
+            expected(&["a", "b", "c", "d", "e"])
+        );
+        assert_eq!(
+            range(
+                LexBound::Inclusive("b".to_string()),
+                LexBound::Inclusive("d".to_string())
+            ),
+            expected(&["b", "c", "d"])
+        );
+        assert_eq!(
+            range(
+                LexBound::Exclusive("b".to_string()),
+                LexBound::Exclusive("d".to_string())
+            ),
+            expected(&["c"])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zunionstore_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*10\r\n$11\r\nzunionstore\r\n$4\r\ndest\r\n$1\r\n2\r\n$2\r\nz1\r\n$2\r\nz2\r\n$7\r\nWEIGHTS\r\n$1\r\n2\r\n$1\r\n3\r\n$9\r\nAGGREGATE\r\n$3\r\nmax\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result: ZUnionStore = frame.try_into()?;
+        assert_eq!(result.dest, "dest");
+        assert_eq!(result.keys, vec!["z1", "z2"]);
+        assert_eq!(result.weights, vec![2.0, 3.0]);
+        assert_eq!(result.aggregate, Aggregate::Max);
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$11\r\nzinterstore\r\n$4\r\ndest\r\n$1\r\n2\r\n$2\r\nz1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(ZInterStore::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zunionstore_weighted() -> Result<()> {
+        let backend = Backend::new();
+        backend
+            .zadd(
+                "z1".to_string(),
+                vec![(1.0, "a".to_string()), (2.0, "b".to_string())],
+            )
+            .unwrap();
+        backend
+            .zadd(
+                "z2".to_string(),
+                vec![(1.0, "b".to_string()), (3.0, "c".to_string())],
+            )
+            .unwrap();
+
+        let cmd = ZUnionStore {
+            dest: "dest".to_string(),
+            keys: vec!["z1".to_string(), "z2".to_string()],
+            weights: vec![2.0, 3.0],
+            aggregate: Aggregate::Sum,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+        assert_eq!(backend.zscore("dest", "a"), Some(2.0));
+        assert_eq!(backend.zscore("dest", "b"), Some(7.0));
+        assert_eq!(backend.zscore("dest", "c"), Some(9.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zinterstore_min() -> Result<()> {
+        let backend = Backend::new();
+        backend
+            .zadd(
+                "z1".to_string(),
+                vec![(1.0, "a".to_string()), (5.0, "b".to_string())],
+            )
+            .unwrap();
+        backend
+            .zadd(
+                "z2".to_string(),
+                vec![(2.0, "b".to_string()), (3.0, "c".to_string())],
+            )
+            .unwrap();
+
+        let cmd = ZInterStore {
+            dest: "dest".to_string(),
+            keys: vec!["z1".to_string(), "z2".to_string()],
+            weights: vec![1.0, 1.0],
+            aggregate: Aggregate::Min,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.zscore("dest", "b"), Some(2.0));
+        assert_eq!(backend.zscore("dest", "a"), None);
+
+        Ok(())
+    }
+
+    fn zadd(backend: &Backend, args: &str) -> Result<RespFrame> {
+        let args = std::iter::once("zadd")
+            .chain(args.split(' '))
+            .map(|arg| BulkString::from(arg).into())
+            .collect::<Vec<RespFrame>>();
+        Ok(ZAdd::try_from(RespArray::new(args))?.execute(backend))
+    }
+
+    #[test]
+    fn test_zadd_nx_xx() -> Result<()> {
+        let backend = Backend::new();
+        assert_eq!(zadd(&backend, "z XX 1 a")?, RespFrame::Integer(0));
+        assert_eq!(backend.key_type("z"), None);
+
+        assert_eq!(zadd(&backend, "z NX 1 a")?, RespFrame::Integer(1));
+        assert_eq!(zadd(&backend, "z NX 5 a 2 b")?, RespFrame::Integer(1));
+        assert_eq!(backend.zscore("z", "a"), Some(1.0));
+
+        assert_eq!(zadd(&backend, "z XX CH 3 a 3 c")?, RespFrame::Integer(1));
+        assert_eq!(backend.zscore("z", "a"), Some(3.0));
+        assert_eq!(backend.zscore("z", "c"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zadd_gt_lt_ch() -> Result<()> {
+        let backend = Backend::new();
+        zadd(&backend, "z 5 a 5 b")?;
+
+        // GT 只在分数变大时更新，但不影响新增成员
+        assert_eq!(
+            zadd(&backend, "z GT CH 4 a 6 b 1 c")?,
+            RespFrame::Integer(2)
+        );
+        assert_eq!(backend.zscore("z", "a"), Some(5.0));
+        assert_eq!(backend.zscore("z", "b"), Some(6.0));
+        assert_eq!(backend.zscore("z", "c"), Some(1.0));
+
+        assert_eq!(zadd(&backend, "z LT 4 a 7 b")?, RespFrame::Integer(0));
+        assert_eq!(backend.zscore("z", "a"), Some(4.0));
+        assert_eq!(backend.zscore("z", "b"), Some(6.0));
+
+        // 没有 CH 时只统计新增的成员，分数没变的不算 changed
+        assert_eq!(zadd(&backend, "z CH 4 a 8 b")?, RespFrame::Integer(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zadd_counts_added_vs_changed() -> Result<()> {
+        let backend = Backend::new();
+        assert_eq!(zadd(&backend, "z 1 a 2 b")?, RespFrame::Integer(2));
+
+        // 没有 CH：只更新分数不计数，只有新成员计数
+        assert_eq!(zadd(&backend, "z 10 a 20 b")?, RespFrame::Integer(0));
+        assert_eq!(zadd(&backend, "z 11 a 3 c")?, RespFrame::Integer(1));
+        assert_eq!(backend.zscore("z", "a"), Some(11.0));
+
+        // CH：新增加上分数改变的成员，分数相同的不算
+        assert_eq!(zadd(&backend, "z CH 11 a 21 b 4 d")?, RespFrame::Integer(2));
+        assert_eq!(zadd(&backend, "z CH 11 a 21 b")?, RespFrame::Integer(0));
+
+        // 同一次调用里重复的成员按顺序处理，和 Redis 一致
+        assert_eq!(zadd(&backend, "z 1 e 2 e")?, RespFrame::Integer(1));
+        assert_eq!(zadd(&backend, "z CH 1 f 2 f")?, RespFrame::Integer(2));
+        assert_eq!(backend.zscore("z", "f"), Some(2.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zadd_incr() -> Result<()> {
+        let backend = Backend::new();
+        assert_eq!(zadd(&backend, "z INCR 2 a")?, RespFrame::Double(2.0));
+        assert_eq!(zadd(&backend, "z INCR 1.5 a")?, RespFrame::Double(3.5));
+        assert_eq!(zadd(&backend, "z NX INCR 1 a")?, RespFrame::Null(RespNull));
+        assert_eq!(zadd(&backend, "z GT INCR -1 a")?, RespFrame::Null(RespNull));
+        assert_eq!(zadd(&backend, "z XX INCR 1 b")?, RespFrame::Null(RespNull));
+        assert_eq!(backend.zscore("z", "b"), None);
+
+        assert_eq!(
+            zadd(&backend, "z INCR inf c")?,
+            RespFrame::Double(f64::INFINITY)
+        );
+        assert!(matches!(
+            zadd(&backend, "z INCR -inf c")?,
+            RespFrame::Error(_)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zadd_incr_with_nx_xx() -> Result<()> {
+        let backend = Backend::new();
+        // 新成员时 NX 不阻止，回复增量本身
+        assert_eq!(zadd(&backend, "z NX INCR 2.5 a")?, RespFrame::Double(2.5));
+        // 已存在的成员 NX 阻止更新，回复 null，分数不变
+        let blocked = zadd(&backend, "z NX INCR 1 a")?;
+        assert_eq!(blocked, RespFrame::Null(RespNull));
+        assert_eq!(
+            blocked.into_version(RespVersion::Resp2).encode(),
+            b"$-1\r\n"
+        );
+        assert_eq!(backend.zscore("z", "a"), Some(2.5));
+
+        assert_eq!(zadd(&backend, "z XX INCR 1 a")?, RespFrame::Double(3.5));
+        assert_eq!(zadd(&backend, "z XX INCR 1 b")?, RespFrame::Null(RespNull));
+        assert_eq!(backend.zscore("z", "b"), None);
+        // XX 不会创建 key
+        assert_eq!(
+            zadd(&backend, "missing XX INCR 1 a")?,
+            RespFrame::Null(RespNull)
+        );
+        assert_eq!(backend.key_type("missing"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_zincrby_rejects_nan() -> Result<()> {
+        let backend = Backend::new();
+        let zincrby = |args: &str| -> Result<RespFrame, CommandError> {
+            let args = std::iter::once("zincrby")
+                .chain(args.split(' '))
+                .map(|arg| BulkString::from(arg).into())
+                .collect::<Vec<RespFrame>>();
+            Ok(ZIncrBy::try_from(RespArray::new(args))?.execute(&backend))
+        };
+
+        assert_eq!(zincrby("z 1.5 a")?, RespFrame::Double(1.5));
+        let err = zincrby("z nan a").unwrap_err();
+        assert_eq!(
+            RespFrame::from(err),
+            RespFrame::error("ERR", "value is not a valid float")
+        );
+
+        assert_eq!(zincrby("z +inf b")?, RespFrame::Double(f64::INFINITY));
+        assert_eq!(
+            zincrby("z -inf b")?,
+            RespFrame::error("ERR", "resulting score is not a number (NaN)")
+        );
+        // 出错后集合保持原样
+        assert_eq!(backend.zscore("z", "b"), Some(f64::INFINITY));
+        assert_eq!(backend.zscore("z", "a"), Some(1.5));
+
+        // 对不存在的 key 出错时也不会留下空集合
+        assert!(zincrby("empty nan a").is_err());
+        assert_eq!(backend.key_type("empty"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zadd_incompatible_flags() {
+        let backend = Backend::new();
+        for args in [
+            "z GT NX 1 a",
+            "z LT NX 1 a",
+            "z GT LT 1 a",
+            "z NX XX 1 a",
+            "z INCR 1 a 2 b",
+            "z CH",
+        ] {
+            assert!(zadd(&backend, args).is_err(), "{}", args);
+        }
+    }
+
+    #[test]
+    fn test_zmpop() -> Result<()> {
+        let backend = Backend::new();
+        let zmpop = |args: &str| -> Result<RespFrame> {
+            let args = std::iter::once("zmpop")
+                .chain(args.split(' '))
+                .map(|arg| BulkString::from(arg).into())
+                .collect::<Vec<RespFrame>>();
+            Ok(ZMPop::try_from(RespArray::new(args))?.execute(&backend))
+        };
+        let popped = |key: &str, members: &[(&str, f64)]| -> RespFrame {
+            let members = members
+                .iter()
+                .map(|(member, score)| {
+                    RespArray::new(vec![
+                        BulkString::from(*member).into(),
+                        RespFrame::Double(*score),
+                    ])
+                    .into()
+                })
+                .collect::<Vec<RespFrame>>();
+            RespArray::new(vec![
+                BulkString::from(key).into(),
+                RespArray::new(members).into(),
+            ])
+            .into()
+        };
+        zadd(&backend, "z2 1 a 2 b 3 c")?;
+
+        // 第一个 key 不存在，从第二个 key 弹出
+        assert_eq!(zmpop("2 z1 z2 MIN")?, popped("z2", &[("a", 1.0)]));
+        assert_eq!(
+            zmpop("2 z1 z2 MAX COUNT 5")?,
+            popped("z2", &[("c", 3.0), ("b", 2.0)])
+        );
+        assert_eq!(backend.key_type("z2"), None);
+        assert_eq!(zmpop("2 z1 z2 MIN")?, RespArray::null().into());
+        assert_eq!(zmpop("1 z1 MIN")?.encode(), b"*-1\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrangestore() -> Result<()> {
+        let backend = Backend::new();
+        let zrangestore = |args: &str| -> Result<RespFrame> {
+            let args = std::iter::once("zrangestore")
+                .chain(args.split(' '))
+                .map(|arg| BulkString::from(arg).into())
+                .collect::<Vec<RespFrame>>();
+            Ok(ZRangeStore::try_from(RespArray::new(args))?.execute(&backend))
+        };
+        let stored = |key: &str| -> Vec<(String, f64)> {
+            backend
+                .zset
+                .get(key)
+                .map(|z| z.iter().map(|(m, s)| (m.to_string(), s)).collect())
+                .unwrap_or_default()
+        };
+        let members = |members: &[(&str, f64)]| -> Vec<(String, f64)> {
+            members.iter().map(|(m, s)| (m.to_string(), *s)).collect()
+        };
+        zadd(&backend, "src 1 a 2 b 3 c 4 d 5 e")?;
+
+        assert_eq!(zrangestore("dst src 1 -2")?, RespFrame::Integer(3));
+        assert_eq!(
+            stored("dst"),
+            members(&[("b", 2.0), ("c", 3.0), ("d", 4.0)])
+        );
+        // REV 时下标从最高分开始
+        assert_eq!(zrangestore("dst src 0 1 REV")?, RespFrame::Integer(2));
+        assert_eq!(stored("dst"), members(&[("d", 4.0), ("e", 5.0)]));
+
+        assert_eq!(
+            zrangestore("dst src (1 +inf BYSCORE LIMIT 1 2")?,
+            RespFrame::Integer(2)
+        );
+        assert_eq!(stored("dst"), members(&[("c", 3.0), ("d", 4.0)]));
+        assert_eq!(
+            zrangestore("dst src [c - BYLEX REV")?,
+            RespFrame::Integer(3)
+        );
+        assert_eq!(
+            stored("dst"),
+            members(&[("a", 1.0), ("b", 2.0), ("c", 3.0)])
+        );
+
+        // 结果为空时删除 dest
+        assert_eq!(zrangestore("dst src 10 20")?, RespFrame::Integer(0));
+        assert_eq!(backend.key_type("dst"), None);
+        assert_eq!(zrangestore("dst missing 0 -1")?, RespFrame::Integer(0));
+
+        assert!(zrangestore("dst src 0 1 LIMIT 0 1").is_err());
+        assert!(zrangestore("dst src 0 1 BYSCORE BYLEX").is_err());
+        assert!(zrangestore("dst src a 1").is_err());
+        backend.set("str".to_string(), BulkString::from("v").into());
+        assert_eq!(
+            zrangestore("dst str 0 -1")?.encode(),
+            b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_zstore_overwrites_other_types() -> Result<()> {
+        let backend = Backend::new();
+        zadd(&backend, "src 1 a")?;
+        backend.rpush("dest".to_string(), vec![BulkString::from("x").into()])?;
+
+        let cmd = ZUnionStore {
+            dest: "dest".to_string(),
+            keys: vec!["src".to_string()],
+            weights: vec![1.0],
+            aggregate: Aggregate::Sum,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.key_type("dest"), Some("zset"));
+        assert!(!backend.list.contains_key("dest"));
+
+        // 结果为空时原来的字符串同样被删除
+        backend.set("dest".to_string(), BulkString::from("v").into());
+        let cmd = ZInterStore {
+            dest: "dest".to_string(),
+            keys: vec!["src".to_string(), "missing".to_string()],
+            weights: vec![1.0, 1.0],
+            aggregate: Aggregate::Sum,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert_eq!(backend.key_type("dest"), None);
+        assert!(!backend.access.contains_key("dest"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrange_orders_equal_scores_by_member() -> Result<()> {
+        let backend = Backend::new();
+        let zrange = |args: &str, version: RespVersion| -> Result<Vec<u8>> {
+            let args = std::iter::once("zrange")
+                .chain(args.split(' '))
+                .map(|arg| BulkString::from(arg).into())
+                .collect::<Vec<RespFrame>>();
+            let ctx = ConnectionContext {
+                version,
+                ..Default::default()
+            };
+            let reply = ZRange::try_from(RespArray::new(args))?.execute_with(&backend, &ctx);
+            Ok(reply.into_version(version).encode())
+        };
+        // 插入顺序与字典序不同，同分成员应按成员名排序
+        zadd(&backend, "z 1 c 1 a 1 b 0 z")?;
+
+        assert_eq!(
+            zrange("z 0 -1", RespVersion::Resp2)?,
+            b"*4\r\n$1\r\nz\r\n$1\r\na\r\n$1\r\nb\r\n$1\r\nc\r\n"
+        );
+        assert_eq!(
+            zrange("z 0 -1 REV", RespVersion::Resp2)?,
+            b"*4\r\n$1\r\nc\r\n$1\r\nb\r\n$1\r\na\r\n$1\r\nz\r\n"
+        );
+        assert_eq!(
+            zrange("z 1 1 BYSCORE WITHSCORES", RespVersion::Resp2)?,
+            b"*6\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n$1\r\n1\r\n$1\r\nc\r\n$1\r\n1\r\n"
+        );
+        assert_eq!(
+            zrange(
+                "z +inf -inf BYSCORE REV LIMIT 0 2 WITHSCORES",
+                RespVersion::Resp3
+            )?,
+            b"*2\r\n*2\r\n$1\r\nc\r\n,+1\r\n*2\r\n$1\r\nb\r\n,+1\r\n"
+        );
+        assert_eq!(
+            zrange("z [a [b BYLEX", RespVersion::Resp3)?,
+            b"*2\r\n$1\r\na\r\n$1\r\nb\r\n"
+        );
+        assert!(zrange("z - + BYLEX WITHSCORES", RespVersion::Resp2).is_err());
+        assert!(zrange("z 0 -1 LIMIT 0 1", RespVersion::Resp2).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrank_withscore() -> Result<()> {
+        let backend = Backend::new();
+        let rank = |name: &str, args: &str| -> Result<RespFrame> {
+            let args = std::iter::once(name)
+                .chain(args.split(' '))
+                .map(|arg| BulkString::from(arg).into())
+                .collect::<Vec<RespFrame>>();
+            let frame = RespArray::new(args);
+            Ok(if name == "zrank" {
+                ZRank::try_from(frame)?.execute(&backend)
+            } else {
+                ZRevRank::try_from(frame)?.execute(&backend)
+            })
+        };
+        zadd(&backend, "z 1 a 2 b 3 c")?;
+
+        assert_eq!(rank("zrank", "z a")?, RespFrame::Integer(0));
+        assert_eq!(rank("zrank", "z c")?, RespFrame::Integer(2));
+        assert_eq!(rank("zrevrank", "z c")?, RespFrame::Integer(0));
+        assert_eq!(
+            rank("zrank", "z b WITHSCORE")?,
+            RespArray::new(vec![RespFrame::Integer(1), RespFrame::Double(2.0)]).into()
+        );
+        assert_eq!(
+            rank("zrevrank", "z a withscore")?,
+            RespArray::new(vec![RespFrame::Integer(2), RespFrame::Double(1.0)]).into()
+        );
+        // RESP2 下分数按 bulk string 返回
+        assert_eq!(
+            rank("zrank", "z b WITHSCORE")?
+                .into_version(RespVersion::Resp2)
+                .encode(),
+            b"*2\r\n:1\r\n$1\r\n2\r\n"
+        );
+
+        // 成员或 key 不存在时返回 null
+        assert_eq!(rank("zrank", "z x")?, RespFrame::Null(RespNull));
+        assert_eq!(rank("zrank", "z x WITHSCORE")?, RespFrame::Null(RespNull));
+        assert_eq!(
+            rank("zrevrank", "missing a WITHSCORE")?,
+            RespFrame::Null(RespNull)
+        );
+
+        backend.set("s".to_string(), BulkString::from("v").into());
+        assert_eq!(rank("zrank", "s a")?, crate::BackendError::WrongType.into());
+        assert!(rank("zrank", "z a WITHSCORES").is_err());
+        assert!(rank("zrank", "z a WITHSCORE extra").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrevrange_matches_reversed_zrange() -> Result<()> {
+        let backend = Backend::new();
+        let members = |cmd: &str| -> Result<Vec<RespFrame>> {
+            let args = cmd
+                .split(' ')
+                .map(|arg| BulkString::from(arg).into())
+                .collect::<Vec<RespFrame>>();
+            let frame: RespFrame = RespArray::new(args).into();
+            match crate::cmd::Command::try_from(frame)?.execute(&backend) {
+                RespFrame::Array(members) => Ok(members.to_vec()),
+                other => anyhow::bail!("unexpected reply {:?}", other),
+            }
+        };
+        let reversed = |cmd: &str| -> Result<Vec<RespFrame>> {
+            let mut members = members(cmd)?;
+            members.reverse();
+            Ok(members)
+        };
+        // 同分成员在反向结果中按成员名倒序
+        zadd(&backend, "z 1 b 1 a 2 d 2 c 3 e -inf m +inf n")?;
+
+        assert_eq!(members("zrevrange z 0 -1")?, reversed("zrange z 0 -1")?);
+        assert_eq!(
+            members("zrevrange z 0 -1")?,
+            ["n", "e", "d", "c", "b", "a", "m"]
+                .map(|m| BulkString::from(m).into())
+                .to_vec()
+        );
+        assert_eq!(members("zrevrange z 1 3")?, reversed("zrange z -4 -2")?);
+        assert_eq!(members("zrevrange z 5 2")?, Vec::<RespFrame>::new());
+        assert_eq!(
+            members("zrevrangebyscore z +inf -inf")?,
+            reversed("zrange z -inf +inf BYSCORE")?
+        );
+        assert_eq!(
+            members("zrevrangebyscore z 2 (1")?,
+            reversed("zrange z (1 2 BYSCORE")?
+        );
+        assert_eq!(members("zrevrangebyscore z 1 2")?, Vec::<RespFrame>::new());
+
+        // WITHSCORES 按 member/score 成对反转
+        let pairs = |frames: Vec<RespFrame>| -> Vec<Vec<RespFrame>> {
+            frames.chunks(2).map(|pair| pair.to_vec()).collect()
+        };
+        let mut forward = pairs(members("zrange z 1 2 BYSCORE WITHSCORES")?);
+        forward.reverse();
+        assert_eq!(
+            pairs(members("zrevrangebyscore z 2 1 WITHSCORES")?),
+            forward
+        );
+        let mut forward = pairs(members("zrange z 0 -1 WITHSCORES")?);
+        forward.reverse();
+        assert_eq!(pairs(members("zrevrange z 0 -1 withscores")?), forward);
+
+        assert!(members("zrevrange z 0 -1 BYSCORE").is_err());
+        assert!(members("zrevrangebyscore z x 1").is_err());
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 mod backend;
 pub mod cmd;
+mod glob;
 pub mod network;
 mod resp;
 mod respv2;
@@ -1,17 +1,37 @@
+use std::sync::Arc;
+use std::time::Instant;
+
 use anyhow::Result;
+use bytes::BytesMut;
 use futures::SinkExt;
+use tokio::io::AsyncWrite;
 use tokio::net::TcpStream;
+use tokio::sync::Mutex;
 use tokio_stream::StreamExt;
 use tracing::info;
 
 use crate::{
-    cmd::{Command, CommandExecutor},
-    Backend, RespDecodeV2, RespEncode, RespError, RespFrame, SimpleError,
+    cmd::{Command, CommandError, CommandExecutor},
+    Backend, ConnectionContext, RespDecodeV2, RespEncode, RespError, RespFrame, RespVersion,
+    SharedFrame, Subscriber,
 };
-use tokio_util::codec::{Decoder, Encoder, Framed};
+use tokio_util::codec::{Decoder, Encoder, FramedRead, FramedWrite};
+
+#[derive(Debug, Default)]
+struct RespFrameCodec {
+    // 连接通过 HELLO 协商的协议版本，编码时据此转换 RESP3 类型
+    version: RespVersion,
+    // 解码时从配置读取 proto-max-* 限制，CONFIG SET 对后续的请求立即生效
+    backend: Option<Backend>,
+}
 
+/// Write half of a connection shared by command replies and pub/sub push
+/// messages. Every write holds the lock until its frames are fully flushed,
+/// so a push delivery can never land in the middle of a reply.
 #[derive(Debug)]
-struct RespFrameCodec;
+pub struct ConnectionWriter<W> {
+    inner: Arc<Mutex<FramedWrite<W, RespFrameCodec>>>,
+}
 
 #[derive(Debug)]
 struct RedisRequest {
@@ -21,57 +41,285 @@ struct RedisRequest {
 
 #[derive(Debug)]
 struct RedisResponse {
-    frame: RespFrame,
+    frames: Vec<RespFrame>,
+    // QUIT：回复之后关闭连接
+    close: bool,
 }
 
 pub async fn stream_handler(stream: TcpStream, backend: Backend) -> Result<()> {
-    // how to get a frame from the stream?
-    let mut framed = Framed::new(stream, RespFrameCodec);
+    let (reader, writer) = stream.into_split();
+    let mut frames = FramedRead::new(reader, RespFrameCodec::for_backend(&backend));
+    let writer = ConnectionWriter::new(writer);
+    let client = match backend.try_connect() {
+        Ok(client) => client,
+        Err(frame) => return writer.reply(vec![frame]).await,
+    };
+    let mut subscriber = backend.subscriber();
+    let mut ctx = ConnectionContext {
+        id: client.id(),
+        ..Default::default()
+    };
     loop {
-        match framed.next().await {
-            Some(Ok(frame)) => {
-                info!("Received frame: {:?}", frame);
-                let request = RedisRequest {
-                    frame,
-                    backend: backend.clone(),
-                };
-                let response = request_handler(request).await;
-                match response {
-                    Ok(response) => {
-                        info!("Sending response: {:?}", response.frame);
-                        framed.send(response.frame).await?;
-                    }
-                    Err(e) => {
-                        info!("Error: {:?}", e);
-                        let response = RedisResponse {
-                            frame: RespFrame::Error(SimpleError::from(format!("{}", e))),
-                        };
-                        framed.send(response.frame).await?;
+        tokio::select! {
+            ret = frames.next() => match ret {
+                Some(Ok(frame)) => {
+                    info!("Received frame: {:?}", frame);
+                    let request = RedisRequest {
+                        frame,
+                        backend: backend.clone(),
+                    };
+                    let response = request_handler(request, &mut subscriber, &mut ctx).await;
+                    // HELLO 的回复已经使用新协商的版本编码
+                    writer.set_version(backend.config().reply_version(ctx.version)).await;
+                    match response {
+                        Ok(response) => {
+                            info!("Sending response: {:?}", response.frames);
+                            writer.reply(response.frames).await?;
+                            if response.close {
+                                return Ok(());
+                            }
+                        }
+                        Err(e) => {
+                            info!("Error: {:?}", e);
+                            let frame = match e.downcast::<CommandError>() {
+                                Ok(e) => e.into(),
+                                Err(e) => RespFrame::error("ERR", &e.to_string()),
+                            };
+                            writer.reply(vec![frame]).await?;
+                        }
                     }
                 }
-                // how to send the response back to the stream?
-            }
-            Some(Err(e)) => return Err(e),
-            None => return Ok(()),
+                Some(Err(e)) => return Err(e),
+                None => return Ok(()),
+            },
+            message = subscriber.recv() => match message {
+                Some(message) => {
+                    info!("Sending message: {:?}", message);
+                    writer.push(message).await?;
+                }
+                // 消费太慢，缓冲区满后被断开
+                None => {
+                    info!("Closing slow pubsub subscriber");
+                    return Ok(());
+                }
+            },
+        }
+    }
+}
+
+impl<W: AsyncWrite + Unpin> ConnectionWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(FramedWrite::new(
+                writer,
+                RespFrameCodec::default(),
+            ))),
+        }
+    }
+
+    pub async fn set_version(&self, version: RespVersion) {
+        self.inner.lock().await.encoder_mut().version = version;
+    }
+
+    /// Write the frames replying to one command back to back.
+    pub async fn reply(&self, frames: Vec<RespFrame>) -> Result<()> {
+        let mut sink = self.inner.lock().await;
+        for frame in frames {
+            sink.feed(frame).await?;
+        }
+        SinkExt::<RespFrame>::flush(&mut *sink).await
+    }
+
+    pub async fn push(&self, frame: SharedFrame) -> Result<()> {
+        self.inner.lock().await.send(frame).await
+    }
+}
+
+impl<W> Clone for ConnectionWriter<W> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
         }
     }
 }
 
-async fn request_handler(request: RedisRequest) -> Result<RedisResponse> {
+async fn request_handler(
+    request: RedisRequest,
+    subscriber: &mut Subscriber,
+    ctx: &mut ConnectionContext,
+) -> Result<RedisResponse> {
     let (frame, backend) = (request.frame, request.backend);
-    let cmd = Command::try_from(frame)?;
+    // 超出限速的请求直接拒绝，不解析也不计入命令统计
+    if !backend.allow_command(ctx) {
+        return Ok(RedisResponse {
+            frames: vec![rate_limited()],
+            close: false,
+        });
+    }
+    let name = command_name(&frame);
+    let logged = backend.aof_enabled().then(|| frame.clone());
+    let cmd = Command::parse(frame, backend.config())?;
+    let is_write = cmd.is_write();
+    // 相对的过期时间要在执行前换算成绝对时间，和执行时的时刻一致
+    let entry = logged
+        .is_some()
+        .then(|| cmd.aof_entry(backend.unix_millis()));
+    ctx.subscriptions = subscriber.subscription_count();
+    if ctx.subscriptions > 0 && !cmd.allowed_in_subscribe_mode() {
+        let frame = RespFrame::error(
+            "ERR",
+            &format!(
+                "Can't execute '{}': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / RESET are allowed in this context",
+                name
+            ),
+        );
+        return Ok(RedisResponse {
+            frames: vec![frame],
+            close: false,
+        });
+    }
     info!("Executing command: {:?}", cmd);
-    let frame = cmd.execute(&backend);
-    Ok(RedisResponse { frame })
+    // NO-TOUCH 连接的读命令不更新 key 的访问时间，写命令照常更新
+    let backend = if ctx.flags.no_touch && !is_write {
+        backend.no_touch()
+    } else {
+        backend
+    };
+    let recorded = !matches!(cmd, Command::Unrecognized(_));
+    let close = matches!(cmd, Command::Quit(_));
+    let start = Instant::now();
+    let frames = match cmd {
+        Command::Subscribe(cmd) => cmd.apply(subscriber),
+        Command::Unsubscribe(cmd) => cmd.apply(subscriber),
+        Command::PSubscribe(cmd) => cmd.apply(subscriber),
+        Command::PUnsubscribe(cmd) => cmd.apply(subscriber),
+        // strict-resp2 模式下和只支持 RESP2 的 Redis 一样拒绝切换到 RESP3
+        Command::Hello(cmd)
+            if backend.config().strict_resp2() && cmd.protover() == Some(RespVersion::Resp3) =>
+        {
+            vec![RespFrame::error("NOPROTO", "unsupported protocol version")]
+        }
+        Command::Hello(cmd) => vec![cmd.apply(ctx)],
+        Command::Client(cmd) => vec![cmd.apply(&mut ctx.flags)],
+        Command::Reset(cmd) => vec![cmd.apply(subscriber, ctx)],
+        cmd if cmd.is_blocking() => {
+            vec![tokio::task::block_in_place(|| {
+                cmd.execute_with(&backend, ctx)
+            })]
+        }
+        cmd => vec![cmd.execute_with(&backend, ctx)],
+    };
+    ctx.subscriptions = subscriber.subscription_count();
+    if recorded {
+        backend.record_command(&name, start.elapsed());
+    }
+    // 只记录执行成功的写命令
+    if let (true, Some(frame), Some(entry), Some(reply)) = (is_write, logged, entry, frames.first())
+    {
+        if !matches!(reply, RespFrame::Error(_)) {
+            for frame in entry.into_frames(frame, reply) {
+                backend.append_aof(frame)?;
+            }
+        }
+    }
+    let limit = backend.config().max_reply_size();
+    let frames = frames
+        .into_iter()
+        .map(|frame| guard_reply_size(frame, limit))
+        .collect();
+    Ok(RedisResponse { frames, close })
+}
+
+// 回复过大时改为返回错误，避免为一条命令编码出巨大的缓冲区
+fn guard_reply_size(frame: RespFrame, limit: usize) -> RespFrame {
+    let len = frame.estimated_encoded_len();
+    if limit == 0 || len <= limit {
+        return frame;
+    }
+    RespFrame::error(
+        "ERR",
+        &format!(
+            "reply of {} bytes exceeds the max reply size {}",
+            len, limit
+        ),
+    )
+}
+
+fn rate_limited() -> RespFrame {
+    RespFrame::error("ERR", "rate limit exceeded")
+}
+
+fn command_name(frame: &RespFrame) -> String {
+    match frame {
+        RespFrame::Array(array) => match array.first() {
+            Some(RespFrame::BulkString(name)) => String::from_utf8_lossy(name).to_lowercase(),
+            _ => String::new(),
+        },
+        _ => String::new(),
+    }
+}
+
+/// Decode every complete command in `buf`, execute them against `backend`
+/// on behalf of the connection `ctx` and return the encoded replies back to
+/// back. A trailing partial command is left in `buf`. A malformed frame is
+/// answered with an error and processing resumes with the frame after it.
+pub fn process_buffer(
+    buf: &mut BytesMut,
+    backend: &Backend,
+    ctx: &mut ConnectionContext,
+) -> Vec<u8> {
+    let mut replies = Vec::new();
+    while !buf.is_empty() {
+        let reply = match <RespFrame as RespDecodeV2>::decode(buf) {
+            // 流水线里的每条命令同样要经过限速
+            Ok(_) if !backend.allow_command(ctx) => rate_limited(),
+            Ok(frame) => match Command::parse(frame, backend.config()) {
+                Ok(Command::Hello(cmd))
+                    if backend.config().strict_resp2()
+                        && cmd.protover() == Some(RespVersion::Resp3) =>
+                {
+                    RespFrame::error("NOPROTO", "unsupported protocol version")
+                }
+                Ok(Command::Hello(cmd)) => cmd.apply(ctx),
+                Ok(cmd) => cmd.execute_with(backend, ctx),
+                Err(e) => e.into(),
+            },
+            Err(RespError::NotComplete) => break,
+            // 只有长度完整的帧才会解析失败，此时整帧已被取走，从下一帧继续即可
+            Err(e) => RespFrame::error("ERR", &format!("Protocol error: {}", e)),
+        };
+        // 和 Redis 一样默认按 RESP2 编码，HELLO 3 之后才使用 RESP3
+        let version = backend.config().reply_version(ctx.version);
+        replies.extend(reply.into_version(version).encode());
+    }
+    replies
+}
+
+impl RespFrameCodec {
+    fn for_backend(backend: &Backend) -> Self {
+        Self {
+            backend: Some(backend.clone()),
+            ..Default::default()
+        }
+    }
 }
 
 impl Encoder<RespFrame> for RespFrameCodec {
     type Error = anyhow::Error;
 
     fn encode(&mut self, item: RespFrame, dst: &mut bytes::BytesMut) -> Result<()> {
-        let encoded = item.encode();
-        info!("Encoding frame: {:?}", String::from_utf8_lossy(&encoded));
-        dst.extend_from_slice(&encoded);
+        // 直接编码到写缓冲区，大回复不再先整体编码一遍再复制
+        item.into_version(self.version).encode_into(dst);
+        Ok(())
+    }
+}
+
+// 共享的 frame 直接写入缓存的编码结果，避免每个订阅者都复制一次
+impl Encoder<SharedFrame> for RespFrameCodec {
+    type Error = anyhow::Error;
+
+    fn encode(&mut self, item: SharedFrame, dst: &mut bytes::BytesMut) -> Result<()> {
+        dst.extend_from_slice(item.encode_for(self.version));
         Ok(())
     }
 }
@@ -81,10 +329,444 @@ impl Decoder for RespFrameCodec {
     type Error = anyhow::Error;
 
     fn decode(&mut self, src: &mut bytes::BytesMut) -> Result<Option<RespFrame>> {
+        if let Some(backend) = &self.backend {
+            backend.config().decode_limits().check(src)?;
+        }
+        let len = src.len();
         match RespFrame::decode(src) {
-            Ok(frame) => Ok(Some(frame)),
-            Err(RespError::NotComplete) => Ok(None),
+            Ok(frame) => {
+                if let Some(backend) = &self.backend {
+                    backend.record_parsed_frame(len - src.len());
+                }
+                Ok(Some(frame))
+            }
+            Err(RespError::NotComplete) => {
+                // 缓冲区读空时 FramedRead 也会调用 decode，只统计真正的半包
+                if let (Some(backend), false) = (&self.backend, src.is_empty()) {
+                    backend.record_parse_not_complete();
+                }
+                Ok(None)
+            }
             Err(e) => Err(e.into()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespArray};
+    use tokio::net::TcpListener;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_push_and_reply_stay_intact() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let client = TcpStream::connect(listener.local_addr()?).await?;
+        let (server, _) = listener.accept().await?;
+        let writer = ConnectionWriter::new(server);
+
+        // 足够大的消息会被拆成多次写入，更容易暴露交错的问题
+        let message: RespFrame = BulkString::new(vec![b'x'; 256 * 1024]).into();
+        let shared = message.clone().into_shared();
+        let mut tasks = Vec::new();
+        for _ in 0..4 {
+            let (writer, shared) = (writer.clone(), shared.clone());
+            tasks.push(tokio::spawn(async move {
+                for _ in 0..4 {
+                    writer.push(shared.clone()).await?;
+                }
+                anyhow::Ok(())
+            }));
+        }
+        let pong: RespFrame = crate::SimpleString::new("PONG").into();
+        let reply = {
+            let (writer, pong) = (writer.clone(), pong.clone());
+            tokio::spawn(async move { writer.reply(vec![pong]).await })
+        };
+
+        let mut frames = FramedRead::new(client, RespFrameCodec::default());
+        let (mut pushes, mut pongs) = (0, 0);
+        while pushes + pongs < 17 {
+            let frame = frames.next().await.expect("connection closed")?;
+            if frame == pong {
+                pongs += 1;
+            } else {
+                assert_eq!(frame, message);
+                pushes += 1;
+            }
+        }
+        assert_eq!((pushes, pongs), (16, 1));
+
+        for task in tasks {
+            task.await??;
+        }
+        reply.await??;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_max_reply_size_guards_large_hgetall() -> Result<()> {
+        let backend = Backend::new();
+        for i in 0..100 {
+            backend.hset(
+                "hash".to_string(),
+                format!("field:{}", i),
+                BulkString::from("value").into(),
+            )?;
+        }
+        let hgetall = || RedisRequest {
+            frame: RespArray::new(vec![
+                BulkString::from("hgetall").into(),
+                BulkString::from("hash").into(),
+            ])
+            .into(),
+            backend: backend.clone(),
+        };
+        let mut subscriber = backend.subscriber();
+        let mut ctx = ConnectionContext::default();
+
+        let response = request_handler(hgetall(), &mut subscriber, &mut ctx).await?;
+        assert!(matches!(response.frames[0], RespFrame::Map(_)));
+
+        backend.config().set_max_reply_size(1024);
+        let response = request_handler(hgetall(), &mut subscriber, &mut ctx).await?;
+        let RespFrame::Error(ref e) = response.frames[0] else {
+            panic!("expected an error reply, got {:?}", response.frames);
+        };
+        assert!(e.starts_with("ERR reply of "));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_command_rate_limit() -> Result<()> {
+        use std::time::Duration;
+
+        let clock = Arc::new(crate::ManualClock::new());
+        let backend = Backend::with_clock(clock.clone());
+        backend.config().set_client_command_rate_limit(3);
+        let ping = || RedisRequest {
+            frame: RespArray::new(vec![BulkString::from("ping").into()]).into(),
+            backend: backend.clone(),
+        };
+        let mut subscriber = backend.subscriber();
+        let mut ctx = ConnectionContext::default();
+        let limited = RespFrame::error("ERR", "rate limit exceeded");
+
+        // 每一轮依次发送 PING，记录哪些请求被限速
+        let rounds = [
+            // 一秒内最多突发 3 个命令
+            (Duration::ZERO, vec![false, false, false, true]),
+            // 半秒补充 1.5 个令牌
+            (Duration::from_millis(500), vec![false, true]),
+            // 令牌不会超过桶的容量
+            (Duration::from_secs(10), vec![false, false, false, true]),
+        ];
+        for (elapsed, expected) in rounds {
+            clock.advance(elapsed);
+            let mut got = Vec::new();
+            for _ in 0..expected.len() {
+                let response = request_handler(ping(), &mut subscriber, &mut ctx).await?;
+                got.push(response.frames[0] == limited);
+            }
+            assert_eq!(got, expected);
+        }
+
+        // 不限速时不受影响
+        backend.config().set_client_command_rate_limit(0);
+        for _ in 0..10 {
+            let response = request_handler(ping(), &mut subscriber, &mut ctx).await?;
+            assert_ne!(response.frames[0], limited);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_info_commandstats_counts_calls() -> Result<()> {
+        let backend = Backend::new();
+        let request = |args: &[&str]| RedisRequest {
+            frame: RespArray::new(
+                args.iter()
+                    .map(|arg| BulkString::from(*arg).into())
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            backend: backend.clone(),
+        };
+        let mut subscriber = backend.subscriber();
+        let mut ctx = ConnectionContext::default();
+        for args in [
+            &["set", "a", "1"][..],
+            &["set", "b", "2"],
+            &["get", "a"],
+            &["get", "b"],
+            &["get", "missing"],
+            &["nosuchcommand"],
+        ] {
+            request_handler(request(args), &mut subscriber, &mut ctx).await?;
+        }
+
+        let response = request_handler(
+            request(&["info", "commandstats"]),
+            &mut subscriber,
+            &mut ctx,
+        )
+        .await?;
+        let RespFrame::BulkString(ref info) = response.frames[0] else {
+            panic!("expected a bulk string, got {:?}", response.frames);
+        };
+        let info = String::from_utf8_lossy(info);
+        let calls: Vec<_> = info
+            .lines()
+            .skip(1)
+            .map(|line| line.split_once(",usec=").expect("missing usec").0)
+            .collect();
+        assert!(info.starts_with("# Commandstats\r\n"));
+        assert_eq!(calls, ["cmdstat_get:calls=3", "cmdstat_set:calls=2"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_set_proto_max_bulk_len_affects_decode() -> Result<()> {
+        let backend = Backend::new();
+        let mut codec = RespFrameCodec::for_backend(&backend);
+        let config = |value: &str| {
+            let frame = RespArray::new(
+                ["config", "set", "proto-max-bulk-len", value]
+                    .into_iter()
+                    .map(|arg| BulkString::from(arg).into())
+                    .collect::<Vec<_>>(),
+            );
+            Command::try_from(RespFrame::from(frame)).map(|cmd| cmd.execute(&backend))
+        };
+        let len = 1024 * 1024 + 1;
+        let frame: RespFrame = BulkString::new(vec![b'x'; len]).into();
+        let encoded = frame.clone().encode();
+
+        assert_eq!(config("1mb")?, crate::SimpleString::new("OK").into());
+        let mut src = bytes::BytesMut::from(&encoded[..]);
+        assert!(codec.decode(&mut src).is_err());
+        // 只收到头部时就能拒绝，不需要等待整个 bulk string
+        let mut src = bytes::BytesMut::from(&encoded[..16]);
+        assert!(codec.decode(&mut src).is_err());
+
+        assert_eq!(config("2mb")?, crate::SimpleString::new("OK").into());
+        let mut src = bytes::BytesMut::from(&encoded[..]);
+        assert_eq!(codec.decode(&mut src)?, Some(frame));
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_rejects_deep_and_wide_requests() -> Result<()> {
+        let backend = Backend::new();
+        let mut codec = RespFrameCodec::for_backend(&backend);
+        let nested = |depth: usize| {
+            let mut frame: RespFrame = BulkString::from("ping").into();
+            for _ in 0..depth {
+                frame = RespArray::new(vec![frame]).into();
+            }
+            frame.encode()
+        };
+
+        // 默认最多嵌套 64 层
+        let mut src = BytesMut::from(&nested(64)[..]);
+        assert!(codec.decode(&mut src)?.is_some());
+        let mut src = BytesMut::from(&nested(65)[..]);
+        assert!(codec.decode(&mut src).is_err());
+
+        backend.config().set_proto_max_nesting_depth(2);
+        let mut src = BytesMut::from(&nested(3)[..]);
+        assert!(codec.decode(&mut src).is_err());
+
+        // 元素个数只看头部，不需要等待元素到达
+        backend.config().set_proto_max_multibulk_len(3);
+        let mut src = BytesMut::from(&b"*4\r\n"[..]);
+        assert!(codec.decode(&mut src).is_err());
+        let mut src =
+            BytesMut::from(&RespArray::new(vec![BulkString::from("ping").into(); 3]).encode()[..]);
+        assert!(codec.decode(&mut src)?.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_stats_count_partial_reads() -> Result<()> {
+        let backend = Backend::new();
+        let mut codec = RespFrameCodec::for_backend(&backend);
+        let encoded = b"*2\r\n$3\r\nget\r\n$3\r\nkey\r\n";
+
+        // 分三次到达，前两次都只有半个 frame
+        let mut src = bytes::BytesMut::from(&encoded[..5]);
+        assert_eq!(codec.decode(&mut src)?, None);
+        src.extend_from_slice(&encoded[5..14]);
+        assert_eq!(codec.decode(&mut src)?, None);
+        src.extend_from_slice(&encoded[14..]);
+        assert!(codec.decode(&mut src)?.is_some());
+        assert_eq!(
+            backend.parse_stats(),
+            crate::ParseStats {
+                frames: 1,
+                bytes: encoded.len() as u64,
+                not_complete: 2,
+            }
+        );
+
+        // 一次到达的两个 frame 不算半包，读空缓冲区也不计数
+        let mut src = bytes::BytesMut::from(&[&encoded[..], &encoded[..]].concat()[..]);
+        assert!(codec.decode(&mut src)?.is_some());
+        assert!(codec.decode(&mut src)?.is_some());
+        assert_eq!(codec.decode(&mut src)?, None);
+        let stats = backend.parse_stats();
+        assert_eq!(stats.frames, 3);
+        assert_eq!(stats.bytes, 3 * encoded.len() as u64);
+        assert_eq!(stats.not_complete, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_buffer_pipeline() {
+        let backend = Backend::new();
+        let mut buf = BytesMut::from(
+            &b"*3\r\n$3\r\nset\r\n$1\r\na\r\n$1\r\n1\r\n\
+               *2\r\n$3\r\nget\r\n$1\r\na\r\n\
+               *2\r\n$3\r\nget\r\n$1\r"[..],
+        );
+        let replies = process_buffer(&mut buf, &backend, &mut ConnectionContext::default());
+        assert_eq!(replies, b"+OK\r\n$1\r\n1\r\n");
+        assert_eq!(buf.as_ref(), b"*2\r\n$3\r\nget\r\n$1\r");
+
+        // 补齐后续数据后继续处理
+        buf.extend_from_slice(b"\na\r\n");
+        assert_eq!(
+            process_buffer(&mut buf, &backend, &mut ConnectionContext::default()),
+            b"$1\r\n1\r\n"
+        );
+        assert!(buf.is_empty());
+
+        // 非法帧回复错误，并从下一条命令继续
+        let mut buf = BytesMut::from(&b"*1\r\n:abc\r\n*1\r\n$4\r\nping\r\n"[..]);
+        let replies = String::from_utf8(process_buffer(
+            &mut buf,
+            &backend,
+            &mut ConnectionContext::default(),
+        ))
+        .unwrap();
+        assert!(replies.starts_with("-ERR Protocol error"), "{}", replies);
+        assert!(replies.ends_with("\r\n+PONG\r\n"), "{}", replies);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_process_buffer_applies_rate_limit() {
+        let clock = Arc::new(crate::ManualClock::new());
+        let backend = Backend::with_clock(clock);
+        backend.config().set_client_command_rate_limit(2);
+        let mut ctx = ConnectionContext::default();
+        let mut buf = BytesMut::from(&b"*1\r\n$4\r\nping\r\n"[..].repeat(3)[..]);
+        assert_eq!(
+            process_buffer(&mut buf, &backend, &mut ctx),
+            b"+PONG\r\n+PONG\r\n-ERR rate limit exceeded\r\n"
+        );
+        assert!(buf.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_strict_resp2_replies_match_redis_bytes() -> Result<()> {
+        let backend = Backend::new();
+        let config = |value: &str| {
+            let mut buf = BytesMut::from(
+                format!(
+                    "*4\r\n$6\r\nconfig\r\n$3\r\nset\r\n$12\r\nstrict-resp2\r\n${}\r\n{}\r\n",
+                    value.len(),
+                    value
+                )
+                .as_bytes(),
+            );
+            process_buffer(&mut buf, &backend, &mut ConnectionContext::default())
+        };
+        let get_miss = b"*2\r\n$3\r\nget\r\n$7\r\nmissing\r\n";
+
+        // 默认按 RESP2 编码，HELLO 3 之后 GET 未命中才是 RESP3 null
+        let mut ctx = ConnectionContext::default();
+        let mut buf = BytesMut::from(&get_miss[..]);
+        assert_eq!(process_buffer(&mut buf, &backend, &mut ctx), b"$-1\r\n");
+        let mut buf = BytesMut::from(&b"*2\r\n$5\r\nhello\r\n$1\r\n3\r\n"[..]);
+        process_buffer(&mut buf, &backend, &mut ctx);
+        assert_eq!(ctx.version, RespVersion::Resp3);
+        let mut buf = BytesMut::from(&get_miss[..]);
+        assert_eq!(process_buffer(&mut buf, &backend, &mut ctx), b"_\r\n");
+
+        assert_eq!(config("yes"), b"+OK\r\n");
+        let mut buf = BytesMut::from(
+            &b"*2\r\n$3\r\nget\r\n$7\r\nmissing\r\n\
+               *1\r\n$4\r\nping\r\n\
+               *2\r\n$4\r\necho\r\n$5\r\nhello\r\n"[..],
+        );
+        assert_eq!(
+            process_buffer(&mut buf, &backend, &mut ConnectionContext::default()),
+            b"$-1\r\n+PONG\r\n$5\r\nhello\r\n"
+        );
+
+        // 连接上协商过 RESP3 也按 RESP2 回复，且不能再切换到 RESP3
+        let request = |args: &[&str]| RedisRequest {
+            frame: RespArray::new(
+                args.iter()
+                    .map(|arg| BulkString::from(*arg).into())
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            backend: backend.clone(),
+        };
+        let mut subscriber = backend.subscriber();
+        let mut ctx = ConnectionContext {
+            version: RespVersion::Resp3,
+            ..Default::default()
+        };
+        assert_eq!(
+            backend.config().reply_version(ctx.version),
+            RespVersion::Resp2
+        );
+        let response = request_handler(request(&["hello", "3"]), &mut subscriber, &mut ctx).await?;
+        assert_eq!(
+            response.frames,
+            vec![RespFrame::error("NOPROTO", "unsupported protocol version")]
+        );
+
+        assert_eq!(config("no"), b"+OK\r\n");
+        assert_eq!(
+            backend.config().reply_version(ctx.version),
+            RespVersion::Resp3
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reset_and_quit_in_subscribe_mode() -> Result<()> {
+        let backend = Backend::new();
+        let request = |args: &[&str]| RedisRequest {
+            frame: RespArray::new(
+                args.iter()
+                    .map(|arg| BulkString::from(*arg).into())
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            backend: backend.clone(),
+        };
+        let mut subscriber = backend.subscriber();
+        let mut ctx = ConnectionContext::default();
+        request_handler(request(&["subscribe", "news"]), &mut subscriber, &mut ctx).await?;
+        assert_eq!(ctx.subscriptions, 1);
+
+        let response = request_handler(request(&["reset"]), &mut subscriber, &mut ctx).await?;
+        assert_eq!(
+            response.frames,
+            vec![crate::SimpleString::new("RESET").into()]
+        );
+        assert!(!response.close);
+        assert_eq!((ctx.subscriptions, subscriber.subscription_count()), (0, 0));
+
+        request_handler(request(&["subscribe", "news"]), &mut subscriber, &mut ctx).await?;
+        let response = request_handler(request(&["quit"]), &mut subscriber, &mut ctx).await?;
+        assert_eq!(response.frames, vec![crate::SimpleString::new("OK").into()]);
+        assert!(response.close);
+        Ok(())
+    }
+}
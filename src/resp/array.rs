@@ -48,6 +48,7 @@ impl RespDecode for RespArray {
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
         let (end, len) = parse_length_isize(buf, Self::PREFIX)?;
         if len == -1 {
+            buf.advance(end + CRLF_LEN);
             return Ok(RespArray::null());
         }
         let len = len as usize;
@@ -1,4 +1,4 @@
-use super::{parse_length_isize, CRLF_LEN};
+use super::{parse_length_isize, CRLF, CRLF_LEN};
 use crate::{RespDecode, RespEncode, RespError};
 use bytes::{Buf, BytesMut};
 use lazy_static::lazy_static;
@@ -52,6 +52,7 @@ impl RespDecode for BulkString {
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
         let (end, len) = parse_length_isize(buf, Self::PREFIX)?;
         if len == -1 {
+            buf.advance(end + CRLF_LEN);
             return Ok(BulkString::null());
         }
         let len = len as usize;
@@ -59,6 +60,12 @@ impl RespDecode for BulkString {
         if remained.len() < len + CRLF_LEN {
             return Err(RespError::NotComplete);
         }
+        if &remained[len..len + CRLF_LEN] != CRLF {
+            return Err(RespError::InvalidFrame(format!(
+                "bulk string of length {} is not followed by CRLF",
+                len
+            )));
+        }
 
         buf.advance(end + CRLF_LEN);
 
@@ -2,8 +2,8 @@ use bytes::BytesMut;
 use enum_dispatch::enum_dispatch;
 
 use crate::{
-    BulkString, RespArray, RespDecode, RespError, RespMap, RespNull, RespSet, SimpleError,
-    SimpleString,
+    BulkString, RespArray, RespDecode, RespEncode, RespError, RespMap, RespNull, RespPush, RespSet,
+    RespVersion, SimpleError, SimpleString,
 };
 
 #[enum_dispatch(RespEncode, RespDecode)]
@@ -19,6 +19,7 @@ pub enum RespFrame {
     Double(f64),
     Map(RespMap),
     Set(RespSet),
+    Push(RespPush),
 }
 
 impl RespDecode for RespFrame {
@@ -67,6 +68,10 @@ impl RespDecode for RespFrame {
                 let frame = RespSet::decode(buf)?;
                 Ok(frame.into())
             }
+            Some(b'>') => {
+                let frame = RespPush::decode(buf)?;
+                Ok(frame.into())
+            }
             None => Err(RespError::NotComplete),
             _ => Err(RespError::InvalidFrameType(format!(
                 "expect_length: unknown frame type: {:?}",
@@ -80,6 +85,7 @@ impl RespDecode for RespFrame {
         match iter.peek() {
             Some(b'*') => RespArray::expect_length(buf),
             Some(b'~') => RespSet::expect_length(buf),
+            Some(b'>') => RespPush::expect_length(buf),
             Some(b'%') => RespMap::expect_length(buf),
             Some(b'$') => BulkString::expect_length(buf),
             Some(b':') => i64::expect_length(buf),
@@ -88,9 +94,182 @@ impl RespDecode for RespFrame {
             Some(b'#') => bool::expect_length(buf),
             Some(b',') => f64::expect_length(buf),
             Some(b'_') => RespNull::expect_length(buf),
-            _ => Err(RespError::NotComplete),
+            None => Err(RespError::NotComplete),
+            _ => Err(RespError::InvalidFrameType(format!(
+                "expect_length: unknown frame type: {:?}",
+                buf
+            ))),
+        }
+    }
+}
+
+impl RespFrame {
+    /// Build an error reply in the `CODE message` form clients parse, e.g.
+    /// `RespFrame::error("WRONGTYPE", "Operation against a key ...")`.
+    pub fn error(code: &str, msg: &str) -> RespFrame {
+        SimpleError::new(format!("{} {}", code, msg)).into()
+    }
+
+    /// Convert RESP3-only frames to the shape a RESP2 client understands.
+    pub fn into_resp2(self) -> RespFrame {
+        match self {
+            RespFrame::Null(_) => BulkString::null().into(),
+            RespFrame::Boolean(b) => RespFrame::Integer(b as i64),
+            RespFrame::Double(d) => BulkString::from(format_double(d)).into(),
+            RespFrame::Array(RespArray(Some(frames))) => RespArray::new(
+                frames
+                    .into_iter()
+                    .map(|f| f.into_resp2())
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            RespFrame::Set(set) => RespArray::new(
+                set.0
+                    .into_iter()
+                    .map(|f| f.into_resp2())
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            RespFrame::Push(push) => RespArray::new(
+                push.0
+                    .into_iter()
+                    .map(|f| f.into_resp2())
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            RespFrame::Map(map) => RespArray::new(
+                map.0
+                    .into_iter()
+                    .flat_map(|(k, v)| [BulkString::from(k).into(), v.into_resp2()])
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            frame => frame,
+        }
+    }
+
+    /// Size of the RESP3 encoding computed without encoding, exact for every
+    /// type but doubles whose textual form is estimated.
+    pub fn estimated_encoded_len(&self) -> usize {
+        // 类型前缀 + 长度或内容 + CRLF
+        let header = |n: usize| 1 + decimal_len(n as i64) + 2;
+        match self {
+            RespFrame::SimpleString(s) => 1 + s.len() + 2,
+            RespFrame::Error(e) => 1 + e.len() + 2,
+            RespFrame::Integer(i) => 1 + decimal_len(*i) + 2,
+            RespFrame::BulkString(BulkString(Some(data))) => header(data.len()) + data.len() + 2,
+            RespFrame::BulkString(BulkString(None)) => 5,
+            RespFrame::Array(RespArray(Some(frames))) => {
+                header(frames.len())
+                    + frames
+                        .iter()
+                        .map(Self::estimated_encoded_len)
+                        .sum::<usize>()
+            }
+            RespFrame::Array(RespArray(None)) => 5,
+            RespFrame::Null(_) => 3,
+            RespFrame::Boolean(_) => 4,
+            RespFrame::Double(_) => 1 + 24 + 2,
+            RespFrame::Map(map) => {
+                header(map.len())
+                    + map
+                        .iter()
+                        .map(|(k, v)| 1 + k.len() + 2 + v.estimated_encoded_len())
+                        .sum::<usize>()
+            }
+            RespFrame::Set(set) => {
+                header(set.len()) + set.iter().map(Self::estimated_encoded_len).sum::<usize>()
+            }
+            RespFrame::Push(push) => {
+                header(push.len()) + push.iter().map(Self::estimated_encoded_len).sum::<usize>()
+            }
         }
     }
+
+    pub fn into_version(self, version: RespVersion) -> RespFrame {
+        match version {
+            RespVersion::Resp2 => self.into_resp2(),
+            RespVersion::Resp3 => self,
+        }
+    }
+
+    /// Encode straight into `dst`. Aggregates are written element by element
+    /// instead of each level being encoded into its own buffer and copied.
+    /// The frame itself is still built in full beforehand, this only saves
+    /// the intermediate copies. The bytes are the same as `encode`.
+    pub fn encode_into(self, dst: &mut BytesMut) {
+        match self {
+            RespFrame::Array(RespArray(Some(frames))) => encode_aggregate(b'*', frames, dst),
+            RespFrame::Set(set) => encode_aggregate(b'~', set.0, dst),
+            RespFrame::Push(push) => encode_aggregate(b'>', push.0, dst),
+            RespFrame::Map(map) => {
+                dst.extend_from_slice(format!("%{}\r\n", map.len()).as_bytes());
+                for (key, value) in map.0 {
+                    dst.extend_from_slice(&SimpleString::new(key).encode());
+                    value.encode_into(dst);
+                }
+            }
+            frame => dst.extend_from_slice(&frame.encode()),
+        }
+    }
+}
+
+fn encode_aggregate(prefix: u8, frames: Vec<RespFrame>, dst: &mut BytesMut) {
+    dst.extend_from_slice(&[prefix]);
+    dst.extend_from_slice(format!("{}\r\n", frames.len()).as_bytes());
+    for frame in frames {
+        frame.encode_into(dst);
+    }
+}
+
+fn decimal_len(n: i64) -> usize {
+    let sign = (n < 0) as usize;
+    sign + n
+        .unsigned_abs()
+        .checked_ilog10()
+        .map_or(1, |d| d as usize + 1)
+}
+
+// RESP2 下 double 以字符串返回，格式和 Redis 的分数一致：按 %.17g 的规则选择定点或科学计数法，
+// 数字取能精确还原的最短表示，不带多余的 0，如 "3"、"3.14"、"1e+100"、"inf"
+fn format_double(d: f64) -> String {
+    if d.is_nan() {
+        return "nan".to_string();
+    }
+    if d.is_infinite() {
+        return if d > 0.0 { "inf" } else { "-inf" }.to_string();
+    }
+    // {:e} 给出最短的有效数字和十进制指数，如 "-3.14e0"、"1e100"
+    let sci = format!("{:e}", d);
+    let (mantissa, exp) = sci.split_once('e').unwrap_or((&sci, "0"));
+    let exp: i32 = exp.parse().unwrap_or(0);
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", mantissa),
+    };
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+
+    // 和 %.17g 一样，指数小于 -4 或不小于精度时使用科学计数法，指数至少两位
+    if !(-4..17).contains(&exp) {
+        let (first, rest) = digits.split_at(1);
+        let fraction = if rest.is_empty() {
+            String::new()
+        } else {
+            format!(".{}", rest)
+        };
+        let exp_sign = if exp < 0 { '-' } else { '+' };
+        return format!("{}{}{}e{}{:02}", sign, first, fraction, exp_sign, exp.abs());
+    }
+    if exp < 0 {
+        let zeros = "0".repeat((-exp - 1) as usize);
+        return format!("{}0.{}{}", sign, zeros, digits);
+    }
+    let point = exp as usize + 1;
+    if digits.len() <= point {
+        format!("{}{}{}", sign, digits, "0".repeat(point - digits.len()))
+    } else {
+        format!("{}{}.{}", sign, &digits[..point], &digits[point..])
+    }
 }
 
 impl From<&str> for RespFrame {
@@ -113,5 +292,248 @@ impl<const N: usize> From<&[u8; N]> for RespFrame {
 
 #[cfg(test)]
 mod tests {
-    // TODO: Add tests
+    use super::*;
+    use crate::RespEncode;
+
+    #[test]
+    fn test_encode_into_matches_encode_for_nested_frames() {
+        let mut map = RespMap::new();
+        map.insert("k\r\n".to_string(), RespSet::new(vec![1.5.into()]).into());
+        map.insert("n".to_string(), RespArray::null().into());
+        let frame: RespFrame = RespArray::new(vec![
+            BulkString::from("a").into(),
+            map.into(),
+            RespPush::new(vec![RespFrame::Integer(1), RespNull.into()]).into(),
+            RespArray::new(vec![]).into(),
+        ])
+        .into();
+
+        let mut dst = BytesMut::from(&b"prefix"[..]);
+        frame.clone().encode_into(&mut dst);
+        assert_eq!(&dst[..6], b"prefix");
+        assert_eq!(dst[6..], frame.encode());
+    }
+
+    #[test]
+    fn test_null_forms_round_trip_separately() -> anyhow::Result<()> {
+        use crate::{RespDecode, RespDecodeV2};
+
+        let cases: [(&[u8], RespFrame); 3] = [
+            (b"_\r\n", RespNull.into()),
+            (b"$-1\r\n", BulkString::null().into()),
+            (b"*-1\r\n", RespArray::null().into()),
+        ];
+        for (encoded, expected) in cases {
+            let mut buf = BytesMut::from(encoded);
+            let frame = <RespFrame as RespDecode>::decode(&mut buf)?;
+            assert_eq!(frame, expected);
+            assert!(buf.is_empty());
+
+            let mut buf = BytesMut::from(encoded);
+            let frame = <RespFrame as RespDecodeV2>::decode(&mut buf)?;
+            assert_eq!(frame, expected);
+            assert!(buf.is_empty());
+
+            assert_eq!(frame.encode(), encoded);
+        }
+
+        // 三种 null 互不相等，RESP2 下 RESP3 null 才转换成 null bulk string
+        let null: RespFrame = RespNull.into();
+        let null_bulk: RespFrame = BulkString::null().into();
+        let null_array: RespFrame = RespArray::null().into();
+        assert_ne!(null, null_bulk);
+        assert_ne!(null, null_array);
+        assert_ne!(null_bulk, null_array);
+        assert_eq!(null.into_resp2().encode(), b"$-1\r\n");
+        assert_eq!(null_array.into_resp2().encode(), b"*-1\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_estimated_encoded_len_matches_encoding() {
+        let mut map = RespMap::new();
+        map.insert("field".to_string(), BulkString::from("value").into());
+        let frames: Vec<RespFrame> = vec![
+            SimpleString::new("OK").into(),
+            RespFrame::error("ERR", "oops"),
+            RespFrame::Integer(-12345),
+            RespFrame::Integer(0),
+            BulkString::new(vec![b'x'; 1000]).into(),
+            BulkString::null().into(),
+            RespArray::null().into(),
+            RespNull.into(),
+            RespFrame::Boolean(true),
+            map.into(),
+            RespSet::new(vec![RespFrame::Integer(1)]).into(),
+        ];
+        for frame in &frames {
+            assert_eq!(frame.estimated_encoded_len(), frame.clone().encode().len());
+        }
+        let array: RespFrame = RespArray::new(frames).into();
+        assert_eq!(array.estimated_encoded_len(), array.clone().encode().len());
+    }
+
+    #[test]
+    fn test_error_builder_encodes_code_and_message() {
+        let frame = RespFrame::error(
+            "WRONGTYPE",
+            "Operation against a key holding the wrong kind of value",
+        );
+        assert_eq!(
+            frame.encode(),
+            b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n"
+        );
+        let frame = RespFrame::error("ERR", "unknown command 'foo'");
+        assert_eq!(frame.encode(), b"-ERR unknown command 'foo'\r\n");
+    }
+
+    #[test]
+    fn test_into_resp2() {
+        let frame: RespFrame = RespNull.into();
+        assert_eq!(frame.into_resp2().encode(), b"$-1\r\n");
+
+        let frame: RespFrame = 1.5.into();
+        assert_eq!(frame.into_resp2().encode(), b"$3\r\n1.5\r\n");
+
+        let frame: RespFrame = f64::NEG_INFINITY.into();
+        assert_eq!(frame.into_resp2().encode(), b"$4\r\n-inf\r\n");
+
+        let frame: RespFrame = true.into();
+        assert_eq!(frame.into_resp2().encode(), b":1\r\n");
+
+        let mut map = RespMap::new();
+        map.insert("k".to_string(), RespNull.into());
+        let frame: RespFrame = RespArray::new(vec![map.into()]).into();
+        assert_eq!(
+            frame.into_resp2().encode(),
+            b"*1\r\n*2\r\n$1\r\nk\r\n$-1\r\n"
+        );
+    }
+
+    #[test]
+    // 3.14 是常见的分数写法，不是想表示 PI
+    #[allow(clippy::approx_constant)]
+    fn test_format_double_matches_redis_scores() {
+        for (d, expected) in [
+            (3.0, "3"),
+            (3.14, "3.14"),
+            (-2.5, "-2.5"),
+            (0.0, "0"),
+            (1e100, "1e+100"),
+            (-1.5e-7, "-1.5e-07"),
+            (1e-5, "1e-05"),
+            (0.0001, "0.0001"),
+            (0.1 + 0.2, "0.30000000000000004"),
+            (123456789.0, "123456789"),
+            (1e16, "10000000000000000"),
+            (1.5e17, "1.5e+17"),
+            (f64::INFINITY, "inf"),
+            (f64::NEG_INFINITY, "-inf"),
+        ] {
+            assert_eq!(format_double(d), expected, "{:?}", d);
+            if d.is_finite() {
+                assert_eq!(expected.parse::<f64>().unwrap(), d);
+            }
+        }
+        assert_eq!(
+            RespFrame::Double(3.14).into_resp2().encode(),
+            b"$4\r\n3.14\r\n"
+        );
+    }
+
+    #[test]
+    fn test_into_version_resp3_unchanged() {
+        let frame: RespFrame = 1.5.into();
+        assert_eq!(frame.clone().into_version(RespVersion::Resp3), frame);
+    }
+
+    // 手写的随机 frame 生成器，depth 限制嵌套层数
+    fn arbitrary_frame(rng: &crate::Rng, depth: usize) -> RespFrame {
+        let text = |rng: &crate::Rng| -> String {
+            // simple string 和 error 不能包含 \r\n
+            (0..rng.below(12))
+                .map(|_| (b' ' + rng.below(95) as u8) as char)
+                .collect()
+        };
+        let kinds = if depth == 0 { 10 } else { 14 };
+        match rng.below(kinds) {
+            0 => SimpleString::new(text(rng)).into(),
+            1 => SimpleError::new(text(rng)).into(),
+            2 => {
+                let edge = [0, 1, -1, i64::MIN, i64::MAX];
+                match rng.below(2) {
+                    0 => RespFrame::Integer(edge[rng.below(edge.len())]),
+                    _ => RespFrame::Integer(rng.next_u64() as i64),
+                }
+            }
+            3 => BulkString::new(
+                (0..rng.below(32))
+                    .map(|_| rng.below(256) as u8)
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            4 => BulkString::null().into(),
+            5 => RespArray::null().into(),
+            6 => RespNull.into(),
+            7 => RespFrame::Boolean(rng.below(2) == 0),
+            8 => {
+                let edge = [0.0, -1.5, 1e-9, 1.23456e8, f64::INFINITY, f64::NEG_INFINITY];
+                match rng.below(2) {
+                    0 => RespFrame::Double(edge[rng.below(edge.len())]),
+                    _ => RespFrame::Double(rng.next_u64() as i64 as f64 / 1024.0),
+                }
+            }
+            9 => BulkString::new(Vec::new()).into(),
+            10 => RespArray::new(
+                (0..rng.below(5))
+                    .map(|_| arbitrary_frame(rng, depth - 1))
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            11 => {
+                let mut map = RespMap::new();
+                for _ in 0..rng.below(5) {
+                    map.insert(text(rng), arbitrary_frame(rng, depth - 1));
+                }
+                map.into()
+            }
+            12 => RespSet::new(
+                (0..rng.below(5))
+                    .map(|_| arbitrary_frame(rng, depth - 1))
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            _ => RespPush::new(
+                (0..rng.below(5))
+                    .map(|_| arbitrary_frame(rng, depth - 1))
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_round_trip_property() -> anyhow::Result<()> {
+        use crate::{RespDecode, RespDecodeV2};
+        use anyhow::Context;
+
+        let rng = crate::Rng::new(0x5eed);
+        for _ in 0..2000 {
+            let frame = arbitrary_frame(&rng, 3);
+            let encoded = frame.clone().encode();
+
+            let mut buf = BytesMut::from(encoded.as_slice());
+            let decoded = <RespFrame as RespDecode>::decode(&mut buf)
+                .with_context(|| format!("v1 decode of {:?}", frame))?;
+            assert_eq!(decoded, frame, "v1 decode of {:?}", encoded);
+            assert!(buf.is_empty());
+
+            let mut buf = BytesMut::from(encoded.as_slice());
+            let decoded = <RespFrame as RespDecodeV2>::decode(&mut buf)
+                .with_context(|| format!("v2 decode of {:?}", frame))?;
+            assert_eq!(decoded, frame, "v2 decode of {:?}", encoded);
+            assert!(buf.is_empty(), "v2 left {:?} of {:?}", buf, frame);
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,125 @@
+use serde_json::{Map, Number, Value};
+
+use crate::RespFrame;
+
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+impl RespFrame {
+    /// Map a frame to JSON for logging or bridging to HTTP.
+    ///
+    /// Bulk strings that are valid UTF-8 become JSON strings, binary ones become
+    /// `{"base64": "..."}` so no byte is lost. Errors become `{"error": "..."}`,
+    /// null bulk strings / arrays and RESP3 null become `null`, and non-finite
+    /// doubles are rendered as the strings "inf", "-inf" and "nan".
+    pub fn to_json(&self) -> Value {
+        match self {
+            RespFrame::SimpleString(s) => Value::String(s.0.clone()),
+            RespFrame::Error(e) => {
+                let mut map = Map::new();
+                map.insert("error".to_string(), Value::String(e.0.clone()));
+                Value::Object(map)
+            }
+            RespFrame::Integer(n) => Value::Number((*n).into()),
+            RespFrame::BulkString(s) => match &s.0 {
+                Some(data) => bytes_to_json(data),
+                None => Value::Null,
+            },
+            RespFrame::Array(array) => match &array.0 {
+                Some(frames) => Value::Array(frames.iter().map(|f| f.to_json()).collect()),
+                None => Value::Null,
+            },
+            RespFrame::Null(_) => Value::Null,
+            RespFrame::Boolean(b) => Value::Bool(*b),
+            RespFrame::Double(d) => match Number::from_f64(*d) {
+                Some(n) => Value::Number(n),
+                None if d.is_nan() => Value::String("nan".to_string()),
+                None if *d > 0.0 => Value::String("inf".to_string()),
+                None => Value::String("-inf".to_string()),
+            },
+            RespFrame::Map(map) => {
+                Value::Object(map.iter().map(|(k, v)| (k.clone(), v.to_json())).collect())
+            }
+            RespFrame::Set(set) => Value::Array(set.iter().map(|f| f.to_json()).collect()),
+            RespFrame::Push(push) => Value::Array(push.iter().map(|f| f.to_json()).collect()),
+        }
+    }
+}
+
+fn bytes_to_json(data: &[u8]) -> Value {
+    match std::str::from_utf8(data) {
+        Ok(s) => Value::String(s.to_string()),
+        Err(_) => {
+            let mut map = Map::new();
+            map.insert("base64".to_string(), Value::String(base64_encode(data)));
+            Value::Object(map)
+        }
+    }
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_CHARS[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespArray, RespMap, RespNull, SimpleError};
+    use serde_json::json;
+
+    #[test]
+    fn test_nested_array_to_json() {
+        let frame: RespFrame = RespArray::new(vec![
+            BulkString::from("hello").into(),
+            RespFrame::Integer(42),
+            RespArray::new(vec![RespNull.into(), true.into(), 1.5.into()]).into(),
+            BulkString::null().into(),
+            SimpleError::new("ERR oops").into(),
+        ])
+        .into();
+        assert_eq!(
+            frame.to_json(),
+            json!(["hello", 42, [null, true, 1.5], null, {"error": "ERR oops"}])
+        );
+    }
+
+    #[test]
+    fn test_map_to_json() {
+        let mut map = RespMap::new();
+        map.insert("name".to_string(), BulkString::from("redis").into());
+        map.insert(
+            "items".to_string(),
+            RespArray::new(vec![RespFrame::Integer(1), f64::INFINITY.into()]).into(),
+        );
+        let frame: RespFrame = map.into();
+        assert_eq!(
+            frame.to_json(),
+            json!({"name": "redis", "items": [1, "inf"]})
+        );
+    }
+
+    #[test]
+    fn test_binary_bulk_string_to_json() {
+        let frame: RespFrame = BulkString::new(vec![0xff, 0x00, 0x61]).into();
+        assert_eq!(frame.to_json(), json!({"base64": "/wBh"}));
+
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+    }
+}
@@ -48,9 +48,21 @@ impl RespDecode for RespMap {
 
         let mut frames = RespMap::new();
         for _ in 0..len {
-            let key = SimpleString::decode(buf)?;
+            // 带 CR/LF 的 key 会被编码成 bulk string
+            let key = match RespFrame::decode(buf)? {
+                RespFrame::SimpleString(key) => key.0,
+                RespFrame::BulkString(key) => {
+                    String::from_utf8_lossy(&key.get_data()?).into_owned()
+                }
+                other => {
+                    return Err(RespError::InvalidFrameType(format!(
+                        "expect: map key, got: {:?}",
+                        other
+                    )))
+                }
+            };
             let value = RespFrame::decode(buf)?;
-            frames.insert(key.0, value);
+            frames.insert(key, value);
         }
 
         Ok(frames)
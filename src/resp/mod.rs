@@ -4,15 +4,21 @@ mod bulk_string;
 mod double;
 mod frame;
 mod integer;
+#[cfg(feature = "json")]
+mod json;
 mod map;
 mod null;
+mod push;
 mod set;
+mod shared;
 mod simple_error;
 mod simple_string;
+mod visitor;
 
 pub use self::{
     array::RespArray, bulk_string::BulkString, frame::RespFrame, map::RespMap, null::RespNull,
-    set::RespSet, simple_error::SimpleError, simple_string::SimpleString,
+    push::RespPush, set::RespSet, shared::SharedFrame, simple_error::SimpleError,
+    simple_string::SimpleString, visitor::RespVisitor,
 };
 use bytes::{Buf, BytesMut};
 use enum_dispatch::enum_dispatch;
@@ -22,6 +28,14 @@ pub const BUF_CAP: usize = 4096;
 const CRLF: &[u8] = b"\r\n";
 const CRLF_LEN: usize = CRLF.len();
 
+/// Protocol negotiated with a client through HELLO, RESP2 by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RespVersion {
+    #[default]
+    Resp2,
+    Resp3,
+}
+
 #[enum_dispatch]
 pub trait RespEncode {
     fn encode(self) -> Vec<u8>;
@@ -43,6 +57,17 @@ pub enum RespError {
     InvalidFrameLength(isize),
     #[error("Frame is not complete")]
     NotComplete,
+    #[error("Frame nesting is deeper than {0}")]
+    NestingTooDeep(usize),
+    #[error("Bulk string of {0} bytes is too long")]
+    BulkTooLong(i64),
+    #[error("Aggregate of {0} elements is too large")]
+    TooManyElements(i64),
+    #[error("Invalid frame at pipeline index {index}: {source}")]
+    InvalidFrameAt {
+        index: usize,
+        source: Box<RespError>,
+    },
 
     #[error("Parse error: {0}")]
     ParseIntError(#[from] std::num::ParseIntError),
@@ -108,36 +133,46 @@ fn parse_length(buf: &[u8], prefix: &str) -> Result<(usize, usize), RespError> {
     Ok((end, length as usize))
 }
 
+// 头部已经完整，解析失败说明数据本身有问题，不能当作 NotComplete 继续等待
 fn parse_length_isize(buf: &[u8], prefix: &str) -> Result<(usize, isize), RespError> {
     let end = extract_simple_frame_data(buf, prefix)?;
     let s = String::from_utf8_lossy(&buf[prefix.len()..end]);
-    Ok((end, s.parse()?))
+    let len: isize = s
+        .parse()
+        .map_err(|_| RespError::InvalidFrame(format!("invalid length header: {:?}", s)))?;
+    // -1 表示 null，其他负数都是非法的
+    if len < -1 {
+        return Err(RespError::InvalidFrameLength(len));
+    }
+    Ok((end, len))
 }
 
 fn calc_total_length(buf: &[u8], end: usize, len: usize, prefix: &str) -> Result<usize, RespError> {
     let mut total = end + CRLF_LEN;
     let mut data = &buf[total..];
+    // bulk string 的长度只由头部算出，数据可能还没有全部到达
+    let mut skip_element = |data: &mut &[u8]| -> Result<(), RespError> {
+        let len = RespFrame::expect_length(data)?;
+        if len > data.len() {
+            return Err(RespError::NotComplete);
+        }
+        *data = &data[len..];
+        total += len;
+        Ok(())
+    };
     match prefix {
-        "*" | "~" => {
-            // find nth CRLF in the buffer, for array and set, we need to find 1 CRLF for each element
+        "*" | "~" | ">" => {
+            // find nth CRLF in the buffer, for array, set and push, we need to find 1 CRLF for each element
             for _ in 0..len {
-                let len = RespFrame::expect_length(data)?;
-                data = &data[len..];
-                total += len;
+                skip_element(&mut data)?;
             }
             Ok(total)
         }
         "%" => {
             // find nth CRLF in the buffer. For map, we need to find 2 CRLF for each key-value pair
             for _ in 0..len {
-                let len = SimpleString::expect_length(data)?;
-
-                data = &data[len..];
-                total += len;
-
-                let len = RespFrame::expect_length(data)?;
-                data = &data[len..];
-                total += len;
+                skip_element(&mut data)?;
+                skip_element(&mut data)?;
             }
             Ok(total)
         }
@@ -164,4 +199,55 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_malformed_frame_is_not_incomplete() {
+        let decode = |input: &[u8]| RespFrame::decode(&mut BytesMut::from(input));
+        let invalid = |input: &[u8]| match decode(input) {
+            Err(RespError::NotComplete) | Ok(_) => {
+                panic!("{:?} should be invalid", String::from_utf8_lossy(input))
+            }
+            Err(_) => {}
+        };
+
+        // 长度头部不是数字
+        assert!(matches!(
+            decode(b"$abc\r\n"),
+            Err(RespError::InvalidFrame(_))
+        ));
+        assert!(matches!(
+            decode(b"*1x\r\n"),
+            Err(RespError::InvalidFrame(_))
+        ));
+        assert_eq!(decode(b"$-5\r\n"), Err(RespError::InvalidFrameLength(-5)));
+        assert_eq!(decode(b"*-3\r\n"), Err(RespError::InvalidFrameLength(-3)));
+        // 数据后面不是 CRLF
+        invalid(b"$3\r\nabcde\r\n");
+        // 数组元素的类型未知
+        invalid(b"*1\r\n!x\r\n");
+        invalid(b"*2\r\n$1\r\na\r\n$x\r\n");
+    }
+
+    #[test]
+    fn test_truncated_frame_is_incomplete() {
+        for input in [
+            &b"$"[..],
+            b"$3",
+            b"$3\r\nab",
+            b"$3\r\nabc",
+            b"*2\r\n",
+            b"*2\r\n$3\r\nset\r\n",
+            b"*1\r\n$10\r\nab",
+            b"*1\r\n*1\r\n$3\r\nab",
+            b"%1\r\n+a\r\n",
+            b":12",
+        ] {
+            assert_eq!(
+                RespFrame::decode(&mut BytesMut::from(input)),
+                Err(RespError::NotComplete),
+                "{:?}",
+                String::from_utf8_lossy(input)
+            );
+        }
+    }
 }
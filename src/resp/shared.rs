@@ -0,0 +1,89 @@
+use std::sync::{Arc, OnceLock};
+
+use super::{RespEncode, RespFrame, RespVersion};
+
+/// Cheaply cloneable, immutable frame for replies handed to many connections
+/// (pub/sub fan-out). Cloning only bumps a reference count, and the encoded
+/// bytes are computed once per protocol version and shared by every clone.
+#[derive(Debug, Clone)]
+pub struct SharedFrame(Arc<SharedInner>);
+
+#[derive(Debug)]
+struct SharedInner {
+    frame: RespFrame,
+    resp2: OnceLock<Vec<u8>>,
+    resp3: OnceLock<Vec<u8>>,
+}
+
+impl RespFrame {
+    pub fn into_shared(self) -> SharedFrame {
+        SharedFrame(Arc::new(SharedInner {
+            frame: self,
+            resp2: OnceLock::new(),
+            resp3: OnceLock::new(),
+        }))
+    }
+}
+
+impl SharedFrame {
+    pub fn frame(&self) -> &RespFrame {
+        &self.0.frame
+    }
+
+    /// Take the frame back, copying it only if other handles are still alive.
+    pub fn into_frame(self) -> RespFrame {
+        match Arc::try_unwrap(self.0) {
+            Ok(inner) => inner.frame,
+            Err(shared) => shared.frame.clone(),
+        }
+    }
+
+    /// Encoded bytes for `version`, encoding on first use only.
+    pub fn encode_for(&self, version: RespVersion) -> &[u8] {
+        let cell = match version {
+            RespVersion::Resp2 => &self.0.resp2,
+            RespVersion::Resp3 => &self.0.resp3,
+        };
+        cell.get_or_init(|| self.0.frame.clone().into_version(version).encode())
+    }
+}
+
+impl PartialEq for SharedFrame {
+    fn eq(&self, other: &Self) -> bool {
+        self.frame() == other.frame()
+    }
+}
+
+impl From<RespFrame> for SharedFrame {
+    fn from(frame: RespFrame) -> Self {
+        frame.into_shared()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BulkString;
+
+    #[test]
+    fn test_shared_frame_encodes_once() {
+        let frame: RespFrame = BulkString::new(vec![b'a'; 1024]).into();
+        let shared = frame.clone().into_shared();
+        let other = shared.clone();
+
+        let a = shared.encode_for(RespVersion::Resp2);
+        let b = other.encode_for(RespVersion::Resp2);
+        assert_eq!(a, frame.clone().encode());
+        assert!(std::ptr::eq(a, b));
+
+        drop(shared);
+        assert_eq!(other.into_frame(), frame);
+    }
+
+    #[test]
+    fn test_shared_frame_per_version() {
+        let shared = RespFrame::Double(1.5).into_shared();
+        assert_eq!(shared.encode_for(RespVersion::Resp2), b"$3\r\n1.5\r\n");
+        assert_eq!(shared.encode_for(RespVersion::Resp3), b",+1.5\r\n");
+    }
+}
@@ -2,7 +2,7 @@ use std::ops::Deref;
 
 use bytes::BytesMut;
 
-use crate::{RespDecode, RespEncode, RespError};
+use crate::{BulkString, RespDecode, RespEncode, RespError};
 
 use super::{extract_simple_frame_data, CRLF_LEN};
 
@@ -16,8 +16,13 @@ impl SimpleString {
 }
 
 // simple string: "+OK\r\n"
+// 内容里带 CR/LF 时按 simple string 编码会截断帧，客户端会把剩下的部分当成下一个回复，
+// 这种情况退化成 bulk string
 impl RespEncode for SimpleString {
     fn encode(self) -> Vec<u8> {
+        if self.0.contains(['\r', '\n']) {
+            return BulkString::new(self.0).encode();
+        }
         format!("+{}\r\n", self.0).into_bytes()
     }
 }
@@ -76,6 +81,23 @@ mod tests {
         assert_eq!(frame.encode(), b"+OK\r\n");
     }
 
+    #[test]
+    fn test_simple_string_with_crlf_encodes_as_bulk_string() -> Result<()> {
+        let frame: RespFrame = SimpleString::new("OK\r\n+INJECTED").into();
+        assert_eq!(frame.encode(), b"$13\r\nOK\r\n+INJECTED\r\n");
+
+        // map 的 key 也按 simple string 编码，同样不能被拆成两个回复
+        let mut map = crate::RespMap::new();
+        map.insert("a\nb".to_string(), RespFrame::Integer(1));
+        let mut buf = BytesMut::from(&RespFrame::from(map).encode()[..]);
+        let RespFrame::Map(decoded) = RespFrame::decode(&mut buf)? else {
+            panic!("expected a map");
+        };
+        assert!(buf.is_empty());
+        assert_eq!(decoded.get("a\nb"), Some(&RespFrame::Integer(1)));
+        Ok(())
+    }
+
     #[test]
     fn test_simple_string_decode() -> Result<()> {
         let mut buf = BytesMut::new();
@@ -0,0 +1,128 @@
+use super::RespFrame;
+
+/// Callbacks invoked while walking a frame with [`RespFrame::accept`]. Every
+/// method defaults to a no-op so a visitor only implements what it needs.
+/// Data is handed out by reference, walking never clones or allocates.
+#[allow(unused_variables)]
+pub trait RespVisitor {
+    fn visit_simple_string(&mut self, value: &str) {}
+    fn visit_error(&mut self, message: &str) {}
+    fn visit_integer(&mut self, value: i64) {}
+    /// `None` for a null bulk string.
+    fn visit_bulk(&mut self, value: Option<&[u8]>) {}
+    fn visit_null(&mut self) {}
+    fn visit_boolean(&mut self, value: bool) {}
+    fn visit_double(&mut self, value: f64) {}
+    /// `None` for a null array, which is not followed by `visit_array_end`.
+    fn visit_array_start(&mut self, len: Option<usize>) {}
+    fn visit_array_end(&mut self) {}
+    /// Each key is followed by the walk of its value.
+    fn visit_map_start(&mut self, len: usize) {}
+    fn visit_map_key(&mut self, key: &str) {}
+    fn visit_map_end(&mut self) {}
+    fn visit_set_start(&mut self, len: usize) {}
+    fn visit_set_end(&mut self) {}
+}
+
+impl RespFrame {
+    /// Walk the frame depth-first, calling `visitor` for every element.
+    pub fn accept<V: RespVisitor + ?Sized>(&self, visitor: &mut V) {
+        match self {
+            RespFrame::SimpleString(s) => visitor.visit_simple_string(&s.0),
+            RespFrame::Error(e) => visitor.visit_error(&e.0),
+            RespFrame::Integer(n) => visitor.visit_integer(*n),
+            RespFrame::BulkString(s) => visitor.visit_bulk(s.0.as_deref()),
+            RespFrame::Null(_) => visitor.visit_null(),
+            RespFrame::Boolean(b) => visitor.visit_boolean(*b),
+            RespFrame::Double(d) => visitor.visit_double(*d),
+            RespFrame::Array(array) => match &array.0 {
+                Some(items) => {
+                    visitor.visit_array_start(Some(items.len()));
+                    for item in items {
+                        item.accept(visitor);
+                    }
+                    visitor.visit_array_end();
+                }
+                None => visitor.visit_array_start(None),
+            },
+            RespFrame::Map(map) => {
+                visitor.visit_map_start(map.0.len());
+                for (key, value) in &map.0 {
+                    visitor.visit_map_key(key);
+                    value.accept(visitor);
+                }
+                visitor.visit_map_end();
+            }
+            // push 只是带外发送的数组
+            RespFrame::Push(push) => {
+                visitor.visit_array_start(Some(push.0.len()));
+                for item in &push.0 {
+                    item.accept(visitor);
+                }
+                visitor.visit_array_end();
+            }
+            RespFrame::Set(set) => {
+                visitor.visit_set_start(set.0.len());
+                for item in &set.0 {
+                    item.accept(visitor);
+                }
+                visitor.visit_set_end();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespArray, RespMap, RespSet};
+
+    #[derive(Default)]
+    struct BulkCounter {
+        bulks: usize,
+        depth: usize,
+        max_depth: usize,
+    }
+
+    impl RespVisitor for BulkCounter {
+        fn visit_bulk(&mut self, _: Option<&[u8]>) {
+            self.bulks += 1;
+        }
+
+        fn visit_array_start(&mut self, len: Option<usize>) {
+            if len.is_some() {
+                self.depth += 1;
+                self.max_depth = self.max_depth.max(self.depth);
+            }
+        }
+
+        fn visit_array_end(&mut self) {
+            self.depth -= 1;
+        }
+    }
+
+    #[test]
+    fn test_visitor_counts_nested_bulk_strings() {
+        let mut map = RespMap::new();
+        map.insert("k".to_string(), BulkString::from("v").into());
+        let frame: RespFrame = RespArray::new(vec![
+            BulkString::from("a").into(),
+            RespFrame::Integer(1),
+            RespArray::new(vec![
+                BulkString::null().into(),
+                RespArray::new(vec![BulkString::from("b").into()]).into(),
+                RespArray::null().into(),
+            ])
+            .into(),
+            map.into(),
+            RespSet::new(vec![BulkString::from("c").into()]).into(),
+        ])
+        .into();
+
+        let mut counter = BulkCounter::default();
+        frame.accept(&mut counter);
+        assert_eq!(counter.bulks, 5);
+        assert_eq!(counter.max_depth, 3);
+        assert_eq!(counter.depth, 0);
+    }
+}
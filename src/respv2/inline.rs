@@ -0,0 +1,29 @@
+use crate::{BulkString, RespArray, RespError, RespFrame};
+
+// RESP 类型前缀，其他字节开头的数据按 inline 命令解析
+const TYPE_PREFIXES: &[u8] = b"+-:$*_#,%~>";
+
+/// Whether `buf` starts with an inline command (e.g. `ping\r\n` typed in telnet)
+/// instead of a RESP frame.
+pub fn is_inline(buf: &[u8]) -> bool {
+    buf.first().is_some_and(|b| !TYPE_PREFIXES.contains(b))
+}
+
+/// Length of the inline command line at the start of `buf`, including the
+/// `\n` (or `\r\n`) terminator.
+pub fn parse_inline_length(buf: &[u8]) -> Result<usize, RespError> {
+    buf.iter()
+        .position(|b| *b == b'\n')
+        .map(|pos| pos + 1)
+        .ok_or(RespError::NotComplete)
+}
+
+/// Split one complete inline line into a command array of bulk strings.
+pub fn parse_inline(line: &[u8]) -> RespFrame {
+    let args = line
+        .split(|b| b.is_ascii_whitespace())
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| BulkString::new(arg.to_vec()).into())
+        .collect::<Vec<RespFrame>>();
+    RespArray::new(args).into()
+}
@@ -0,0 +1,206 @@
+use bytes::BytesMut;
+
+use super::RespDecodeV2;
+use crate::{RespError, RespFrame};
+
+const CRLF: &[u8] = b"\r\n";
+// 和 Redis 一致：单个 bulk string 最大 512MB，单个 multibulk 最多 1024*1024 个元素
+const MAX_BULK_LEN: usize = 512 * 1024 * 1024;
+const MAX_ELEMENTS: usize = 1024 * 1024;
+const MAX_DEPTH: usize = 64;
+
+/// Safety bounds for decoding frames from untrusted clients. Every limit is
+/// checked on the frame headers alone, before any payload is buffered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// Deepest nesting of aggregates, a flat array has depth 1.
+    pub max_depth: usize,
+    /// Longest bulk string in bytes.
+    pub max_bulk_len: usize,
+    /// Most elements in one aggregate, a map counts its key/value pairs.
+    pub max_elements: usize,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: MAX_DEPTH,
+            max_bulk_len: MAX_BULK_LEN,
+            max_elements: MAX_ELEMENTS,
+        }
+    }
+}
+
+impl DecodeLimits {
+    /// No limit but `max_bulk_len`.
+    pub fn bulk_len(max: usize) -> Self {
+        Self {
+            max_depth: usize::MAX,
+            max_bulk_len: max,
+            max_elements: usize::MAX,
+        }
+    }
+
+    /// Scan the headers of the first frame in `input` and reject it as soon as
+    /// one of them breaks a limit. Incomplete input is not an error here, the
+    /// regular decode reports it.
+    pub fn check(&self, input: &[u8]) -> Result<(), RespError> {
+        let mut input = input;
+        // 每层 aggregate 还剩多少个元素没检查，栈的深度就是当前的嵌套层数
+        let mut pending: Vec<usize> = Vec::new();
+        loop {
+            let Some((&kind, rest)) = input.split_first() else {
+                return Ok(());
+            };
+            let Some(end) = rest.windows(2).position(|w| w == CRLF) else {
+                return Ok(());
+            };
+            let line = &rest[..end];
+            input = &rest[end + 2..];
+            if let Some(top) = pending.last_mut() {
+                *top -= 1;
+            }
+            let number =
+                || -> Result<i64, RespError> { Ok(String::from_utf8_lossy(line).parse()?) };
+            match kind {
+                b'$' => {
+                    let len = number()?;
+                    if exceeds(len, self.max_bulk_len) {
+                        return Err(RespError::BulkTooLong(len));
+                    }
+                    // null bulk string 没有数据部分，不完整时交给正常的解码处理
+                    if len >= 0 {
+                        let skip = len as usize + 2;
+                        if input.len() < skip {
+                            return Ok(());
+                        }
+                        input = &input[skip..];
+                    }
+                }
+                b'*' | b'~' | b'>' | b'%' => {
+                    let count = number()?;
+                    if exceeds(count, self.max_elements) {
+                        return Err(RespError::TooManyElements(count));
+                    }
+                    if pending.len() >= self.max_depth {
+                        return Err(RespError::NestingTooDeep(self.max_depth));
+                    }
+                    let count = count.max(0) as usize;
+                    let children = if kind == b'%' { 2 * count } else { count };
+                    if children > 0 {
+                        pending.push(children);
+                    }
+                }
+                _ => {}
+            }
+            while pending.last() == Some(&0) {
+                pending.pop();
+            }
+            if pending.is_empty() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+// 不能把上限转成 i64 比较，usize::MAX 会变成 -1
+fn exceeds(announced: i64, max: usize) -> bool {
+    usize::try_from(announced).is_ok_and(|n| n > max)
+}
+
+/// Reject a frame as soon as one of its bulk string headers announces more
+/// than `max` bytes, without waiting for the payload to arrive.
+pub fn check_bulk_len(input: &[u8], max: usize) -> Result<(), RespError> {
+    DecodeLimits::bulk_len(max).check(input)
+}
+
+impl RespFrame {
+    /// Decode one frame like `RespDecodeV2::decode`, first rejecting it if it
+    /// breaks any of `limits`. The buffer is left untouched on a violation.
+    pub fn decode_with_limits(
+        buf: &mut BytesMut,
+        limits: &DecodeLimits,
+    ) -> Result<Self, RespError> {
+        limits.check(buf)?;
+        <RespFrame as RespDecodeV2>::decode(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespArray, RespEncode};
+
+    fn nested(depth: usize) -> Vec<u8> {
+        let mut frame: RespFrame = BulkString::from("x").into();
+        for _ in 0..depth {
+            frame = RespArray::new(vec![frame]).into();
+        }
+        frame.encode()
+    }
+
+    #[test]
+    fn test_decode_with_limits_rejects_each_limit() {
+        let limits = DecodeLimits {
+            max_depth: 3,
+            max_bulk_len: 16,
+            max_elements: 4,
+        };
+        let decode = |data: &[u8]| {
+            let mut buf = BytesMut::from(data);
+            let ret = RespFrame::decode_with_limits(&mut buf, &limits);
+            (ret, buf.len())
+        };
+
+        assert!(decode(&nested(3)).0.is_ok());
+        let deep = nested(4);
+        assert_eq!(
+            decode(&deep),
+            (Err(RespError::NestingTooDeep(3)), deep.len())
+        );
+
+        let huge_bulk = BulkString::new(vec![b'x'; 17]).encode();
+        assert_eq!(decode(&huge_bulk).0, Err(RespError::BulkTooLong(17)));
+        // 只有头部时就能拒绝
+        assert_eq!(
+            decode(b"$1000000\r\n").0,
+            Err(RespError::BulkTooLong(1000000))
+        );
+
+        let args =
+            |n: usize| -> Vec<u8> { RespArray::new(vec![RespFrame::Integer(1); n]).encode() };
+        assert!(decode(&args(4)).0.is_ok());
+        assert_eq!(decode(&args(5)).0, Err(RespError::TooManyElements(5)));
+        assert_eq!(decode(b"%5\r\n").0, Err(RespError::TooManyElements(5)));
+    }
+
+    #[test]
+    fn test_bulk_len_only_limits_bulk_strings() {
+        let frame = RespArray::new(vec![BulkString::from("ping").into()]).encode();
+        assert_eq!(check_bulk_len(&frame, 16), Ok(()));
+        assert_eq!(
+            check_bulk_len(&BulkString::new(vec![b'x'; 17]).encode(), 16),
+            Err(RespError::BulkTooLong(17))
+        );
+    }
+
+    #[test]
+    fn test_limits_only_look_at_the_first_frame() {
+        let limits = DecodeLimits {
+            max_depth: 2,
+            ..Default::default()
+        };
+        // 后面还有一个超出深度的 frame，不影响第一个
+        let mut data = nested(2);
+        data.extend_from_slice(&nested(5));
+        assert_eq!(limits.check(&data), Ok(()));
+        // 同一层的兄弟元素不增加深度
+        let siblings: RespFrame = RespArray::new(vec![
+            RespArray::new(vec![RespFrame::Integer(1)]).into(),
+            RespArray::new(vec![]).into(),
+            RespArray::new(vec![RespFrame::Integer(2)]).into(),
+        ])
+        .into();
+        assert_eq!(limits.check(&siblings.encode()), Ok(()));
+    }
+}
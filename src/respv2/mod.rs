@@ -1,5 +1,8 @@
+mod inline;
+mod limits;
 mod parser;
 
+pub use self::limits::{check_bulk_len, DecodeLimits};
 pub use self::parser::{parse_frame, parse_frame_length};
 use crate::{RespError, RespFrame};
 use bytes::BytesMut;
@@ -9,18 +12,88 @@ pub trait RespDecodeV2: Sized {
     fn expect_length(buf: &[u8]) -> Result<usize, RespError>;
 }
 
+/// Decodes consecutive frames from a buffer, stopping after the first error.
+/// A trailing partial frame yields `Err(RespError::NotComplete)` and is left in
+/// the buffer.
+#[derive(Debug)]
+pub struct RespFrameIter<'a> {
+    buf: &'a mut BytesMut,
+    done: bool,
+    position: usize,
+    track_position: bool,
+}
+
 impl RespDecodeV2 for RespFrame {
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        // 跳过 inline 命令之间的空行
+        while inline::is_inline(buf) {
+            let len = inline::parse_inline_length(buf)?;
+            let line = buf.split_to(len);
+            let frame = inline::parse_inline(&line);
+            if !matches!(&frame, RespFrame::Array(args) if args.is_empty()) {
+                return Ok(frame);
+            }
+        }
         let len = Self::expect_length(buf)?;
         let data = buf.split_to(len);
         parse_frame(&mut data.as_ref()).map_err(|e| RespError::InvalidFrame(e.to_string()))
     }
 
     fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        if inline::is_inline(buf) {
+            return inline::parse_inline_length(buf);
+        }
         parse_frame_length(buf)
     }
 }
 
+impl<'a> RespFrameIter<'a> {
+    pub fn new(buf: &'a mut BytesMut) -> Self {
+        Self {
+            buf,
+            done: false,
+            position: 0,
+            track_position: false,
+        }
+    }
+
+    /// Wrap decode errors in `RespError::InvalidFrameAt` carrying the index of
+    /// the failing frame in the pipeline. `NotComplete` is never wrapped.
+    pub fn track_position(mut self) -> Self {
+        self.track_position = true;
+        self
+    }
+
+    /// Index of the next frame to decode, i.e. how many frames were decoded.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl Iterator for RespFrameIter<'_> {
+    type Item = Result<RespFrame, RespError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.buf.is_empty() {
+            return None;
+        }
+        let ret = match <RespFrame as RespDecodeV2>::decode(self.buf) {
+            Ok(frame) => {
+                self.position += 1;
+                return Some(Ok(frame));
+            }
+            Err(RespError::NotComplete) => Err(RespError::NotComplete),
+            Err(e) if self.track_position => Err(RespError::InvalidFrameAt {
+                index: self.position,
+                source: Box::new(e),
+            }),
+            Err(e) => Err(e),
+        };
+        self.done = true;
+        Some(ret)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{BulkString, RespArray, RespMap};
@@ -141,14 +214,14 @@ mod tests {
 
     #[test]
     fn respv2_map_length_should_work() {
-        let buf = b"%2\r\n+OK\r\n-ERR\r\n";
+        let buf = b"%1\r\n+OK\r\n-ERR\r\n";
         let len = RespFrame::expect_length(buf).unwrap();
         assert_eq!(len, buf.len());
     }
 
     #[test]
     fn respv2_map_should_work() {
-        let mut buf = BytesMut::from("%2\r\n+OK\r\n-ERR\r\n");
+        let mut buf = BytesMut::from("%1\r\n+OK\r\n-ERR\r\n");
         let frame = RespFrame::decode(&mut buf).unwrap();
         let items: BTreeMap<String, RespFrame> =
             [("OK".to_string(), RespFrame::Error("ERR".into()))]
@@ -156,4 +229,61 @@ mod tests {
                 .collect();
         assert_eq!(frame, RespFrame::Map(RespMap(items)));
     }
+
+    #[test]
+    fn respv2_map_with_bulk_string_key_should_work() {
+        let buf = b"%1\r\n$3\r\na\nb\r\n:1\r\n";
+        assert_eq!(RespFrame::expect_length(buf).unwrap(), buf.len());
+        let frame = RespFrame::decode(&mut BytesMut::from(&buf[..])).unwrap();
+        let items: BTreeMap<String, RespFrame> = [("a\nb".to_string(), RespFrame::Integer(1))]
+            .into_iter()
+            .collect();
+        assert_eq!(frame, RespFrame::Map(RespMap(items)));
+    }
+
+    #[test]
+    fn respv2_inline_command_should_work() {
+        let mut buf = BytesMut::from("set  hello world\r\n");
+        let frame = RespFrame::decode(&mut buf).unwrap();
+        assert_eq!(
+            frame,
+            RespFrame::Array(RespArray::new(vec![
+                BulkString::new("set").into(),
+                BulkString::new("hello").into(),
+                BulkString::new("world").into(),
+            ]))
+        );
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn respv2_pipelined_inline_commands_should_work() {
+        let mut buf = BytesMut::from("ping\r\nping\r\necho hel");
+        let frames: Vec<_> = RespFrameIter::new(&mut buf).collect();
+        let ping = RespFrame::Array(RespArray::new(vec![BulkString::new("ping").into()]));
+        assert_eq!(
+            frames,
+            vec![Ok(ping.clone()), Ok(ping), Err(RespError::NotComplete)]
+        );
+        // 不完整的命令留在缓冲区等待更多数据
+        assert_eq!(buf.as_ref(), b"echo hel");
+    }
+
+    #[test]
+    fn respv2_pipeline_error_reports_index() {
+        let mut buf = BytesMut::from("*1\r\n$4\r\nping\r\n:abc\r\n*1\r\n$4\r\nping\r\n");
+        let mut iter = RespFrameIter::new(&mut buf).track_position();
+        assert!(iter.next().unwrap().is_ok());
+
+        let err = iter.next().unwrap().unwrap_err();
+        assert!(matches!(err, RespError::InvalidFrameAt { index: 1, .. }));
+        assert!(err.to_string().contains("index 1"));
+        assert_eq!(iter.position(), 1);
+        assert!(iter.next().is_none());
+
+        // 默认不包装，保持原有错误
+        let mut buf = BytesMut::from(":abc\r\n");
+        let err = RespFrameIter::new(&mut buf).next().unwrap().unwrap_err();
+        assert!(matches!(err, RespError::InvalidFrame(_)));
+    }
 }
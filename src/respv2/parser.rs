@@ -8,7 +8,8 @@ use winnow::token::{any, take, take_until};
 use winnow::{PResult, Parser};
 
 use crate::{
-    BulkString, RespArray, RespError, RespFrame, RespMap, RespNull, SimpleError, SimpleString,
+    BulkString, RespArray, RespError, RespFrame, RespMap, RespNull, RespPush, RespSet, SimpleError,
+    SimpleString,
 };
 
 const CRLF: &[u8] = b"\r\n";
@@ -40,7 +41,8 @@ fn parse_frame_len(input: &mut &[u8]) -> PResult<()> {
         b'#' => simple_parser,
         b',' => simple_parser,
         b'%' => map_len,
-        // b'~' => set,
+        b'~' => array_len,
+        b'>' => array_len,
         _v => fail::<_,_,_>,
     }
     .parse_next(input)
@@ -59,7 +61,8 @@ pub fn parse_frame(input: &mut &[u8]) -> PResult<RespFrame> {
         b'#' => boolean.map(RespFrame::Boolean),
         b',' => double.map(RespFrame::Double),
         b'%' => map.map(RespFrame::Map),
-        // b'~' => set,
+        b'~' => set.map(RespFrame::Set),
+        b'>' => push.map(RespFrame::Push),
         _v => fail::<_,_,_>,
     }
     .parse_next(input)
@@ -81,9 +84,8 @@ fn error(input: &mut &[u8]) -> PResult<SimpleError> {
 
 // - integer: ":-1000\r\n"
 fn integer(input: &mut &[u8]) -> PResult<i64> {
-    let sign = opt('-').parse_next(input)?.is_some();
-    let v: i64 = terminated(digit1.parse_to(), CRLF).parse_next(input)?;
-    Ok(if sign { -v } else { v })
+    // 符号和数字一起解析，否则 i64::MIN 的绝对值会溢出
+    terminated((opt('-'), digit1).take().parse_to(), CRLF).parse_next(input)
 }
 
 // Null bulk strings: "$-1\r\n"
@@ -91,13 +93,10 @@ fn null_bulk_string(input: &mut &[u8]) -> PResult<BulkString> {
     "-1\r\n".value(BulkString::null()).parse_next(input)
 }
 
-// - bulk string: "$6\r\nfoobar\r\n"
-#[allow(clippy::comparison_chain)]
+// - bulk string: "$6\r\nfoobar\r\n"，空字符串 "$0\r\n\r\n" 同样带有结尾的 CRLF
 fn bulk_string(input: &mut &[u8]) -> PResult<BulkString> {
     let len: i64 = integer.parse_next(input)?;
-    if len == 0 {
-        return Ok(BulkString::new(vec![]));
-    } else if len < 0 {
+    if len < 0 {
         return Err(err_cut("bulk string length must be non-negative"));
     }
     let data = terminated(take(len as usize), CRLF)
@@ -108,7 +107,7 @@ fn bulk_string(input: &mut &[u8]) -> PResult<BulkString> {
 
 fn bulk_string_len(input: &mut &[u8]) -> PResult<()> {
     let len: i64 = integer.parse_next(input)?;
-    if len == 0 || len == -1 {
+    if len == -1 {
         return Ok(());
     } else if len < 0 {
         return Err(err_cut("bulk string length must be non-negative"));
@@ -162,7 +161,7 @@ fn array_len(input: &mut &[u8]) -> PResult<()> {
 
 // Booleans: "#<t|f>\r\n"
 fn boolean(input: &mut &[u8]) -> PResult<bool> {
-    let v = alt((b't', b'f')).parse_next(input)?;
+    let v = terminated(alt((b't', b'f')), CRLF).parse_next(input)?;
     Ok(v == b't')
 }
 
@@ -171,16 +170,34 @@ fn double(input: &mut &[u8]) -> PResult<f64> {
     terminated(float, CRLF).parse_next(input)
 }
 
-// - map: "%2\r\n$3\r\nkey\r\n$5\r\nvalue\r\n$3\r\nkey\r\n$5\r\nvalue\r\n"
+// - set: "~2\r\n$3\r\nset\r\n$5\r\nhello\r\n"
+fn set(input: &mut &[u8]) -> PResult<RespSet> {
+    let RespArray(frames) = array(input)?;
+    Ok(RespSet::new(frames.unwrap_or_default()))
+}
+
+// - push: ">2\r\n$7\r\nmessage\r\n$5\r\nhello\r\n"
+fn push(input: &mut &[u8]) -> PResult<RespPush> {
+    let RespArray(frames) = array(input)?;
+    Ok(RespPush::new(frames.unwrap_or_default()))
+}
+
+// - map: "%2\r\n+key\r\n$5\r\nvalue\r\n+key\r\n$5\r\nvalue\r\n"，长度是键值对的个数
 fn map(input: &mut &[u8]) -> PResult<RespMap> {
     let len: i64 = integer.parse_next(input)?;
-    if len <= 0 {
+    if len < 0 {
         return Err(err_cut("map length must be non-negative"));
     }
-    let len = (len / 2) as usize;
+    let len = len as usize;
     let mut frames = BTreeMap::new();
     for _ in 0..len {
-        let key = preceded('+', parse_string).parse_next(input)?;
+        // 带 CR/LF 的 key 会被编码成 bulk string
+        let key = alt((
+            preceded('+', parse_string),
+            preceded('$', bulk_string)
+                .map(|s| String::from_utf8_lossy(s.0.as_deref().unwrap_or_default()).into_owned()),
+        ))
+        .parse_next(input)?;
         let value = parse_frame(input)?;
         frames.insert(key, value);
     }
@@ -189,14 +206,12 @@ fn map(input: &mut &[u8]) -> PResult<RespMap> {
 
 fn map_len(input: &mut &[u8]) -> PResult<()> {
     let len: i64 = integer.parse_next(input)?;
-    if len <= 0 {
+    if len < 0 {
         return Err(err_cut("map length must be non-negative"));
     }
-    let len = (len / 2) as usize;
+    let len = len as usize;
     for _ in 0..len {
-        terminated(take_until(0.., CRLF), CRLF)
-            .value(())
-            .parse_next(input)?;
+        parse_frame_len(input)?;
         parse_frame_len(input)?;
     }
     Ok(())
@@ -0,0 +1,59 @@
+use anyhow::Result;
+use simple_redis::{network, Backend};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+
+// 等待服务端处理完连接的建立或断开
+fn wait_for_clients(backend: &Backend, expected: usize) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        if backend.connected_clients() == expected {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+#[test]
+fn connected_clients_should_follow_connection_lifecycle() -> Result<()> {
+    let rt = tokio::runtime::Runtime::new()?;
+    let backend = Backend::new();
+    let listener = rt.block_on(TcpListener::bind("127.0.0.1:0"))?;
+    let addr = listener.local_addr()?;
+
+    let server_backend = backend.clone();
+    rt.spawn(async move {
+        loop {
+            let Ok((stream, _)) = listener.accept().await else {
+                return;
+            };
+            let backend = server_backend.clone();
+            tokio::spawn(network::stream_handler(stream, backend));
+        }
+    });
+
+    let first = TcpStream::connect(addr)?;
+    let mut second = TcpStream::connect(addr)?;
+    assert!(wait_for_clients(&backend, 2));
+
+    second.write_all(b"*2\r\n$4\r\ninfo\r\n$7\r\nclients\r\n")?;
+    let mut reply = Vec::new();
+    let mut buf = [0u8; 256];
+    while !reply.ends_with(b"protocol_version:2\r\n\r\n") {
+        let n = second.read(&mut buf)?;
+        assert!(n > 0, "connection closed before INFO reply");
+        reply.extend_from_slice(&buf[..n]);
+    }
+    assert!(String::from_utf8(reply)?.contains("connected_clients:2\r\n"));
+
+    drop(first);
+    assert!(wait_for_clients(&backend, 1));
+    drop(second);
+    assert!(wait_for_clients(&backend, 0));
+
+    Ok(())
+}
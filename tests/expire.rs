@@ -0,0 +1,49 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use simple_redis::{
+    cmd::{Command, CommandExecutor},
+    Backend, BulkString, ManualClock, RespArray, RespFrame, RespNull, SimpleString,
+};
+
+fn run(backend: &Backend, args: &[&str]) -> Result<RespFrame> {
+    let frame = RespArray::new(
+        args.iter()
+            .map(|arg| BulkString::from(*arg).into())
+            .collect::<Vec<_>>(),
+    );
+    Ok(Command::try_from(RespFrame::from(frame))?.execute(backend))
+}
+
+#[test]
+fn expired_keys_are_removed_lazily_when_active_expire_is_off() -> Result<()> {
+    let clock = Arc::new(ManualClock::new());
+    let backend = Backend::with_clock(clock.clone());
+    let ok: RespFrame = SimpleString::new("OK").into();
+
+    assert_eq!(run(&backend, &["debug", "set-active-expire", "0"])?, ok);
+    for key in ["a", "b"] {
+        run(&backend, &["set", key, "v"])?;
+        run(&backend, &["pexpire", key, "1500"])?;
+    }
+    assert_eq!(run(&backend, &["pttl", "a"])?, RespFrame::Integer(1500));
+    assert_eq!(run(&backend, &["ttl", "a"])?, RespFrame::Integer(2));
+
+    clock.advance(Duration::from_millis(1000));
+    assert_eq!(run(&backend, &["pttl", "a"])?, RespFrame::Integer(500));
+
+    clock.advance(Duration::from_millis(1000));
+    // 主动过期已关闭，过期的 key 还留在内存里
+    assert_eq!(backend.active_expire_cycle(), 0);
+
+    // 访问时才惰性删除，且只删除被访问的 key
+    assert_eq!(run(&backend, &["pttl", "a"])?, RespFrame::Integer(-2));
+    assert_eq!(run(&backend, &["ttl", "a"])?, RespFrame::Integer(-2));
+
+    // 重新打开后主动过期只剩下没被访问过的 b 需要清理
+    assert_eq!(run(&backend, &["debug", "set-active-expire", "1"])?, ok);
+    assert_eq!(backend.active_expire_cycle(), 1);
+    assert_eq!(run(&backend, &["get", "b"])?, RespFrame::Null(RespNull));
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use anyhow::Result;
+use bytes::BytesMut;
+use simple_redis::{
+    cmd::{Command, CommandExecutor},
+    Backend, BulkString, RespDecodeV2, RespEncode, RespError, RespFrame, RespNull, SimpleString,
+};
+
+const PIPELINE: &[u8] = b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n\
+*2\r\n$3\r\nget\r\n$5\r\nhello\r\n\
+*2\r\n$3\r\nget\r\n$7\r\nmissing\r\n\
+*4\r\n$4\r\nhset\r\n$3\r\nmap\r\n$5\r\nfield\r\n$5\r\nvalue\r\n\
+*3\r\n$4\r\nhget\r\n$3\r\nmap\r\n$5\r\nfield\r\n\
+*3\r\n$4\r\nsadd\r\n$3\r\nset\r\n$6\r\nmember\r\n\
+*3\r\n$9\r\nsismember\r\n$3\r\nset\r\n$6\r\nmember\r\n\
+*4\r\n$5\r\nrpush\r\n$4\r\nlist\r\n$1\r\na\r\n$1\r\nb\r\n\
+*3\r\n$5\r\nlpush\r\n$4\r\nlist\r\n$1\r\nc\r\n\
+*2\r\n$4\r\necho\r\n$4\r\ndone\r\n\
+*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nagain\r\n\
+*2\r\n$3\r\nget\r\n$5\r\nhello\r\n";
+
+#[test]
+fn pipelined_replies_should_follow_request_order() -> Result<()> {
+    let backend = Backend::new();
+    let mut buf = BytesMut::from(PIPELINE);
+
+    // decode every command first, then execute them in order and batch the replies
+    let mut commands = Vec::new();
+    while !buf.is_empty() {
+        let frame = <RespFrame as RespDecodeV2>::decode(&mut buf)?;
+        commands.push(Command::try_from(frame)?);
+    }
+    assert_eq!(commands.len(), 12);
+
+    let mut replies = BytesMut::new();
+    for cmd in commands {
+        replies.extend_from_slice(&cmd.execute(&backend).encode());
+    }
+
+    let ok: RespFrame = SimpleString::new("OK").into();
+    let expected = vec![
+        ok.clone(),
+        BulkString::from("world").into(),
+        RespFrame::Null(RespNull),
+        ok.clone(),
+        BulkString::from("value").into(),
+        RespFrame::Integer(1),
+        RespFrame::Integer(1),
+        RespFrame::Integer(2),
+        RespFrame::Integer(3),
+        BulkString::from("done").into(),
+        ok,
+        BulkString::from("again").into(),
+    ];
+
+    let mut actual = Vec::new();
+    while !replies.is_empty() {
+        actual.push(<RespFrame as RespDecodeV2>::decode(&mut replies)?);
+    }
+    assert_eq!(actual, expected);
+
+    Ok(())
+}
+
+#[test]
+fn pipelined_replies_should_not_depend_on_split_points() -> Result<()> {
+    let mut expected: Option<Vec<RespFrame>> = None;
+    // feed the same pipeline in chunks of different sizes, as a socket would
+    for chunk_size in [1, 3, 7, 16, PIPELINE.len()] {
+        let backend = Backend::new();
+        let mut buf = BytesMut::new();
+        let mut replies = Vec::new();
+        for chunk in PIPELINE.chunks(chunk_size) {
+            buf.extend_from_slice(chunk);
+            loop {
+                match <RespFrame as RespDecodeV2>::decode(&mut buf) {
+                    Ok(frame) => {
+                        let cmd = Command::try_from(frame)?;
+                        replies.push(cmd.execute(&backend));
+                    }
+                    Err(RespError::NotComplete) => break,
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+        assert!(buf.is_empty());
+        assert_eq!(replies.len(), 12);
+        assert!(matches!(replies[9], RespFrame::BulkString(ref s) if s.as_ref() == b"done"));
+
+        match expected {
+            Some(ref expected) => assert_eq!(&replies, expected),
+            None => expected = Some(replies),
+        }
+    }
+
+    Ok(())
+}